@@ -0,0 +1,116 @@
+mod preview;
+mod room;
+
+use clap::Parser;
+use preview::PreviewWorker;
+use room::Room;
+use shared_types::dds_manager;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Drawnix 白板服务 - 无界面地维护白板的权威状态
+#[derive(Parser)]
+#[command(name = "drawnix-board-server")]
+#[command(about = "无界面地加入 DDS 域，维护并持久化白板状态，应答后加入者的快照请求")]
+struct Cli {
+    /// DDS 域 ID
+    #[arg(short, long, default_value_t = dds_manager::DEFAULT_DOMAIN_ID)]
+    domain_id: u32,
+
+    /// 要托管的房间（白板主题），可重复指定
+    #[arg(short, long = "room", default_value = dds_manager::BOARD_TOPIC)]
+    rooms: Vec<String>,
+
+    /// 历史和快照文件目录
+    #[arg(short = 'D', long, default_value = "board-data")]
+    data_dir: PathBuf,
+
+    /// 每隔多少秒把有变化的房间渲染成 <房间>.png 预览图，不指定则不生成
+    #[arg(long, value_name = "SECS")]
+    preview_interval: Option<u64>,
+
+    /// 预览图的缩放倍数
+    #[arg(long, default_value = "0.5")]
+    preview_scale: f32,
+}
+
+fn main() {
+    let cli = Cli::parse();
+    let server_id = format!("board-server-{}", uuid::Uuid::new_v4());
+
+    println!("🚀 启动 Drawnix 白板服务 {}", server_id);
+    println!("   🌐 域 ID: {}", cli.domain_id);
+    println!("   💾 数据目录: {}", cli.data_dir.display());
+    if let Some(secs) = cli.preview_interval {
+        println!("   🖼️ 预览图: 每 {} 秒更新，缩放 {}", secs, cli.preview_scale);
+    }
+
+    if let Err(e) = room::check_rooms(&cli.rooms) {
+        eprintln!("❌ 初始化失败: {}", e);
+        std::process::exit(1);
+    }
+    let mut rooms = Vec::new();
+    for topic in &cli.rooms {
+        match Room::open(cli.domain_id, topic, &cli.data_dir, &server_id) {
+            Ok(room) => rooms.push(room),
+            Err(e) => {
+                eprintln!("❌ 初始化失败: {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    // systemd 停止服务时发送 SIGTERM，与 Ctrl+C 一样优雅退出
+    let running = Arc::new(AtomicBool::new(true));
+    let r = running.clone();
+    ctrlc::set_handler(move || {
+        println!("\n🛑 接收到退出信号，正在保存并退出...");
+        r.store(false, Ordering::SeqCst);
+    })
+    .expect("设置信号处理器失败");
+
+    println!("👂 白板服务已启动，托管 {} 个房间", rooms.len());
+    let preview_interval = cli.preview_interval.map(Duration::from_secs);
+    let preview_worker = preview_interval.map(|_| PreviewWorker::spawn());
+    let mut last_preview = Instant::now();
+    while running.load(Ordering::SeqCst) {
+        let mut busy = false;
+        for room in rooms.iter_mut() {
+            busy |= room.poll();
+        }
+        if let (Some(worker), Some(interval)) = (&preview_worker, preview_interval) {
+            if last_preview.elapsed() >= interval {
+                last_preview = Instant::now();
+                for job in rooms.iter_mut().filter_map(|room| room.take_preview(cli.preview_scale)) {
+                    worker.submit(job);
+                }
+            }
+        }
+        if !busy {
+            // 短暂休眠避免过度消耗 CPU
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    for room in rooms.iter_mut() {
+        match room.save_document() {
+            Ok(path) => println!("💾 已保存 {}", path.display()),
+            Err(e) => eprintln!("❌ 保存失败: {}", e),
+        }
+        if let Some(worker) = &preview_worker {
+            if let Some(job) = room.take_preview(cli.preview_scale) {
+                println!("🖼️ 更新预览图 {}", job.path.display());
+                worker.submit(job);
+            }
+        }
+        println!("📊 {}", room.summary());
+    }
+    // 等后台线程写完最后的预览图
+    if let Some(worker) = preview_worker {
+        worker.finish();
+    }
+    println!("🎉 白板服务已停止");
+}
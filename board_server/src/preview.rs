@@ -0,0 +1,69 @@
+//! 预览图在后台线程渲染，大白板的栅格化不会阻塞 DDS 消息循环
+
+use shared_types::raster::{self, PngOptions};
+use shared_types::PlaitElement;
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread::{self, JoinHandle};
+
+/// 某个房间某一时刻的白板，交给后台线程渲染
+pub struct PreviewJob {
+    pub topic: String,
+    pub path: PathBuf,
+    pub elements: Vec<PlaitElement>,
+    pub scale: f32,
+}
+
+pub struct PreviewWorker {
+    sender: Sender<PreviewJob>,
+    handle: JoinHandle<()>,
+}
+
+impl PreviewWorker {
+    pub fn spawn() -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let handle = thread::spawn(move || run(receiver));
+        PreviewWorker { sender, handle }
+    }
+
+    pub fn submit(&self, job: PreviewJob) {
+        if self.sender.send(job).is_err() {
+            eprintln!("❌ 预览图线程已退出");
+        }
+    }
+
+    /// 渲染完已提交的预览图后退出
+    pub fn finish(self) {
+        drop(self.sender);
+        if self.handle.join().is_err() {
+            eprintln!("❌ 预览图线程异常退出");
+        }
+    }
+}
+
+fn run(receiver: Receiver<PreviewJob>) {
+    while let Ok(job) = receiver.recv() {
+        // 渲染跟不上时同一房间只渲染最新的一份
+        let mut latest: Vec<PreviewJob> = vec![job];
+        for job in receiver.try_iter() {
+            match latest.iter_mut().find(|queued| queued.topic == job.topic) {
+                Some(queued) => *queued = job,
+                None => latest.push(job),
+            }
+        }
+        for job in latest {
+            if let Err(e) = render(&job) {
+                eprintln!("❌ [{}] 预览图生成失败: {}", job.topic, e);
+            }
+        }
+    }
+}
+
+fn render(job: &PreviewJob) -> Result<(), String> {
+    let png = raster::render_png(&job.elements, &PngOptions { scale: job.scale, ..Default::default() })?;
+
+    // 先写临时文件再改名，读取方不会拿到写了一半的图片
+    let tmp = job.path.with_extension("png.tmp");
+    std::fs::write(&tmp, png).map_err(|e| format!("写入 {} 失败: {}", tmp.display(), e))?;
+    std::fs::rename(&tmp, &job.path).map_err(|e| format!("写入 {} 失败: {}", job.path.display(), e))
+}
@@ -0,0 +1,178 @@
+//! 单个房间（一个 DDS 白板主题）的权威状态
+
+use shared_types::dds_manager::DDSManager;
+use shared_types::document::DrawnixDocument;
+use shared_types::history::BoardHistory;
+use crate::preview::PreviewJob;
+use shared_types::*;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+pub struct Room {
+    dds: DDSManager,
+    /// 历史按全序重放得到白板，与各协作端收到同一批变化后的状态一致
+    history: BoardHistory,
+    server_id: String,
+    data_dir: PathBuf,
+    change_count: u64,
+    snapshot_count: u64,
+    /// 上次生成预览图之后白板是否有变化
+    preview_stale: bool,
+}
+
+impl Room {
+    /// 加入房间主题，并从历史文件恢复白板
+    pub fn open(domain_id: u32, topic: &str, data_dir: &Path, server_id: &str) -> Result<Self, String> {
+        let history_path = data_dir.join(format!("{}.history.jsonl", file_stem(topic)));
+        let history = BoardHistory::open(&history_path)
+            .map_err(|e| format!("打开历史文件 {} 失败: {}", history_path.display(), e))?;
+        let dds = DDSManager::connect(domain_id, topic).map_err(|e| format!("加入主题 {} 失败: {}", topic, e))?;
+
+        println!("🏠 房间 {} 已就绪，当前 {} 个元素", topic, history.board().children.len());
+        Ok(Room {
+            dds,
+            history,
+            server_id: server_id.to_string(),
+            data_dir: data_dir.to_path_buf(),
+            change_count: 0,
+            snapshot_count: 0,
+            preview_stale: true,
+        })
+    }
+
+    pub fn topic(&self) -> &str {
+        self.dds.board_topic()
+    }
+
+    /// 处理一轮消息，有消息时返回 true
+    pub fn poll(&mut self) -> bool {
+        let mut busy = false;
+
+        match self.dds.try_receive_board_change() {
+            Ok(Some(change)) => {
+                busy = true;
+                self.apply_change(change);
+            }
+            Ok(None) => {}
+            Err(e) => eprintln!("❌ [{}] 白板消息接收错误: {}", self.topic(), e),
+        }
+
+        match self.dds.try_receive_snapshot_request() {
+            Ok(Some(request)) => {
+                busy = true;
+                // 所有房间共用请求主题，只应答自己的
+                if request.topic == self.topic() {
+                    self.answer_snapshot(request);
+                }
+            }
+            Ok(None) => {}
+            Err(e) => eprintln!("❌ [{}] 快照请求接收错误: {}", self.topic(), e),
+        }
+
+        busy
+    }
+
+    fn apply_change(&mut self, change: BoardChangeData) {
+        if let Err(e) = self.history.record_change(&change) {
+            eprintln!("❌ [{}] 历史记录写入失败: {}", self.topic(), e);
+        }
+        self.change_count += 1;
+        self.preview_stale = true;
+    }
+
+    fn answer_snapshot(&mut self, request: SnapshotRequest) {
+        let snapshot = self.history.snapshot(&request, &self.server_id);
+        match self.dds.publish_snapshot(&snapshot) {
+            Ok(()) => {
+                self.snapshot_count += 1;
+                println!(
+                    "🗂️ [{}] 已向 {} 发送快照（{} 个元素，附带最近 {} 条变化）",
+                    self.topic(),
+                    request.source_id,
+                    self.history.board().children.len(),
+                    snapshot.changes.len()
+                );
+            }
+            Err(e) => eprintln!("❌ [{}] 快照发送失败: {}", self.topic(), e),
+        }
+    }
+
+    /// 退出前把当前白板另存为 .drawnix，便于用 drawnix-cli 查看
+    pub fn save_document(&self) -> Result<PathBuf, String> {
+        let path = self.data_dir.join(format!("{}.drawnix", file_stem(self.topic())));
+        DrawnixDocument::new(self.history.board().children.clone()).save(&path)?;
+        Ok(path)
+    }
+
+    /// 白板有变化时取出一份待渲染的 <房间>.png 预览图，供静态文件服务器等直接提供；没有变化时返回 None
+    pub fn take_preview(&mut self, scale: f32) -> Option<PreviewJob> {
+        if !self.preview_stale {
+            return None;
+        }
+        self.preview_stale = false;
+        Some(PreviewJob {
+            topic: self.topic().to_string(),
+            path: self.data_dir.join(format!("{}.png", file_stem(self.topic()))),
+            elements: self.history.board().children.clone(),
+            scale,
+        })
+    }
+
+    pub fn summary(&self) -> String {
+        format!(
+            "{}: {} 个元素，处理变化 {} 条，发送快照 {} 次",
+            self.topic(),
+            self.history.board().children.len(),
+            self.change_count,
+            self.snapshot_count
+        )
+    }
+}
+
+/// 主题名可能含有不适合作文件名的字符
+fn file_stem(topic: &str) -> String {
+    topic
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect()
+}
+
+/// 启动前检查房间：重复的房间，或文件名相同的房间（如 `a/b` 和 `a_b`，或只差大小写）会共用同一个历史文件
+pub fn check_rooms(topics: &[String]) -> Result<(), String> {
+    let mut stems: HashMap<String, &str> = HashMap::new();
+    for topic in topics {
+        if let Some(other) = stems.insert(file_stem(topic).to_lowercase(), topic) {
+            return Err(if other == topic {
+                format!("房间 {} 重复指定", topic)
+            } else {
+                format!("房间 {} 和 {} 会写入同一个历史文件 {}.history.jsonl，请换一个主题名", other, topic, file_stem(topic))
+            });
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rooms(topics: &[&str]) -> Vec<String> {
+        topics.iter().map(|t| t.to_string()).collect()
+    }
+
+    #[test]
+    fn file_stems_replace_unsafe_characters() {
+        assert_eq!(file_stem("DrawnixBoard"), "DrawnixBoard");
+        assert_eq!(file_stem("team-a/board_1"), "team-a_board_1");
+        assert_eq!(file_stem("../设计"), "_____");
+    }
+
+    #[test]
+    fn colliding_rooms_are_rejected() {
+        assert!(check_rooms(&rooms(&["a", "b", "a-b"])).is_ok());
+        assert!(check_rooms(&rooms(&["a/b", "c", "a_b"])).unwrap_err().contains("a/b 和 a_b"));
+        assert!(check_rooms(&rooms(&["Board", "board"])).is_err());
+        assert!(check_rooms(&rooms(&["设计", "草图"])).is_err());
+        assert!(check_rooms(&rooms(&["a", "a"])).unwrap_err().contains("重复指定"));
+    }
+}
@@ -0,0 +1,287 @@
+//! DDS 流量录制与回放，用于确定性地复现协作问题
+//!
+//! 录制文件为 JSONL，每行一个收到的样本。
+
+use colored::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, LineWriter, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+use zrdds_safe::prelude::*;
+
+/// 样本原始内容：UTF-8 文本原样保存，其他字节按数组保存
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Payload {
+    Text(String),
+    Bytes(Vec<u8>),
+}
+
+impl Payload {
+    fn from_bytes(data: Vec<u8>) -> Self {
+        match String::from_utf8(data) {
+            Ok(text) => Payload::Text(text),
+            Err(e) => Payload::Bytes(e.into_bytes()),
+        }
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        match self {
+            Payload::Text(text) => text.as_bytes(),
+            Payload::Bytes(bytes) => bytes,
+        }
+    }
+}
+
+/// 录制文件中的一个样本
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CapturedSample {
+    pub topic: String,
+    /// 到达时间（RFC3339）
+    pub received_at: String,
+    /// 相对录制开始的毫秒数，回放按它还原时序
+    pub offset_ms: u64,
+    /// 消息中的 source_id（如果能解析出来）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_id: Option<String>,
+    pub payload: Payload,
+}
+
+/// 回放节奏
+pub enum ReplayPace {
+    /// 按录制时的间隔，除以倍速；倍速为 0 时不等待
+    Timed(f64),
+    /// 每个样本按回车发送一次
+    Step,
+}
+
+fn source_of(payload: &Payload) -> Option<String> {
+    let value: serde_json::Value = serde_json::from_slice(payload.as_bytes()).ok()?;
+    value.get("source_id")?.as_str().map(String::from)
+}
+
+fn install_ctrlc(running: &Arc<AtomicBool>) {
+    let r = running.clone();
+    ctrlc::set_handler(move || {
+        println!("\n{}", "🛑 接收到中断信号，正在优雅退出...".yellow());
+        r.store(false, Ordering::SeqCst);
+    })
+    .expect("设置 Ctrl+C 处理器失败");
+}
+
+/// 把指定主题上收到的每个样本写入录制文件
+pub fn record(domain_id: u32, topics: &[String], output: &Path, timeout: u64) -> Result<(), String> {
+    let domain = Domain::builder()
+        .domain_id(domain_id)
+        .build()
+        .map_err(|e| format!("创建域失败: {}", e))?;
+    let mut subscribers = Vec::new();
+    for topic in topics {
+        let subscriber = domain
+            .create_subscriber(topic)
+            .map_err(|e| format!("订阅主题 {} 失败: {}", topic, e))?;
+        subscribers.push((topic.clone(), subscriber));
+    }
+
+    let file = File::create(output).map_err(|e| format!("创建录制文件 {} 失败: {}", output.display(), e))?;
+    // 逐行刷新，程序异常退出时已录制的样本也不会丢
+    let mut writer = LineWriter::new(file);
+
+    let running = Arc::new(AtomicBool::new(true));
+    install_ctrlc(&running);
+
+    println!("{}", "⏺️  开始录制 DDS 流量...".bright_green());
+    println!("   📡 主题: {}", topics.join(", "));
+    println!("   🌐 域 ID: {}", domain_id);
+    println!("   💾 录制文件: {}", output.display());
+
+    let start_time = Instant::now();
+    let mut count = 0u64;
+    while running.load(Ordering::SeqCst) {
+        if timeout > 0 && start_time.elapsed().as_secs() >= timeout {
+            println!("\n{}", "⏰ 录制时间到达，退出...".yellow());
+            break;
+        }
+
+        let mut busy = false;
+        for (topic, subscriber) in subscribers.iter_mut() {
+            match subscriber.try_recv() {
+                Ok(Some(data)) => {
+                    busy = true;
+                    let payload = Payload::from_bytes(data);
+                    let sample = CapturedSample {
+                        topic: topic.clone(),
+                        received_at: chrono::Utc::now().to_rfc3339(),
+                        offset_ms: start_time.elapsed().as_millis() as u64,
+                        source_id: source_of(&payload),
+                        payload,
+                    };
+                    let line = serde_json::to_string(&sample).map_err(|e| e.to_string())?;
+                    writeln!(writer, "{}", line).map_err(|e| format!("写入录制文件失败: {}", e))?;
+                    count += 1;
+                }
+                Ok(None) => {}
+                Err(e) => eprintln!("{} [{}] {}", "❌ 消息接收错误:".red(), topic, e),
+            }
+        }
+
+        if !busy {
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    writer.flush().map_err(|e| format!("写入录制文件失败: {}", e))?;
+    println!("\n{}", "📊 录制统计:".bright_blue());
+    println!("   📨 样本数量: {}", count.to_string().bright_green());
+    println!("   ⏱️  录制时长: {:.2} 秒", start_time.elapsed().as_secs_f64());
+    Ok(())
+}
+
+/// 读取录制文件，跳过无法解析的行
+pub fn load(path: &Path) -> Result<Vec<CapturedSample>, String> {
+    let file = File::open(path).map_err(|e| format!("打开录制文件 {} 失败: {}", path.display(), e))?;
+    let mut samples = Vec::new();
+    for (i, line) in BufReader::new(file).lines().enumerate() {
+        let line = line.map_err(|e| format!("读取录制文件失败: {}", e))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str::<CapturedSample>(&line) {
+            Ok(sample) => samples.push(sample),
+            Err(e) => eprintln!("{} 第 {} 行: {}", "⚠️ 跳过无效样本".yellow(), i + 1, e),
+        }
+    }
+    Ok(samples)
+}
+
+/// 把录制文件重新发布到域上
+pub fn replay(domain_id: u32, input: &Path, pace: ReplayPace) -> Result<(), String> {
+    let samples = load(input)?;
+    let domain = Domain::builder()
+        .domain_id(domain_id)
+        .build()
+        .map_err(|e| format!("创建域失败: {}", e))?;
+    let mut publishers: HashMap<String, Publisher> = HashMap::new();
+
+    let running = Arc::new(AtomicBool::new(true));
+    if let ReplayPace::Timed(_) = pace {
+        install_ctrlc(&running);
+    }
+
+    println!("{}", "▶️  开始回放 DDS 流量...".bright_green());
+    println!("   💾 录制文件: {}（{} 个样本）", input.display(), samples.len());
+    println!("   🌐 域 ID: {}", domain_id);
+    match pace {
+        ReplayPace::Timed(speed) if speed > 0.0 => println!("   ⏩ 倍速: {}", speed),
+        ReplayPace::Timed(_) => println!("   ⏩ 不等待，尽快发送"),
+        ReplayPace::Step => println!("   👣 单步模式：回车发送下一个，输入 q 退出"),
+    }
+
+    let start_time = Instant::now();
+    let first_offset = samples.first().map(|s| s.offset_ms).unwrap_or(0);
+    let mut sent = 0usize;
+    let stdin = io::stdin();
+    for (i, sample) in samples.iter().enumerate() {
+        if !running.load(Ordering::SeqCst) {
+            break;
+        }
+
+        let source = sample.source_id.as_deref().unwrap_or("-");
+        match pace {
+            ReplayPace::Timed(speed) if speed > 0.0 => {
+                let due = Duration::from_secs_f64(sample.offset_ms.saturating_sub(first_offset) as f64 / 1000.0 / speed);
+                while running.load(Ordering::SeqCst) {
+                    let elapsed = start_time.elapsed();
+                    if elapsed >= due {
+                        break;
+                    }
+                    thread::sleep((due - elapsed).min(Duration::from_millis(50)));
+                }
+                if !running.load(Ordering::SeqCst) {
+                    break;
+                }
+            }
+            ReplayPace::Timed(_) => {}
+            ReplayPace::Step => {
+                print!(
+                    "[{}/{}] {} 来自 {}，{} 字节 ⏎ ",
+                    i + 1,
+                    samples.len(),
+                    sample.topic.cyan(),
+                    source,
+                    sample.payload.as_bytes().len()
+                );
+                io::stdout().flush().ok();
+                let mut input = String::new();
+                if stdin.lock().read_line(&mut input).unwrap_or(0) == 0 || input.trim() == "q" {
+                    break;
+                }
+            }
+        }
+
+        if !publishers.contains_key(&sample.topic) {
+            let publisher = domain
+                .create_publisher(&sample.topic)
+                .map_err(|e| format!("创建主题 {} 的发布者失败: {}", sample.topic, e))?;
+            publishers.insert(sample.topic.clone(), publisher);
+        }
+        match publishers[&sample.topic].publish(sample.payload.as_bytes()) {
+            Ok(()) => {
+                sent += 1;
+                println!(
+                    "{} 📤 #{} {} 来自 {}",
+                    format!("[+{} ms]", sample.offset_ms).dimmed(),
+                    (i + 1).to_string().bright_yellow(),
+                    sample.topic.cyan(),
+                    source
+                );
+            }
+            Err(e) => eprintln!("{} #{} {}", "❌ 发布失败:".red(), i + 1, e),
+        }
+    }
+
+    println!("\n{}", "📊 回放统计:".bright_blue());
+    println!("   📤 已发送: {} / {}", sent.to_string().bright_green(), samples.len());
+    println!("   ⏱️  回放时长: {:.2} 秒", start_time.elapsed().as_secs_f64());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(payload: Payload) -> CapturedSample {
+        CapturedSample { topic: "DrawnixBoardChanges".into(), received_at: "2024-01-01T10:00:00+00:00".into(), offset_ms: 42, source_id: source_of(&payload), payload }
+    }
+
+    #[test]
+    fn payload_round_trips_text_and_bytes() {
+        let text = br#"{"source_id":"p1","operations":[]}"#.to_vec();
+        let binary = vec![0xff, 0x00, 0xfe, b'a'];
+        for (data, is_text) in [(text, true), (binary, false)] {
+            let payload = Payload::from_bytes(data.clone());
+            assert_eq!(matches!(payload, Payload::Text(_)), is_text);
+            let line = serde_json::to_string(&sample(payload)).unwrap();
+            let restored: CapturedSample = serde_json::from_str(&line).unwrap();
+            assert_eq!(restored.payload.as_bytes(), data.as_slice());
+            assert_eq!(restored.source_id.as_deref(), is_text.then_some("p1"));
+        }
+    }
+
+    #[test]
+    fn load_skips_invalid_lines() {
+        let path = std::env::temp_dir().join(format!("drawnix-capture-{}.jsonl", std::process::id()));
+        let line = serde_json::to_string(&sample(Payload::from_bytes(b"hello".to_vec()))).unwrap();
+        std::fs::write(&path, format!("{}\n\n无法解析的一行\n{}\n", line, line)).unwrap();
+        let samples = load(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(samples.len(), 2);
+        assert_eq!(samples[1].payload.as_bytes(), b"hello");
+        assert_eq!(samples[1].offset_ms, 42);
+    }
+}
@@ -0,0 +1,175 @@
+//! 按来源、操作类型和元素过滤收到的消息
+
+use crate::message::ElementChangesData;
+use clap::{Args, ValueEnum};
+use shared_types::{BoardChangeData, Operation};
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OpKind {
+    Insert,
+    Remove,
+    Set,
+}
+
+impl OpKind {
+    fn wire_name(self) -> &'static str {
+        match self {
+            OpKind::Insert => "insert_node",
+            OpKind::Remove => "remove_node",
+            OpKind::Set => "set_node",
+        }
+    }
+}
+
+/// 各项条件之间为“且”，同一项重复指定时为“或”
+#[derive(Args, Clone, Default)]
+pub struct MessageFilter {
+    /// 只显示这些 source_id 发布的消息，可重复指定
+    #[arg(short, long = "source")]
+    pub sources: Vec<String>,
+
+    /// 只显示这些类型的操作，可重复指定
+    #[arg(long = "op", value_enum)]
+    pub ops: Vec<OpKind>,
+
+    /// 只显示涉及这些元素 id 的操作，可重复指定
+    #[arg(long = "element-id")]
+    pub element_ids: Vec<String>,
+
+    /// 只显示这些类型的元素，可写 geometry、rectangle 或 geometry/rectangle，可重复指定
+    #[arg(long = "element-type")]
+    pub element_types: Vec<String>,
+}
+
+impl MessageFilter {
+    pub fn is_empty(&self) -> bool {
+        self.sources.is_empty() && !self.filters_elements()
+    }
+
+    fn filters_elements(&self) -> bool {
+        !self.ops.is_empty() || !self.element_ids.is_empty() || !self.element_types.is_empty()
+    }
+
+    fn matches_source(&self, source_id: Option<&str>) -> bool {
+        self.sources.is_empty() || source_id.is_some_and(|id| self.sources.iter().any(|s| s == id))
+    }
+
+    fn matches_element(&self, op_type: &str, id: &str, element_type: &str, shape: &str) -> bool {
+        (self.ops.is_empty() || self.ops.iter().any(|op| op.wire_name() == op_type))
+            && (self.element_ids.is_empty() || self.element_ids.iter().any(|e| e == id))
+            && (self.element_types.is_empty()
+                || self.element_types.iter().any(|t| {
+                    t == element_type || (!shape.is_empty() && (t == shape || *t == format!("{}/{}", element_type, shape)))
+                }))
+    }
+
+    pub fn matches_operation(&self, op: &Operation) -> bool {
+        let node = op.node();
+        self.matches_element(op.op_type(), &node.id, &node.element_type, &node.shape)
+    }
+
+    /// 返回满足条件的操作下标；整条消息被过滤掉时返回 None
+    pub fn board(&self, change: &BoardChangeData) -> Option<Vec<usize>> {
+        if !self.matches_source(Some(&change.source_id)) {
+            return None;
+        }
+        let kept: Vec<usize> = (0..change.operations.len())
+            .filter(|&i| self.matches_operation(&change.operations[i]))
+            .collect();
+        if self.filters_elements() && kept.is_empty() {
+            return None;
+        }
+        Some(kept)
+    }
+
+    /// 元素变化消息没有 source_id；新增/删除/修改分别对应 insert/remove/set
+    pub fn elements(&self, data: &ElementChangesData) -> Option<ElementChangesData> {
+        if !self.sources.is_empty() {
+            return None;
+        }
+        let keep = |items: &[serde_json::Value], op_type: &str| -> Vec<serde_json::Value> {
+            items
+                .iter()
+                .filter(|item| {
+                    let field = |key: &str| item.get(key).and_then(|v| v.as_str()).unwrap_or_default();
+                    self.matches_element(op_type, field("id"), field("type"), field("shape"))
+                })
+                .cloned()
+                .collect()
+        };
+        let filtered = ElementChangesData {
+            added: keep(&data.added, "insert_node"),
+            removed: keep(&data.removed, "remove_node"),
+            modified: keep(&data.modified, "set_node"),
+            timestamp: data.timestamp.clone(),
+        };
+        if self.filters_elements() && filtered.added.is_empty() && filtered.removed.is_empty() && filtered.modified.is_empty() {
+            return None;
+        }
+        Some(filtered)
+    }
+
+    /// 无法识别结构的消息只按 source_id 字段过滤
+    pub fn other(&self, value: &serde_json::Value) -> bool {
+        !self.filters_elements() && self.matches_source(value.get("source_id").and_then(|v| v.as_str()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn change(source_id: &str, operations: serde_json::Value) -> BoardChangeData {
+        serde_json::from_value(serde_json::json!({ "operations": operations, "timestamp": "2024-01-01T10:00:00Z", "source_id": source_id })).unwrap()
+    }
+
+    fn operations() -> serde_json::Value {
+        serde_json::json!([
+            { "type": "insert_node", "path": [0], "node": { "id": "a", "type": "geometry", "shape": "rectangle" } },
+            { "type": "set_node", "path": [1], "node": { "id": "l", "type": "arrow-line" } },
+            { "type": "remove_node", "path": [2], "node": { "id": "e", "type": "geometry", "shape": "ellipse" } },
+        ])
+    }
+
+    #[test]
+    fn board_changes_keep_matching_operations() {
+        let data = change("p1", operations());
+        assert_eq!(MessageFilter::default().board(&data), Some(vec![0, 1, 2]));
+
+        let by_source = MessageFilter { sources: vec!["p2".into()], ..Default::default() };
+        assert_eq!(by_source.board(&data), None);
+
+        let by_op = MessageFilter { ops: vec![OpKind::Insert, OpKind::Remove], ..Default::default() };
+        assert_eq!(by_op.board(&data), Some(vec![0, 2]));
+
+        // 类型可写大类、形状或 大类/形状，条件之间为“且”
+        for (types, kept) in [(vec!["geometry"], Some(vec![0, 2])), (vec!["ellipse"], Some(vec![2])), (vec!["geometry/rectangle", "arrow-line"], Some(vec![0, 1])), (vec!["text"], None)] {
+            let filter = MessageFilter { element_types: types.into_iter().map(String::from).collect(), ..Default::default() };
+            assert_eq!(filter.board(&data), kept);
+        }
+        let both = MessageFilter { ops: vec![OpKind::Set], element_ids: vec!["a".into()], ..Default::default() };
+        assert_eq!(both.board(&data), None);
+    }
+
+    #[test]
+    fn element_changes_and_other_messages() {
+        let data = ElementChangesData {
+            added: vec![serde_json::json!({ "id": "a", "type": "geometry" })],
+            removed: vec![serde_json::json!({ "id": "b", "type": "freehand" })],
+            modified: Vec::new(),
+            timestamp: "2024-01-01T10:00:00Z".into(),
+        };
+        let filter = MessageFilter { element_ids: vec!["b".into()], ..Default::default() };
+        let kept = filter.elements(&data).unwrap();
+        assert!(kept.added.is_empty());
+        assert_eq!(kept.removed.len(), 1);
+        assert!(MessageFilter { ops: vec![OpKind::Set], ..Default::default() }.elements(&data).is_none());
+        // 元素变化没有来源，指定来源时全部过滤掉
+        assert!(MessageFilter { sources: vec!["p1".into()], ..Default::default() }.elements(&data).is_none());
+
+        let other = serde_json::json!({ "source_id": "p1" });
+        assert!(MessageFilter { sources: vec!["p1".into()], ..Default::default() }.other(&other));
+        assert!(!MessageFilter { sources: vec!["p2".into()], ..Default::default() }.other(&other));
+        assert!(!filter.other(&other));
+    }
+}
@@ -1,6 +1,21 @@
+mod capture;
+mod filter;
+mod message;
+mod output;
+mod stats;
+mod watch;
+
+use capture::ReplayPace;
 use clap::{Parser, Subcommand};
 use colored::*;
-use serde::{Deserialize, Serialize};
+use filter::MessageFilter;
+use message::{ElementChangesData, Message, ELEMENT_TOPIC};
+use output::{MessageRecord, OutputFormat, RecordWriter};
+use stats::Stats;
+use shared_types::dds_manager::BOARD_TOPIC;
+use shared_types::{BoardChangeData, Operation};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::thread;
@@ -23,309 +38,249 @@ enum Commands {
         /// DDS 域 ID
         #[arg(short, long, default_value = "150")]
         domain_id: u32,
-        
-        /// 要监听的主题
-        #[arg(short, long)]
-        topic: Option<String>,
-        
+
+        /// 要监听的主题，可重复指定
+        #[arg(short, long = "topic", default_values = [BOARD_TOPIC, ELEMENT_TOPIC])]
+        topics: Vec<String>,
+
+        #[command(flatten)]
+        filter: MessageFilter,
+
+        /// 输出格式；非 text 格式时状态信息打印到标准错误
+        #[arg(short, long, value_enum, default_value = "text")]
+        format: OutputFormat,
+
         /// 显示详细信息
         #[arg(short, long)]
         verbose: bool,
-        
+
         /// 监听时长（秒），0 表示无限期监听
         #[arg(short = 'T', long, default_value = "0")]
         timeout: u64,
+
+        /// 每隔多少秒输出一次吞吐和延迟汇总，0 表示只在退出时输出报告
+        #[arg(long, default_value = "0")]
+        stats_interval: u64,
     },
-    /// 运行测试模式
-    Test {
+    /// 把收到的每个样本录制到文件
+    Record {
         /// DDS 域 ID
         #[arg(short, long, default_value = "150")]
         domain_id: u32,
+
+        /// 要录制的主题，可重复指定
+        #[arg(short, long = "topic", default_values = [BOARD_TOPIC, ELEMENT_TOPIC])]
+        topics: Vec<String>,
+
+        /// 录制文件
+        #[arg(short, long, default_value = "capture.jsonl")]
+        output: PathBuf,
+
+        /// 录制时长（秒），0 表示直到 Ctrl+C
+        #[arg(short = 'T', long, default_value = "0")]
+        timeout: u64,
     },
-}
+    /// 把录制文件重新发布到域上
+    Replay {
+        /// 录制文件
+        input: PathBuf,
 
-#[derive(Debug, Serialize, Deserialize)]
-struct ElementChange {
-    operation_type: String,
-    element_id: Option<String>,
-    element_type: Option<String>,
-    element_data: Option<serde_json::Value>,
-    timestamp: String,
-}
+        /// DDS 域 ID
+        #[arg(short, long, default_value = "150")]
+        domain_id: u32,
 
-#[derive(Debug, Serialize, Deserialize)]
-struct BoardChangeData {
-    children_count: usize,
-    operation_count: usize,
-    operations: Vec<serde_json::Value>,
-    changes: Vec<ElementChange>,
-    timestamp: String,
-}
+        /// 回放倍速，0 表示不等待
+        #[arg(short, long, default_value = "1.0")]
+        speed: f64,
 
-#[derive(Debug, Serialize, Deserialize)]
-struct ElementChangesData {
-    added: Vec<serde_json::Value>,
-    removed: Vec<serde_json::Value>,
-    modified: Vec<serde_json::Value>,
-    timestamp: String,
+        /// 单步模式，每按一次回车发送一个样本
+        #[arg(long, conflicts_with = "speed")]
+        step: bool,
+    },
+    /// 实时重建白板并在终端中绘制
+    Watch {
+        /// DDS 域 ID
+        #[arg(short, long, default_value = "150")]
+        domain_id: u32,
+
+        /// 白板主题
+        #[arg(short, long, default_value = BOARD_TOPIC)]
+        topic: String,
+    },
+    /// 运行测试模式
+    Test {
+        /// DDS 域 ID
+        #[arg(short, long, default_value = "150")]
+        domain_id: u32,
+
+        /// 要测试的主题，可重复指定
+        #[arg(short, long = "topic", default_values = [BOARD_TOPIC, ELEMENT_TOPIC])]
+        topics: Vec<String>,
+    },
 }
 
 struct DDSSubscriber {
+    // 保持域参与者存活，订阅者依赖它
+    #[allow(dead_code)]
     domain: Domain,
-    board_subscriber: Subscriber,
-    element_subscriber: Subscriber,
+    subscribers: Vec<(String, Subscriber)>,
+    format: OutputFormat,
+}
+
+/// 状态信息：文本格式时打印到标准输出，其他格式时打印到标准错误，保持标准输出可被脚本解析
+fn status(format: OutputFormat, line: impl std::fmt::Display) {
+    if format == OutputFormat::Text {
+        println!("{}", line);
+    } else {
+        eprintln!("{}", line);
+    }
 }
 
 impl DDSSubscriber {
-    fn new(domain_id: u32) -> zrdds_safe::Result<Self> {
-        println!("{}", "🔌 初始化 DDS 订阅者连接...".cyan());
-        
+    fn new(domain_id: u32, topics: &[String], format: OutputFormat) -> zrdds_safe::Result<Self> {
+        status(format, "🔌 初始化 DDS 订阅者连接...".cyan());
+
+        // zrdds-safe 没有提供主题发现，无法按通配符订阅
+        if let Some(pattern) = topics.iter().find(|t| t.contains(['*', '?'])) {
+            return Err(zrdds_safe::Error::Other {
+                message: format!("不支持通配符主题 {}，请列出完整的主题名", pattern),
+            });
+        }
+
         let domain = Domain::builder()
             .domain_id(domain_id)
             .build()?;
-            
-        let board_subscriber = domain.create_subscriber("DrawnixBoardChanges")?;
-        let element_subscriber = domain.create_subscriber("DrawnixElementChanges")?;
-        
-        println!("{}", "✅ DDS 订阅者连接已建立".green());
-        println!("   📡 监听主题: DrawnixBoardChanges, DrawnixElementChanges");
-        println!("   🌐 域 ID: {}", domain_id);
-        
+
+        let mut subscribers = Vec::new();
+        for topic in topics {
+            subscribers.push((topic.clone(), domain.create_subscriber(topic)?));
+        }
+
+        status(format, "✅ DDS 订阅者连接已建立".green());
+        status(format, format!("   📡 监听主题: {}", topics.join(", ")));
+        status(format, format!("   🌐 域 ID: {}", domain_id));
+
         Ok(DDSSubscriber {
             domain,
-            board_subscriber,
-            element_subscriber,
+            subscribers,
+            format,
         })
     }
-    
-    fn listen(&mut self, verbose: bool, timeout: u64) -> zrdds_safe::Result<()> {
+
+    fn status(&self, line: impl std::fmt::Display) {
+        status(self.format, line);
+    }
+
+    fn listen(&mut self, filter: &MessageFilter, verbose: bool, timeout: u64, stats_interval: u64) -> zrdds_safe::Result<()> {
         let running = Arc::new(AtomicBool::new(true));
         let r = running.clone();
-        
+        let format = self.format;
+
         // 设置 Ctrl+C 处理
         ctrlc::set_handler(move || {
-            println!("\n{}", "🛑 接收到中断信号，正在优雅退出...".yellow());
+            status(format, format!("\n{}", "🛑 接收到中断信号，正在优雅退出...".yellow()));
             r.store(false, Ordering::SeqCst);
         }).expect("设置 Ctrl+C 处理器失败");
-        
+
         let start_time = std::time::Instant::now();
-        let mut board_count = 0;
-        let mut element_count = 0;
-        
-        println!("{}", "👂 开始监听 DDS 消息...".bright_green());
-        println!("{}", "   按 Ctrl+C 停止监听".dimmed());
+        let mut count = 0;
+        let mut filtered_count = 0;
+        let mut topic_counts: BTreeMap<String, u32> = BTreeMap::new();
+        let mut writer = RecordWriter::new(self.format);
+        let mut stats = Stats::new();
+
+        self.status("👂 开始监听 DDS 消息...".bright_green());
+        self.status("   按 Ctrl+C 停止监听".dimmed());
         if timeout > 0 {
-            println!("   ⏱️  监听时长: {} 秒", timeout);
+            self.status(format!("   ⏱️  监听时长: {} 秒", timeout));
+        }
+        if !filter.is_empty() {
+            self.status("   🔍 已启用消息过滤");
         }
-        println!("{}", "━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━".dimmed());
-        
+        self.status("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━".dimmed());
+
+        writer.begin();
         while running.load(Ordering::SeqCst) {
             // 检查超时
             if timeout > 0 && start_time.elapsed().as_secs() >= timeout {
-                println!("\n{}", "⏰ 监听时间到达，退出...".yellow());
+                self.status(format!("\n{}", "⏰ 监听时间到达，退出...".yellow()));
                 break;
             }
-            
-            // 检查白板变化消息
-            match self.board_subscriber.try_recv() {
-                Ok(Some(data)) => {
-                    board_count += 1;
-                    self.handle_board_message(data, board_count, verbose)?;
-                }
-                Ok(None) => {}
-                Err(e) => {
-                    eprintln!("{} {}", "❌ 白板消息接收错误:".red(), e);
-                }
-            }
-            
-            // 检查元素变化消息
-            match self.element_subscriber.try_recv() {
-                Ok(Some(data)) => {
-                    element_count += 1;
-                    self.handle_element_message(data, element_count, verbose)?;
-                }
-                Ok(None) => {}
-                Err(e) => {
-                    eprintln!("{} {}", "❌ 元素消息接收错误:".red(), e);
-                }
-            }
-            
-            // 短暂休眠避免过度消耗 CPU
-            thread::sleep(Duration::from_millis(10));
-        }
-        
-        println!("\n{}", "📊 监听统计:".bright_blue());
-        println!("   📋 白板变化消息: {}", board_count.to_string().bright_green());
-        println!("   🔧 元素变化消息: {}", element_count.to_string().bright_green());
-        println!("   ⏱️  总监听时长: {:.2} 秒", start_time.elapsed().as_secs_f64());
-        println!("{}", "🎉 监听结束".bright_green());
-        
-        Ok(())
-    }
-    
-    fn handle_board_message(&self, data: Vec<u8>, count: u32, verbose: bool) -> zrdds_safe::Result<()> {
-        let timestamp = chrono::Local::now().format("%H:%M:%S%.3f");
-        
-        match String::from_utf8(data.clone()) {
-            Ok(json_str) => {
-                match serde_json::from_str::<BoardChangeData>(&json_str) {
-                    Ok(board_data) => {
-                        println!("{} {} #{} 📋 白板变化", 
-                            format!("[{}]", timestamp).dimmed(),
-                            "📨".bright_green(),
-                            count.to_string().bright_yellow()
-                        );
-                        println!("   📊 元素数量: {}", board_data.children_count.to_string().cyan());
-                        println!("   🔧 操作数量: {}", board_data.operation_count.to_string().cyan());
-                        
-                        if verbose {
-                            println!("   📋 操作详情:");
-                            for (i, op) in board_data.operations.iter().enumerate() {
-                                println!("     [{}] {}", i, serde_json::to_string_pretty(op).unwrap_or_default());
-                            }
-                            
-                            println!("   🔍 变化详情:");
-                            for (i, change) in board_data.changes.iter().enumerate() {
-                                println!("     [{}] 类型: {}, ID: {:?}, 元素类型: {:?}", 
-                                    i, 
-                                    change.operation_type.bright_magenta(), 
-                                    change.element_id, 
-                                    change.element_type
-                                );
-                                if let Some(data) = &change.element_data {
-                                    println!("         数据: {}", serde_json::to_string_pretty(data).unwrap_or_default());
-                                }
-                            }
+
+            let mut busy = false;
+            for (topic, subscriber) in self.subscribers.iter_mut() {
+                match subscriber.try_recv() {
+                    Ok(Some(data)) => {
+                        busy = true;
+                        count += 1;
+                        *topic_counts.entry(topic.clone()).or_insert(0) += 1;
+                        let decoded = message::decode(topic, &data);
+                        stats.record(data.len(), &decoded);
+                        if !handle_message(topic, &data, decoded, count, filter, verbose, &mut writer) {
+                            filtered_count += 1;
                         }
                     }
+                    Ok(None) => {}
                     Err(e) => {
-                        println!("{} {} #{} ❌ JSON 解析失败: {}", 
-                            format!("[{}]", timestamp).dimmed(),
-                            "📨".bright_green(),
-                            count.to_string().bright_yellow(),
-                            e.to_string().red()
-                        );
-                        if verbose {
-                            println!("   原始数据: {}", json_str);
-                        }
+                        eprintln!("{} [{}] {}", "❌ 消息接收错误:".red(), topic, e);
                     }
                 }
             }
-            Err(e) => {
-                println!("{} {} #{} ❌ UTF-8 解码失败: {}", 
-                    format!("[{}]", timestamp).dimmed(),
-                    "📨".bright_green(),
-                    count.to_string().bright_yellow(),
-                    e.to_string().red()
-                );
-                if verbose {
-                    println!("   原始字节: {:?}", data);
-                }
-            }
-        }
-        
-        Ok(())
-    }
-    
-    fn handle_element_message(&self, data: Vec<u8>, count: u32, verbose: bool) -> zrdds_safe::Result<()> {
-        let timestamp = chrono::Local::now().format("%H:%M:%S%.3f");
-        
-        match String::from_utf8(data.clone()) {
-            Ok(json_str) => {
-                match serde_json::from_str::<ElementChangesData>(&json_str) {
-                    Ok(element_data) => {
-                        println!("{} {} #{} 🔧 元素变化", 
-                            format!("[{}]", timestamp).dimmed(),
-                            "📨".bright_green(),
-                            count.to_string().bright_yellow()
-                        );
-                        println!("   ➕ 新增: {}", element_data.added.len().to_string().green());
-                        println!("   ➖ 删除: {}", element_data.removed.len().to_string().red());
-                        println!("   🔄 修改: {}", element_data.modified.len().to_string().blue());
-                        
-                        if verbose {
-                            if !element_data.added.is_empty() {
-                                println!("   ➕ 新增元素:");
-                                for (i, elem) in element_data.added.iter().enumerate() {
-                                    println!("     [{}] {}", i, serde_json::to_string_pretty(elem).unwrap_or_default());
-                                }
-                            }
-                            
-                            if !element_data.removed.is_empty() {
-                                println!("   ➖ 删除元素:");
-                                for (i, elem) in element_data.removed.iter().enumerate() {
-                                    println!("     [{}] {}", i, serde_json::to_string_pretty(elem).unwrap_or_default());
-                                }
-                            }
-                            
-                            if !element_data.modified.is_empty() {
-                                println!("   🔄 修改元素:");
-                                for (i, elem) in element_data.modified.iter().enumerate() {
-                                    println!("     [{}] {}", i, serde_json::to_string_pretty(elem).unwrap_or_default());
-                                }
-                            }
-                        }
-                    }
-                    Err(e) => {
-                        println!("{} {} #{} ❌ JSON 解析失败: {}", 
-                            format!("[{}]", timestamp).dimmed(),
-                            "📨".bright_green(),
-                            count.to_string().bright_yellow(),
-                            e.to_string().red()
-                        );
-                        if verbose {
-                            println!("   原始数据: {}", json_str);
-                        }
-                    }
-                }
+
+            if let Some(line) = stats.periodic(stats_interval) {
+                self.status(line.bright_blue());
             }
-            Err(e) => {
-                println!("{} {} #{} ❌ UTF-8 解码失败: {}", 
-                    format!("[{}]", timestamp).dimmed(),
-                    "📨".bright_green(),
-                    count.to_string().bright_yellow(),
-                    e.to_string().red()
-                );
-                if verbose {
-                    println!("   原始字节: {:?}", data);
-                }
+
+            if !busy {
+                // 短暂休眠避免过度消耗 CPU
+                thread::sleep(Duration::from_millis(10));
             }
         }
-        
+        writer.finish();
+
+        self.status(format!("\n{}", "📊 监听统计:".bright_blue()));
+        for (topic, _) in &self.subscribers {
+            let received = topic_counts.get(topic).copied().unwrap_or(0);
+            self.status(format!("   📨 {}: {}", topic, received.to_string().bright_green()));
+        }
+        if !filter.is_empty() {
+            self.status(format!("   🔍 被过滤的消息: {}", filtered_count.to_string().yellow()));
+        }
+        self.status(format!("   ⏱️  总监听时长: {:.2} 秒", start_time.elapsed().as_secs_f64()));
+        for line in stats.report() {
+            self.status(line);
+        }
+        self.status("🎉 监听结束".bright_green());
+
         Ok(())
     }
-    
+
     fn run_test(&mut self) -> zrdds_safe::Result<()> {
         println!("{}", "🧪 启动测试模式".bright_blue());
         println!("   监听 10 秒钟，然后显示统计信息");
-        
+
         let start_time = std::time::Instant::now();
-        let mut messages = Vec::new();
-        
+        let mut topic_counts: BTreeMap<String, usize> = BTreeMap::new();
+
         while start_time.elapsed().as_secs() < 10 {
-            // 检查白板变化消息
-            if let Ok(Some(data)) = self.board_subscriber.try_recv() {
-                messages.push(("board", data, chrono::Local::now()));
-            }
-            
-            // 检查元素变化消息
-            if let Ok(Some(data)) = self.element_subscriber.try_recv() {
-                messages.push(("element", data, chrono::Local::now()));
+            for (topic, subscriber) in self.subscribers.iter_mut() {
+                if let Ok(Some(_)) = subscriber.try_recv() {
+                    *topic_counts.entry(topic.clone()).or_insert(0) += 1;
+                }
             }
-            
+
             thread::sleep(Duration::from_millis(100));
         }
-        
+
+        let total: usize = topic_counts.values().sum();
         println!("\n{}", "📊 测试结果:".bright_blue());
-        println!("   📨 接收到消息总数: {}", messages.len().to_string().bright_green());
-        
-        let board_count = messages.iter().filter(|(t, _, _)| *t == "board").count();
-        let element_count = messages.iter().filter(|(t, _, _)| *t == "element").count();
-        
-        println!("   📋 白板变化消息: {}", board_count.to_string().cyan());
-        println!("   🔧 元素变化消息: {}", element_count.to_string().cyan());
-        
-        if messages.is_empty() {
+        println!("   📨 接收到消息总数: {}", total.to_string().bright_green());
+        for (topic, _) in &self.subscribers {
+            let received = topic_counts.get(topic).copied().unwrap_or(0);
+            println!("   📋 {}: {}", topic, received.to_string().cyan());
+        }
+
+        if total == 0 {
             println!("\n{}", "⚠️  未接收到任何消息".yellow());
             println!("   可能的原因:");
             println!("   1. 发布者尚未启动");
@@ -334,24 +289,162 @@ impl DDSSubscriber {
         } else {
             println!("\n{}", "✅ 测试成功！DDS 通信正常".bright_green());
         }
-        
+
         Ok(())
     }
 }
 
+/// 解码、过滤并输出一条消息；被过滤掉时返回 false
+fn handle_message(
+    topic: &str,
+    data: &[u8],
+    decoded: Result<Message, String>,
+    count: u32,
+    filter: &MessageFilter,
+    verbose: bool,
+    writer: &mut RecordWriter,
+) -> bool {
+    let timestamp = chrono::Local::now().format("%H:%M:%S%.3f");
+    let header = format!(
+        "{} {} #{}",
+        format!("[{}]", timestamp).dimmed(),
+        "📨".bright_green(),
+        count.to_string().bright_yellow()
+    );
+
+    match decoded {
+        Ok(Message::Board(board_data)) => {
+            let Some(kept) = filter.board(&board_data) else { return false };
+            if !writer.is_text() {
+                writer.write(&MessageRecord::board(count, topic, data.len(), &board_data, &kept));
+                return true;
+            }
+            println!("{} 📋 白板变化 {}", header, topic.dimmed());
+            print_board_change(&board_data, &kept, verbose);
+        }
+        Ok(Message::Elements(element_data)) => {
+            let Some(element_data) = filter.elements(&element_data) else { return false };
+            if !writer.is_text() {
+                writer.write(&MessageRecord::elements(count, topic, data.len(), &element_data));
+                return true;
+            }
+            println!("{} 🔧 元素变化 {}", header, topic.dimmed());
+            print_element_changes(&element_data, verbose);
+        }
+        Ok(Message::Other(value)) => {
+            if !filter.other(&value) {
+                return false;
+            }
+            if !writer.is_text() {
+                writer.write(&MessageRecord::other(count, topic, data.len(), &value));
+                return true;
+            }
+            println!("{} 📦 {}", header, topic.cyan());
+            if verbose {
+                println!("   {}", serde_json::to_string_pretty(&value).unwrap_or_default());
+            } else {
+                println!("   {}", value);
+            }
+        }
+        Err(e) => {
+            // 解码失败的消息无法判断是否满足过滤条件，总是显示
+            if !writer.is_text() {
+                writer.write(&MessageRecord::error(count, topic, data.len(), e));
+                return true;
+            }
+            println!("{} {} ❌ {}", header, topic.dimmed(), e.red());
+            if verbose {
+                println!("   原始数据: {}", String::from_utf8_lossy(data));
+            }
+        }
+    }
+    true
+}
+
+fn print_board_change(board_data: &BoardChangeData, kept: &[usize], verbose: bool) {
+    println!("   🆔 来源: {}", board_data.source_id.cyan());
+    println!("   🕒 发布时间: {}", board_data.timestamp.cyan());
+    if kept.len() == board_data.operations.len() {
+        println!("   🔧 操作数量: {}", board_data.operations.len().to_string().cyan());
+    } else {
+        println!(
+            "   🔧 操作数量: {}（显示 {} 个）",
+            board_data.operations.len().to_string().cyan(),
+            kept.len()
+        );
+    }
+
+    for &i in kept {
+        let op = &board_data.operations[i];
+        println!("     [{}] {}", i, describe_operation(op));
+        if verbose {
+            println!("         节点: {}", serde_json::to_string_pretty(op.node()).unwrap_or_default());
+        }
+    }
+}
+
+fn print_element_changes(element_data: &ElementChangesData, verbose: bool) {
+    println!("   ➕ 新增: {}", element_data.added.len().to_string().green());
+    println!("   ➖ 删除: {}", element_data.removed.len().to_string().red());
+    println!("   🔄 修改: {}", element_data.modified.len().to_string().blue());
+
+    if verbose {
+        if !element_data.added.is_empty() {
+            println!("   ➕ 新增元素:");
+            for (i, elem) in element_data.added.iter().enumerate() {
+                println!("     [{}] {}", i, serde_json::to_string_pretty(elem).unwrap_or_default());
+            }
+        }
+
+        if !element_data.removed.is_empty() {
+            println!("   ➖ 删除元素:");
+            for (i, elem) in element_data.removed.iter().enumerate() {
+                println!("     [{}] {}", i, serde_json::to_string_pretty(elem).unwrap_or_default());
+            }
+        }
+
+        if !element_data.modified.is_empty() {
+            println!("   🔄 修改元素:");
+            for (i, elem) in element_data.modified.iter().enumerate() {
+                println!("     [{}] {}", i, serde_json::to_string_pretty(elem).unwrap_or_default());
+            }
+        }
+    }
+}
+
+/// 一行描述一个操作：类型、路径和节点概要
+fn describe_operation(op: &Operation) -> String {
+    // 按线上的 "type" 字段显示，而不是按反序列化出的变体
+    let kind = match op.op_type() {
+        "insert_node" => "➕ insert_node".green(),
+        "remove_node" => "➖ remove_node".red(),
+        "set_node" => "✏️  set_node".blue(),
+        other => other.yellow(),
+    };
+    let node = op.node();
+    let mut line = format!("{} @ {:?} {}", kind, op.path(), node.id.bright_magenta());
+    if !node.element_type.is_empty() {
+        line.push_str(&format!(" ({}", node.element_type));
+        if !node.shape.is_empty() {
+            line.push_str(&format!("/{}", node.shape));
+        }
+        line.push(')');
+    }
+    if !node.points.is_empty() {
+        let points: Vec<String> = node.points.iter().map(|p| format!("({}, {})", p.0, p.1)).collect();
+        line.push_str(&format!(" 坐标 {}", points.join(" → ")));
+    }
+    line
+}
+
 fn main() {
     let cli = Cli::parse();
-    
+
     match cli.command {
-        Commands::Listen { domain_id, topic, verbose, timeout } => {
-            match DDSSubscriber::new(domain_id) {
+        Commands::Listen { domain_id, topics, filter, format, verbose, timeout, stats_interval } => {
+            match DDSSubscriber::new(domain_id, &topics, format) {
                 Ok(mut subscriber) => {
-                    if let Some(topic_name) = topic {
-                        println!("⚠️  注意: 当前版本不支持自定义主题过滤，将监听所有主题");
-                        println!("   请求的主题: {}", topic_name);
-                    }
-                    
-                    if let Err(e) = subscriber.listen(verbose, timeout) {
+                    if let Err(e) = subscriber.listen(&filter, verbose, timeout, stats_interval) {
                         eprintln!("{} {}", "❌ 监听失败:".red(), e);
                         std::process::exit(1);
                     }
@@ -362,8 +455,27 @@ fn main() {
                 }
             }
         }
-        Commands::Test { domain_id } => {
-            match DDSSubscriber::new(domain_id) {
+        Commands::Record { domain_id, topics, output, timeout } => {
+            if let Err(e) = capture::record(domain_id, &topics, &output, timeout) {
+                eprintln!("{} {}", "❌ 录制失败:".red(), e);
+                std::process::exit(1);
+            }
+        }
+        Commands::Replay { input, domain_id, speed, step } => {
+            let pace = if step { ReplayPace::Step } else { ReplayPace::Timed(speed) };
+            if let Err(e) = capture::replay(domain_id, &input, pace) {
+                eprintln!("{} {}", "❌ 回放失败:".red(), e);
+                std::process::exit(1);
+            }
+        }
+        Commands::Watch { domain_id, topic } => {
+            if let Err(e) = watch::watch(domain_id, &topic) {
+                eprintln!("{} {}", "❌ 实时视图失败:".red(), e);
+                std::process::exit(1);
+            }
+        }
+        Commands::Test { domain_id, topics } => {
+            match DDSSubscriber::new(domain_id, &topics, OutputFormat::Text) {
                 Ok(mut subscriber) => {
                     if let Err(e) = subscriber.run_test() {
                         eprintln!("{} {}", "❌ 测试失败:".red(), e);
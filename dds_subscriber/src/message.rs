@@ -0,0 +1,49 @@
+//! 订阅者收到的各类消息的解码
+
+use serde::{Deserialize, Serialize};
+use shared_types::dds_manager::BOARD_TOPIC;
+use shared_types::BoardChangeData;
+
+/// 元素级变化主题（由 test_publisher 等工具发布）
+pub const ELEMENT_TOPIC: &str = "DrawnixElementChanges";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ElementChangesData {
+    pub added: Vec<serde_json::Value>,
+    pub removed: Vec<serde_json::Value>,
+    pub modified: Vec<serde_json::Value>,
+    pub timestamp: String,
+}
+
+/// 解码后的消息
+pub enum Message {
+    Board(BoardChangeData),
+    Elements(ElementChangesData),
+    /// 其他主题上无法识别结构的 JSON
+    Other(serde_json::Value),
+}
+
+/// 按主题解码：已知主题必须符合对应结构，其他主题依次尝试已知结构
+pub fn decode(topic: &str, data: &[u8]) -> Result<Message, String> {
+    let text = std::str::from_utf8(data).map_err(|e| format!("UTF-8 解码失败: {}", e))?;
+    let value: serde_json::Value = serde_json::from_str(text).map_err(|e| format!("JSON 解析失败: {}", e))?;
+
+    if topic == BOARD_TOPIC {
+        return serde_json::from_value(value)
+            .map(Message::Board)
+            .map_err(|e| format!("JSON 解析失败: {}", e));
+    }
+    if topic == ELEMENT_TOPIC {
+        return serde_json::from_value(value)
+            .map(Message::Elements)
+            .map_err(|e| format!("JSON 解析失败: {}", e));
+    }
+
+    if let Ok(board) = serde_json::from_value::<BoardChangeData>(value.clone()) {
+        return Ok(Message::Board(board));
+    }
+    if let Ok(elements) = serde_json::from_value::<ElementChangesData>(value.clone()) {
+        return Ok(Message::Elements(elements));
+    }
+    Ok(Message::Other(value))
+}
@@ -0,0 +1,243 @@
+//! 供脚本和分析工具使用的结构化输出
+
+use crate::message::ElementChangesData;
+use chrono::SecondsFormat;
+use clap::ValueEnum;
+use serde::Serialize;
+use shared_types::{BoardChangeData, Operation};
+use std::io::Write;
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// 彩色文本，供人阅读
+    Text,
+    /// 一个 JSON 数组，退出时结束
+    Json,
+    /// 每行一个 JSON 对象
+    Ndjson,
+    /// 带表头的 CSV
+    Csv,
+}
+
+/// 一个操作的概要
+#[derive(Serialize)]
+pub struct OperationSummary {
+    #[serde(rename = "type")]
+    pub op_type: String,
+    pub path: Vec<usize>,
+    pub id: String,
+    /// "类型/形状"，如 geometry/rectangle
+    pub element_type: String,
+}
+
+impl OperationSummary {
+    fn new(op_type: &str, path: Vec<usize>, id: &str, element_type: &str, shape: &str) -> Self {
+        OperationSummary {
+            op_type: op_type.to_string(),
+            path,
+            id: id.to_string(),
+            element_type: if shape.is_empty() {
+                element_type.to_string()
+            } else {
+                format!("{}/{}", element_type, shape)
+            },
+        }
+    }
+
+    pub fn of(op: &Operation) -> Self {
+        let node = op.node();
+        OperationSummary::new(op.op_type(), op.path().to_vec(), &node.id, &node.element_type, &node.shape)
+    }
+}
+
+/// 每条收到的消息对应一条记录
+#[derive(Serialize)]
+pub struct MessageRecord {
+    /// 订阅者本地的接收序号，从 1 开始
+    pub received_index: u32,
+    pub topic: String,
+    pub received_at: String,
+    /// "board" | "elements" | "other" | "error"
+    pub kind: &'static str,
+    pub size: usize,
+    pub source_id: Option<String>,
+    /// 发布者按 source_id 填写的序号，旧版本发布者没有
+    pub seq: Option<u64>,
+    /// 消息自带的发布时间
+    pub published_at: Option<String>,
+    pub operation_count: usize,
+    /// 通过过滤条件的操作
+    pub operations: Vec<OperationSummary>,
+    pub error: Option<String>,
+}
+
+impl MessageRecord {
+    fn new(received_index: u32, topic: &str, size: usize, kind: &'static str) -> Self {
+        MessageRecord {
+            received_index,
+            topic: topic.to_string(),
+            received_at: chrono::Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true),
+            kind,
+            size,
+            source_id: None,
+            seq: None,
+            published_at: None,
+            operation_count: 0,
+            operations: Vec::new(),
+            error: None,
+        }
+    }
+
+    pub fn board(received_index: u32, topic: &str, size: usize, change: &BoardChangeData, kept: &[usize]) -> Self {
+        let mut record = MessageRecord::new(received_index, topic, size, "board");
+        record.source_id = Some(change.source_id.clone());
+        record.seq = change.seq;
+        record.published_at = Some(change.timestamp.clone());
+        record.operation_count = change.operations.len();
+        record.operations = kept.iter().map(|&i| OperationSummary::of(&change.operations[i])).collect();
+        record
+    }
+
+    /// 元素变化的新增/删除/修改分别记为 insert_node/remove_node/set_node
+    pub fn elements(received_index: u32, topic: &str, size: usize, data: &ElementChangesData) -> Self {
+        let mut record = MessageRecord::new(received_index, topic, size, "elements");
+        record.published_at = Some(data.timestamp.clone());
+        for (items, op_type) in [(&data.added, "insert_node"), (&data.removed, "remove_node"), (&data.modified, "set_node")] {
+            for item in items {
+                let field = |key: &str| item.get(key).and_then(|v| v.as_str()).unwrap_or_default();
+                record
+                    .operations
+                    .push(OperationSummary::new(op_type, Vec::new(), field("id"), field("type"), field("shape")));
+            }
+        }
+        record.operation_count = record.operations.len();
+        record
+    }
+
+    pub fn other(received_index: u32, topic: &str, size: usize, value: &serde_json::Value) -> Self {
+        let mut record = MessageRecord::new(received_index, topic, size, "other");
+        let field = |key: &str| value.get(key).and_then(|v| v.as_str()).map(String::from);
+        record.source_id = field("source_id");
+        record.seq = value.get("seq").and_then(|v| v.as_u64());
+        record.published_at = field("timestamp");
+        record
+    }
+
+    pub fn error(received_index: u32, topic: &str, size: usize, error: String) -> Self {
+        let mut record = MessageRecord::new(received_index, topic, size, "error");
+        record.error = Some(error);
+        record
+    }
+
+    fn csv_row(&self) -> String {
+        let operations: Vec<String> = self
+            .operations
+            .iter()
+            .map(|op| format!("{} {:?} {}", op.op_type, op.path, op.id))
+            .collect();
+        [
+            self.received_index.to_string(),
+            self.received_at.clone(),
+            self.topic.clone(),
+            self.kind.to_string(),
+            self.size.to_string(),
+            self.source_id.clone().unwrap_or_default(),
+            self.seq.map(|seq| seq.to_string()).unwrap_or_default(),
+            self.published_at.clone().unwrap_or_default(),
+            self.operation_count.to_string(),
+            operations.join("; "),
+            self.error.clone().unwrap_or_default(),
+        ]
+        .iter()
+        .map(|field| csv_field(field))
+        .collect::<Vec<_>>()
+        .join(",")
+    }
+}
+
+const CSV_HEADER: &str =
+    "received_index,received_at,topic,kind,size,source_id,seq,published_at,operation_count,operations,error";
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// 把记录按所选格式写到标准输出
+pub struct RecordWriter {
+    format: OutputFormat,
+    written: usize,
+}
+
+impl RecordWriter {
+    pub fn new(format: OutputFormat) -> Self {
+        RecordWriter { format, written: 0 }
+    }
+
+    pub fn is_text(&self) -> bool {
+        self.format == OutputFormat::Text
+    }
+
+    pub fn begin(&mut self) {
+        match self.format {
+            OutputFormat::Json => print!("["),
+            OutputFormat::Csv => println!("{}", CSV_HEADER),
+            OutputFormat::Text | OutputFormat::Ndjson => {}
+        }
+    }
+
+    pub fn write(&mut self, record: &MessageRecord) {
+        match self.format {
+            OutputFormat::Json => {
+                // 逗号写在下一条记录前面，每条记录写完立即刷新，便于边收边处理
+                let separator = if self.written == 0 { "" } else { "," };
+                print!("{}\n  {}", separator, serde_json::to_string(record).unwrap_or_default());
+                std::io::stdout().flush().ok();
+            }
+            OutputFormat::Ndjson => println!("{}", serde_json::to_string(record).unwrap_or_default()),
+            OutputFormat::Csv => println!("{}", record.csv_row()),
+            OutputFormat::Text => {}
+        }
+        self.written += 1;
+    }
+
+    pub fn finish(&mut self) {
+        if self.format == OutputFormat::Json {
+            println!("\n]");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn csv_fields_are_quoted_when_needed() {
+        assert_eq!(csv_field("plain"), "plain");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_field("two\nlines"), "\"two\nlines\"");
+    }
+
+    #[test]
+    fn csv_rows_match_the_header() {
+        let change: BoardChangeData = serde_json::from_value(serde_json::json!({
+            "operations": [{ "type": "insert_node", "path": [0, 1], "node": { "id": "a", "type": "geometry", "shape": "rectangle" } }],
+            "timestamp": "2024-01-01T10:00:00Z",
+            "source_id": "p1",
+            "seq": 7,
+        }))
+        .unwrap();
+        let record = MessageRecord::board(3, "DrawnixBoardChanges", 120, &change, &[0]);
+        let row = record.csv_row();
+        assert!(row.starts_with("3,"));
+        assert!(row.ends_with(",p1,7,2024-01-01T10:00:00Z,1,\"insert_node [0, 1] a\","), "{}", row);
+
+        let error = MessageRecord::error(4, "t", 3, "坏的, 消息".into());
+        assert!(error.csv_row().ends_with(",,,,0,,\"坏的, 消息\""), "{}", error.csv_row());
+    }
+}
@@ -0,0 +1,360 @@
+//! 延迟与吞吐统计
+
+use crate::message::Message;
+use std::collections::BTreeMap;
+use std::time::Instant;
+
+/// 固定分桶的直方图；分位数取所在桶的上界
+struct Histogram {
+    bounds: &'static [f64],
+    counts: Vec<u64>,
+    count: u64,
+    sum: f64,
+    min: f64,
+    max: f64,
+}
+
+const LATENCY_BOUNDS_MS: &[f64] = &[1.0, 2.0, 5.0, 10.0, 20.0, 50.0, 100.0, 200.0, 500.0, 1000.0, 2000.0, 5000.0];
+const SIZE_BOUNDS: &[f64] = &[128.0, 256.0, 512.0, 1024.0, 2048.0, 4096.0, 8192.0, 16384.0, 32768.0, 65536.0];
+/// 每个来源最多记住的缺口数，超出时丢掉最早的，之后补到的消息不再从丢失数中扣除
+const MAX_HOLES: usize = 1024;
+
+impl Histogram {
+    fn new(bounds: &'static [f64]) -> Self {
+        Histogram { bounds, counts: vec![0; bounds.len() + 1], count: 0, sum: 0.0, min: f64::MAX, max: 0.0 }
+    }
+
+    fn add(&mut self, value: f64) {
+        let bucket = self.bounds.iter().position(|&b| value <= b).unwrap_or(self.bounds.len());
+        self.counts[bucket] += 1;
+        self.count += 1;
+        self.sum += value;
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+    }
+
+    fn mean(&self) -> f64 {
+        if self.count == 0 { 0.0 } else { self.sum / self.count as f64 }
+    }
+
+    fn percentile(&self, p: f64) -> f64 {
+        let rank = ((self.count as f64) * p).ceil().max(1.0) as u64;
+        let mut seen = 0;
+        for (i, &c) in self.counts.iter().enumerate() {
+            seen += c;
+            if seen >= rank {
+                return self.bounds.get(i).copied().unwrap_or(self.max);
+            }
+        }
+        self.max
+    }
+
+    /// 每个非空桶一行，条形长度按最多的桶缩放
+    fn bars(&self, unit: &str) -> Vec<String> {
+        let peak = self.counts.iter().copied().max().unwrap_or(0).max(1);
+        let mut lines = Vec::new();
+        for (i, &c) in self.counts.iter().enumerate() {
+            if c == 0 {
+                continue;
+            }
+            let label = match self.bounds.get(i) {
+                Some(b) => format!("≤{}", format_amount(*b, unit)),
+                None => format!(">{}", format_amount(self.bounds[self.bounds.len() - 1], unit)),
+            };
+            let width = ((c as f64 / peak as f64) * 40.0).ceil() as usize;
+            lines.push(format!("{:>10} {} {}", label, "█".repeat(width), c));
+        }
+        lines
+    }
+}
+
+fn format_amount(value: f64, unit: &str) -> String {
+    if unit == "B" && value >= 1024.0 {
+        format!("{}KB", value / 1024.0)
+    } else {
+        format!("{}{}", value, unit)
+    }
+}
+
+#[derive(Default)]
+struct SourceStats {
+    messages: u64,
+    latency: Option<Histogram>,
+    last_seq: Option<u64>,
+    /// 序号跳跃的次数及丢失的消息数
+    gaps: u64,
+    missing: u64,
+    /// 序号小于等于上一条（乱序或重复）
+    reordered: u64,
+    /// 尚未补上的缺口，起始序号 → 结束序号（含）
+    holes: BTreeMap<u64, u64>,
+    /// 序号回到 1 的次数（发布者重启）
+    restarts: u64,
+    /// 发布时间晚于接收时间（时钟不同步）
+    clock_skew: u64,
+}
+
+impl SourceStats {
+    fn record_seq(&mut self, seq: u64) {
+        match self.last_seq {
+            Some(last) if seq == last + 1 => {}
+            Some(last) if seq > last + 1 => {
+                self.gaps += 1;
+                self.missing += seq - last - 1;
+                self.holes.insert(last + 1, seq - 1);
+                if self.holes.len() > MAX_HOLES {
+                    self.holes.pop_first();
+                }
+            }
+            // 序号回到 1 说明发布者重启了，之前的缺口不会再补上
+            Some(_) if seq == 1 => {
+                self.restarts += 1;
+                self.holes.clear();
+            }
+            Some(_) => {
+                self.reordered += 1;
+                // 迟到的消息落在缺口里：它并没有丢
+                let hole = self.holes.range(..=seq).next_back().map(|(&start, &end)| (start, end));
+                if let Some((start, end)) = hole.filter(|&(_, end)| seq <= end) {
+                    self.missing -= 1;
+                    self.holes.remove(&start);
+                    if start < seq {
+                        self.holes.insert(start, seq - 1);
+                    }
+                    if seq < end {
+                        self.holes.insert(seq + 1, end);
+                    }
+                }
+                return;
+            }
+            None => {}
+        }
+        self.last_seq = Some(seq);
+    }
+}
+
+/// 当前统计周期
+struct Window {
+    started: Instant,
+    messages: u64,
+    bytes: u64,
+    latency_sum: f64,
+    latency_count: u64,
+    failures: u64,
+}
+
+impl Window {
+    fn new() -> Self {
+        Window { started: Instant::now(), messages: 0, bytes: 0, latency_sum: 0.0, latency_count: 0, failures: 0 }
+    }
+}
+
+pub struct Stats {
+    started: Instant,
+    messages: u64,
+    bytes: u64,
+    failures: u64,
+    sizes: Histogram,
+    latency: Histogram,
+    sources: BTreeMap<String, SourceStats>,
+    window: Window,
+}
+
+impl Stats {
+    pub fn new() -> Self {
+        Stats {
+            started: Instant::now(),
+            messages: 0,
+            bytes: 0,
+            failures: 0,
+            sizes: Histogram::new(SIZE_BOUNDS),
+            latency: Histogram::new(LATENCY_BOUNDS_MS),
+            sources: BTreeMap::new(),
+            window: Window::new(),
+        }
+    }
+
+    /// 统计一条收到的消息（过滤之前）
+    pub fn record(&mut self, size: usize, decoded: &Result<Message, String>) {
+        self.messages += 1;
+        self.bytes += size as u64;
+        self.sizes.add(size as f64);
+        self.window.messages += 1;
+        self.window.bytes += size as u64;
+
+        let (source_id, timestamp, seq) = match decoded {
+            Ok(Message::Board(change)) => (change.source_id.clone(), Some(change.timestamp.clone()), change.seq),
+            Ok(Message::Elements(data)) => ("-".to_string(), Some(data.timestamp.clone()), None),
+            Ok(Message::Other(value)) => {
+                let field = |key: &str| value.get(key).and_then(|v| v.as_str()).map(String::from);
+                (field("source_id").unwrap_or_else(|| "-".to_string()), field("timestamp"), None)
+            }
+            Err(_) => {
+                self.failures += 1;
+                self.window.failures += 1;
+                return;
+            }
+        };
+
+        let source = self.sources.entry(source_id).or_default();
+        source.messages += 1;
+
+        if let Some(published) = timestamp.and_then(|t| chrono::DateTime::parse_from_rfc3339(&t).ok()) {
+            let mut latency_ms = (chrono::Utc::now() - published.with_timezone(&chrono::Utc)).num_microseconds().unwrap_or(0) as f64 / 1000.0;
+            if latency_ms < 0.0 {
+                source.clock_skew += 1;
+                latency_ms = 0.0;
+            }
+            source.latency.get_or_insert_with(|| Histogram::new(LATENCY_BOUNDS_MS)).add(latency_ms);
+            self.latency.add(latency_ms);
+            self.window.latency_sum += latency_ms;
+            self.window.latency_count += 1;
+        }
+
+        if let Some(seq) = seq {
+            source.record_seq(seq);
+        }
+    }
+
+    /// 距上次周期汇总超过 `interval_secs` 时返回一行汇总并开始新周期
+    pub fn periodic(&mut self, interval_secs: u64) -> Option<String> {
+        let elapsed = self.window.started.elapsed().as_secs_f64();
+        if interval_secs == 0 || elapsed < interval_secs as f64 {
+            return None;
+        }
+        let w = &self.window;
+        let latency = if w.latency_count > 0 {
+            format!("{:.1} ms", w.latency_sum / w.latency_count as f64)
+        } else {
+            "-".to_string()
+        };
+        let line = format!(
+            "📈 [{:.0}s] {} 条（{:.1} 条/秒，{:.1} KB/秒），平均延迟 {}，解码失败 {}",
+            self.started.elapsed().as_secs_f64(),
+            w.messages,
+            w.messages as f64 / elapsed,
+            w.bytes as f64 / 1024.0 / elapsed,
+            latency,
+            w.failures
+        );
+        self.window = Window::new();
+        Some(line)
+    }
+
+    /// 退出时的完整报告
+    pub fn report(&self) -> Vec<String> {
+        let elapsed = self.started.elapsed().as_secs_f64().max(0.001);
+        let mut lines = vec!["📈 统计报告:".to_string()];
+        lines.push(format!(
+            "   📨 消息总数: {}，平均 {:.1} 条/秒，{:.1} KB/秒",
+            self.messages,
+            self.messages as f64 / elapsed,
+            self.bytes as f64 / 1024.0 / elapsed
+        ));
+        let failure_rate = if self.messages > 0 { self.failures as f64 * 100.0 / self.messages as f64 } else { 0.0 };
+        lines.push(format!("   ❌ 解码失败: {}（{:.1}%）", self.failures, failure_rate));
+        if self.messages == 0 {
+            return lines;
+        }
+
+        lines.push(format!(
+            "   📦 消息大小: 最小 {:.0}，平均 {:.0}，最大 {:.0} 字节，P50 ≤ {}，P95 ≤ {}",
+            self.sizes.min,
+            self.sizes.mean(),
+            self.sizes.max,
+            format_amount(self.sizes.percentile(0.5), "B"),
+            format_amount(self.sizes.percentile(0.95), "B")
+        ));
+        lines.extend(self.sizes.bars("B").into_iter().map(|l| format!("      {}", l)));
+
+        if !self.sources.is_empty() {
+            lines.push("   🆔 按来源:".to_string());
+        }
+        for (source_id, source) in &self.sources {
+            let mut line = format!("      {} - {} 条", source_id, source.messages);
+            if let Some(latency) = &source.latency {
+                line.push_str(&format!(
+                    "，延迟 平均 {:.1} ms / P50 ≤ {} ms / P95 ≤ {} ms / P99 ≤ {} ms / 最大 {:.1} ms",
+                    latency.mean(),
+                    latency.percentile(0.5),
+                    latency.percentile(0.95),
+                    latency.percentile(0.99),
+                    latency.max
+                ));
+            }
+            if source.last_seq.is_some() {
+                line.push_str(&format!("，序号缺口 {}（丢失 {} 条），乱序 {}", source.gaps, source.missing, source.reordered));
+            }
+            if source.restarts > 0 {
+                line.push_str(&format!("，重启 {} 次", source.restarts));
+            }
+            if source.clock_skew > 0 {
+                line.push_str(&format!("，⚠️ {} 条发布时间晚于接收时间（时钟不同步）", source.clock_skew));
+            }
+            lines.push(line);
+        }
+
+        if self.latency.count > 0 {
+            lines.push("   ⏱️  端到端延迟分布:".to_string());
+            lines.extend(self.latency.bars(" ms").into_iter().map(|l| format!("      {}", l)));
+        }
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared_types::BoardChangeData;
+
+    fn board(source_id: &str, seq: u64) -> Result<Message, String> {
+        let change: BoardChangeData = serde_json::from_value(serde_json::json!({
+            "operations": [], "timestamp": chrono::Utc::now().to_rfc3339(), "source_id": source_id, "seq": seq,
+        }))
+        .unwrap();
+        Ok(Message::Board(change))
+    }
+
+    #[test]
+    fn percentiles_use_bucket_upper_bounds() {
+        let mut histogram = Histogram::new(LATENCY_BOUNDS_MS);
+        for value in [0.5, 1.5, 3.0, 3.0, 8.0, 15.0, 40.0, 90.0, 150.0, 9000.0] {
+            histogram.add(value);
+        }
+        assert_eq!(histogram.percentile(0.0), 1.0);
+        assert_eq!(histogram.percentile(0.5), 10.0);
+        assert_eq!(histogram.percentile(0.9), 200.0);
+        // 超出最后一个桶时取最大值
+        assert_eq!(histogram.percentile(1.0), 9000.0);
+        assert_eq!((histogram.min, histogram.max, histogram.count), (0.5, 9000.0, 10));
+        assert_eq!(Histogram::new(SIZE_BOUNDS).mean(), 0.0);
+    }
+
+    #[test]
+    fn sequence_gaps_reorders_and_restarts() {
+        let mut stats = Stats::new();
+        // 迟到的 4 补上了 3..4 的缺口，只丢了 3 和 7、8
+        for seq in [1, 2, 5, 4, 6, 9, 1, 2] {
+            stats.record(100, &board("p1", seq));
+        }
+        // 第一条消息的序号不算缺口；重复的消息不影响丢失数
+        for seq in [3, 6, 4, 5, 5, 4] {
+            stats.record(100, &board("p2", seq));
+        }
+        // 只发过一条就重启
+        for seq in [1, 1, 2] {
+            stats.record(100, &board("p3", seq));
+        }
+        stats.record(10, &Err("JSON 解析失败".into()));
+
+        let p1 = &stats.sources["p1"];
+        assert_eq!((p1.messages, p1.gaps, p1.missing, p1.reordered, p1.restarts, p1.last_seq), (8, 2, 3, 1, 1, Some(2)));
+        let p2 = &stats.sources["p2"];
+        assert_eq!((p2.gaps, p2.missing, p2.reordered, p2.last_seq), (1, 0, 4, Some(6)));
+        let p3 = &stats.sources["p3"];
+        assert_eq!((p3.gaps, p3.reordered, p3.restarts), (0, 0, 1));
+        assert_eq!((stats.messages, stats.failures), (18, 1));
+        let report = stats.report();
+        assert!(report.iter().any(|line| line.contains("p1 - 8 条") && line.contains("序号缺口 2（丢失 3 条），乱序 1，重启 1 次")));
+    }
+}
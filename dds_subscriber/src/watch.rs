@@ -0,0 +1,410 @@
+//! 实时重建白板并在终端中绘制
+
+use colored::*;
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use crossterm::{cursor, execute, queue, terminal};
+use shared_types::board::BoardState;
+use shared_types::dds_manager::{self, DDSManager, PendingSnapshot};
+use shared_types::geometry::{self, Rect};
+use shared_types::replica::Replica;
+use shared_types::{BoardChangeData, Operation, PlaitElement, SnapshotRequest};
+use std::collections::HashSet;
+use std::io::{self, Write};
+use std::time::Duration;
+
+/// 最近一次变化
+struct LastChange {
+    source_id: String,
+    received_at: String,
+    summary: Vec<String>,
+    /// 本次变化涉及、且仍在白板上的元素
+    touched: HashSet<String>,
+}
+
+struct WatchState {
+    topic: String,
+    domain_id: u32,
+    /// 按全序应用变化，与其他端看到的白板一致
+    replica: Replica,
+    change_count: u64,
+    duplicate_count: u64,
+    last: Option<LastChange>,
+}
+
+impl WatchState {
+    fn apply(&mut self, change: &BoardChangeData) {
+        if !self.replica.apply(change.clone()) {
+            self.duplicate_count += 1;
+            return;
+        }
+        self.change_count += 1;
+        self.last = Some(LastChange {
+            source_id: change.source_id.clone(),
+            received_at: chrono::Local::now().format("%H:%M:%S").to_string(),
+            summary: change.operations.iter().map(summarize).collect(),
+            touched: change
+                .operations
+                .iter()
+                .filter(|op| op.op_type() != "remove_node")
+                .map(|op| op.node().id.clone())
+                .collect(),
+        });
+    }
+}
+
+fn summarize(op: &Operation) -> String {
+    let sign = match op.op_type() {
+        "insert_node" => "+",
+        "remove_node" => "-",
+        "set_node" => "~",
+        _ => "?",
+    };
+    format!("{}{}", sign, op.node().id)
+}
+
+fn kind_of(element: &PlaitElement) -> String {
+    match (element.element_type.as_str(), element.shape.as_str()) {
+        ("", "") => "-".to_string(),
+        (t, "") => t.to_string(),
+        ("", s) => s.to_string(),
+        (t, s) => format!("{}/{}", t, s),
+    }
+}
+
+/// 退出时（包括出错返回）恢复终端
+struct TerminalGuard;
+
+impl TerminalGuard {
+    fn enter() -> io::Result<Self> {
+        terminal::enable_raw_mode()?;
+        execute!(io::stdout(), terminal::EnterAlternateScreen, cursor::Hide)?;
+        Ok(TerminalGuard)
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = execute!(io::stdout(), cursor::Show, terminal::LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+/// 加入白板主题，向白板服务请求快照，然后持续应用收到的操作并重绘
+pub fn watch(domain_id: u32, topic: &str) -> Result<(), String> {
+    let mut dds = DDSManager::connect(domain_id, topic).map_err(|e| format!("加入主题 {} 失败: {}", topic, e))?;
+    let source_id = format!("dds-subscriber-{}", std::process::id());
+    let request = SnapshotRequest {
+        topic: topic.to_string(),
+        request_id: format!("{}-{}", source_id, chrono::Utc::now().timestamp_millis()),
+        source_id,
+        timestamp: chrono::Utc::now().to_rfc3339(),
+    };
+    // 请求可能早于发现过程，按间隔重发；没有白板服务时收不到快照，从空白板开始
+    let mut pending_snapshot = Some(PendingSnapshot::new(request, dds_manager::SNAPSHOT_TIMEOUT));
+    // 等待快照期间收到的变化，快照到达后把其中快照没有包含的重新应用
+    let mut buffered = Vec::new();
+
+    let mut state = WatchState {
+        topic: topic.to_string(),
+        domain_id,
+        replica: Replica::new(BoardState::new()),
+        change_count: 0,
+        duplicate_count: 0,
+        last: None,
+    };
+
+    let _guard = TerminalGuard::enter().map_err(|e| format!("初始化终端失败: {}", e))?;
+    let mut dirty = true;
+    loop {
+        if pending_snapshot.as_mut().is_some_and(|pending| !pending.poll(&dds)) {
+            pending_snapshot = None;
+            buffered.clear();
+        }
+        if let Ok(Some(snapshot)) = dds.try_receive_snapshot() {
+            if pending_snapshot.as_ref().is_some_and(|pending| pending.answered_by(&snapshot)) {
+                pending_snapshot = None;
+                state.replica = Replica::from_snapshot(snapshot, buffered.drain(..));
+                dirty = true;
+            }
+        }
+        while let Ok(Some(change)) = dds.try_receive_board_change() {
+            if pending_snapshot.is_some() {
+                buffered.push(change.clone());
+            }
+            state.apply(&change);
+            dirty = true;
+        }
+
+        if dirty {
+            let (cols, rows) = terminal::size().map_err(|e| e.to_string())?;
+            draw(&render(&state, cols as usize, rows as usize)).map_err(|e| format!("绘制失败: {}", e))?;
+            dirty = false;
+        }
+
+        if event::poll(Duration::from_millis(50)).map_err(|e| e.to_string())? {
+            match event::read().map_err(|e| e.to_string())? {
+                // 原始模式下 Ctrl+C 不会产生信号，按按键处理
+                Event::Key(key) if key.kind == KeyEventKind::Press => match key.code {
+                    KeyCode::Char('q') | KeyCode::Esc => break,
+                    KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => break,
+                    _ => {}
+                },
+                Event::Resize(_, _) => dirty = true,
+                _ => {}
+            }
+        }
+    }
+    Ok(())
+}
+
+fn draw(lines: &[String]) -> io::Result<()> {
+    let mut stdout = io::stdout();
+    queue!(stdout, cursor::MoveTo(0, 0), terminal::Clear(terminal::ClearType::All))?;
+    // 原始模式下换行不会回到行首
+    write!(stdout, "{}", lines.join("\r\n"))?;
+    stdout.flush()
+}
+
+/// 把状态绘制成若干行文本：标题、ASCII 布局、元素列表和提示
+fn render(state: &WatchState, cols: usize, rows: usize) -> Vec<String> {
+    let mut elements = Vec::new();
+    flatten(&state.replica.board().children, 0, &mut elements);
+    let touched = state.last.as_ref().map(|l| &l.touched);
+    let is_touched = |id: &str| touched.is_some_and(|t| t.contains(id));
+
+    let mut lines = Vec::new();
+    lines.push(
+        format!(
+            "Drawnix 白板  主题 {}  域 {}  元素 {}  变化 {}  重复 {}  乱序重放 {}",
+            state.topic,
+            state.domain_id,
+            elements.len(),
+            state.change_count,
+            state.duplicate_count,
+            state.replica.rebuilds()
+        )
+        .bold()
+        .to_string(),
+    );
+    lines.push(match &state.last {
+        Some(last) => truncate(
+            &format!("最近 {} 来自 {}: {}", last.received_at, last.source_id, last.summary.join(" ")),
+            cols,
+        )
+        .yellow()
+        .to_string(),
+        None => "等待白板变化...".dimmed().to_string(),
+    });
+
+    // 列表最多占三分之一高度，其余给画布
+    let list_rows = elements.len().min(rows.saturating_sub(6) / 3).max(1);
+    let canvas_rows = rows.saturating_sub(lines.len() + list_rows + 4).max(3);
+    let canvas_cols = cols.saturating_sub(2).max(10);
+
+    let border = format!("+{}+", "-".repeat(canvas_cols));
+    lines.push(border.dimmed().to_string());
+    let world = geometry::bounds_of(&state.replica.board().children);
+    for row in render_canvas(&elements, world, &is_touched, canvas_cols, canvas_rows) {
+        lines.push(format!("{}{}{}", "|".dimmed(), row, "|".dimmed()));
+    }
+    lines.push(border.dimmed().to_string());
+
+    let shown = if elements.len() > list_rows { list_rows.saturating_sub(1) } else { list_rows };
+    for (depth, element) in elements.iter().take(shown) {
+        let bounds = match element.bounds() {
+            Some(r) => format!("[{:.0}, {:.0}] - [{:.0}, {:.0}]", r.min_x, r.min_y, r.max_x, r.max_y),
+            None => "-".to_string(),
+        };
+        let line = truncate(
+            &format!("{}{} {:<24} {:<22} {}", "  ".repeat(*depth), if is_touched(&element.id) { ">" } else { " " }, element.id, kind_of(element), bounds),
+            cols,
+        );
+        lines.push(if is_touched(&element.id) { line.yellow().bold().to_string() } else { line });
+    }
+    if elements.len() > shown {
+        lines.push(format!("... 还有 {} 个元素", elements.len() - shown).dimmed().to_string());
+    }
+    lines.push("q 退出".dimmed().to_string());
+    lines
+}
+
+fn flatten<'a>(nodes: &'a [PlaitElement], depth: usize, out: &mut Vec<(usize, &'a PlaitElement)>) {
+    for node in nodes {
+        out.push((depth, node));
+        if let Some(children) = &node.children {
+            flatten(children, depth + 1, out);
+        }
+    }
+}
+
+fn truncate(text: &str, width: usize) -> String {
+    text.chars().take(width).collect()
+}
+
+/// 按比例把元素画到字符网格上；字符高约为宽的两倍，纵向坐标减半以保持形状
+fn render_canvas(
+    elements: &[(usize, &PlaitElement)],
+    world: Option<Rect>,
+    is_touched: &dyn Fn(&str) -> bool,
+    width: usize,
+    height: usize,
+) -> Vec<String> {
+    let mut grid = vec![vec![(' ', false); width]; height];
+    if let Some(world) = world {
+        let scale_x = (width - 1) as f64 / world.width().max(1.0);
+        let scale_y = (height - 1) as f64 / world.height().max(1.0) * 2.0;
+        let scale = scale_x.min(scale_y);
+        let to_cell = |x: f64, y: f64| -> (i64, i64) {
+            (((x - world.min_x) * scale).round() as i64, ((y - world.min_y) * scale / 2.0).round() as i64)
+        };
+
+        // 先画其他元素，最近变化的元素最后画，保证不被遮住
+        let mut ordered: Vec<&PlaitElement> = elements.iter().map(|(_, e)| *e).collect();
+        ordered.sort_by_key(|e| is_touched(&e.id));
+        for element in ordered {
+            let highlight = is_touched(&element.id);
+            let cells: Vec<(i64, i64)> = element.points.iter().map(|p| to_cell(p.0, p.1)).collect();
+            let mut plot = |x: i64, y: i64, c: char| {
+                if x >= 0 && y >= 0 && (x as usize) < width && (y as usize) < height {
+                    grid[y as usize][x as usize] = (c, highlight);
+                }
+            };
+            match element.element_type.as_str() {
+                "arrow-line" | "vector-line" | "line" => {
+                    for pair in cells.windows(2) {
+                        line(pair[0], pair[1], &mut plot, '*');
+                    }
+                    if let Some(&(x, y)) = cells.last() {
+                        plot(x, y, if element.element_type == "arrow-line" { '>' } else { '*' });
+                    }
+                }
+                "freehand" | "pen" => {
+                    for &(x, y) in &cells {
+                        plot(x, y, '.');
+                    }
+                }
+                _ => match element.bounds() {
+                    Some(r) if cells.len() >= 2 => {
+                        let (x1, y1) = to_cell(r.min_x, r.min_y);
+                        let (x2, y2) = to_cell(r.max_x, r.max_y);
+                        draw_box(x1, y1, x2, y2, element, &mut plot);
+                    }
+                    _ => {
+                        for &(x, y) in &cells {
+                            plot(x, y, 'x');
+                        }
+                    }
+                },
+            }
+        }
+    }
+
+    grid.into_iter()
+        .map(|row| {
+            row.into_iter()
+                .map(|(c, highlight)| if highlight { c.to_string().yellow().bold().to_string() } else { c.to_string() })
+                .collect()
+        })
+        .collect()
+}
+
+/// 矩形画 `+` 角，椭圆画 `.` 角，框内左上角写 id 前缀
+fn draw_box(x1: i64, y1: i64, x2: i64, y2: i64, element: &PlaitElement, plot: &mut impl FnMut(i64, i64, char)) {
+    let corner = if element.shape == "ellipse" { '.' } else { '+' };
+    for x in x1..=x2 {
+        plot(x, y1, '-');
+        plot(x, y2, '-');
+    }
+    for y in y1..=y2 {
+        plot(x1, y, '|');
+        plot(x2, y, '|');
+    }
+    for (x, y) in [(x1, y1), (x2, y1), (x1, y2), (x2, y2)] {
+        plot(x, y, corner);
+    }
+    if y2 - y1 >= 2 {
+        for (i, c) in element.id.chars().take((x2 - x1 - 1).max(0) as usize).enumerate() {
+            plot(x1 + 1 + i as i64, y1 + 1, c);
+        }
+    }
+}
+
+/// Bresenham 直线
+fn line(from: (i64, i64), to: (i64, i64), plot: &mut impl FnMut(i64, i64, char), c: char) {
+    let (mut x, mut y) = from;
+    let dx = (to.0 - x).abs();
+    let dy = -(to.1 - y).abs();
+    let sx = if x < to.0 { 1 } else { -1 };
+    let sy = if y < to.1 { 1 } else { -1 };
+    let mut err = dx + dy;
+    loop {
+        plot(x, y, c);
+        if (x, y) == to {
+            break;
+        }
+        let e2 = 2 * err;
+        if e2 >= dy {
+            err += dy;
+            x += sx;
+        }
+        if e2 <= dx {
+            err += dx;
+            y += sy;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn change(seq: u64, operations: serde_json::Value) -> BoardChangeData {
+        serde_json::from_value(serde_json::json!({ "operations": operations, "timestamp": "2024-01-01T10:00:00Z", "source_id": "p1", "seq": seq })).unwrap()
+    }
+
+    fn node(id: &str, element_type: &str, shape: &str, points: [[f64; 2]; 2]) -> serde_json::Value {
+        serde_json::json!({ "id": id, "type": element_type, "shape": shape, "points": points })
+    }
+
+    fn state() -> WatchState {
+        WatchState { topic: "t".into(), domain_id: 0, replica: Replica::new(BoardState::new()), change_count: 0, duplicate_count: 0, last: None }
+    }
+
+    #[test]
+    fn applies_changes_and_counts_duplicates() {
+        let mut state = state();
+        let insert = change(1, serde_json::json!([
+            { "type": "insert_node", "path": [0], "node": node("a", "geometry", "rectangle", [[0.0, 0.0], [10.0, 10.0]]) },
+            { "type": "insert_node", "path": [1], "node": node("b", "geometry", "rectangle", [[20.0, 0.0], [30.0, 10.0]]) },
+        ]));
+        state.apply(&insert);
+        state.apply(&insert);
+        state.apply(&change(2, serde_json::json!([{ "type": "remove_node", "path": [0], "node": node("a", "geometry", "rectangle", [[0.0, 0.0], [10.0, 10.0]]) }])));
+
+        assert_eq!((state.change_count, state.duplicate_count), (2, 1));
+        assert_eq!(state.replica.board().children.len(), 1);
+        let last = state.last.as_ref().unwrap();
+        assert_eq!(last.summary, ["-a"]);
+        // 删除的元素不再高亮
+        assert!(last.touched.is_empty());
+    }
+
+    #[test]
+    fn canvas_draws_boxes_and_arrows() {
+        colored::control::set_override(false);
+        let board: Vec<PlaitElement> = serde_json::from_value(serde_json::json!([
+            node("r", "geometry", "rectangle", [[0.0, 0.0], [40.0, 40.0]]),
+            node("l", "arrow-line", "straight", [[60.0, 20.0], [100.0, 20.0]]),
+        ]))
+        .unwrap();
+        let mut elements = Vec::new();
+        flatten(&board, 0, &mut elements);
+        let rows = render_canvas(&elements, geometry::bounds_of(&board), &|_| false, 21, 11);
+
+        assert_eq!(rows.len(), 11);
+        assert!(rows[0].starts_with('+'), "{:?}", rows);
+        assert!(rows[1].starts_with("|r"), "{:?}", rows);
+        assert!(rows.iter().any(|row| row.trim_end().ends_with("*>")), "{:?}", rows);
+        assert!(rows.iter().all(|row| row.chars().count() == 21));
+    }
+}
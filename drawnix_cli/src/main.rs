@@ -0,0 +1,661 @@
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use colored::*;
+use shared_types::document::DrawnixDocument;
+use shared_types::geometry::{self, Rect};
+use shared_types::schema::{self, SchemaKind};
+use shared_types::{board, diff, drawio, excalidraw, merge, mermaid, outline, pdf, raster, svg, validate, BoardChangeData, PlaitElement};
+use std::io::{Read, Write};
+use std::path::PathBuf;
+
+/// Drawnix 命令行工具 - 离线处理 .drawnix 文件
+#[derive(Parser)]
+#[command(name = "drawnix-cli")]
+#[command(about = "离线处理 .drawnix 白板文件")]
+struct Cli {
+    #[command(subcommand)]
+    command: Commands,
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    /// 比较两个 .drawnix 文件
+    Diff {
+        /// 旧文件
+        before: PathBuf,
+
+        /// 新文件
+        after: PathBuf,
+
+        /// 输出格式
+        #[arg(short, long, value_enum, default_value = "text")]
+        format: DiffFormat,
+    },
+    /// 三方合并离线编辑的 .drawnix 文件，有冲突时退出码为 1
+    Merge {
+        /// 共同祖先
+        base: PathBuf,
+
+        /// 我方版本
+        ours: PathBuf,
+
+        /// 对方版本
+        theirs: PathBuf,
+
+        /// 输出文件，不指定时输出到标准输出
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// 输出格式
+        #[arg(short, long, value_enum, default_value = "document")]
+        format: MergeFormat,
+
+        /// 冲突时采用哪一边
+        #[arg(short, long, value_enum, default_value = "ours")]
+        prefer: PreferSide,
+    },
+    /// 检查 .drawnix 文件中会让前端出错的数据，仍有错误时退出码为 1
+    Validate {
+        /// .drawnix 文件，- 表示标准输入
+        input: PathBuf,
+
+        /// 修复可以安全修复的问题，修复后的文档写到输出，报告写到标准错误
+        #[arg(long)]
+        fix: bool,
+
+        /// 修复后的文档，不指定时输出到标准输出
+        #[arg(short, long, requires = "fix")]
+        output: Option<PathBuf>,
+
+        /// 报告格式
+        #[arg(short, long, value_enum, default_value = "text")]
+        format: ReportFormat,
+    },
+    /// 输出线上消息和 .drawnix 文件的 JSON Schema
+    Schema {
+        /// 要输出的类型：board-change、operation、insert-node、remove-node、set-node、element、document、snapshot-request、board-snapshot
+        #[arg(value_parser = parse_schema_kind, required_unless_present = "out_dir")]
+        kind: Option<SchemaKind>,
+
+        /// 把全部 Schema 写到这个目录，每种一个 <类型>.schema.json
+        #[arg(short, long, conflicts_with = "kind")]
+        out_dir: Option<PathBuf>,
+    },
+    /// 按 JSON Schema 校验消息，有无效消息时退出码为 1
+    CheckMessage {
+        /// 单个 JSON 或每行一条消息的 JSON Lines 文件，- 表示标准输入
+        input: PathBuf,
+
+        /// 消息类型，取值同 schema 命令
+        #[arg(short, long, value_parser = parse_schema_kind, default_value = "board-change")]
+        kind: SchemaKind,
+    },
+    /// 把白板渲染成图片，用于文档和 CI
+    Export {
+        /// .drawnix 文件
+        input: PathBuf,
+
+        /// 输出文件，不指定时输出到标准输出
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// 输出格式
+        #[arg(short, long, value_enum, default_value = "svg")]
+        format: ExportFormat,
+
+        /// 内容四周的留白（像素）
+        #[arg(long, default_value = "20")]
+        padding: f64,
+
+        /// 背景色，传 none 表示透明
+        #[arg(long, default_value = "#ffffff")]
+        background: String,
+
+        /// PNG 的缩放倍数；PDF 平铺时为打印比例
+        #[arg(long, default_value = "1")]
+        scale: f32,
+
+        /// PDF 纸张大小
+        #[arg(long, value_enum, default_value = "a4")]
+        page: PageSize,
+
+        /// PDF 纸张方向
+        #[arg(long, value_enum, default_value = "auto")]
+        orientation: Orientation,
+
+        /// PDF 按 --scale 平铺到多页，而不是缩放到一页
+        #[arg(long)]
+        tile: bool,
+
+        /// PDF 页边距（毫米）
+        #[arg(long, default_value = "10")]
+        margin: f32,
+
+        /// PDF 平铺时相邻页的重叠宽度（毫米）
+        #[arg(long, default_value = "10")]
+        overlap: f32,
+
+        /// 只导出画布上的区域，格式为 x,y,宽,高
+        #[arg(long, value_parser = parse_region)]
+        region: Option<Rect>,
+
+        /// 只导出这些 id 的元素，可重复指定
+        #[arg(long = "select")]
+        selection: Vec<String>,
+    },
+    /// 把 Mermaid 流程图或时序图转换成白板元素，可发布到正在进行的会话
+    ImportMermaid {
+        /// Mermaid 文件，- 表示标准输入
+        input: PathBuf,
+
+        #[command(flatten)]
+        import: ImportArgs,
+    },
+    /// 把 Markdown 大纲（标题和嵌套列表）转换成思维导图
+    ImportOutline {
+        /// Markdown 文件，- 表示标准输入
+        input: PathBuf,
+
+        /// 大纲有多个顶层节点时使用的中心主题
+        #[arg(long)]
+        title: Option<String>,
+
+        #[command(flatten)]
+        import: ImportArgs,
+    },
+    /// 把 Excalidraw 文件转换成 .drawnix 文件，无法转换的元素报告到标准错误
+    ImportExcalidraw {
+        /// .excalidraw 文件，- 表示标准输入
+        input: PathBuf,
+
+        /// 输出文件，不指定时输出到标准输出
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// 把 draw.io 图转换成白板元素，无法转换的内容报告到标准错误
+    ImportDrawio {
+        /// .drawio 文件，- 表示标准输入
+        input: PathBuf,
+
+        /// 导入哪一页：页名或从 1 开始的序号，默认第一页
+        #[arg(long)]
+        page: Option<String>,
+
+        #[command(flatten)]
+        import: ImportArgs,
+    },
+    /// 把 .drawnix 文件转换成 Excalidraw 文件，无法转换的元素报告到标准错误
+    ExportExcalidraw {
+        /// .drawnix 文件
+        input: PathBuf,
+
+        /// 输出文件，不指定时输出到标准输出
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// 把白板上的思维导图导出成 Markdown 大纲
+    ExportOutline {
+        /// .drawnix 文件
+        input: PathBuf,
+
+        /// 输出文件，不指定时输出到标准输出
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// 只导出这个 id 的思维导图，不指定时导出全部
+        #[arg(long)]
+        id: Option<String>,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum DiffFormat {
+    /// 人类可读文本
+    Text,
+    /// 结构化 JSON 报告
+    Json,
+    /// 把旧文件变成新文件的 Operation 列表
+    Ops,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum MergeFormat {
+    /// 合并后的 .drawnix 文档
+    Document,
+    /// 把我方版本变成合并结果的 Operation 列表，可发布到正在进行的 DDS 会话
+    Ops,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ReportFormat {
+    /// 人类可读文本
+    Text,
+    /// 结构化 JSON 报告
+    Json,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ExportFormat {
+    Svg,
+    Png,
+    Pdf,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum PageSize {
+    A4,
+    A3,
+    Letter,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Orientation {
+    Auto,
+    Portrait,
+    Landscape,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ImportFormat {
+    /// 单行 BoardChangeData JSON，可直接发给 WebSocket 桥
+    Change,
+    /// Operation 列表
+    Ops,
+    /// 导入后的 .drawnix 文档
+    Document,
+}
+
+/// 导出参数，对应 export 子命令的选项
+struct ExportArgs {
+    format: ExportFormat,
+    scale: f32,
+    svg: svg::SvgOptions,
+    pdf: pdf::PdfOptions,
+}
+
+fn parse_schema_kind(text: &str) -> Result<SchemaKind, String> {
+    SchemaKind::from_name(text).ok_or_else(|| {
+        let names: Vec<&str> = SchemaKind::ALL.iter().map(|kind| kind.name()).collect();
+        format!("未知的类型，可选: {}", names.join(", "))
+    })
+}
+
+fn parse_region(text: &str) -> Result<Rect, String> {
+    let values = text
+        .split(',')
+        .map(|v| v.trim().parse::<f64>().map_err(|e| format!("{}: {}", v, e)))
+        .collect::<Result<Vec<_>, _>>()?;
+    match values[..] {
+        [x, y, width, height] if width > 0.0 && height > 0.0 => {
+            Ok(Rect { min_x: x, min_y: y, max_x: x + width, max_y: y + height })
+        }
+        _ => Err("区域格式应为 x,y,宽,高，且宽高为正数".into()),
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum PreferSide {
+    Ours,
+    Theirs,
+}
+
+fn run_diff(before: &PathBuf, after: &PathBuf, format: DiffFormat) -> Result<(), String> {
+    let before = DrawnixDocument::load(before)?;
+    let after = DrawnixDocument::load(after)?;
+
+    let output = match format {
+        DiffFormat::Text => diff::diff_documents(&before, &after).to_text(),
+        DiffFormat::Json => serde_json::to_string_pretty(&diff::diff_documents(&before, &after))
+            .map_err(|e| e.to_string())?,
+        DiffFormat::Ops => serde_json::to_string_pretty(&diff::diff_operations(&before.elements, &after.elements))
+            .map_err(|e| e.to_string())?,
+    };
+    println!("{}", output.trim_end());
+    Ok(())
+}
+
+fn run_merge(
+    base: &PathBuf,
+    ours: &PathBuf,
+    theirs: &PathBuf,
+    output: Option<&PathBuf>,
+    format: MergeFormat,
+    prefer: PreferSide,
+) -> Result<bool, String> {
+    let base = DrawnixDocument::load(base)?;
+    let ours = DrawnixDocument::load(ours)?;
+    let theirs = DrawnixDocument::load(theirs)?;
+    let prefer = match prefer {
+        PreferSide::Ours => merge::Side::Ours,
+        PreferSide::Theirs => merge::Side::Theirs,
+    };
+
+    let outcome = merge::merge_elements(&base.elements, &ours.elements, &theirs.elements, prefer)?;
+    // 冲突报告写到标准错误，不影响标准输出中的结果
+    eprint!("{}", outcome.to_text());
+
+    let content = match format {
+        MergeFormat::Document => {
+            let mut merged = ours.clone();
+            merged.elements = outcome.elements.clone();
+            merged.to_json()?
+        }
+        MergeFormat::Ops => serde_json::to_string_pretty(&diff::diff_operations(&ours.elements, &outcome.elements))
+            .map_err(|e| e.to_string())?,
+    };
+    match output {
+        Some(path) => std::fs::write(path, content).map_err(|e| format!("写入 {} 失败: {}", path.display(), e))?,
+        None => println!("{}", content),
+    }
+    Ok(outcome.has_conflicts())
+}
+
+fn run_validate(input: &PathBuf, fix: bool, output: Option<&PathBuf>, format: ReportFormat) -> Result<bool, String> {
+    let mut document = DrawnixDocument::from_json(&read_input(input)?)?;
+    if !fix {
+        let diagnostics = validate::validate_document(&document);
+        match format {
+            ReportFormat::Text => print!("{}", validate::diagnostics_to_text(&diagnostics)),
+            ReportFormat::Json => println!("{}", serde_json::to_string_pretty(&diagnostics).map_err(|e| e.to_string())?),
+        }
+        return Ok(diagnostics.iter().any(|d| d.severity == validate::Severity::Error));
+    }
+
+    let report = validate::fix_document(&mut document);
+    // 修复报告写到标准错误，不影响标准输出中的文档
+    match format {
+        ReportFormat::Text => {
+            eprintln!("🔧 修复 {} 处问题", report.fixed.len());
+            eprint!("{}", validate::diagnostics_to_text(&report.remaining));
+        }
+        ReportFormat::Json => eprintln!("{}", serde_json::to_string_pretty(&report).map_err(|e| e.to_string())?),
+    }
+    write_text(output, &document.to_json()?)?;
+    Ok(report.remaining.iter().any(|d| d.severity == validate::Severity::Error))
+}
+
+fn run_schema(kind: Option<SchemaKind>, out_dir: Option<&PathBuf>) -> Result<(), String> {
+    let pretty = |kind: SchemaKind| serde_json::to_string_pretty(&kind.schema()).map_err(|e| e.to_string());
+    match (kind, out_dir) {
+        (Some(kind), _) => write_text(None, &pretty(kind)?),
+        (None, Some(dir)) => {
+            std::fs::create_dir_all(dir).map_err(|e| format!("创建 {} 失败: {}", dir.display(), e))?;
+            for kind in SchemaKind::ALL {
+                let path = dir.join(format!("{}.schema.json", kind.name()));
+                write_text(Some(&path), &(pretty(kind)? + "\n"))?;
+            }
+            println!("✅ 已写入 {} 个 Schema 到 {}", SchemaKind::ALL.len(), dir.display());
+            Ok(())
+        }
+        (None, None) => Err("请指定类型或 --out-dir".into()),
+    }
+}
+
+/// 整个输入是一个 JSON 时按单条消息校验，否则按 JSON Lines 逐行校验
+fn run_check_message(input: &PathBuf, kind: SchemaKind) -> Result<bool, String> {
+    let text = read_input(input)?;
+    let messages: Vec<(usize, Result<serde_json::Value, String>)> = match serde_json::from_str(&text) {
+        Ok(value) => vec![(1, Ok(value))],
+        Err(_) => text
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(i, line)| (i + 1, serde_json::from_str(line).map_err(|e| format!("不是有效的 JSON: {}", e))))
+            .collect(),
+    };
+
+    let mut invalid = 0;
+    for (line, message) in &messages {
+        match message.as_ref().map_err(|e| vec![e.clone()]).and_then(|m| schema::validate_message(kind, m)) {
+            Ok(()) => {}
+            Err(errors) => {
+                invalid += 1;
+                for error in errors {
+                    println!("{} 第 {} 行 {}", "❌".red(), line, error);
+                }
+            }
+        }
+    }
+    if invalid == 0 {
+        println!("✅ {} 条消息均符合 {} Schema", messages.len(), kind.name());
+    } else {
+        println!("📊 {} 条消息中 {} 条无效", messages.len(), invalid);
+    }
+    Ok(invalid > 0)
+}
+
+fn run_export(input: &PathBuf, output: Option<&PathBuf>, args: ExportArgs) -> Result<(), String> {
+    let document = DrawnixDocument::load(input)?;
+
+    let content = match args.format {
+        ExportFormat::Svg => svg::render_svg(&document.elements, &args.svg).svg.into_bytes(),
+        ExportFormat::Png => {
+            let options = raster::PngOptions { scale: args.scale, svg: args.svg };
+            raster::render_png(&document.elements, &options)?
+        }
+        ExportFormat::Pdf => pdf::render_pdf(&document.elements, &pdf::PdfOptions { svg: args.svg, ..args.pdf })?,
+    };
+    match output {
+        Some(path) => std::fs::write(path, content).map_err(|e| format!("写入 {} 失败: {}", path.display(), e))?,
+        None => std::io::stdout().write_all(&content).map_err(|e| e.to_string())?,
+    }
+    Ok(())
+}
+
+/// 读取文件，`-` 表示标准输入
+fn read_input(input: &PathBuf) -> Result<String, String> {
+    let mut source = String::new();
+    if input.as_os_str() == "-" {
+        std::io::stdin().read_to_string(&mut source).map_err(|e| format!("读取标准输入失败: {}", e))?;
+    } else {
+        source = std::fs::read_to_string(input).map_err(|e| format!("读取 {} 失败: {}", input.display(), e))?;
+    }
+    Ok(source)
+}
+
+/// 导入命令的公共参数
+#[derive(Args)]
+struct ImportArgs {
+    /// 追加到这个 .drawnix 白板：内容放在已有元素右侧，插入路径接在末尾
+    #[arg(long)]
+    board: Option<PathBuf>,
+
+    /// 输出文件，不指定时输出到标准输出
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+
+    /// 输出格式
+    #[arg(short, long, value_enum, default_value = "change")]
+    format: ImportFormat,
+
+    /// BoardChangeData 中的发布者 id
+    #[arg(long, default_value = "drawnix-cli")]
+    source_id: String,
+
+    /// 元素 id 前缀，默认按当前时间生成，多次导入同一白板不会冲突
+    #[arg(long)]
+    id_prefix: Option<String>,
+}
+
+impl ImportArgs {
+    fn load_board(&self) -> Result<DrawnixDocument, String> {
+        match &self.board {
+            Some(path) => DrawnixDocument::load(path),
+            None => Ok(DrawnixDocument::new(Vec::new())),
+        }
+    }
+
+    /// 未指定前缀时按导入类型和当前时间生成
+    fn id_prefix(&self, kind: &str) -> String {
+        self.id_prefix.clone().unwrap_or_else(|| format!("{}-{:x}", kind, chrono::Utc::now().timestamp_millis()))
+    }
+
+    /// 把导入的元素追加到白板末尾，按指定格式输出
+    fn write(self, mut document: DrawnixDocument, elements: Vec<PlaitElement>) -> Result<(), String> {
+        let operations = board::insert_operations(document.elements.len(), elements.clone());
+        let content = match self.format {
+            ImportFormat::Change => serde_json::to_string(&BoardChangeData {
+                operations,
+                timestamp: chrono::Utc::now().to_rfc3339(),
+                source_id: self.source_id,
+                seq: None,
+            })
+            .map_err(|e| e.to_string())?,
+            ImportFormat::Ops => serde_json::to_string_pretty(&operations).map_err(|e| e.to_string())?,
+            ImportFormat::Document => {
+                document.elements.extend(elements);
+                document.to_json()?
+            }
+        };
+        write_text(self.output.as_ref(), &content)
+    }
+}
+
+fn write_text(output: Option<&PathBuf>, content: &str) -> Result<(), String> {
+    match output {
+        Some(path) => std::fs::write(path, content).map_err(|e| format!("写入 {} 失败: {}", path.display(), e)),
+        None => {
+            println!("{}", content.trim_end());
+            Ok(())
+        }
+    }
+}
+
+fn run_import_mermaid(input: &PathBuf, args: ImportArgs) -> Result<(), String> {
+    let source = read_input(input)?;
+    let document = args.load_board()?;
+    let options = mermaid::MermaidOptions::beside(&document.elements, args.id_prefix("mermaid"));
+    let elements = mermaid::import_mermaid(&source, &options)?;
+    eprintln!("✅ 导入 {} 个元素", elements.len());
+    args.write(document, elements)
+}
+
+fn run_import_outline(input: &PathBuf, title: Option<String>, args: ImportArgs) -> Result<(), String> {
+    let markdown = read_input(input)?;
+    let document = args.load_board()?;
+    let mut options = outline::OutlineOptions::beside(&document.elements, args.id_prefix("outline"));
+    if let Some(title) = title {
+        options.title = title;
+    }
+    let mind_map = outline::markdown_to_mind_map(&markdown, &options)?;
+    eprintln!("✅ 导入思维导图: {}", mind_map.id);
+    args.write(document, vec![mind_map])
+}
+
+fn run_export_outline(input: &PathBuf, output: Option<&PathBuf>, id: Option<&str>) -> Result<(), String> {
+    let document = DrawnixDocument::load(input)?;
+    let markdown = match id {
+        Some(id) => {
+            let element = document.elements.iter().find(|e| e.id == id).ok_or_else(|| format!("顶层元素中没有 {}", id))?;
+            outline::mind_map_to_markdown(element)?
+        }
+        None => outline::board_to_markdown(&document.elements)?,
+    };
+    write_text(output, &markdown)
+}
+
+fn run_import_excalidraw(input: &PathBuf, output: Option<&PathBuf>) -> Result<(), String> {
+    let conversion = excalidraw::from_excalidraw(&read_input(input)?)?;
+    eprint!("{}", conversion.to_text());
+    write_text(output, &conversion.output.to_json()?)
+}
+
+fn run_import_drawio(input: &PathBuf, page: Option<String>, args: ImportArgs) -> Result<(), String> {
+    let xml = read_input(input)?;
+    let document = args.load_board()?;
+    let options = drawio::DrawioOptions {
+        page,
+        // 追加到已有白板时放在已有内容右侧，否则保持原坐标
+        origin: args.board.is_some().then(|| geometry::place_beside(&document.elements)),
+        id_prefix: args.id_prefix("drawio"),
+    };
+    let conversion = drawio::import_drawio(&xml, &options)?;
+    eprint!("{}", conversion.to_text());
+    args.write(document, conversion.output)
+}
+
+fn run_export_excalidraw(input: &PathBuf, output: Option<&PathBuf>) -> Result<(), String> {
+    let conversion = excalidraw::to_excalidraw(&DrawnixDocument::load(input)?);
+    eprint!("{}", conversion.to_text());
+    write_text(output, &serde_json::to_string_pretty(&conversion.output).map_err(|e| e.to_string())?)
+}
+
+fn main() {
+    let cli = Cli::parse();
+
+    let result = match cli.command {
+        Commands::Diff { before, after, format } => run_diff(&before, &after, format),
+        Commands::Merge { base, ours, theirs, output, format, prefer } => {
+            match run_merge(&base, &ours, &theirs, output.as_ref(), format, prefer) {
+                Ok(true) => std::process::exit(1),
+                other => other.map(|_| ()),
+            }
+        }
+        Commands::Validate { input, fix, output, format } => match run_validate(&input, fix, output.as_ref(), format) {
+            Ok(true) => std::process::exit(1),
+            other => other.map(|_| ()),
+        },
+        Commands::Schema { kind, out_dir } => run_schema(kind, out_dir.as_ref()),
+        Commands::CheckMessage { input, kind } => match run_check_message(&input, kind) {
+            Ok(true) => std::process::exit(1),
+            other => other.map(|_| ()),
+        },
+        Commands::Export {
+            input,
+            output,
+            format,
+            padding,
+            background,
+            scale,
+            region,
+            selection,
+            page,
+            orientation,
+            tile,
+            margin,
+            overlap,
+        } => {
+            let pdf = pdf::PdfOptions {
+                page: match page {
+                    PageSize::A4 => pdf::PageSize::A4,
+                    PageSize::A3 => pdf::PageSize::A3,
+                    PageSize::Letter => pdf::PageSize::Letter,
+                },
+                orientation: match orientation {
+                    Orientation::Auto => pdf::Orientation::Auto,
+                    Orientation::Portrait => pdf::Orientation::Portrait,
+                    Orientation::Landscape => pdf::Orientation::Landscape,
+                },
+                layout: if tile {
+                    pdf::PdfLayout::Tile { scale, overlap_mm: overlap }
+                } else {
+                    pdf::PdfLayout::Fit
+                },
+                margin_mm: margin,
+                ..Default::default()
+            };
+            let args = ExportArgs {
+                format,
+                scale,
+                pdf,
+                svg: svg::SvgOptions {
+                    padding,
+                    background: Some(background).filter(|b| b != "none"),
+                    region,
+                    selection: Some(selection).filter(|ids| !ids.is_empty()),
+                },
+            };
+            run_export(&input, output.as_ref(), args)
+        }
+        Commands::ImportMermaid { input, import } => run_import_mermaid(&input, import),
+        Commands::ImportOutline { input, title, import } => run_import_outline(&input, title, import),
+        Commands::ImportExcalidraw { input, output } => run_import_excalidraw(&input, output.as_ref()),
+        Commands::ImportDrawio { input, page, import } => run_import_drawio(&input, page, import),
+        Commands::ExportExcalidraw { input, output } => run_export_excalidraw(&input, output.as_ref()),
+        Commands::ExportOutline { input, output, id } => run_export_outline(&input, output.as_ref(), id.as_deref()),
+    };
+
+    if let Err(e) = result {
+        eprintln!("{} {}", "❌".red(), e);
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,95 @@
+//! 由字节生成一块白板和一串操作：直接应用、经过线上编码后应用、经过 Replica 应用都不能 panic，
+//! 且经过编码的操作与原操作完全相同（不会变成别的类型）。
+
+#![no_main]
+
+use libfuzzer_sys::arbitrary::{Result, Unstructured};
+use libfuzzer_sys::fuzz_target;
+use shared_types::board::BoardState;
+use shared_types::replica::Replica;
+use shared_types::*;
+
+/// id 取自很小的集合，让操作经常命中同一个元素
+const IDS: [&str; 4] = ["a", "b", "c", "d"];
+
+fn element(u: &mut Unstructured, depth: u32) -> Result<PlaitElement> {
+    let mut points = Vec::new();
+    for _ in 0..u.int_in_range(0..=3)? {
+        // 整数坐标：JSON 往返后仍然完全相等
+        points.push(Point(f64::from(u.arbitrary::<i16>()?), f64::from(u.arbitrary::<i16>()?)));
+    }
+    let children = if depth < 3 && u.arbitrary::<bool>()? {
+        let mut children = Vec::new();
+        for _ in 0..u.int_in_range(0..=3)? {
+            children.push(element(u, depth + 1)?);
+        }
+        Some(children)
+    } else {
+        None
+    };
+    Ok(PlaitElement {
+        id: u.choose(&IDS)?.to_string(),
+        element_type: u.choose(&["", "geometry", "arrow-line", "mindmap"])?.to_string(),
+        shape: u.choose(&["", "rectangle", "ellipse"])?.to_string(),
+        points,
+        children,
+        extra: Default::default(),
+    })
+}
+
+fn operation(u: &mut Unstructured) -> Result<Operation> {
+    let mut path = Vec::new();
+    for _ in 0..u.int_in_range(0..=4)? {
+        path.push(u.int_in_range(0..=5)?);
+    }
+    let node = element(u, 1)?;
+    Ok(match u.int_in_range(0..=2)? {
+        0 => Operation::insert(path, node),
+        1 => Operation::remove(path, node),
+        _ => Operation::set(path, node),
+    })
+}
+
+fn input(data: &[u8]) -> Result<(Vec<PlaitElement>, Vec<Operation>)> {
+    let mut u = Unstructured::new(data);
+    let mut elements = Vec::new();
+    for _ in 0..u.int_in_range(0..=4)? {
+        elements.push(element(&mut u, 0)?);
+    }
+    let mut operations = Vec::new();
+    while !u.is_empty() && operations.len() < 32 {
+        operations.push(operation(&mut u)?);
+    }
+    Ok((elements, operations))
+}
+
+fuzz_target!(|data: &[u8]| {
+    let Ok((elements, operations)) = input(data) else {
+        return;
+    };
+    let base = BoardState::from_elements(elements);
+
+    let mut direct = base.clone();
+    direct.apply_all(&operations);
+
+    let change = BoardChangeData {
+        operations: operations.clone(),
+        timestamp: "2024-01-01T00:00:00Z".into(),
+        source_id: "fuzz".into(),
+        seq: Some(1),
+    };
+    let json = serde_json::to_string(&change).expect("操作应能编码");
+    match serde_json::from_str::<BoardChangeData>(&json) {
+        Ok(decoded) => {
+            assert_eq!(decoded.operations, operations);
+            let mut remote = base.clone();
+            remote.apply_all(&decoded.operations);
+            assert_eq!(remote, direct);
+        }
+        // 只有空路径会被拒绝
+        Err(_) => assert!(operations.iter().any(|op| op.path().is_empty())),
+    }
+
+    let mut replica = Replica::new(base);
+    replica.apply(change);
+});
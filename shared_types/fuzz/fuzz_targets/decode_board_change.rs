@@ -0,0 +1,34 @@
+//! 任意字节作为线上的白板变化解码：不能 panic；解码成功的消息中每个操作的变体
+//! 必须与 `type` 一致，重新编码后能原样解码，并且可以安全地应用到白板上。
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use shared_types::board::BoardState;
+use shared_types::*;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(text) = std::str::from_utf8(data) else {
+        return;
+    };
+    let Ok(change) = serde_json::from_str::<BoardChangeData>(text) else {
+        return;
+    };
+
+    for op in &change.operations {
+        let expected = match op {
+            Operation::Insert(_) => "insert_node",
+            Operation::Remove(_) => "remove_node",
+            Operation::Set(_) => "set_node",
+        };
+        assert_eq!(op.op_type(), expected);
+        assert!(!op.path().is_empty());
+    }
+
+    let json = serde_json::to_string(&change).expect("解码成功的消息应能重新编码");
+    let again: BoardChangeData = serde_json::from_str(&json).expect("重新编码的消息应能解码");
+    assert_eq!(again, change);
+
+    let mut board = BoardState::new();
+    board.apply_all(&change.operations);
+});
@@ -0,0 +1,117 @@
+//! 白板状态：在内存中维护元素树，并按路径应用 Operation
+//!
+//! 路径语义与前端 `applyOperationsToBoardState` 保持一致：
+//! `path` 的最后一位是在父节点 `children` 中的下标，前面各位逐层定位父节点。
+
+use crate::types::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct BoardState {
+    pub children: Vec<PlaitElement>,
+}
+
+impl BoardState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from_elements(children: Vec<PlaitElement>) -> Self {
+        BoardState { children }
+    }
+
+    /// 按路径取节点
+    pub fn get(&self, path: &[usize]) -> Option<&PlaitElement> {
+        let (last, parents) = path.split_last()?;
+        let mut siblings = &self.children;
+        for &index in parents {
+            siblings = siblings.get(index)?.children.as_ref()?;
+        }
+        siblings.get(*last)
+    }
+
+    /// 在整棵树中按 id 查找节点，返回节点及其路径
+    pub fn find(&self, id: &str) -> Option<(Vec<usize>, &PlaitElement)> {
+        fn walk<'a>(nodes: &'a [PlaitElement], id: &str, prefix: &mut Vec<usize>) -> Option<(Vec<usize>, &'a PlaitElement)> {
+            for (i, node) in nodes.iter().enumerate() {
+                prefix.push(i);
+                if node.id == id {
+                    return Some((prefix.clone(), node));
+                }
+                if let Some(children) = &node.children {
+                    if let Some(found) = walk(children, id, prefix) {
+                        return Some(found);
+                    }
+                }
+                prefix.pop();
+            }
+            None
+        }
+        walk(&self.children, id, &mut Vec::new())
+    }
+
+    pub fn apply(&mut self, op: &Operation) -> Result<(), String> {
+        let (&index, parents) = op
+            .path()
+            .split_last()
+            .ok_or_else(|| "操作路径为空".to_string())?;
+        // 只有插入才会为叶子节点创建 children，删除/更新不应改变树结构
+        let create = matches!(op, Operation::Insert(_));
+        let siblings = self.siblings_mut(parents, create)?;
+
+        match op {
+            Operation::Insert(insert) => {
+                if index > siblings.len() {
+                    return Err(format!("插入位置越界: {:?}（同级节点数 {}）", insert.path, siblings.len()));
+                }
+                siblings.insert(index, insert.node.clone());
+            }
+            Operation::Remove(remove) => {
+                if index >= siblings.len() {
+                    return Err(format!("删除的节点不存在: {:?}", remove.path));
+                }
+                siblings.remove(index);
+            }
+            Operation::Set(set) => {
+                let target = siblings
+                    .get_mut(index)
+                    .ok_or_else(|| format!("更新的节点不存在: {:?}", set.path))?;
+                // 与前端一致：全量替换节点
+                *target = set.node.clone();
+            }
+        }
+        Ok(())
+    }
+
+    /// 依次应用一批操作，遇到失败的操作时记录并跳过（前端同样会忽略无效路径）
+    pub fn apply_all(&mut self, ops: &[Operation]) -> Vec<String> {
+        ops.iter()
+            .filter_map(|op| self.apply(op).err())
+            .collect()
+    }
+
+    fn siblings_mut(&mut self, parents: &[usize], create: bool) -> Result<&mut Vec<PlaitElement>, String> {
+        let mut siblings = &mut self.children;
+        for (depth, &index) in parents.iter().enumerate() {
+            let parent = siblings
+                .get_mut(index)
+                .ok_or_else(|| format!("父节点不存在: {:?}", &parents[..=depth]))?;
+            if parent.children.is_none() && !create {
+                return Err(format!("父节点没有子节点: {:?}", &parents[..=depth]));
+            }
+            siblings = parent.children.get_or_insert_with(Vec::new);
+        }
+        Ok(siblings)
+    }
+}
+
+/// 把一批新元素依次插入到顶层 `start` 开始的位置，生成可以直接发布的插入操作
+///
+/// `start` 取白板当前的顶层元素数即追加到末尾；取 0 时无论对端白板状态如何路径都有效。
+pub fn insert_operations(start: usize, elements: Vec<PlaitElement>) -> Vec<Operation> {
+    elements
+        .into_iter()
+        .enumerate()
+        .map(|(i, element)| Operation::insert(vec![start + i], element))
+        .collect()
+}
@@ -0,0 +1,33 @@
+//! 与其他白板格式（Excalidraw、draw.io 等）互转的结果和转换报告。
+
+use serde::Serialize;
+
+/// 没有转换或只转换了一部分的内容
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Unsupported {
+    pub id: String,
+    /// 源文件中的元素类型
+    pub kind: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct Conversion<T> {
+    pub output: T,
+    pub unsupported: Vec<Unsupported>,
+}
+
+impl<T> Conversion<T> {
+    /// 人类可读的转换报告
+    pub fn to_text(&self) -> String {
+        if self.unsupported.is_empty() {
+            return "✅ 全部元素已转换\n".into();
+        }
+        let mut out = String::new();
+        for item in &self.unsupported {
+            out += &format!("⚠️  {} ({}): {}\n", item.id, item.kind, item.reason);
+        }
+        out += &format!("📊 {} 处内容未能转换\n", self.unsupported.len());
+        out
+    }
+}
@@ -0,0 +1,170 @@
+use zrdds_safe::prelude::*;
+use crate::types::{BoardChangeData, BoardSnapshot, SnapshotRequest};
+use serde::{de::DeserializeOwned, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+pub const DEFAULT_DOMAIN_ID: u32 = 150;
+pub const BOARD_TOPIC: &str = "DrawnixBoardChanges";
+pub const SNAPSHOT_REQUEST_TOPIC: &str = "DrawnixSnapshotRequests";
+pub const SNAPSHOT_TOPIC: &str = "DrawnixSnapshots";
+/// 快照请求的重发间隔和放弃等待的期限
+pub const SNAPSHOT_RETRY_INTERVAL: Duration = Duration::from_secs(1);
+pub const SNAPSHOT_TIMEOUT: Duration = Duration::from_secs(30);
+
+pub struct DDSManager {
+    // 域参与者只需与发布者、订阅者同生命周期，不会被直接读取
+    #[allow(dead_code)]
+    domain: Domain,
+    board_topic: String,
+    board_publisher: Publisher,
+    board_subscriber: Subscriber,
+    snapshot_request_publisher: Publisher,
+    snapshot_request_subscriber: Subscriber,
+    snapshot_publisher: Publisher,
+    snapshot_subscriber: Subscriber,
+    /// 每个 source_id 已发布的白板变化数（WebSocket 桥会代多个浏览器发布）
+    sequences: Mutex<HashMap<String, u64>>,
+}
+
+impl DDSManager {
+    pub fn new() -> zrdds_safe::Result<Self> {
+        Self::connect(DEFAULT_DOMAIN_ID, BOARD_TOPIC)
+    }
+
+    /// 加入指定域，`board_topic` 即白板（房间）对应的主题
+    pub fn connect(domain_id: u32, board_topic: &str) -> zrdds_safe::Result<Self> {
+        println!("🔌 初始化 DDS 连接...");
+
+        let domain = Domain::builder()
+            .domain_id(domain_id)
+            .build()?;
+
+        let board_publisher = domain.create_publisher(board_topic)?;
+        let board_subscriber = domain.create_subscriber(board_topic)?;
+        let snapshot_request_publisher = domain.create_publisher(SNAPSHOT_REQUEST_TOPIC)?;
+        let snapshot_request_subscriber = domain.create_subscriber(SNAPSHOT_REQUEST_TOPIC)?;
+        let snapshot_publisher = domain.create_publisher(SNAPSHOT_TOPIC)?;
+        let snapshot_subscriber = domain.create_subscriber(SNAPSHOT_TOPIC)?;
+
+        println!("✅ DDS 连接已建立（域 {}，主题 {}）", domain_id, board_topic);
+
+        Ok(DDSManager {
+            domain,
+            board_topic: board_topic.to_string(),
+            board_publisher,
+            board_subscriber,
+            snapshot_request_publisher,
+            snapshot_request_subscriber,
+            snapshot_publisher,
+            snapshot_subscriber,
+            sequences: Mutex::new(HashMap::new()),
+        })
+    }
+
+    pub fn board_topic(&self) -> &str {
+        &self.board_topic
+    }
+
+    /// 为没有序号的变化按 source_id 编号。需要在发布前把变化应用到本地副本时先调用，
+    /// 保证本地和对端看到的是同一条消息
+    pub fn assign_seq(&self, data: &mut BoardChangeData) {
+        if data.seq.is_some() {
+            return;
+        }
+        if let Ok(mut sequences) = self.sequences.lock() {
+            let seq = sequences.entry(data.source_id.clone()).or_insert(0);
+            *seq += 1;
+            data.seq = Some(*seq);
+        }
+    }
+
+    /// 发布白板变化；没有序号时按 source_id 编号
+    pub fn publish_board_change(&self, data: &BoardChangeData) -> zrdds_safe::Result<()> {
+        if data.seq.is_some() {
+            return publish_json(&self.board_publisher, data);
+        }
+        let mut numbered = data.clone();
+        self.assign_seq(&mut numbered);
+        publish_json(&self.board_publisher, &numbered)
+    }
+
+    /// 无效的消息（未知操作类型、空路径等）返回错误，不会交给前端
+    pub fn try_receive_board_change(&mut self) -> zrdds_safe::Result<Option<BoardChangeData>> {
+        receive_json(&mut self.board_subscriber)
+    }
+
+    /// 后加入的客户端请求当前白板的完整状态
+    pub fn request_snapshot(&self, request: &SnapshotRequest) -> zrdds_safe::Result<()> {
+        publish_json(&self.snapshot_request_publisher, request)
+    }
+
+    pub fn try_receive_snapshot_request(&mut self) -> zrdds_safe::Result<Option<SnapshotRequest>> {
+        receive_json(&mut self.snapshot_request_subscriber)
+    }
+
+    pub fn publish_snapshot(&self, snapshot: &BoardSnapshot) -> zrdds_safe::Result<()> {
+        publish_json(&self.snapshot_publisher, snapshot)
+    }
+
+    pub fn try_receive_snapshot(&mut self) -> zrdds_safe::Result<Option<BoardSnapshot>> {
+        receive_json(&mut self.snapshot_subscriber)
+    }
+}
+
+fn publish_json<T: Serialize>(publisher: &Publisher, data: &T) -> zrdds_safe::Result<()> {
+    let json_data = serde_json::to_string(data)
+        .map_err(|e| zrdds_safe::Error::Other { message: format!("JSON序列化失败: {}", e) })?;
+    publisher.publish(json_data.as_bytes())?;
+    Ok(())
+}
+
+fn receive_json<T: DeserializeOwned>(subscriber: &mut Subscriber) -> zrdds_safe::Result<Option<T>> {
+    match subscriber.try_recv()? {
+        Some(data) => {
+            let json_str = String::from_utf8(data)
+                .map_err(|e| zrdds_safe::Error::Other { message: format!("UTF8转换失败: {}", e) })?;
+            let value: T = serde_json::from_str(&json_str)
+                .map_err(|e| zrdds_safe::Error::Other { message: format!("JSON反序列化失败: {}", e) })?;
+            Ok(Some(value))
+        }
+        None => Ok(None),
+    }
+}
+
+/// 等待应答的快照请求。
+///
+/// 刚加入域时发现过程还没有匹配到白板服务，第一次发出的请求可能直接丢失，
+/// 因此按 `SNAPSHOT_RETRY_INTERVAL` 重发同一个请求，直到收到应答或超过期限。
+pub struct PendingSnapshot {
+    request: SnapshotRequest,
+    last_sent: Option<Instant>,
+    deadline: Instant,
+}
+
+impl PendingSnapshot {
+    pub fn new(request: SnapshotRequest, timeout: Duration) -> Self {
+        PendingSnapshot { request, last_sent: None, deadline: Instant::now() + timeout }
+    }
+
+    /// 到了重发时间就发送请求；超过期限返回 false，调用方应放弃等待
+    pub fn poll(&mut self, manager: &DDSManager) -> bool {
+        let now = Instant::now();
+        if now >= self.deadline {
+            return false;
+        }
+        if self.last_sent.is_none_or(|sent| now.duration_since(sent) >= SNAPSHOT_RETRY_INTERVAL) {
+            self.last_sent = Some(now);
+            if let Err(e) = manager.request_snapshot(&self.request) {
+                eprintln!("快照请求发送失败: {}", e);
+            }
+        }
+        true
+    }
+
+    /// 是否是对这个请求的应答（重发的请求可能收到多份应答，只取第一份）
+    pub fn answered_by(&self, snapshot: &BoardSnapshot) -> bool {
+        snapshot.request_id == self.request.request_id
+    }
+}
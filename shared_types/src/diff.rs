@@ -0,0 +1,328 @@
+//! 两个白板之间的结构化差异：按元素 id 对齐，报告新增、删除、移动和属性变化，
+//! 并能生成把旧白板变成新白板的 Operation 列表。
+
+use crate::document::DrawnixDocument;
+use crate::types::*;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+/// 属性类别，方便按坐标 / 文本 / 形状 / 样式分组查看
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PropertyKind {
+    Points,
+    Text,
+    Shape,
+    Style,
+}
+
+impl PropertyKind {
+    pub fn of(key: &str) -> Self {
+        match key {
+            "points" => PropertyKind::Points,
+            "text" | "texts" | "data" => PropertyKind::Text,
+            "type" | "shape" => PropertyKind::Shape,
+            _ => PropertyKind::Style,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            PropertyKind::Points => "坐标",
+            PropertyKind::Text => "文本",
+            PropertyKind::Shape => "形状",
+            PropertyKind::Style => "样式",
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct PropertyChange {
+    pub key: String,
+    pub kind: PropertyKind,
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ElementRef {
+    pub id: String,
+    pub element_type: String,
+    pub shape: String,
+    pub path: Vec<usize>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct MovedElement {
+    pub id: String,
+    pub from: Vec<usize>,
+    pub to: Vec<usize>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ChangedElement {
+    pub id: String,
+    pub path: Vec<usize>,
+    pub changes: Vec<PropertyChange>,
+}
+
+#[derive(Serialize, Debug, Clone, Default, PartialEq)]
+pub struct BoardDiff {
+    pub added: Vec<ElementRef>,
+    pub removed: Vec<ElementRef>,
+    pub moved: Vec<MovedElement>,
+    pub changed: Vec<ChangedElement>,
+}
+
+impl BoardDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.moved.is_empty() && self.changed.is_empty()
+    }
+
+    /// 人类可读的文本报告
+    pub fn to_text(&self) -> String {
+        if self.is_empty() {
+            return "✅ 两个白板没有差异\n".into();
+        }
+
+        let mut out = String::new();
+        for el in &self.added {
+            out += &format!("➕ 新增 {} ({}) @ {:?}\n", el.id, describe_kind(&el.element_type, &el.shape), el.path);
+        }
+        for el in &self.removed {
+            out += &format!("➖ 删除 {} ({}) @ {:?}\n", el.id, describe_kind(&el.element_type, &el.shape), el.path);
+        }
+        for el in &self.moved {
+            out += &format!("🔀 移动 {}: {:?} → {:?}\n", el.id, el.from, el.to);
+        }
+        for el in &self.changed {
+            out += &format!("✏️  修改 {} @ {:?}\n", el.id, el.path);
+            for change in &el.changes {
+                out += &format!(
+                    "     [{}] {}: {} → {}\n",
+                    change.kind.label(),
+                    change.key,
+                    short_value(change.before.as_ref()),
+                    short_value(change.after.as_ref())
+                );
+            }
+        }
+        out += &format!(
+            "📊 新增 {}，删除 {}，移动 {}，修改 {}\n",
+            self.added.len(),
+            self.removed.len(),
+            self.moved.len(),
+            self.changed.len()
+        );
+        out
+    }
+}
+
+fn describe_kind(element_type: &str, shape: &str) -> String {
+    match (element_type.is_empty(), shape.is_empty()) {
+        (true, _) => "node".into(),
+        (false, true) => element_type.into(),
+        (false, false) => format!("{}/{}", element_type, shape),
+    }
+}
+
+pub fn short_value(value: Option<&Value>) -> String {
+    const MAX_CHARS: usize = 60;
+    match value {
+        None => "∅".into(),
+        Some(v) => {
+            let s = v.to_string();
+            if s.chars().count() > MAX_CHARS {
+                format!("{}…", s.chars().take(MAX_CHARS).collect::<String>())
+            } else {
+                s
+            }
+        }
+    }
+}
+
+/// 一个元素在树中的位置
+struct Located<'a> {
+    path: Vec<usize>,
+    parent: Option<&'a str>,
+    /// 在同一父节点下、两边都存在的兄弟节点之间的相对次序
+    rank: usize,
+    node: &'a PlaitElement,
+}
+
+/// 按 id 建立索引；id 重复时只保留先出现的那个
+fn index_tree(nodes: &[PlaitElement]) -> (Vec<&str>, HashMap<&str, Located<'_>>) {
+    fn walk<'a>(
+        nodes: &'a [PlaitElement],
+        parent: Option<&'a str>,
+        prefix: &mut Vec<usize>,
+        order: &mut Vec<&'a str>,
+        index: &mut HashMap<&'a str, Located<'a>>,
+    ) {
+        for (i, node) in nodes.iter().enumerate() {
+            prefix.push(i);
+            if !index.contains_key(node.id.as_str()) {
+                order.push(&node.id);
+                index.insert(&node.id, Located { path: prefix.clone(), parent, rank: 0, node });
+            }
+            if let Some(children) = &node.children {
+                walk(children, Some(&node.id), prefix, order, index);
+            }
+            prefix.pop();
+        }
+    }
+
+    let mut order = Vec::new();
+    let mut index = HashMap::new();
+    walk(nodes, None, &mut Vec::new(), &mut order, &mut index);
+    (order, index)
+}
+
+/// 计算 `rank`：只在两边父节点相同的元素之间排序，这样别处的插入删除不会被误报为移动
+fn assign_ranks<'a>(order: &[&'a str], index: &mut HashMap<&'a str, Located<'a>>, other: &HashMap<&'a str, Located<'a>>) {
+    let mut counters: HashMap<Option<&str>, usize> = HashMap::new();
+    for id in order {
+        let parent = index[id].parent;
+        let stable = other.get(id).map(|o| o.parent == parent).unwrap_or(false);
+        if stable {
+            let counter = counters.entry(parent).or_insert(0);
+            index.get_mut(id).unwrap().rank = *counter;
+            *counter += 1;
+        }
+    }
+}
+
+fn property_changes(before: &PlaitElement, after: &PlaitElement) -> Vec<PropertyChange> {
+    let before = before.properties();
+    let after = after.properties();
+    let keys: BTreeSet<&String> = before.keys().chain(after.keys()).collect();
+    keys.into_iter()
+        .filter(|key| before.get(*key) != after.get(*key))
+        .map(|key| PropertyChange {
+            key: key.clone(),
+            kind: PropertyKind::of(key),
+            before: before.get(key).cloned(),
+            after: after.get(key).cloned(),
+        })
+        .collect()
+}
+
+fn element_ref(located: &Located) -> ElementRef {
+    ElementRef {
+        id: located.node.id.clone(),
+        element_type: located.node.element_type.clone(),
+        shape: located.node.shape.clone(),
+        path: located.path.clone(),
+    }
+}
+
+/// 比较两棵元素树
+pub fn diff_elements(before: &[PlaitElement], after: &[PlaitElement]) -> BoardDiff {
+    let (before_order, mut before_index) = index_tree(before);
+    let (after_order, mut after_index) = index_tree(after);
+    assign_ranks(&before_order, &mut before_index, &after_index);
+    assign_ranks(&after_order, &mut after_index, &before_index);
+
+    let mut diff = BoardDiff::default();
+    for id in &before_order {
+        if !after_index.contains_key(id) {
+            diff.removed.push(element_ref(&before_index[id]));
+        }
+    }
+    for id in &after_order {
+        let new = &after_index[id];
+        let Some(old) = before_index.get(id) else {
+            diff.added.push(element_ref(new));
+            continue;
+        };
+
+        if old.parent != new.parent || old.rank != new.rank {
+            diff.moved.push(MovedElement { id: id.to_string(), from: old.path.clone(), to: new.path.clone() });
+        }
+        let changes = property_changes(old.node, new.node);
+        if !changes.is_empty() {
+            diff.changed.push(ChangedElement { id: id.to_string(), path: new.path.clone(), changes });
+        }
+    }
+    diff
+}
+
+pub fn diff_documents(before: &DrawnixDocument, after: &DrawnixDocument) -> BoardDiff {
+    diff_elements(&before.elements, &after.elements)
+}
+
+/// 生成把 `before` 变成 `after` 的操作序列，按顺序应用到 `before` 上即可得到 `after`
+pub fn diff_operations(before: &[PlaitElement], after: &[PlaitElement]) -> Vec<Operation> {
+    let mut current = before.to_vec();
+    let mut ops = Vec::new();
+    transform(&mut current, after, &mut Vec::new(), &mut ops);
+    ops
+}
+
+/// 除子节点内容外是否相同（子节点有无也算在内）
+fn shallow_eq(a: &PlaitElement, b: &PlaitElement) -> bool {
+    a.id == b.id
+        && a.element_type == b.element_type
+        && a.shape == b.shape
+        && a.points == b.points
+        && a.extra == b.extra
+        && a.children.is_some() == b.children.is_some()
+}
+
+fn child_path(prefix: &[usize], index: usize) -> Vec<usize> {
+    let mut path = prefix.to_vec();
+    path.push(index);
+    path
+}
+
+fn transform(current: &mut Vec<PlaitElement>, target: &[PlaitElement], prefix: &mut Vec<usize>, ops: &mut Vec<Operation>) {
+    // 1. 删除目标中不存在的节点（从后往前，保证下标不变）
+    let target_ids: HashSet<&str> = target.iter().map(|n| n.id.as_str()).collect();
+    for i in (0..current.len()).rev() {
+        if !target_ids.contains(current[i].id.as_str()) {
+            let node = current.remove(i);
+            ops.push(Operation::remove(child_path(prefix, i), node));
+        }
+    }
+
+    // 2. 按目标顺序排列：已有节点挪到位，缺少的节点插入
+    for (i, wanted) in target.iter().enumerate() {
+        if current.get(i).map(|n| n.id == wanted.id).unwrap_or(false) {
+            continue;
+        }
+        match current.iter().skip(i).position(|n| n.id == wanted.id) {
+            Some(offset) => {
+                let node = current.remove(i + offset);
+                ops.push(Operation::remove(child_path(prefix, i + offset), node.clone()));
+                ops.push(Operation::insert(child_path(prefix, i), node.clone()));
+                current.insert(i, node);
+            }
+            None => {
+                ops.push(Operation::insert(child_path(prefix, i), wanted.clone()));
+                current.insert(i, wanted.clone());
+            }
+        }
+    }
+    // id 重复时可能剩下多余节点
+    while current.len() > target.len() {
+        let i = current.len() - 1;
+        let node = current.remove(i);
+        ops.push(Operation::remove(child_path(prefix, i), node));
+    }
+
+    // 3. 更新属性：节点本身变化时整体替换，否则递归比较子节点
+    for (i, wanted) in target.iter().enumerate() {
+        if current[i] == *wanted {
+            continue;
+        }
+        if !shallow_eq(&current[i], wanted) {
+            ops.push(Operation::set(child_path(prefix, i), wanted.clone()));
+            current[i] = wanted.clone();
+        } else if let (Some(children), Some(wanted_children)) = (current[i].children.as_mut(), wanted.children.as_ref()) {
+            prefix.push(i);
+            transform(children, wanted_children, prefix, ops);
+            prefix.pop();
+        }
+    }
+}
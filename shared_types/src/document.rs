@@ -0,0 +1,77 @@
+//! `.drawnix` 文件格式，与前端 `serializeAsJSON` 导出的结构一致
+
+use crate::types::*;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+pub const DRAWNIX_TYPE: &str = "drawnix";
+pub const DRAWNIX_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Viewport {
+    pub zoom: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub origination: Option<Point>,
+}
+
+impl Default for Viewport {
+    fn default() -> Self {
+        Viewport { zoom: 1.0, origination: None }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct DrawnixDocument {
+    #[serde(rename = "type")]
+    #[cfg_attr(feature = "schema", schemars(extend("const" = "drawnix")))]
+    pub doc_type: String, // 固定为 "drawnix"
+    pub version: u32,
+    #[serde(default = "default_source")]
+    pub source: String,
+    pub elements: Vec<PlaitElement>,
+    #[serde(default)]
+    pub viewport: Viewport,
+}
+
+fn default_source() -> String {
+    "web".into()
+}
+
+impl DrawnixDocument {
+    pub fn new(elements: Vec<PlaitElement>) -> Self {
+        DrawnixDocument {
+            doc_type: DRAWNIX_TYPE.into(),
+            version: DRAWNIX_VERSION,
+            source: default_source(),
+            elements,
+            viewport: Viewport::default(),
+        }
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let doc: DrawnixDocument =
+            serde_json::from_str(json).map_err(|e| format!("解析 .drawnix 文件失败: {}", e))?;
+        if doc.doc_type != DRAWNIX_TYPE {
+            return Err(format!("不是 drawnix 文件（type = {:?}）", doc.doc_type));
+        }
+        Ok(doc)
+    }
+
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string_pretty(self).map_err(|e| format!("序列化 .drawnix 文件失败: {}", e))
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref();
+        let json = fs::read_to_string(path).map_err(|e| format!("读取 {} 失败: {}", path.display(), e))?;
+        Self::from_json(&json)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), String> {
+        let path = path.as_ref();
+        fs::write(path, self.to_json()?).map_err(|e| format!("写入 {} 失败: {}", path.display(), e))
+    }
+}
@@ -0,0 +1,541 @@
+//! draw.io（mxGraph XML）导入：把 `.drawio` 文件中的图形和连线转换成白板元素。
+//!
+//! 支持未压缩的 `<mxGraphModel>` 和默认的压缩格式（base64 + deflate + URL 编码）。
+//! 顶点按样式映射到几何图形，边映射为绑定两端图形的连线，标签（含 HTML 标签）转换成纯文字；
+//! 无法对应的内容跳过或按矩形导入，记录在 [`Conversion::unsupported`] 中。
+
+use crate::conversion::{Conversion, Unsupported};
+use crate::geometry::{bounds_of, Rect};
+use crate::types::*;
+use base64::Engine;
+use miniz_oxide::inflate::TINFLStatus;
+use roxmltree::Node;
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+
+/// 单个压缩页面解压后的最大字节数
+const MAX_PAGE_BYTES: usize = 64 * 1024 * 1024;
+
+#[derive(Debug, Clone)]
+pub struct DrawioOptions {
+    /// 导入哪一页：页名或从 1 开始的序号，默认第一页
+    pub page: Option<String>,
+    /// 把图整体平移到这个位置（左上角），默认保持原坐标
+    pub origin: Option<Point>,
+    /// 元素 id 前缀，draw.io 的 id 常是 "2"、"3" 这样的短数字，加前缀避免与白板上已有元素冲突
+    pub id_prefix: String,
+}
+
+impl Default for DrawioOptions {
+    fn default() -> Self {
+        DrawioOptions { page: None, origin: None, id_prefix: "drawio".into() }
+    }
+}
+
+/// 解析 `.drawio` 文件，返回按原图层次排列的元素
+pub fn import_drawio(xml: &str, options: &DrawioOptions) -> Result<Conversion<Vec<PlaitElement>>, String> {
+    let document = roxmltree::Document::parse(xml).map_err(|e| format!("解析 draw.io 文件失败: {}", e))?;
+    let root = document.root_element();
+    match root.tag_name().name() {
+        "mxGraphModel" => Ok(import_model(root, options)),
+        "mxfile" => {
+            let pages: Vec<Node> = root.children().filter(|n| n.has_tag_name("diagram")).collect();
+            let page = select_page(&pages, options.page.as_deref())?;
+            if let Some(model) = page.children().find(|n| n.has_tag_name("mxGraphModel")) {
+                return Ok(import_model(model, options));
+            }
+            let xml = decompress(page.text().unwrap_or_default())?;
+            let document = roxmltree::Document::parse(&xml).map_err(|e| format!("解析 draw.io 页面失败: {}", e))?;
+            Ok(import_model(document.root_element(), options))
+        }
+        other => Err(format!("不是 draw.io 文件（根元素为 <{}>）", other)),
+    }
+}
+
+fn select_page<'a, 'input>(pages: &[Node<'a, 'input>], wanted: Option<&str>) -> Result<Node<'a, 'input>, String> {
+    let names = || pages.iter().map(|p| p.attribute("name").unwrap_or("?")).collect::<Vec<_>>().join("、");
+    match wanted {
+        None => pages.first().copied().ok_or_else(|| "draw.io 文件中没有页面".to_string()),
+        Some(wanted) => pages
+            .iter()
+            .copied()
+            .find(|p| p.attribute("name") == Some(wanted))
+            .or_else(|| wanted.parse::<usize>().ok().and_then(|i| i.checked_sub(1)).and_then(|i| pages.get(i).copied()))
+            .ok_or_else(|| format!("没有页面 {}（共有: {}）", wanted, names())),
+    }
+}
+
+/// 压缩页面：base64 → raw deflate → encodeURIComponent 编码的 XML。
+/// 解压后的大小有上限，很小的压缩数据也可能展开成极大的内容
+fn decompress(data: &str) -> Result<String, String> {
+    let data: String = data.chars().filter(|c| !c.is_whitespace()).collect();
+    let bytes = base64::engine::general_purpose::STANDARD.decode(data).map_err(|e| format!("draw.io 页面不是有效的 base64: {}", e))?;
+    let inflated = miniz_oxide::inflate::decompress_to_vec_with_limit(&bytes, MAX_PAGE_BYTES).map_err(|e| match e.status {
+        TINFLStatus::HasMoreOutput => format!("draw.io 页面解压后超过 {} MB", MAX_PAGE_BYTES / 1024 / 1024),
+        status => format!("解压 draw.io 页面失败: {:?}", status),
+    })?;
+    let encoded = String::from_utf8(inflated).map_err(|e| format!("draw.io 页面不是 UTF-8: {}", e))?;
+    percent_decode(&encoded)
+}
+
+fn percent_decode(text: &str) -> Result<String, String> {
+    let bytes = text.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = (bytes[i] == b'%').then(|| bytes.get(i + 1..i + 3)).flatten();
+        match hex.and_then(|h| u8::from_str_radix(std::str::from_utf8(h).ok()?, 16).ok()) {
+            Some(byte) => {
+                out.push(byte);
+                i += 3;
+            }
+            None => {
+                out.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8(out).map_err(|e| format!("draw.io 页面不是 UTF-8: {}", e))
+}
+
+/// `key=value;` 形式的样式，没有等号的词（如 ellipse、text）单独记录
+#[derive(Default)]
+struct Style {
+    values: HashMap<String, String>,
+    words: Vec<String>,
+}
+
+impl Style {
+    fn parse(style: &str) -> Self {
+        let mut parsed = Style::default();
+        for part in style.split(';').map(str::trim).filter(|p| !p.is_empty()) {
+            match part.split_once('=') {
+                Some((key, value)) => {
+                    parsed.values.insert(key.into(), value.into());
+                }
+                None => parsed.words.push(part.into()),
+            }
+        }
+        parsed
+    }
+
+    fn get(&self, key: &str) -> Option<&str> {
+        self.values.get(key).map(String::as_str)
+    }
+
+    fn number(&self, key: &str) -> Option<f64> {
+        self.get(key).and_then(|v| v.parse().ok())
+    }
+
+    fn flag(&self, key: &str) -> bool {
+        self.get(key) == Some("1")
+    }
+
+    fn has(&self, word: &str) -> bool {
+        self.words.iter().any(|w| w == word)
+    }
+
+    /// shape=... 优先，否则取第一个不带等号的词
+    fn shape(&self) -> &str {
+        self.get("shape").or(self.words.first().map(String::as_str)).unwrap_or_default()
+    }
+}
+
+#[derive(Default)]
+struct Geometry {
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+    points: Vec<Point>,
+    source_point: Option<Point>,
+    target_point: Option<Point>,
+}
+
+struct Cell {
+    id: String,
+    parent: Option<String>,
+    label: String,
+    style: Style,
+    vertex: bool,
+    edge: bool,
+    source: Option<String>,
+    target: Option<String>,
+    geometry: Geometry,
+}
+
+fn point(node: Node) -> Point {
+    let value = |key| node.attribute(key).and_then(|v: &str| v.parse().ok()).unwrap_or(0.0);
+    Point(value("x"), value("y"))
+}
+
+fn parse_cell(cell: Node, wrapper: Option<Node>) -> Cell {
+    // UserObject/object 包装的单元格，id 和标签写在外层
+    let attribute = |key: &str| wrapper.and_then(|w| w.attribute(key)).or_else(|| cell.attribute(key));
+    let style = Style::parse(cell.attribute("style").unwrap_or_default());
+    let raw_label = wrapper.and_then(|w| w.attribute("label")).or_else(|| cell.attribute("value")).unwrap_or_default();
+    let label = if style.flag("html") { html_text(raw_label) } else { raw_label.to_string() };
+
+    let mut geometry = Geometry::default();
+    if let Some(node) = cell.children().find(|n| n.has_tag_name("mxGeometry")) {
+        let value = |key| node.attribute(key).and_then(|v: &str| v.parse().ok()).unwrap_or(0.0);
+        geometry.x = value("x");
+        geometry.y = value("y");
+        geometry.width = value("width");
+        geometry.height = value("height");
+        for child in node.children().filter(|n| n.is_element()) {
+            match (child.tag_name().name(), child.attribute("as")) {
+                ("mxPoint", Some("sourcePoint")) => geometry.source_point = Some(point(child)),
+                ("mxPoint", Some("targetPoint")) => geometry.target_point = Some(point(child)),
+                ("Array", Some("points")) => {
+                    geometry.points = child.children().filter(|n| n.has_tag_name("mxPoint")).map(point).collect();
+                }
+                _ => {}
+            }
+        }
+    }
+
+    Cell {
+        id: attribute("id").unwrap_or_default().into(),
+        parent: cell.attribute("parent").map(String::from),
+        label,
+        style,
+        vertex: cell.attribute("vertex") == Some("1"),
+        edge: cell.attribute("edge") == Some("1"),
+        source: cell.attribute("source").map(String::from),
+        target: cell.attribute("target").map(String::from),
+        geometry,
+    }
+}
+
+/// 开启 html=1 的标签是 HTML 片段：换行标签转成换行，其余标签去掉，解码实体
+fn html_text(html: &str) -> String {
+    let mut text = String::new();
+    let mut rest = html;
+    while let Some(start) = rest.find('<') {
+        text.push_str(&rest[..start]);
+        let Some(end) = rest[start..].find('>') else {
+            rest = &rest[start..];
+            break;
+        };
+        let tag = rest[start + 1..start + end].trim().to_ascii_lowercase();
+        let name = tag.trim_start_matches('/').split([' ', '/']).next().unwrap_or_default();
+        if name == "br" || (tag.starts_with('/') && matches!(name, "div" | "p" | "li")) {
+            text.push('\n');
+        }
+        rest = &rest[start + end + 1..];
+    }
+    text.push_str(rest);
+    let text = decode_entities(&text);
+    text.lines().map(str::trim).collect::<Vec<_>>().join("\n").trim().to_string()
+}
+
+fn decode_entities(text: &str) -> String {
+    let mut out = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let decoded = rest.find(';').filter(|&end| end <= 10).and_then(|end| {
+            let entity = &rest[1..end];
+            let c = match entity {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" | "#39" => Some('\''),
+                "nbsp" => Some(' '),
+                _ => entity
+                    .strip_prefix("#x")
+                    .or_else(|| entity.strip_prefix("#X"))
+                    .map(|hex| u32::from_str_radix(hex, 16).ok())
+                    .unwrap_or_else(|| entity.strip_prefix('#').and_then(|dec| dec.parse().ok()))
+                    .and_then(char::from_u32),
+            };
+            c.map(|c| (c, end))
+        });
+        match decoded {
+            Some((c, end)) => {
+                out.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+fn import_model(model: Node, options: &DrawioOptions) -> Conversion<Vec<PlaitElement>> {
+    let Some(root) = model.children().find(|n| n.has_tag_name("root")) else {
+        return Conversion { output: Vec::new(), unsupported: Vec::new() };
+    };
+    let cells: Vec<Cell> = root
+        .children()
+        .filter(|n| n.is_element())
+        .filter_map(|node| match node.tag_name().name() {
+            "mxCell" => Some(parse_cell(node, None)),
+            "UserObject" | "object" => node.children().find(|n| n.has_tag_name("mxCell")).map(|cell| parse_cell(cell, Some(node))),
+            _ => None,
+        })
+        .collect();
+    let by_id: HashMap<&str, &Cell> = cells.iter().map(|c| (c.id.as_str(), c)).collect();
+
+    // 分组和容器中的子元素坐标相对于父元素
+    let offset = |cell: &Cell| {
+        let mut offset = Point(0.0, 0.0);
+        let mut parent = cell.parent.as_deref();
+        let mut depth = 0;
+        while let Some(p) = parent.and_then(|id| by_id.get(id)).filter(|p| p.vertex) {
+            offset = Point(offset.0 + p.geometry.x, offset.1 + p.geometry.y);
+            parent = p.parent.as_deref();
+            depth += 1;
+            if depth > cells.len() {
+                break;
+            }
+        }
+        offset
+    };
+    let rects: HashMap<&str, Rect> = cells
+        .iter()
+        .filter(|c| c.vertex)
+        .map(|c| {
+            let Point(x, y) = offset(c);
+            let (x, y) = (x + c.geometry.x, y + c.geometry.y);
+            (c.id.as_str(), Rect { min_x: x, min_y: y, max_x: x + c.geometry.width, max_y: y + c.geometry.height })
+        })
+        .collect();
+
+    // 边上的文字可能是以边为父元素的单独顶点（edgeLabel）
+    let mut edge_labels: HashMap<&str, Vec<&Cell>> = HashMap::new();
+    for cell in &cells {
+        if let Some(parent) = cell.parent.as_deref().filter(|p| by_id.get(p).is_some_and(|p| p.edge)) {
+            if cell.vertex {
+                edge_labels.entry(parent).or_default().push(cell);
+            }
+        }
+    }
+
+    let id = |cell_id: &str| format!("{}-{}", options.id_prefix, cell_id);
+    let mut elements = Vec::new();
+    let mut unsupported = Vec::new();
+    for cell in &cells {
+        let report = |reason: &str| Unsupported { id: cell.id.clone(), kind: cell.style.shape().to_string(), reason: reason.into() };
+        if cell.vertex {
+            if cell.parent.as_deref().is_some_and(|p| by_id.get(p).is_some_and(|p| p.edge)) || cell.style.has("group") {
+                continue;
+            }
+            if cell.style.shape() == "image" || cell.style.get("image").is_some() {
+                unsupported.push(report("图片暂不支持"));
+                continue;
+            }
+            let (shape, exact) = vertex_shape(&cell.style);
+            if shape == "text" && cell.label.is_empty() {
+                continue;
+            }
+            if !exact {
+                unsupported.push(report("没有对应的图形，按矩形导入"));
+            }
+            elements.push(vertex(id(&cell.id), shape, cell, rects[cell.id.as_str()]));
+        } else if cell.edge {
+            let labels = edge_labels.get(cell.id.as_str()).map(Vec::as_slice).unwrap_or_default();
+            match edge(cell, labels, offset(cell), &rects, &id) {
+                Some(element) => elements.push(element),
+                None => unsupported.push(Unsupported { id: cell.id.clone(), kind: "edge".into(), reason: "缺少起点或终点".into() }),
+            }
+        }
+    }
+
+    if let (Some(origin), Some(bounds)) = (&options.origin, bounds_of(&elements)) {
+        let (dx, dy) = (origin.0 - bounds.min_x, origin.1 - bounds.min_y);
+        for element in &mut elements {
+            for p in &mut element.points {
+                *p = Point(p.0 + dx, p.1 + dy);
+            }
+        }
+    }
+    Conversion { output: elements, unsupported }
+}
+
+/// draw.io 图形对应的 Drawnix 形状，第二项为 false 表示没有对应图形、按矩形近似
+fn vertex_shape(style: &Style) -> (&'static str, bool) {
+    let shape = match style.shape() {
+        "ellipse" | "doubleEllipse" | "mxgraph.flowchart.start_1" | "mxgraph.flowchart.start_2" => "ellipse",
+        "rhombus" => "diamond",
+        "text" => "text",
+        "hexagon" => "hexagon",
+        "parallelogram" => "parallelogram",
+        "trapezoid" => "trapezoid",
+        "triangle" => "triangle",
+        "process" => "predefinedProcess",
+        "cylinder" | "cylinder3" | "datastore" => "database",
+        "document" | "mxgraph.flowchart.document" => "document",
+        "mxgraph.flowchart.terminator" => "terminal",
+        "mxgraph.flowchart.decision" => "decision",
+        "mxgraph.flowchart.process" => "process",
+        "mxgraph.flowchart.data" => "data",
+        "" | "rounded" | "whiteSpace" | "html" | "swimlane" | "rectangle" | "label" => {
+            if style.flag("rounded") { "roundRectangle" } else { "rectangle" }
+        }
+        _ => return ("rectangle", false),
+    };
+    (shape, true)
+}
+
+/// 描边、填充、线型和透明度；draw.io 默认描边宽度为 1
+fn import_style(style: &Style, extra: &mut Map<String, Value>, default_fill: Option<&str>) {
+    if let Some(stroke) = style.get("strokeColor").filter(|c| *c != "default") {
+        extra.insert("strokeColor".into(), json!(stroke));
+    }
+    match style.get("fillColor").filter(|c| *c != "default") {
+        Some("none") => {}
+        Some(fill) => {
+            extra.insert("fill".into(), json!(fill));
+        }
+        None => {
+            if let Some(fill) = default_fill {
+                extra.insert("fill".into(), json!(fill));
+            }
+        }
+    }
+    extra.insert("strokeWidth".into(), json!(style.number("strokeWidth").unwrap_or(1.0)));
+    if style.flag("dashed") {
+        // dashPattern 以 1 开头的短虚线（如 "1 2"）视为点线
+        let dotted = style.get("dashPattern").is_some_and(|p| p.split_whitespace().next() == Some("1"));
+        extra.insert("strokeStyle".into(), json!(if dotted { "dotted" } else { "dashed" }));
+    }
+    extra.insert("opacity".into(), json!(style.number("opacity").unwrap_or(100.0) / 100.0));
+}
+
+fn text_value(text: &str, style: &Style, align: bool) -> Value {
+    let mut leaf = json!({ "text": text });
+    if let Some(color) = style.get("fontColor").filter(|c| *c != "default" && *c != "none") {
+        leaf["color"] = json!(color);
+    }
+    let mut value = json!({ "children": [leaf] });
+    if align {
+        value["align"] = json!(style.get("align").unwrap_or("center"));
+    }
+    value
+}
+
+fn text_size(text: &str) -> (f64, f64) {
+    let width = text.lines().map(|line| line.chars().map(|c| if c.is_ascii() { 8.0 } else { 14.0 }).sum::<f64>()).fold(0.0, f64::max);
+    (width, text.lines().count().max(1) as f64 * 20.0)
+}
+
+fn vertex(id: String, shape: &str, cell: &Cell, rect: Rect) -> PlaitElement {
+    let mut extra = Map::new();
+    extra.insert("angle".into(), json!(cell.style.number("rotation").unwrap_or(0.0).to_radians()));
+    import_style(&cell.style, &mut extra, (shape != "text").then_some("#ffffff"));
+    if !cell.label.is_empty() {
+        extra.insert("text".into(), text_value(&cell.label, &cell.style, true));
+        extra.insert("textHeight".into(), json!(text_size(&cell.label).1));
+    }
+    PlaitElement {
+        id,
+        element_type: "geometry".into(),
+        shape: shape.into(),
+        points: vec![Point(rect.min_x, rect.min_y), Point(rect.max_x, rect.max_y)],
+        children: None,
+        extra,
+    }
+}
+
+/// draw.io 箭头样式对应的端点标记
+fn marker(style: &Style, arrow: &str, fill: &str, default: &str) -> &'static str {
+    let filled = style.get(fill) != Some("0");
+    match style.get(arrow).unwrap_or(default) {
+        "none" | "" => "none",
+        "block" | "blockThin" if !filled => "hollow-triangle",
+        "block" | "blockThin" => "solid-triangle",
+        "open" | "openThin" | "openAsync" => "open-triangle",
+        "dash" => "single-line",
+        _ => "arrow",
+    }
+}
+
+fn edge(cell: &Cell, labels: &[&Cell], offset: Point, rects: &HashMap<&str, Rect>, id: &dyn Fn(&str) -> String) -> Option<PlaitElement> {
+    let shift = |p: &Point| Point(p.0 + offset.0, p.1 + offset.1);
+    let source_rect = cell.source.as_deref().and_then(|s| rects.get(s).map(|r| (s, *r)));
+    let target_rect = cell.target.as_deref().and_then(|t| rects.get(t).map(|r| (t, *r)));
+    let waypoints: Vec<Point> = cell.geometry.points.iter().map(shift).collect();
+    let center = |r: &Rect| Point((r.min_x + r.max_x) / 2.0, (r.min_y + r.max_y) / 2.0);
+
+    // 两端的大致位置，用于在没有指定连接点时判断从哪一侧连出
+    let source_hint = source_rect.map(|(_, r)| center(&r)).or_else(|| cell.geometry.source_point.as_ref().map(shift))?;
+    let target_hint = target_rect.map(|(_, r)| center(&r)).or_else(|| cell.geometry.target_point.as_ref().map(shift))?;
+
+    let style = &cell.style;
+    let end = |bound: Option<(&str, Rect)>, free: &Option<Point>, toward: &Point, x_key: &str, y_key: &str, marker: &str| {
+        let mut value = json!({ "marker": marker });
+        let at = match bound {
+            Some((bound_id, rect)) => {
+                let connection = match (style.number(x_key), style.number(y_key)) {
+                    (Some(x), Some(y)) => [x, y],
+                    _ => facing_side(&rect, toward),
+                };
+                value["connection"] = json!(connection);
+                value["boundId"] = json!(id(bound_id));
+                Point(rect.min_x + rect.width() * connection[0], rect.min_y + rect.height() * connection[1])
+            }
+            None => shift(free.as_ref().expect("已检查端点存在")),
+        };
+        (value, at)
+    };
+    let source_marker = marker(style, "startArrow", "startFill", "none");
+    let target_marker = marker(style, "endArrow", "endFill", "classic");
+    let (source, start) = end(source_rect, &cell.geometry.source_point, waypoints.first().unwrap_or(&target_hint), "exitX", "exitY", source_marker);
+    let (target, finish) = end(target_rect, &cell.geometry.target_point, waypoints.last().unwrap_or(&source_hint), "entryX", "entryY", target_marker);
+
+    let shape = if style.flag("curved") {
+        "curve"
+    } else if style.get("edgeStyle").is_some_and(|s| s != "none") {
+        "elbow"
+    } else {
+        "straight"
+    };
+    let mut points = vec![start];
+    if shape != "elbow" {
+        // 折线的拐点由前端按连接方向计算，只保留两端
+        points.extend(waypoints);
+    }
+    points.push(finish);
+
+    // 边自身的文字位置在 mxGeometry 的 x 中，-1 到 1 对应从起点到终点
+    let label = |text: &str, style: &Style, x: f64| {
+        let (width, height) = text_size(text);
+        json!({ "text": text_value(text, style, false), "position": ((x + 1.0) / 2.0).clamp(0.0, 1.0), "width": width, "height": height })
+    };
+    let mut texts = Vec::new();
+    if !cell.label.is_empty() {
+        texts.push(label(&cell.label, style, cell.geometry.x));
+    }
+    for child in labels.iter().filter(|c| !c.label.is_empty()) {
+        texts.push(label(&child.label, &child.style, child.geometry.x));
+    }
+
+    let mut extra = Map::new();
+    extra.insert("source".into(), source);
+    extra.insert("target".into(), target);
+    extra.insert("texts".into(), Value::Array(texts));
+    import_style(style, &mut extra, None);
+    extra.remove("fill");
+    Some(PlaitElement { id: id(&cell.id), element_type: "line".into(), shape: shape.into(), points, children: None, extra })
+}
+
+/// 连线从图形朝向另一端的那条边的中点连出
+fn facing_side(rect: &Rect, toward: &Point) -> [f64; 2] {
+    let dx = toward.0 - (rect.min_x + rect.max_x) / 2.0;
+    let dy = toward.1 - (rect.min_y + rect.max_y) / 2.0;
+    if dx.abs() * rect.height() >= dy.abs() * rect.width() {
+        if dx >= 0.0 { [1.0, 0.5] } else { [0.0, 0.5] }
+    } else if dy >= 0.0 {
+        [0.5, 1.0]
+    } else {
+        [0.5, 0.0]
+    }
+}
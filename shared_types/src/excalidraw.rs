@@ -0,0 +1,527 @@
+//! Excalidraw 文件（`.excalidraw`）与 `.drawnix` 互转。
+//!
+//! 支持矩形、椭圆、菱形、文字（独立文字和图形、连线上的文字）、箭头/直线和自由画笔，
+//! 这部分来回转换不丢信息；其余元素跳过并记录在 [`Conversion::unsupported`] 中。
+//! 手绘风格（roughness、fillStyle、字体）等 Drawnix 没有的属性不会保留。
+
+use crate::conversion::{Conversion, Unsupported};
+use crate::document::DrawnixDocument;
+use crate::geometry::Rect;
+use crate::svg::{line_route, plain_text};
+use crate::types::*;
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+
+/// Excalidraw 的默认描边色，对应 Drawnix 中不写 strokeColor
+const EXCALIDRAW_STROKE: &str = "#1e1e1e";
+const FONT_SIZE: f64 = 16.0;
+const LINE_HEIGHT: f64 = 1.25;
+/// 图形内文字距边框的距离
+const TEXT_PADDING: f64 = 5.0;
+
+fn text<'a>(value: &'a Value, key: &str) -> Option<&'a str> {
+    value.get(key).and_then(Value::as_str)
+}
+
+fn number(value: &Value, key: &str) -> f64 {
+    value.get(key).and_then(Value::as_f64).unwrap_or(0.0)
+}
+
+/// 整数写成整数，保证来回转换后的 JSON 与原值相等
+fn num(value: f64) -> Value {
+    if value.fract() == 0.0 && value.abs() < 1e15 {
+        json!(value as i64)
+    } else {
+        json!(value)
+    }
+}
+
+/// Excalidraw 箭头样式与 Drawnix 连线端点标记的对应关系
+const MARKERS: [(&str, &str); 4] = [
+    ("arrow", "arrow"),
+    ("triangle", "solid-triangle"),
+    ("triangle_outline", "hollow-triangle"),
+    ("bar", "single-line"),
+];
+
+fn marker_from_arrowhead(arrowhead: Option<&str>) -> &'static str {
+    match arrowhead {
+        None => "none",
+        Some(head) => MARKERS.iter().find(|(e, _)| *e == head).map_or("arrow", |(_, d)| d),
+    }
+}
+
+fn arrowhead_from_marker(marker: &str) -> Value {
+    match marker {
+        "none" => Value::Null,
+        _ => json!(MARKERS.iter().find(|(_, d)| *d == marker).map_or("arrow", |(e, _)| e)),
+    }
+}
+
+/// 把 Excalidraw 场景转换成 `.drawnix` 文档
+pub fn from_excalidraw(json: &str) -> Result<Conversion<DrawnixDocument>, String> {
+    let scene: Value = serde_json::from_str(json).map_err(|e| format!("解析 Excalidraw 文件失败: {}", e))?;
+    if text(&scene, "type") != Some("excalidraw") {
+        return Err(format!("不是 Excalidraw 文件（type = {:?}）", scene.get("type")));
+    }
+    let elements: Vec<&Value> = scene
+        .get("elements")
+        .and_then(Value::as_array)
+        .ok_or("Excalidraw 文件缺少 elements")?
+        .iter()
+        .filter(|e| !e.get("isDeleted").and_then(Value::as_bool).unwrap_or(false))
+        .collect();
+    let by_id: HashMap<&str, &Value> = elements.iter().filter_map(|e| Some((text(e, "id")?, *e))).collect();
+    let can_hold_text = |id: &str| by_id.get(id).and_then(|e| text(e, "type")).is_some_and(|kind| {
+        matches!(kind, "rectangle" | "ellipse" | "diamond" | "arrow" | "line")
+    });
+    // 图形和连线上的文字在 Excalidraw 中是单独的 text 元素，用 containerId 指向所属元素
+    let bound_text: HashMap<&str, &Value> = elements
+        .iter()
+        .filter_map(|e| Some((text(e, "containerId")?, *e)))
+        .filter(|(container, _)| can_hold_text(container))
+        .collect();
+
+    let mut converted = Vec::new();
+    let mut unsupported = Vec::new();
+    for element in &elements {
+        let id = text(element, "id").unwrap_or_default();
+        let kind = text(element, "type").unwrap_or_default();
+        let label = bound_text.get(id).copied();
+        let result = match kind {
+            "rectangle" | "ellipse" | "diamond" => Ok(import_shape(element, label)),
+            "text" if text(element, "containerId").is_some_and(can_hold_text) => continue,
+            "text" => Ok(import_text(element)),
+            "arrow" | "line" => Ok(import_line(element, label, &by_id)),
+            "freedraw" => Ok(import_freehand(element)),
+            "image" => Err("图片数据保存在 files 中，暂不支持"),
+            "frame" | "magicframe" => Err("Drawnix 没有画框"),
+            "embeddable" | "iframe" => Err("Drawnix 不支持嵌入网页"),
+            _ => Err("未知的元素类型"),
+        };
+        match result {
+            Ok(element) => converted.push(element),
+            Err(reason) => unsupported.push(Unsupported { id: id.into(), kind: kind.into(), reason: reason.into() }),
+        }
+    }
+
+    let mut document = DrawnixDocument::new(converted);
+    if let Some(zoom) = scene.pointer("/appState/zoom/value").and_then(Value::as_f64) {
+        document.viewport.zoom = zoom;
+    }
+    Ok(Conversion { output: document, unsupported })
+}
+
+fn new_element(source: &Value, element_type: &str, shape: &str, points: Vec<Point>) -> PlaitElement {
+    PlaitElement {
+        id: text(source, "id").unwrap_or_default().into(),
+        element_type: element_type.into(),
+        shape: shape.into(),
+        points,
+        children: None,
+        extra: Map::new(),
+    }
+}
+
+fn box_points(source: &Value) -> Vec<Point> {
+    let (x, y) = (number(source, "x"), number(source, "y"));
+    vec![Point(x, y), Point(x + number(source, "width"), y + number(source, "height"))]
+}
+
+/// 描边、填充和透明度，Excalidraw 的默认值对应 Drawnix 中不写该属性
+fn import_style(source: &Value, extra: &mut Map<String, Value>) {
+    if let Some(stroke) = text(source, "strokeColor").filter(|&c| c != EXCALIDRAW_STROKE) {
+        extra.insert("strokeColor".into(), json!(stroke));
+    }
+    if let Some(fill) = text(source, "backgroundColor").filter(|&c| c != "transparent") {
+        extra.insert("fill".into(), json!(fill));
+    }
+    extra.insert("strokeWidth".into(), num(number(source, "strokeWidth")));
+    if let Some(style) = text(source, "strokeStyle").filter(|&s| s != "solid") {
+        extra.insert("strokeStyle".into(), json!(style));
+    }
+    extra.insert("opacity".into(), num(source.get("opacity").and_then(Value::as_f64).unwrap_or(100.0) / 100.0));
+}
+
+/// Excalidraw 文字元素对应的 Slate 文本片段，文字颜色写在片段上
+fn text_leaf(source: &Value) -> Value {
+    let mut leaf = json!({ "text": text(source, "text").unwrap_or_default() });
+    if let Some(color) = text(source, "strokeColor").filter(|&c| c != EXCALIDRAW_STROKE) {
+        leaf["color"] = json!(color);
+    }
+    leaf
+}
+
+fn import_shape(source: &Value, label: Option<&Value>) -> PlaitElement {
+    let shape = match text(source, "type") {
+        Some("rectangle") if !source.get("roundness").is_none_or(Value::is_null) => "roundRectangle",
+        Some(kind) => kind,
+        None => "rectangle",
+    };
+    let mut element = new_element(source, "geometry", shape, box_points(source));
+    element.extra.insert("angle".into(), num(number(source, "angle")));
+    import_style(source, &mut element.extra);
+    if let Some(label) = label {
+        element.extra.insert("text".into(), json!({ "children": [text_leaf(label)], "align": text(label, "textAlign").unwrap_or("center") }));
+        element.extra.insert("textHeight".into(), num(number(label, "height")));
+    }
+    element
+}
+
+fn import_text(source: &Value) -> PlaitElement {
+    let mut element = new_element(source, "geometry", "text", box_points(source));
+    element.extra.insert("angle".into(), num(number(source, "angle")));
+    element.extra.insert("opacity".into(), num(source.get("opacity").and_then(Value::as_f64).unwrap_or(100.0) / 100.0));
+    element.extra.insert("text".into(), json!({ "children": [text_leaf(source)], "align": text(source, "textAlign").unwrap_or("left") }));
+    element.extra.insert("textHeight".into(), num(number(source, "height")));
+    element
+}
+
+/// Excalidraw 的点相对于元素的 x、y
+fn absolute_points(source: &Value) -> Vec<Point> {
+    let (x, y) = (number(source, "x"), number(source, "y"));
+    source
+        .get("points")
+        .and_then(Value::as_array)
+        .map(|points| {
+            points
+                .iter()
+                .filter_map(|p| Some(Point(x + p.get(0)?.as_f64()?, y + p.get(1)?.as_f64()?)))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn import_line(source: &Value, label: Option<&Value>, by_id: &HashMap<&str, &Value>) -> PlaitElement {
+    let mut points = absolute_points(source);
+    let elbowed = source.get("elbowed").and_then(Value::as_bool).unwrap_or(false);
+    let shape = if elbowed {
+        // Drawnix 只保存折线两端，拐点由前端按连接方向计算
+        if points.len() > 2 {
+            points = vec![points[0].clone(), points[points.len() - 1].clone()];
+        }
+        "elbow"
+    } else if source.get("roundness").is_none_or(Value::is_null) {
+        "straight"
+    } else {
+        "curve"
+    };
+
+    let end = |binding: &str, arrowhead: &str, at: Option<&Point>| {
+        let mut end = json!({ "marker": marker_from_arrowhead(text(source, arrowhead)) });
+        let Some(binding) = source.get(binding).filter(|b| !b.is_null()) else { return end };
+        let Some(bound) = text(binding, "elementId").filter(|id| by_id.contains_key(id)) else { return end };
+        // 绑定点在元素内的相对位置：有 fixedPoint 时直接使用，否则按端点位置换算
+        let connection = match binding.get("fixedPoint").and_then(Value::as_array) {
+            Some(fixed) if fixed.len() == 2 => fixed.iter().map(|v| num(v.as_f64().unwrap_or(0.5))).collect(),
+            _ => {
+                let rect = Rect::from_points(&box_points(by_id[bound])).expect("两个点");
+                let at = at.cloned().unwrap_or(Point(rect.min_x, rect.min_y));
+                let ratio = |v: f64, min: f64, size: f64| if size > 0.0 { ((v - min) / size).clamp(0.0, 1.0) } else { 0.5 };
+                vec![
+                    num((ratio(at.0, rect.min_x, rect.width()) * 1000.0).round() / 1000.0),
+                    num((ratio(at.1, rect.min_y, rect.height()) * 1000.0).round() / 1000.0),
+                ]
+            }
+        };
+        end["connection"] = Value::Array(connection);
+        end["boundId"] = json!(bound);
+        end
+    };
+    let source_end = end("startBinding", "startArrowhead", points.first());
+    let target_end = end("endBinding", "endArrowhead", points.last());
+
+    let mut element = new_element(source, "line", shape, points);
+    element.extra.insert("source".into(), source_end);
+    element.extra.insert("target".into(), target_end);
+    let texts: Vec<Value> = label
+        .map(|label| {
+            json!({
+                "text": { "children": [text_leaf(label)] },
+                "position": 0.5,
+                "width": num(number(label, "width")),
+                "height": num(number(label, "height")),
+            })
+        })
+        .into_iter()
+        .collect();
+    element.extra.insert("texts".into(), Value::Array(texts));
+    import_style(source, &mut element.extra);
+    element
+}
+
+fn import_freehand(source: &Value) -> PlaitElement {
+    let mut element = new_element(source, "freehand", "feltTipPen", absolute_points(source));
+    import_style(source, &mut element.extra);
+    element
+}
+
+/// 把 `.drawnix` 文档转换成 Excalidraw 场景
+pub fn to_excalidraw(document: &DrawnixDocument) -> Conversion<Value> {
+    let exported = |element: &PlaitElement| export_kind(element).is_ok();
+    let ids: HashMap<&str, &PlaitElement> = document.elements.iter().filter(|e| exported(e)).map(|e| (e.id.as_str(), e)).collect();
+
+    // Excalidraw 要求被绑定的图形在 boundElements 中反向记录连线
+    let mut bound_arrows: HashMap<&str, Vec<&str>> = HashMap::new();
+    for element in document.elements.iter().filter(|e| is_line(e)) {
+        for end in ["source", "target"] {
+            if let Some(bound) = element.extra.get(end).and_then(|e| text(e, "boundId")).filter(|id| ids.contains_key(id)) {
+                bound_arrows.entry(bound).or_default().push(&element.id);
+            }
+        }
+    }
+
+    let mut elements = Vec::new();
+    let mut unsupported = Vec::new();
+    for element in &document.elements {
+        let kind = match export_kind(element) {
+            Ok(kind) => kind,
+            Err(reason) => {
+                let kind = if element.shape.is_empty() { element.element_type.clone() } else { format!("{}/{}", element.element_type, element.shape) };
+                unsupported.push(Unsupported { id: element.id.clone(), kind, reason });
+                continue;
+            }
+        };
+        let arrows = bound_arrows.get(element.id.as_str()).map(Vec::as_slice).unwrap_or_default();
+        match kind {
+            "text" => elements.push(export_text(element, None)),
+            "arrow" | "line" => export_line(element, kind, &ids, &mut elements, &mut unsupported),
+            "freedraw" => elements.push(export_freehand(element)),
+            _ => export_shape(element, kind, arrows, &mut elements),
+        }
+    }
+
+    let scene = json!({
+        "type": "excalidraw",
+        "version": 2,
+        "source": "drawnix",
+        "elements": elements,
+        "appState": { "gridSize": null, "viewBackgroundColor": "#ffffff" },
+        "files": {},
+    });
+    Conversion { output: scene, unsupported }
+}
+
+fn is_line(element: &PlaitElement) -> bool {
+    matches!(element.element_type.as_str(), "line" | "arrow-line")
+}
+
+/// Drawnix 元素对应的 Excalidraw 元素类型
+fn export_kind(element: &PlaitElement) -> Result<&'static str, String> {
+    match (element.element_type.as_str(), element.shape.as_str()) {
+        ("geometry", "rectangle" | "roundRectangle") => Ok("rectangle"),
+        ("geometry", "ellipse") => Ok("ellipse"),
+        ("geometry", "diamond") => Ok("diamond"),
+        ("geometry", "text") => Ok("text"),
+        ("geometry", shape) => Err(format!("Excalidraw 没有 {} 形状", shape)),
+        (_, _) if is_line(element) => {
+            let marked = ["source", "target"].iter().any(|end| {
+                element.extra.get(*end).is_some_and(|e| text(e, "marker").is_some_and(|m| m != "none") || e.get("boundId").is_some())
+            });
+            Ok(if marked || has_label(element) { "arrow" } else { "line" })
+        }
+        ("freehand", _) => Ok("freedraw"),
+        ("mindmap" | "mind_map", _) => Err("Excalidraw 没有思维导图".into()),
+        ("image", _) => Err("图片需要内嵌到 files 中，暂不支持".into()),
+        _ => Err("未知的元素类型".into()),
+    }
+}
+
+fn has_label(element: &PlaitElement) -> bool {
+    labels(element).next().is_some()
+}
+
+fn labels(element: &PlaitElement) -> impl Iterator<Item = &Value> {
+    element
+        .extra
+        .get("texts")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter(|label| label.get("text").is_some_and(|t| !plain_text(t).is_empty()))
+}
+
+/// 用 id 生成稳定的随机种子，同一文件每次导出的结果相同
+fn seed(id: &str) -> u32 {
+    id.bytes().fold(2166136261u32, |hash, b| (hash ^ b as u32).wrapping_mul(16777619)) & 0x7fff_ffff
+}
+
+/// 各类 Excalidraw 元素共有的字段
+fn base(id: &str, kind: &str, rect: Rect, element: &PlaitElement) -> Map<String, Value> {
+    let style = |key: &str| element.extra.get(key).and_then(Value::as_str);
+    let number = |key: &str| element.extra.get(key).and_then(Value::as_f64);
+    let value = json!({
+        "id": id,
+        "type": kind,
+        "x": rect.min_x,
+        "y": rect.min_y,
+        "width": rect.width(),
+        "height": rect.height(),
+        "angle": number("angle").unwrap_or(0.0),
+        "strokeColor": style("strokeColor").unwrap_or(EXCALIDRAW_STROKE),
+        "backgroundColor": style("fill").unwrap_or("transparent"),
+        "fillStyle": "solid",
+        "strokeWidth": number("strokeWidth").unwrap_or(2.0),
+        "strokeStyle": style("strokeStyle").unwrap_or("solid"),
+        "roughness": 0,
+        "opacity": (number("opacity").unwrap_or(1.0) * 100.0).round(),
+        "groupIds": [],
+        "frameId": null,
+        "roundness": null,
+        "seed": seed(id),
+        "version": 1,
+        "versionNonce": seed(&format!("{}#", id)),
+        "isDeleted": false,
+        "boundElements": null,
+        "updated": 1,
+        "link": null,
+        "locked": false,
+    });
+    match value {
+        Value::Object(map) => map,
+        _ => unreachable!(),
+    }
+}
+
+fn bounds(points: &[Point]) -> Rect {
+    Rect::from_points(points).unwrap_or(Rect { min_x: 0.0, min_y: 0.0, max_x: 0.0, max_y: 0.0 })
+}
+
+fn export_shape(element: &PlaitElement, kind: &str, arrows: &[&str], out: &mut Vec<Value>) {
+    let rect = bounds(&element.points);
+    let mut shape = base(&element.id, kind, rect, element);
+    if element.shape == "roundRectangle" {
+        shape.insert("roundness".into(), json!({ "type": 3 }));
+    }
+    let mut bound: Vec<Value> = arrows.iter().map(|id| json!({ "id": id, "type": "arrow" })).collect();
+
+    let label = element.extra.get("text").filter(|t| !plain_text(t).is_empty()).map(|_| {
+        let text_id = format!("{}-text", element.id);
+        bound.push(json!({ "id": text_id, "type": "text" }));
+        let height = element.extra.get("textHeight").and_then(Value::as_f64).unwrap_or_else(|| text_height(element));
+        let area = Rect {
+            min_x: rect.min_x + TEXT_PADDING,
+            min_y: (rect.min_y + rect.max_y - height) / 2.0,
+            max_x: rect.max_x - TEXT_PADDING,
+            max_y: (rect.min_y + rect.max_y + height) / 2.0,
+        };
+        let mut label = export_text(element, Some((&text_id, area)));
+        label["verticalAlign"] = json!("middle");
+        label["containerId"] = json!(element.id);
+        label
+    });
+    if !bound.is_empty() {
+        shape.insert("boundElements".into(), Value::Array(bound));
+    }
+    out.push(Value::Object(shape));
+    out.extend(label);
+}
+
+fn text_height(element: &PlaitElement) -> f64 {
+    let lines = element.extra.get("text").map(plain_text).unwrap_or_default().lines().count().max(1);
+    lines as f64 * FONT_SIZE * LINE_HEIGHT
+}
+
+/// 文字元素：`area` 为 None 时是独立文字，位置取元素自身
+fn export_text(element: &PlaitElement, area: Option<(&str, Rect)>) -> Value {
+    let (id, rect) = area.unwrap_or((&element.id, bounds(&element.points)));
+    let rich = element.extra.get("text").cloned().unwrap_or(Value::Null);
+    export_text_value(id, rect, &rich, element)
+}
+
+fn export_text_value(id: &str, rect: Rect, rich: &Value, element: &PlaitElement) -> Value {
+    let content = plain_text(rich);
+    let mut label = base(id, "text", rect, element);
+    label.insert("strokeColor".into(), json!(text_color(rich).unwrap_or(EXCALIDRAW_STROKE)));
+    label.insert("backgroundColor".into(), json!("transparent"));
+    label.insert("text".into(), json!(content));
+    label.insert("originalText".into(), json!(content));
+    label.insert("fontSize".into(), json!(FONT_SIZE));
+    label.insert("fontFamily".into(), json!(2));
+    label.insert("textAlign".into(), json!(text(rich, "align").unwrap_or("left")));
+    label.insert("verticalAlign".into(), json!("top"));
+    label.insert("containerId".into(), Value::Null);
+    label.insert("lineHeight".into(), json!(LINE_HEIGHT));
+    label.insert("autoResize".into(), json!(true));
+    Value::Object(label)
+}
+
+/// 第一个带颜色的文本片段的颜色
+fn text_color(value: &Value) -> Option<&str> {
+    if let Some(color) = text(value, "color") {
+        return Some(color);
+    }
+    value.get("children")?.as_array()?.iter().find_map(text_color)
+}
+
+fn export_line(element: &PlaitElement, kind: &str, ids: &HashMap<&str, &PlaitElement>, out: &mut Vec<Value>, unsupported: &mut Vec<Unsupported>) {
+    let route = line_route(element);
+    let rect = bounds(&route);
+    let origin = route.first().cloned().unwrap_or(Point(0.0, 0.0));
+    let mut arrow = base(&element.id, kind, rect, element);
+    arrow.insert("x".into(), json!(origin.0));
+    arrow.insert("y".into(), json!(origin.1));
+    arrow.insert("points".into(), json!(route.iter().map(|p| [p.0 - origin.0, p.1 - origin.1]).collect::<Vec<_>>()));
+    arrow.insert("lastCommittedPoint".into(), Value::Null);
+    if element.shape == "curve" {
+        arrow.insert("roundness".into(), json!({ "type": 2 }));
+    }
+    arrow.insert("elbowed".into(), json!(element.shape == "elbow"));
+
+    for (end, binding, arrowhead) in [("source", "startBinding", "startArrowhead"), ("target", "endBinding", "endArrowhead")] {
+        let end = element.extra.get(end);
+        let marker = end.and_then(|e| text(e, "marker")).unwrap_or("none");
+        arrow.insert(arrowhead.into(), if kind == "arrow" { arrowhead_from_marker(marker) } else { Value::Null });
+        let bound = end.and_then(|e| text(e, "boundId")).filter(|id| ids.contains_key(id));
+        let value = match bound {
+            Some(bound) => {
+                let mut value = json!({ "elementId": bound, "focus": 0, "gap": 0 });
+                if let Some(connection) = end.and_then(|e| e.get("connection")) {
+                    value["fixedPoint"] = connection.clone();
+                }
+                value
+            }
+            None => Value::Null,
+        };
+        arrow.insert(binding.into(), value);
+    }
+
+    let mut labels = labels(element);
+    let label = labels.next().map(|label| {
+        let text_id = format!("{}-label", element.id);
+        arrow.insert("boundElements".into(), json!([{ "id": text_id, "type": "text" }]));
+        let rich = label.get("text").cloned().unwrap_or(Value::Null);
+        let width = label.get("width").and_then(Value::as_f64).unwrap_or(plain_text(&rich).chars().count() as f64 * FONT_SIZE);
+        let height = label.get("height").and_then(Value::as_f64).unwrap_or(FONT_SIZE * LINE_HEIGHT);
+        let (cx, cy) = ((rect.min_x + rect.max_x) / 2.0, (rect.min_y + rect.max_y) / 2.0);
+        let area = Rect { min_x: cx - width / 2.0, min_y: cy - height / 2.0, max_x: cx + width / 2.0, max_y: cy + height / 2.0 };
+        let mut text = export_text_value(&text_id, area, &rich, element);
+        text["textAlign"] = json!("center");
+        text["verticalAlign"] = json!("middle");
+        text["containerId"] = json!(element.id);
+        text
+    });
+    let dropped = labels.count();
+    if dropped > 0 {
+        unsupported.push(Unsupported {
+            id: element.id.clone(),
+            kind: element.element_type.clone(),
+            reason: format!("Excalidraw 的连线只能有一段文字，其余 {} 段未转换", dropped),
+        });
+    }
+    out.push(Value::Object(arrow));
+    out.extend(label);
+}
+
+fn export_freehand(element: &PlaitElement) -> Value {
+    let rect = bounds(&element.points);
+    let origin = element.points.first().cloned().unwrap_or(Point(0.0, 0.0));
+    let mut freedraw = base(&element.id, "freedraw", rect, element);
+    freedraw.insert("x".into(), json!(origin.0));
+    freedraw.insert("y".into(), json!(origin.1));
+    freedraw.insert("points".into(), json!(element.points.iter().map(|p| [p.0 - origin.0, p.1 - origin.1]).collect::<Vec<_>>()));
+    freedraw.insert("pressures".into(), json!([]));
+    freedraw.insert("simulatePressure".into(), json!(true));
+    freedraw.insert("lastCommittedPoint".into(), Value::Null);
+    Value::Object(freedraw)
+}
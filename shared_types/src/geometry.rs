@@ -0,0 +1,95 @@
+//! 元素的几何信息
+
+use crate::types::{PlaitElement, Point};
+
+/// 轴对齐的矩形区域（画布坐标）
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rect {
+    pub min_x: f64,
+    pub min_y: f64,
+    pub max_x: f64,
+    pub max_y: f64,
+}
+
+impl Rect {
+    /// 包含所有点的最小矩形，没有点时返回 None
+    pub fn from_points(points: &[Point]) -> Option<Rect> {
+        let first = points.first()?;
+        let mut rect = Rect { min_x: first.0, min_y: first.1, max_x: first.0, max_y: first.1 };
+        for p in &points[1..] {
+            rect.min_x = rect.min_x.min(p.0);
+            rect.min_y = rect.min_y.min(p.1);
+            rect.max_x = rect.max_x.max(p.0);
+            rect.max_y = rect.max_y.max(p.1);
+        }
+        Some(rect)
+    }
+
+    pub fn union(&self, other: &Rect) -> Rect {
+        Rect {
+            min_x: self.min_x.min(other.min_x),
+            min_y: self.min_y.min(other.min_y),
+            max_x: self.max_x.max(other.max_x),
+            max_y: self.max_y.max(other.max_y),
+        }
+    }
+
+    pub fn width(&self) -> f64 {
+        self.max_x - self.min_x
+    }
+
+    pub fn height(&self) -> f64 {
+        self.max_y - self.min_y
+    }
+
+    /// 四周各扩大 margin
+    pub fn expand(&self, margin: f64) -> Rect {
+        Rect { min_x: self.min_x - margin, min_y: self.min_y - margin, max_x: self.max_x + margin, max_y: self.max_y + margin }
+    }
+
+    /// 点在矩形内（含边界）
+    pub fn contains(&self, point: &Point) -> bool {
+        (self.min_x..=self.max_x).contains(&point.0) && (self.min_y..=self.max_y).contains(&point.1)
+    }
+
+    /// other 整个落在矩形内
+    pub fn contains_rect(&self, other: &Rect) -> bool {
+        self.min_x <= other.min_x && self.min_y <= other.min_y && other.max_x <= self.max_x && other.max_y <= self.max_y
+    }
+
+    /// 两个矩形有重叠（含边界相接）
+    pub fn intersects(&self, other: &Rect) -> bool {
+        self.min_x <= other.max_x && other.min_x <= self.max_x && self.min_y <= other.max_y && other.min_y <= self.max_y
+    }
+}
+
+impl PlaitElement {
+    /// 元素自身 points 的包围盒（不含子节点），没有坐标的元素返回 None
+    pub fn bounds(&self) -> Option<Rect> {
+        Rect::from_points(&self.points)
+    }
+}
+
+/// 一组元素（含子节点）的总包围盒
+pub fn bounds_of(elements: &[PlaitElement]) -> Option<Rect> {
+    let mut total: Option<Rect> = None;
+    for element in elements {
+        let own = element.bounds();
+        let nested = element.children.as_deref().and_then(bounds_of);
+        for rect in [own, nested].into_iter().flatten() {
+            total = Some(match total {
+                Some(t) => t.union(&rect),
+                None => rect,
+            });
+        }
+    }
+    total
+}
+
+/// 往白板上放新内容的位置：已有元素右侧留出间距，白板为空时为原点
+pub fn place_beside(elements: &[PlaitElement]) -> Point {
+    match bounds_of(elements) {
+        Some(bounds) => Point(bounds.max_x + 100.0, bounds.min_y),
+        None => Point(0.0, 0.0),
+    }
+}
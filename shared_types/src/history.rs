@@ -0,0 +1,314 @@
+//! 白板版本历史：把收发的每一条 BoardChangeData 追加写入 JSONL 文件，
+//! 支持命名检查点，以及把白板还原到任意历史时刻。
+//!
+//! 重放经过 [`Replica`]：变化按发布时的全序应用，而不是按本机记录的先后，
+//! 收到同一批变化的各端（桌面端、白板服务）因此得到相同的白板。
+//! 白板服务的快照作为新的基准写入历史，重放到快照时从快照重新开始。
+
+use crate::board::BoardState;
+use crate::replica::Replica;
+use crate::types::*;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+/// 历史文件中的一行
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum HistoryRecord {
+    Change {
+        seq: u64,
+        recorded_at: String,
+        data: BoardChangeData,
+    },
+    Checkpoint {
+        seq: u64,
+        recorded_at: String,
+        name: String,
+        source_id: String,
+    },
+    /// 白板服务的快照，`since` 之后记录的变化在快照之上重新应用
+    Snapshot {
+        seq: u64,
+        recorded_at: String,
+        since: u64,
+        snapshot: BoardSnapshot,
+    },
+}
+
+impl HistoryRecord {
+    pub fn seq(&self) -> u64 {
+        match self {
+            HistoryRecord::Change { seq, .. }
+            | HistoryRecord::Checkpoint { seq, .. }
+            | HistoryRecord::Snapshot { seq, .. } => *seq,
+        }
+    }
+
+    pub fn recorded_at(&self) -> &str {
+        match self {
+            HistoryRecord::Change { recorded_at, .. }
+            | HistoryRecord::Checkpoint { recorded_at, .. }
+            | HistoryRecord::Snapshot { recorded_at, .. } => recorded_at,
+        }
+    }
+}
+
+/// 提供给前端的历史条目摘要
+#[derive(Serialize, Debug, Clone)]
+pub struct HistoryEntry {
+    pub seq: u64,
+    pub kind: String,
+    pub name: Option<String>,
+    pub source_id: String,
+    /// 作者发布时的时间戳（检查点为创建时间）
+    pub timestamp: String,
+    /// 本机记录时间，按时间回溯时以它为准
+    pub recorded_at: String,
+    pub operation_count: usize,
+}
+
+impl From<&HistoryRecord> for HistoryEntry {
+    fn from(record: &HistoryRecord) -> Self {
+        match record {
+            HistoryRecord::Change { seq, recorded_at, data } => HistoryEntry {
+                seq: *seq,
+                kind: "change".into(),
+                name: None,
+                source_id: data.source_id.clone(),
+                timestamp: data.timestamp.clone(),
+                recorded_at: recorded_at.clone(),
+                operation_count: data.operations.len(),
+            },
+            HistoryRecord::Checkpoint { seq, recorded_at, name, source_id } => HistoryEntry {
+                seq: *seq,
+                kind: "checkpoint".into(),
+                name: Some(name.clone()),
+                source_id: source_id.clone(),
+                timestamp: recorded_at.clone(),
+                recorded_at: recorded_at.clone(),
+                operation_count: 0,
+            },
+            HistoryRecord::Snapshot { seq, recorded_at, snapshot, .. } => HistoryEntry {
+                seq: *seq,
+                kind: "snapshot".into(),
+                name: None,
+                source_id: snapshot.source_id.clone(),
+                timestamp: snapshot.timestamp.clone(),
+                recorded_at: recorded_at.clone(),
+                operation_count: snapshot.changes.len(),
+            },
+        }
+    }
+}
+
+/// 回溯目标：序号、时间点（RFC 3339）或检查点名称
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum HistoryTarget {
+    Seq(u64),
+    Time(String),
+    Checkpoint(String),
+}
+
+pub struct BoardHistory {
+    path: PathBuf,
+    records: Vec<HistoryRecord>,
+    /// 全部变化重放后的白板，随记录增量更新
+    replica: Replica,
+}
+
+impl BoardHistory {
+    /// 打开（或新建）历史文件，无法解析的行会被跳过
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let mut records = Vec::new();
+        if path.exists() {
+            let reader = BufReader::new(File::open(&path)?);
+            for (line_no, line) in reader.lines().enumerate() {
+                let line = line?;
+                if line.trim().is_empty() {
+                    continue;
+                }
+                match serde_json::from_str::<HistoryRecord>(&line) {
+                    Ok(record) => records.push(record),
+                    Err(e) => eprintln!("⚠️ 跳过无法解析的历史记录（第 {} 行）: {}", line_no + 1, e),
+                }
+            }
+        }
+
+        println!("📜 已加载 {} 条白板历史: {}", records.len(), path.display());
+        let replica = replay(&records, u64::MAX);
+        Ok(BoardHistory { path, records, replica })
+    }
+
+    pub fn record_change(&mut self, data: &BoardChangeData) -> io::Result<u64> {
+        let seq = self.next_seq();
+        self.append(HistoryRecord::Change {
+            seq,
+            recorded_at: Utc::now().to_rfc3339(),
+            data: data.clone(),
+        })?;
+        Ok(seq)
+    }
+
+    /// 以白板服务的快照为新的基准；`since` 是发出快照请求时的最新序号，
+    /// 之后记录的变化（本地的和收到的）在快照之上按全序重新应用
+    pub fn reset_to_snapshot(&mut self, snapshot: BoardSnapshot, since: u64) -> io::Result<u64> {
+        let seq = self.next_seq();
+        self.append(HistoryRecord::Snapshot {
+            seq,
+            recorded_at: Utc::now().to_rfc3339(),
+            since,
+            snapshot,
+        })?;
+        Ok(seq)
+    }
+
+    pub fn create_checkpoint(&mut self, name: &str, source_id: &str) -> Result<HistoryEntry, String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("检查点名称不能为空".into());
+        }
+        if self.find_checkpoint(name).is_some() {
+            return Err(format!("检查点已存在: {}", name));
+        }
+
+        let record = HistoryRecord::Checkpoint {
+            seq: self.next_seq(),
+            recorded_at: Utc::now().to_rfc3339(),
+            name: name.to_string(),
+            source_id: source_id.to_string(),
+        };
+        let entry = HistoryEntry::from(&record);
+        self.append(record).map_err(|e| format!("写入历史文件失败: {}", e))?;
+        Ok(entry)
+    }
+
+    pub fn entries(&self) -> Vec<HistoryEntry> {
+        self.records.iter().map(HistoryEntry::from).collect()
+    }
+
+    /// 把回溯目标解析为历史序号（包含该序号）
+    pub fn resolve(&self, target: &HistoryTarget) -> Result<u64, String> {
+        match target {
+            HistoryTarget::Seq(seq) => {
+                if self.records.iter().any(|r| r.seq() == *seq) {
+                    Ok(*seq)
+                } else {
+                    Err(format!("历史记录不存在: #{}", seq))
+                }
+            }
+            HistoryTarget::Time(time) => {
+                let time = DateTime::parse_from_rfc3339(time)
+                    .map_err(|e| format!("时间格式错误（需要 RFC 3339）: {}", e))?;
+                self.records
+                    .iter()
+                    .take_while(|r| {
+                        DateTime::parse_from_rfc3339(r.recorded_at())
+                            .map(|t| t <= time)
+                            .unwrap_or(false)
+                    })
+                    .last()
+                    .map(HistoryRecord::seq)
+                    .ok_or_else(|| format!("{} 之前没有历史记录", time))
+            }
+            HistoryTarget::Checkpoint(name) => self
+                .find_checkpoint(name)
+                .map(HistoryRecord::seq)
+                .ok_or_else(|| format!("检查点不存在: {}", name)),
+        }
+    }
+
+    /// 重放历史直到 `seq`（包含），得到当时的白板
+    pub fn materialize(&self, seq: u64) -> BoardState {
+        if self.records.last().is_none_or(|r| r.seq() <= seq) {
+            return self.current();
+        }
+        replay(&self.records, seq).board().clone()
+    }
+
+    /// 当前（最新）白板
+    pub fn current(&self) -> BoardState {
+        self.replica.board().clone()
+    }
+
+    /// 当前白板的引用，不需要副本时避免克隆
+    pub fn board(&self) -> &BoardState {
+        self.replica.board()
+    }
+
+    /// 应答快照请求，见 [`Replica::snapshot`]
+    pub fn snapshot(&self, request: &SnapshotRequest, source_id: &str) -> BoardSnapshot {
+        self.replica.snapshot(request, source_id)
+    }
+
+    /// 本端发布前调整时间戳，见 [`Replica::stamp`]
+    pub fn stamp(&self, change: &mut BoardChangeData) {
+        self.replica.stamp(change);
+    }
+
+    /// 最新一条历史记录的序号，没有记录时为 0
+    pub fn latest_seq(&self) -> u64 {
+        self.records.last().map(HistoryRecord::seq).unwrap_or(0)
+    }
+
+    fn find_checkpoint(&self, name: &str) -> Option<&HistoryRecord> {
+        self.records
+            .iter()
+            .find(|r| matches!(r, HistoryRecord::Checkpoint { name: n, .. } if n == name))
+    }
+
+    fn next_seq(&self) -> u64 {
+        self.latest_seq() + 1
+    }
+
+    fn append(&mut self, record: HistoryRecord) -> io::Result<()> {
+        let line = serde_json::to_string(&record)?;
+        let mut file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        writeln!(file, "{}", line)?;
+        match &record {
+            HistoryRecord::Change { data, .. } => {
+                self.replica.apply(data.clone());
+            }
+            HistoryRecord::Snapshot { since, snapshot, .. } => {
+                self.replica = Replica::from_snapshot(snapshot.clone(), changes_after(&self.records, *since));
+            }
+            HistoryRecord::Checkpoint { .. } => {}
+        }
+        self.records.push(record);
+        Ok(())
+    }
+}
+
+/// 把 `seq`（包含）之前的变化按全序重放；重复收到的消息只生效一次
+fn replay(records: &[HistoryRecord], seq: u64) -> Replica {
+    let mut replica = Replica::new(BoardState::new());
+    let records = &records[..records.iter().take_while(|r| r.seq() <= seq).count()];
+    for (index, record) in records.iter().enumerate() {
+        match record {
+            HistoryRecord::Change { data, .. } => {
+                replica.apply(data.clone());
+            }
+            HistoryRecord::Snapshot { since, snapshot, .. } => {
+                replica = Replica::from_snapshot(snapshot.clone(), changes_after(&records[..index], *since));
+            }
+            HistoryRecord::Checkpoint { .. } => {}
+        }
+    }
+    replica
+}
+
+/// 序号大于 `since` 的变化
+fn changes_after(records: &[HistoryRecord], since: u64) -> impl Iterator<Item = BoardChangeData> + '_ {
+    records.iter().filter_map(move |record| match record {
+        HistoryRecord::Change { seq, data, .. } if *seq > since => Some(data.clone()),
+        _ => None,
+    })
+}
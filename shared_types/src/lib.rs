@@ -0,0 +1,37 @@
+//! Drawnix 各程序共用的 DDS 消息类型、`.drawnix` 文件格式和白板算法。
+//!
+//! Tauri 后端、`dds_subscriber`、`test_publisher`、白板服务、WebSocket 桥和命令行工具
+//! 都依赖这个 crate，保证线上消息的结构只有一份定义。
+
+mod types;
+pub use types::*;
+
+pub mod board;
+pub mod conversion;
+pub mod diff;
+pub mod document;
+pub mod excalidraw;
+pub mod geometry;
+pub mod history;
+pub mod merge;
+pub mod mermaid;
+pub mod outline;
+pub mod replica;
+pub mod spatial;
+pub mod validate;
+pub mod svg;
+
+#[cfg(feature = "dds")]
+pub mod dds_manager;
+
+#[cfg(feature = "raster")]
+pub mod raster;
+
+#[cfg(feature = "pdf")]
+pub mod pdf;
+
+#[cfg(feature = "drawio")]
+pub mod drawio;
+
+#[cfg(feature = "schema")]
+pub mod schema;
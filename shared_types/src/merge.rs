@@ -0,0 +1,430 @@
+//! 三方合并：以共同祖先为基准，合并两份离线编辑的白板。
+//!
+//! 按元素 id 对齐，逐属性合并；只有两边把同一属性改成不同的值、一边删除另一边修改、
+//! 两边新增同一 id 但内容不同、或两边把元素挪到不同父节点 / 不同次序时才算冲突。
+//! 冲突按 `prefer` 选定一边的值，并在结果中逐条报告。
+
+use crate::diff::short_value;
+use crate::types::*;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+/// 冲突时优先采用哪一边
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Side {
+    #[default]
+    Ours,
+    Theirs,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictKind {
+    /// 两边把同一属性改成了不同的值
+    Property,
+    /// 两边新增了同一 id 的元素，内容不同
+    AddAdd,
+    /// 一边删除、另一边修改（保留修改后的元素）
+    DeleteModify,
+    /// 两边把元素挪到了不同的父节点
+    Parent,
+    /// 两边对同一组兄弟节点做了不同的重新排序
+    Order,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct MergeConflict {
+    /// 元素 id；次序冲突时为父节点 id，顶层为 None
+    pub id: Option<String>,
+    pub kind: ConflictKind,
+    /// 属性冲突时的属性名
+    pub property: Option<String>,
+    pub base: Option<Value>,
+    pub ours: Option<Value>,
+    pub theirs: Option<Value>,
+    pub resolved: Side,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct MergeOutcome {
+    pub elements: Vec<PlaitElement>,
+    pub conflicts: Vec<MergeConflict>,
+}
+
+impl MergeOutcome {
+    pub fn has_conflicts(&self) -> bool {
+        !self.conflicts.is_empty()
+    }
+
+    /// 人类可读的冲突报告
+    pub fn to_text(&self) -> String {
+        if self.conflicts.is_empty() {
+            return "✅ 自动合并成功，没有冲突\n".into();
+        }
+
+        let mut out = String::new();
+        for conflict in &self.conflicts {
+            let id = conflict.id.as_deref().unwrap_or("<顶层>");
+            let what = match conflict.kind {
+                ConflictKind::Property => format!("属性 {}", conflict.property.as_deref().unwrap_or("?")),
+                ConflictKind::AddAdd => "双方新增".into(),
+                ConflictKind::DeleteModify => "删除/修改".into(),
+                ConflictKind::Parent => "父节点".into(),
+                ConflictKind::Order => "子节点次序".into(),
+            };
+            out += &format!("⚠️  冲突 {} [{}]\n", id, what);
+            out += &format!("     base:   {}\n", short_value(conflict.base.as_ref()));
+            out += &format!("     ours:   {}\n", short_value(conflict.ours.as_ref()));
+            out += &format!("     theirs: {}\n", short_value(conflict.theirs.as_ref()));
+            out += &format!(
+                "     采用: {}\n",
+                match conflict.resolved {
+                    Side::Ours => "ours",
+                    Side::Theirs => "theirs",
+                }
+            );
+        }
+        out += &format!("📊 共 {} 处冲突\n", self.conflicts.len());
+        out
+    }
+}
+
+/// 元素在某一版本中的扁平表示
+#[derive(Clone)]
+struct Flat {
+    parent: Option<String>,
+    props: BTreeMap<String, Value>,
+    has_children: bool,
+}
+
+struct Version {
+    /// 先序遍历顺序，用于稳定输出
+    order: Vec<String>,
+    nodes: HashMap<String, Flat>,
+    /// 父节点 id → 子节点 id 顺序
+    children: HashMap<Option<String>, Vec<String>>,
+}
+
+impl Version {
+    fn new(elements: &[PlaitElement]) -> Self {
+        fn walk(nodes: &[PlaitElement], parent: Option<&String>, version: &mut Version) {
+            for node in nodes {
+                // id 重复时只保留先出现的节点的属性，但它的子节点并入先出现的节点，不能丢掉
+                if let Some(first) = version.nodes.get_mut(&node.id) {
+                    first.has_children |= node.children.is_some();
+                    if let Some(children) = &node.children {
+                        walk(children, Some(&node.id), version);
+                    }
+                    continue;
+                }
+                version.order.push(node.id.clone());
+                version.children.entry(parent.cloned()).or_default().push(node.id.clone());
+                version.nodes.insert(
+                    node.id.clone(),
+                    Flat {
+                        parent: parent.cloned(),
+                        props: node.properties(),
+                        has_children: node.children.is_some(),
+                    },
+                );
+                if let Some(children) = &node.children {
+                    walk(children, Some(&node.id), version);
+                }
+            }
+        }
+
+        let mut version = Version { order: Vec::new(), nodes: HashMap::new(), children: HashMap::new() };
+        walk(elements, None, &mut version);
+        version
+    }
+
+    fn node_value(&self, id: &str) -> Option<Value> {
+        self.nodes.get(id).map(|flat| serde_json::to_value(&flat.props).unwrap_or(Value::Null))
+    }
+}
+
+/// 合并过程中的可变状态
+struct Merger<'a> {
+    base: &'a Version,
+    ours: &'a Version,
+    theirs: &'a Version,
+    prefer: Side,
+    merged: HashMap<String, Flat>,
+    /// 父节点 id → 合并结果中的子节点，输出前按 `merged` 分组一次
+    siblings: HashMap<Option<String>, HashSet<String>>,
+    conflicts: Vec<MergeConflict>,
+}
+
+impl<'a> Merger<'a> {
+    fn pick<T>(&self, ours: T, theirs: T) -> T {
+        match self.prefer {
+            Side::Ours => ours,
+            Side::Theirs => theirs,
+        }
+    }
+
+    fn conflict(&mut self, id: Option<&str>, kind: ConflictKind, property: Option<&str>, values: [Option<Value>; 3]) {
+        let [base, ours, theirs] = values;
+        self.conflicts.push(MergeConflict {
+            id: id.map(str::to_string),
+            kind,
+            property: property.map(str::to_string),
+            base,
+            ours,
+            theirs,
+            resolved: self.prefer,
+        });
+    }
+
+    fn merge_element(&mut self, id: &str) {
+        let (base_version, ours_version, theirs_version) = (self.base, self.ours, self.theirs);
+        let base = base_version.nodes.get(id);
+        let ours = ours_version.nodes.get(id);
+        let theirs = theirs_version.nodes.get(id);
+
+        let merged = match (base, ours, theirs) {
+            (_, None, None) => None,
+            (None, Some(o), None) => Some(o.clone()),
+            (None, None, Some(t)) => Some(t.clone()),
+            (None, Some(o), Some(t)) => {
+                if o.props != t.props || o.parent != t.parent {
+                    let values = [None, self.ours.node_value(id), self.theirs.node_value(id)];
+                    self.conflict(Some(id), ConflictKind::AddAdd, None, values);
+                }
+                Some(self.pick(o, t).clone())
+            }
+            (Some(b), None, Some(t)) | (Some(b), Some(t), None) => {
+                if t.props == b.props && t.parent == b.parent {
+                    None
+                } else {
+                    // 一边删除、一边修改：保留修改，避免丢失工作
+                    let values = [self.base.node_value(id), self.ours.node_value(id), self.theirs.node_value(id)];
+                    self.conflict(Some(id), ConflictKind::DeleteModify, None, values);
+                    Some(t.clone())
+                }
+            }
+            (Some(b), Some(o), Some(t)) => Some(self.merge_three(id, b, o, t)),
+        };
+
+        if let Some(flat) = merged {
+            self.merged.insert(id.to_string(), flat);
+        }
+    }
+
+    fn merge_three(&mut self, id: &str, base: &'a Flat, ours: &'a Flat, theirs: &'a Flat) -> Flat {
+        let keys: BTreeSet<&String> = base.props.keys().chain(ours.props.keys()).chain(theirs.props.keys()).collect();
+        let mut props = BTreeMap::new();
+        for key in keys {
+            let (b, o, t) = (base.props.get(key), ours.props.get(key), theirs.props.get(key));
+            let value = if o == t || t == b {
+                o
+            } else if o == b {
+                t
+            } else {
+                self.conflict(Some(id), ConflictKind::Property, Some(key), [b.cloned(), o.cloned(), t.cloned()]);
+                self.pick(o, t)
+            };
+            if let Some(value) = value {
+                props.insert(key.clone(), value.clone());
+            }
+        }
+
+        let parent = if ours.parent == theirs.parent || theirs.parent == base.parent {
+            ours.parent.clone()
+        } else if ours.parent == base.parent {
+            theirs.parent.clone()
+        } else {
+            let as_value = |p: &Option<String>| Some(p.clone().map(Value::String).unwrap_or(Value::Null));
+            self.conflict(
+                Some(id),
+                ConflictKind::Parent,
+                None,
+                [as_value(&base.parent), as_value(&ours.parent), as_value(&theirs.parent)],
+            );
+            self.pick(&ours.parent, &theirs.parent).clone()
+        };
+
+        // 与属性相同的三方规则：一边删掉了 children 字段时不能被另一边的原样保留救回来
+        let has_children = if theirs.has_children == base.has_children { ours.has_children } else { theirs.has_children };
+        Flat { parent, props, has_children }
+    }
+
+    /// 父节点已被删除的元素：把父节点恢复回来（记为删除/修改冲突），哪个版本都找不到的父节点不恢复，子节点放回顶层
+    fn restore_missing_parents(&mut self) {
+        let mut pending: Vec<String> = self
+            .merged
+            .values()
+            .filter_map(|flat| flat.parent.clone())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .rev()
+            .collect();
+        let mut visited = HashSet::new();
+        while let Some(id) = pending.pop() {
+            if self.merged.contains_key(&id) || !visited.insert(id.clone()) {
+                continue;
+            }
+            let restored = [self.ours, self.theirs, self.base]
+                .into_iter()
+                .find_map(|version| version.nodes.get(&id).cloned());
+            let Some(restored) = restored else { continue };
+            let values = [self.base.node_value(&id), self.ours.node_value(&id), self.theirs.node_value(&id)];
+            self.conflict(Some(&id), ConflictKind::DeleteModify, None, values);
+            if let Some(parent) = &restored.parent {
+                pending.push(parent.clone());
+            }
+            self.merged.insert(id, restored);
+        }
+
+        let present: HashSet<String> = self.merged.keys().cloned().collect();
+        for flat in self.merged.values_mut() {
+            if flat.parent.as_ref().is_some_and(|parent| !present.contains(parent)) {
+                flat.parent = None;
+            }
+        }
+    }
+
+    /// 合并一组兄弟节点的次序
+    fn merge_order(&mut self, parent: &Option<String>, members: &HashSet<String>) -> Vec<String> {
+        let list = |version: &Version| -> Vec<String> {
+            version
+                .children
+                .get(parent)
+                .map(|ids| ids.iter().filter(|id| members.contains(*id)).cloned().collect())
+                .unwrap_or_default()
+        };
+        let (base, ours, theirs) = (list(self.base), list(self.ours), list(self.theirs));
+
+        // 只比较三边都在该父节点下的元素的相对次序
+        let sets: [HashSet<&String>; 3] = [base.iter().collect(), ours.iter().collect(), theirs.iter().collect()];
+        let common = |ids: &[String]| -> Vec<String> {
+            ids.iter().filter(|id| sets.iter().all(|set| set.contains(id))).cloned().collect()
+        };
+        let (base_common, ours_common, theirs_common) = (common(&base), common(&ours), common(&theirs));
+        let ours_reordered = ours_common != base_common;
+        let theirs_reordered = theirs_common != base_common;
+        if ours_reordered && theirs_reordered && ours_common != theirs_common {
+            let as_value = |ids: Vec<String>| Some(Value::from(ids));
+            self.conflict(
+                parent.as_deref(),
+                ConflictKind::Order,
+                None,
+                [as_value(base_common), as_value(ours_common), as_value(theirs_common)],
+            );
+        }
+
+        // 以重新排序过的一边为主，另一边独有的元素插到它在原版本中前一个兄弟之后
+        let theirs_primary = match self.prefer {
+            Side::Ours => theirs_reordered && !ours_reordered,
+            Side::Theirs => !ours_reordered || theirs_reordered,
+        };
+        let (primary, secondary) = if theirs_primary { (theirs, ours) } else { (ours, theirs) };
+
+        let mut included: HashSet<String> = primary.iter().cloned().collect();
+        let mut result = primary;
+        for (i, id) in secondary.iter().enumerate() {
+            if !included.insert(id.clone()) {
+                continue;
+            }
+            let position = secondary[..i]
+                .iter()
+                .rev()
+                .find_map(|prev| result.iter().position(|r| r == prev))
+                .map(|p| p + 1)
+                .unwrap_or(0);
+            result.insert(position, id.clone());
+        }
+        for id in base {
+            if included.insert(id.clone()) {
+                result.push(id);
+            }
+        }
+        result
+    }
+
+    fn group_siblings(&mut self) {
+        self.siblings.clear();
+        for (id, flat) in &self.merged {
+            self.siblings.entry(flat.parent.clone()).or_default().insert(id.clone());
+        }
+    }
+
+    /// 每个父节点只会构建一次，构建时取走它的子节点分组
+    fn build(&mut self, parent: Option<String>, placed: &mut HashSet<String>) -> Result<Vec<PlaitElement>, String> {
+        let members = self.siblings.remove(&parent).unwrap_or_default();
+        let mut order = self.merge_order(&parent, &members);
+        // 父节点变化后，成员可能不在任何版本的这组兄弟里，按 id 排序追加，保证不会漏掉
+        let ordered: HashSet<&String> = order.iter().collect();
+        let mut rest: Vec<String> = members.iter().filter(|id| !ordered.contains(id)).cloned().collect();
+        rest.sort();
+        order.extend(rest);
+
+        let mut nodes = Vec::new();
+        for id in order {
+            if !placed.insert(id.clone()) {
+                continue;
+            }
+            let flat = self.merged[&id].clone();
+            let children = self.build(Some(id.clone()), placed)?;
+            let children = (flat.has_children || !children.is_empty()).then_some(children);
+            nodes.push(PlaitElement::from_properties(&id, flat.props, children)?);
+        }
+        Ok(nodes)
+    }
+}
+
+/// 三方合并两棵元素树
+pub fn merge_elements(
+    base: &[PlaitElement],
+    ours: &[PlaitElement],
+    theirs: &[PlaitElement],
+    prefer: Side,
+) -> Result<MergeOutcome, String> {
+    let (base, ours, theirs) = (Version::new(base), Version::new(ours), Version::new(theirs));
+    let mut merger = Merger {
+        base: &base,
+        ours: &ours,
+        theirs: &theirs,
+        prefer,
+        merged: HashMap::new(),
+        siblings: HashMap::new(),
+        conflicts: Vec::new(),
+    };
+
+    let mut seen = HashSet::new();
+    let all_ids: Vec<String> = base
+        .order
+        .iter()
+        .chain(&ours.order)
+        .chain(&theirs.order)
+        .filter(|id| seen.insert(id.as_str()))
+        .cloned()
+        .collect();
+    for id in &all_ids {
+        merger.merge_element(id);
+    }
+    merger.restore_missing_parents();
+    merger.group_siblings();
+
+    let mut placed = HashSet::new();
+    let mut elements = merger.build(None, &mut placed)?;
+
+    // 两边互相挪到对方下面会形成环，环上的元素挂不到根上：放回顶层
+    for id in &all_ids {
+        if merger.merged.contains_key(id) && !placed.contains(id) {
+            if let Some(flat) = merger.merged.get_mut(id) {
+                flat.parent = None;
+            }
+            merger.conflict(Some(id), ConflictKind::Parent, None, [None, None, None]);
+            let flat = merger.merged[id].clone();
+            placed.insert(id.clone());
+            let children = merger.build(Some(id.clone()), &mut placed)?;
+            let children = (flat.has_children || !children.is_empty()).then_some(children);
+            elements.push(PlaitElement::from_properties(id, flat.props, children)?);
+        }
+    }
+
+    Ok(MergeOutcome { elements, conflicts: merger.conflicts })
+}
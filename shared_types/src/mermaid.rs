@@ -0,0 +1,761 @@
+//! Mermaid 导入：把 flowchart（graph）和 sequenceDiagram 转换成白板元素并自动布局。
+//!
+//! 生成的元素与前端绘制的流程图结构相同（`geometry` 图形 + 绑定两端的 `line` 连线），
+//! 配合 [`crate::board::insert_operations`] 即可作为一批插入操作发布到正在进行的白板会话。
+//! 只支持常用语法；样式（style、classDef）、点击事件等会被忽略。
+
+use crate::geometry::place_beside;
+use crate::types::*;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+const NODE_MIN_WIDTH: f64 = 120.0;
+const NODE_MIN_HEIGHT: f64 = 60.0;
+const RANK_GAP: f64 = 70.0;
+const NODE_GAP: f64 = 50.0;
+const LINE_HEIGHT: f64 = 20.0;
+/// 一张图最多的节点（参与者）与连线（消息、备注）数，防止超大输入拖慢布局
+const MAX_ITEMS: usize = 5000;
+
+#[derive(Debug, Clone)]
+pub struct MermaidOptions {
+    /// 图的左上角在画布上的位置
+    pub origin: Point,
+    /// 元素 id 前缀，同一块白板上多次导入时应各不相同
+    pub id_prefix: String,
+}
+
+impl Default for MermaidOptions {
+    fn default() -> Self {
+        MermaidOptions { origin: Point(0.0, 0.0), id_prefix: "mermaid".into() }
+    }
+}
+
+impl MermaidOptions {
+    /// 放在已有元素的右侧，避免与白板上的内容重叠
+    pub fn beside(elements: &[PlaitElement], id_prefix: impl Into<String>) -> Self {
+        MermaidOptions { origin: place_beside(elements), id_prefix: id_prefix.into() }
+    }
+}
+
+/// 解析 Mermaid 文本并布局，返回图形在前、连线在后的元素列表
+pub fn import_mermaid(source: &str, options: &MermaidOptions) -> Result<Vec<PlaitElement>, String> {
+    let mut lines = statements(source);
+    let header = lines.next().ok_or("Mermaid 内容为空")?;
+    let mut words = header.split_whitespace();
+    match words.next() {
+        Some("flowchart") | Some("graph") => {
+            let direction = Direction::parse(words.next().unwrap_or("TD"))?;
+            let chart = parse_flowchart(lines)?;
+            Ok(layout_flowchart(&chart, direction, options))
+        }
+        Some("sequenceDiagram") => Ok(layout_sequence(&parse_sequence(lines)?, options)),
+        _ => Err(format!("不支持的 Mermaid 图类型: {}（支持 flowchart、graph、sequenceDiagram）", header)),
+    }
+}
+
+/// 去掉注释和空行；flowchart 中的 `;` 也是语句分隔符
+fn statements(source: &str) -> impl Iterator<Item = &str> {
+    source
+        .lines()
+        .flat_map(|line| {
+            let line = line.split("%%").next().unwrap_or_default();
+            // sequenceDiagram 的消息文字里可能有分号，只拆不含冒号的行
+            if line.contains(':') { vec![line] } else { line.split(';').collect() }
+        })
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Direction {
+    TopDown,
+    BottomUp,
+    LeftRight,
+    RightLeft,
+}
+
+impl Direction {
+    fn parse(text: &str) -> Result<Self, String> {
+        match text {
+            "TD" | "TB" => Ok(Direction::TopDown),
+            "BT" => Ok(Direction::BottomUp),
+            "LR" => Ok(Direction::LeftRight),
+            "RL" => Ok(Direction::RightLeft),
+            _ => Err(format!("未知的 flowchart 方向: {}", text)),
+        }
+    }
+
+    fn horizontal(self) -> bool {
+        matches!(self, Direction::LeftRight | Direction::RightLeft)
+    }
+}
+
+#[derive(Debug, Default)]
+struct Flowchart {
+    /// 按首次出现的顺序
+    nodes: Vec<FlowNode>,
+    index: HashMap<String, usize>,
+    edges: Vec<FlowEdge>,
+}
+
+#[derive(Debug)]
+struct FlowNode {
+    id: String,
+    text: String,
+    shape: &'static str,
+}
+
+#[derive(Debug)]
+struct FlowEdge {
+    from: usize,
+    to: usize,
+    label: String,
+    link: Link,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Link {
+    source_marker: &'static str,
+    target_marker: &'static str,
+    dotted: bool,
+    thick: bool,
+}
+
+impl Flowchart {
+    /// 声明或引用节点；后出现的形状和文字覆盖仅有 id 的引用
+    fn node(&mut self, id: &str, shape: Option<(&'static str, String)>) -> usize {
+        let index = *self.index.entry(id.to_string()).or_insert_with(|| {
+            self.nodes.push(FlowNode { id: id.to_string(), text: id.to_string(), shape: "process" });
+            self.nodes.len() - 1
+        });
+        if let Some((shape, text)) = shape {
+            self.nodes[index].shape = shape;
+            self.nodes[index].text = text;
+        }
+        index
+    }
+}
+
+fn parse_flowchart<'a>(lines: impl Iterator<Item = &'a str>) -> Result<Flowchart, String> {
+    let mut chart = Flowchart::default();
+    for line in lines {
+        let keyword = line.split_whitespace().next().unwrap_or_default();
+        if matches!(keyword, "style" | "classDef" | "class" | "click" | "linkStyle" | "direction" | "subgraph" | "end") {
+            // 子图只作为分组，其中的节点和连线照常导入
+            continue;
+        }
+        let mut cursor = Cursor { text: line, pos: 0 };
+        let mut previous = cursor.node_group(&mut chart)?;
+        loop {
+            cursor.skip_spaces();
+            if cursor.done() {
+                break;
+            }
+            let (link, label) = cursor.link().ok_or_else(|| format!("无法解析连线: {}", line))?;
+            let next = cursor.node_group(&mut chart)?;
+            // `A & B --> C & D` 展开成两两相连，先按数量检查再生成
+            let budget = MAX_ITEMS.saturating_sub(chart.nodes.len() + chart.edges.len());
+            if previous.len().saturating_mul(next.len()) > budget {
+                return Err(flowchart_too_large());
+            }
+            for &from in &previous {
+                for &to in &next {
+                    chart.edges.push(FlowEdge { from, to, label: label.clone(), link });
+                }
+            }
+            previous = next;
+        }
+    }
+    if chart.nodes.is_empty() {
+        return Err("flowchart 中没有节点".into());
+    }
+    if chart.nodes.len() + chart.edges.len() > MAX_ITEMS {
+        return Err(flowchart_too_large());
+    }
+    Ok(chart)
+}
+
+fn flowchart_too_large() -> String {
+    format!("flowchart 过大：节点和连线超过 {} 个", MAX_ITEMS)
+}
+
+/// 按字符解析一条 flowchart 语句
+struct Cursor<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl Cursor<'_> {
+    fn rest(&self) -> &str {
+        &self.text[self.pos..]
+    }
+
+    fn done(&self) -> bool {
+        self.pos >= self.text.len()
+    }
+
+    fn skip_spaces(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    fn eat(&mut self, prefix: &str) -> bool {
+        if self.rest().starts_with(prefix) {
+            self.pos += prefix.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// `A & B:::class` 这样用 & 连接的一组节点
+    fn node_group(&mut self, chart: &mut Flowchart) -> Result<Vec<usize>, String> {
+        let mut group = Vec::new();
+        loop {
+            self.skip_spaces();
+            let id_len = self.rest().find(|c: char| !(c.is_alphanumeric() || c == '_')).unwrap_or(self.rest().len());
+            if id_len == 0 {
+                return Err(format!("缺少节点 id: {}", self.text));
+            }
+            let id = self.rest()[..id_len].to_string();
+            self.pos += id_len;
+            let shape = self.shape()?;
+            if self.eat(":::") {
+                let class_len = self.rest().find(|c: char| c.is_whitespace() || c == '&').unwrap_or(self.rest().len());
+                self.pos += class_len;
+            }
+            group.push(chart.node(&id, shape));
+            self.skip_spaces();
+            if !self.eat("&") {
+                return Ok(group);
+            }
+        }
+    }
+
+    /// 节点形状和文字，如 `[文字]`、`{判断}`、`([开始])`
+    fn shape(&mut self) -> Result<Option<(&'static str, String)>, String> {
+        // 较长的开括号在前，保证 `([` 不被当作 `(`
+        const SHAPES: [(&str, &[(&str, &str)]); 11] = [
+            ("([", &[("])", "terminal")]),
+            ("[[", &[("]]", "predefinedProcess")]),
+            ("[(", &[(")]", "database")]),
+            ("((", &[("))", "ellipse")]),
+            ("{{", &[("}}", "preparation")]),
+            ("[/", &[("/]", "data"), ("\\]", "trapezoid")]),
+            ("[\\", &[("\\]", "data"), ("/]", "manualOperation")]),
+            ("[", &[("]", "process")]),
+            ("(", &[(")", "roundRectangle")]),
+            ("{", &[("}", "decision")]),
+            (">", &[("]", "process")]),
+        ];
+        for (open, closers) in SHAPES {
+            if !self.rest().starts_with(open) {
+                continue;
+            }
+            self.pos += open.len();
+            let quoted = self.rest().starts_with('"');
+            let search_from = if quoted { self.rest()[1..].find('"').map_or(0, |i| i + 2) } else { 0 };
+            let found = closers
+                .iter()
+                .filter_map(|(close, shape)| self.rest()[search_from..].find(close).map(|i| (search_from + i, *close, *shape)))
+                .min_by_key(|(i, _, _)| *i);
+            let Some((end, close, shape)) = found else {
+                return Err(format!("节点文字缺少结束符 {}: {}", closers[0].0, self.text));
+            };
+            let text = label_text(&self.rest()[..end]);
+            self.pos += end + close.len();
+            return Ok(Some((shape, text)));
+        }
+        Ok(None)
+    }
+
+    /// 连线及其文字：`-->`、`---`、`-.->`、`==>`、`<-->`、`-->|文字|`、`-- 文字 -->`
+    fn link(&mut self) -> Option<(Link, String)> {
+        let start = self.pos;
+        let mut link = Link { source_marker: "none", target_marker: "none", ..Default::default() };
+        if self.eat("<") {
+            link.source_marker = "arrow";
+        }
+        let body_len = self.rest().find(|c| !matches!(c, '-' | '=' | '.')).unwrap_or(self.rest().len());
+        if body_len < 2 {
+            self.pos = start;
+            return None;
+        }
+        let mut body = self.rest()[..body_len].to_string();
+        self.pos += body_len;
+
+        let mut label = String::new();
+        if !self.rest().starts_with(['>', 'x', 'o']) && self.rest().starts_with(' ') && matches!(body.as_str(), "--" | "==" | "-.") {
+            // `-- 文字 -->` 形式：文字之后是连线的后半段
+            const CLOSERS: [&str; 8] = ["-->", "---", "==>", "===", ".->", ".-", "--x", "--o"];
+            let rest = self.rest();
+            let (at, _) = CLOSERS.iter().filter_map(|c| rest.find(c).map(|i| (i, *c))).min_by_key(|(i, _)| *i)?;
+            label = label_text(rest[..at].trim());
+            self.pos += at;
+            let tail_len = self.rest().find(|c| !matches!(c, '-' | '=' | '.')).unwrap_or(self.rest().len());
+            body.push_str(&self.rest()[..tail_len]);
+            self.pos += tail_len;
+        }
+
+        if self.eat(">") {
+            link.target_marker = "arrow";
+        } else if self.rest().starts_with(['x', 'o']) && self.rest()[1..].starts_with(|c: char| c.is_whitespace() || c == '|') {
+            link.target_marker = if self.rest().starts_with('x') { "single-line" } else { "hollow-triangle" };
+            self.pos += 1;
+        }
+        link.dotted = body.contains('.');
+        link.thick = body.contains('=');
+
+        self.skip_spaces();
+        if self.eat("|") {
+            let end = self.rest().find('|')?;
+            label = label_text(&self.rest()[..end]);
+            self.pos += end + 1;
+        }
+        Some((link, label))
+    }
+}
+
+/// 去掉引号，`<br>` 换行
+fn label_text(raw: &str) -> String {
+    let text = raw.trim();
+    let text = text.strip_prefix('"').and_then(|t| t.strip_suffix('"')).unwrap_or(text);
+    text.replace("<br/>", "\n").replace("<br />", "\n").replace("<br>", "\n")
+}
+
+/// 按字数估算文字宽度：全角字符按字号，其余按半个字号多一点
+fn text_size(text: &str) -> (f64, f64) {
+    let lines: Vec<&str> = text.lines().collect();
+    let width = lines
+        .iter()
+        .map(|line| line.chars().map(|c| if c.is_ascii() { 8.0 } else { 14.0 }).sum::<f64>())
+        .fold(0.0, f64::max);
+    (width, lines.len().max(1) as f64 * LINE_HEIGHT)
+}
+
+fn node_size(text: &str, shape: &str) -> (f64, f64) {
+    let (w, h) = text_size(text);
+    match shape {
+        // 菱形和椭圆的内切区域较小
+        "decision" | "ellipse" | "preparation" => ((w * 1.5 + 40.0).max(140.0), (h * 1.5 + 30.0).max(70.0)),
+        _ => ((w + 40.0).max(NODE_MIN_WIDTH), (h + 30.0).max(NODE_MIN_HEIGHT)),
+    }
+}
+
+fn geometry(id: String, shape: &str, text: &str, x: f64, y: f64, width: f64, height: f64) -> PlaitElement {
+    let mut extra = serde_json::Map::new();
+    extra.insert("angle".into(), json!(0));
+    extra.insert("opacity".into(), json!(1));
+    extra.insert("textHeight".into(), json!(text_size(text).1));
+    extra.insert("text".into(), json!({ "children": [{ "text": text }], "align": "center" }));
+    extra.insert("strokeWidth".into(), json!(2));
+    PlaitElement {
+        id,
+        element_type: "geometry".into(),
+        shape: shape.into(),
+        points: vec![Point(x, y), Point(x + width, y + height)],
+        children: None,
+        extra,
+    }
+}
+
+/// 连线的一端：绑定的元素和连接点（元素内的相对坐标）
+struct End<'a> {
+    bound: Option<&'a str>,
+    connection: [f64; 2],
+    marker: &'static str,
+}
+
+fn line(id: String, shape: &str, points: Vec<Point>, source: End, target: End, label: &str, link: Link) -> PlaitElement {
+    let end = |end: &End| {
+        let mut value = json!({ "marker": end.marker, "connection": end.connection });
+        if let Some(bound) = end.bound {
+            value["boundId"] = json!(bound);
+        }
+        value
+    };
+    let texts: Vec<Value> = if label.is_empty() {
+        Vec::new()
+    } else {
+        let (width, height) = text_size(label);
+        vec![json!({ "text": { "children": [{ "text": label }] }, "position": 0.5, "width": width, "height": height })]
+    };
+    let mut extra = serde_json::Map::new();
+    extra.insert("source".into(), end(&source));
+    extra.insert("target".into(), end(&target));
+    extra.insert("texts".into(), Value::Array(texts));
+    extra.insert("opacity".into(), json!(1));
+    extra.insert("strokeWidth".into(), json!(if link.thick { 4 } else { 2 }));
+    if link.dotted {
+        extra.insert("strokeStyle".into(), json!("dashed"));
+    }
+    PlaitElement { id, element_type: "line".into(), shape: shape.into(), points, children: None, extra }
+}
+
+/// 分层布局：按最长路径分层（忽略成环的回边），层内用重心法减少交叉
+fn layout_flowchart(chart: &Flowchart, direction: Direction, options: &MermaidOptions) -> Vec<PlaitElement> {
+    let n = chart.nodes.len();
+    let back = back_edges(chart);
+    // 去掉回边后的邻接表（出边、入边）
+    let mut outgoing = vec![Vec::new(); n];
+    let mut incoming = vec![Vec::new(); n];
+    for (edge, _) in chart.edges.iter().zip(&back).filter(|(_, back)| !**back) {
+        outgoing[edge.from].push(edge.to);
+        incoming[edge.to].push(edge.from);
+    }
+
+    // 最长路径分层：边已无环，按拓扑序推进
+    let mut rank = vec![0usize; n];
+    let mut pending: Vec<usize> = incoming.iter().map(Vec::len).collect();
+    let mut ready: Vec<usize> = (0..n).filter(|&node| pending[node] == 0).collect();
+    while let Some(node) = ready.pop() {
+        for &next in &outgoing[node] {
+            rank[next] = rank[next].max(rank[node] + 1);
+            pending[next] -= 1;
+            if pending[next] == 0 {
+                ready.push(next);
+            }
+        }
+    }
+    let ranks = rank.iter().max().map_or(0, |r| r + 1);
+    let mut layers: Vec<Vec<usize>> = vec![Vec::new(); ranks];
+    for node in 0..n {
+        layers[rank[node]].push(node);
+    }
+
+    // 重心法：按相邻层中相连节点的平均位置排序，上下各扫几遍
+    let mut position = vec![0.0; n];
+    let reindex = |layers: &Vec<Vec<usize>>, position: &mut Vec<f64>| {
+        for layer in layers {
+            for (i, &node) in layer.iter().enumerate() {
+                position[node] = i as f64;
+            }
+        }
+    };
+    reindex(&layers, &mut position);
+    for sweep in 0..4 {
+        let down = sweep % 2 == 0;
+        let order: Vec<usize> = if down { (1..ranks).collect() } else { (0..ranks.saturating_sub(1)).rev().collect() };
+        for r in order {
+            let mut keyed: Vec<(f64, usize)> = layers[r]
+                .iter()
+                .map(|&node| {
+                    let neighbours = if down { &incoming[node] } else { &outgoing[node] };
+                    let key = if neighbours.is_empty() {
+                        position[node]
+                    } else {
+                        neighbours.iter().map(|&other| position[other]).sum::<f64>() / neighbours.len() as f64
+                    };
+                    (key, node)
+                })
+                .collect();
+            keyed.sort_by(|a, b| a.0.total_cmp(&b.0));
+            layers[r] = keyed.into_iter().map(|(_, node)| node).collect();
+            reindex(&layers, &mut position);
+        }
+    }
+
+    // 坐标：主轴沿层推进，副轴上每层居中
+    let sizes: Vec<(f64, f64)> = chart.nodes.iter().map(|node| node_size(&node.text, node.shape)).collect();
+    let horizontal = direction.horizontal();
+    let main = |size: (f64, f64)| if horizontal { size.0 } else { size.1 };
+    let cross = |size: (f64, f64)| if horizontal { size.1 } else { size.0 };
+    let layer_main: Vec<f64> = layers.iter().map(|layer| layer.iter().map(|&i| main(sizes[i])).fold(0.0, f64::max)).collect();
+    let layer_cross: Vec<f64> = layers
+        .iter()
+        .map(|layer| layer.iter().map(|&i| cross(sizes[i])).sum::<f64>() + NODE_GAP * layer.len().saturating_sub(1) as f64)
+        .collect();
+    let widest = layer_cross.iter().copied().fold(0.0, f64::max);
+    let total_main = layer_main.iter().sum::<f64>() + RANK_GAP * ranks.saturating_sub(1) as f64;
+
+    let mut rects = vec![(0.0, 0.0, 0.0, 0.0); n];
+    let mut main_at = 0.0;
+    for (r, layer) in layers.iter().enumerate() {
+        let mut cross_at = (widest - layer_cross[r]) / 2.0;
+        for &node in layer {
+            let size = sizes[node];
+            // 层内沿主轴居中
+            let along = main_at + (layer_main[r] - main(size)) / 2.0;
+            let along = match direction {
+                Direction::BottomUp | Direction::RightLeft => total_main - along - main(size),
+                _ => along,
+            };
+            rects[node] = if horizontal {
+                (options.origin.0 + along, options.origin.1 + cross_at, size.0, size.1)
+            } else {
+                (options.origin.0 + cross_at, options.origin.1 + along, size.0, size.1)
+            };
+            cross_at += cross(size) + NODE_GAP;
+        }
+        main_at += layer_main[r] + RANK_GAP;
+    }
+
+    // 节点和连线的 id 分属不同前缀，节点名为 e0 之类时也不会与连线重复
+    let ids: Vec<String> = (0..n).map(|i| format!("{}-node-{}", options.id_prefix, chart.nodes[i].id)).collect();
+    let mut elements: Vec<PlaitElement> = chart
+        .nodes
+        .iter()
+        .enumerate()
+        .map(|(i, node)| {
+            let (x, y, w, h) = rects[i];
+            geometry(ids[i].clone(), node.shape, &node.text, x, y, w, h)
+        })
+        .collect();
+
+    for (i, edge) in chart.edges.iter().enumerate() {
+        let (source, target) = connections(rects[edge.from], rects[edge.to], direction, back[i] || edge.from == edge.to);
+        let at = |rect: (f64, f64, f64, f64), c: [f64; 2]| Point(rect.0 + rect.2 * c[0], rect.1 + rect.3 * c[1]);
+        elements.push(line(
+            format!("{}-edge-{}", options.id_prefix, i),
+            "elbow",
+            vec![at(rects[edge.from], source), at(rects[edge.to], target)],
+            End { bound: Some(&ids[edge.from]), connection: source, marker: edge.link.source_marker },
+            End { bound: Some(&ids[edge.to]), connection: target, marker: edge.link.target_marker },
+            &edge.label,
+            edge.link,
+        ));
+    }
+    elements
+}
+
+/// 深度优先找出构成环的回边；用显式栈，长链不会耗尽调用栈
+fn back_edges(chart: &Flowchart) -> Vec<bool> {
+    let n = chart.nodes.len();
+    let mut edges_from: Vec<Vec<usize>> = vec![Vec::new(); n];
+    for (i, edge) in chart.edges.iter().enumerate() {
+        edges_from[edge.from].push(i);
+    }
+
+    let mut state = vec![0u8; n]; // 0 未访问，1 在栈上，2 已完成
+    let mut back = vec![false; chart.edges.len()];
+    // (节点, 下一条要检查的出边)
+    let mut stack: Vec<(usize, usize)> = Vec::new();
+    for root in 0..n {
+        if state[root] != 0 {
+            continue;
+        }
+        state[root] = 1;
+        stack.push((root, 0));
+        while let Some((node, next)) = stack.last_mut() {
+            let Some(&edge) = edges_from[*node].get(*next) else {
+                state[*node] = 2;
+                stack.pop();
+                continue;
+            };
+            *next += 1;
+            let to = chart.edges[edge].to;
+            match state[to] {
+                0 => {
+                    state[to] = 1;
+                    stack.push((to, 0));
+                }
+                1 => back[edge] = true,
+                _ => {}
+            }
+        }
+    }
+    back
+}
+
+/// 连线两端的连接点：顺着布局方向从前一层的出口连到后一层的入口，回边从侧面绕行
+fn connections(from: (f64, f64, f64, f64), to: (f64, f64, f64, f64), direction: Direction, back: bool) -> ([f64; 2], [f64; 2]) {
+    let (exit, entry, side) = match direction {
+        Direction::TopDown => ([0.5, 1.0], [0.5, 0.0], [1.0, 0.5]),
+        Direction::BottomUp => ([0.5, 0.0], [0.5, 1.0], [1.0, 0.5]),
+        Direction::LeftRight => ([1.0, 0.5], [0.0, 0.5], [0.5, 1.0]),
+        Direction::RightLeft => ([0.0, 0.5], [1.0, 0.5], [0.5, 1.0]),
+    };
+    if back {
+        return (side, side);
+    }
+    // 同一层的节点之间从侧面相连
+    let same_layer = if direction.horizontal() { (from.0 - to.0).abs() < 1.0 } else { (from.1 - to.1).abs() < 1.0 };
+    if same_layer {
+        let forward = if direction.horizontal() { to.1 > from.1 } else { to.0 > from.0 };
+        let (near, far) = if direction.horizontal() { ([0.5, 1.0], [0.5, 0.0]) } else { ([1.0, 0.5], [0.0, 0.5]) };
+        return if forward { (near, far) } else { (far, near) };
+    }
+    (exit, entry)
+}
+
+#[derive(Debug, Default)]
+struct Sequence {
+    participants: Vec<Participant>,
+    index: HashMap<String, usize>,
+    steps: Vec<Step>,
+}
+
+#[derive(Debug)]
+struct Participant {
+    id: String,
+    label: String,
+    actor: bool,
+}
+
+#[derive(Debug)]
+enum Step {
+    Message { from: usize, to: usize, text: String, dashed: bool, marker: &'static str },
+    Note { first: usize, last: usize, placement: NotePlacement, text: String },
+}
+
+#[derive(Debug, Clone, Copy)]
+enum NotePlacement {
+    LeftOf,
+    RightOf,
+    Over,
+}
+
+impl Sequence {
+    fn participant(&mut self, id: &str) -> usize {
+        *self.index.entry(id.to_string()).or_insert_with(|| {
+            self.participants.push(Participant { id: id.to_string(), label: id.to_string(), actor: false });
+            self.participants.len() - 1
+        })
+    }
+}
+
+fn parse_sequence<'a>(lines: impl Iterator<Item = &'a str>) -> Result<Sequence, String> {
+    // 消息箭头，较长的在前
+    const ARROWS: [(&str, bool, &str); 8] = [
+        ("-->>", true, "arrow"),
+        ("->>", false, "arrow"),
+        ("--x", true, "single-line"),
+        ("-x", false, "single-line"),
+        ("--)", true, "open-triangle"),
+        ("-)", false, "open-triangle"),
+        ("-->", true, "none"),
+        ("->", false, "none"),
+    ];
+
+    let mut sequence = Sequence::default();
+    for line in lines {
+        let (keyword, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        match keyword {
+            "participant" | "actor" => {
+                let (id, label) = rest.split_once(" as ").unwrap_or((rest, rest));
+                let index = sequence.participant(id.trim());
+                sequence.participants[index].label = label_text(label);
+                sequence.participants[index].actor = keyword == "actor";
+            }
+            "Note" | "note" => {
+                let (target, text) = rest.split_once(':').ok_or_else(|| format!("备注缺少冒号: {}", line))?;
+                let target = target.trim();
+                let (placement, names) = if let Some(names) = target.strip_prefix("left of") {
+                    (NotePlacement::LeftOf, names)
+                } else if let Some(names) = target.strip_prefix("right of") {
+                    (NotePlacement::RightOf, names)
+                } else if let Some(names) = target.strip_prefix("over") {
+                    (NotePlacement::Over, names)
+                } else {
+                    return Err(format!("无法解析备注位置: {}", line));
+                };
+                let indices: Vec<usize> = names.split(',').map(|name| sequence.participant(name.trim())).collect();
+                let first = *indices.iter().min().ok_or("备注缺少参与者")?;
+                let last = *indices.iter().max().ok_or("备注缺少参与者")?;
+                sequence.steps.push(Step::Note { first, last, placement, text: label_text(text) });
+            }
+            // 分组、激活和编号不影响导入的元素
+            "loop" | "alt" | "else" | "opt" | "par" | "and" | "critical" | "break" | "rect" | "end" | "autonumber"
+            | "activate" | "deactivate" | "box" | "title" | "create" | "destroy" => {}
+            _ => {
+                let (head, text) = line.split_once(':').unwrap_or((line, ""));
+                let (at, arrow, dashed, marker) = ARROWS
+                    .iter()
+                    .filter_map(|(arrow, dashed, marker)| head.find(arrow).map(|at| (at, *arrow, *dashed, *marker)))
+                    .min_by_key(|(at, arrow, _, _)| (*at, usize::MAX - arrow.len()))
+                    .ok_or_else(|| format!("无法解析的 sequenceDiagram 语句: {}", line))?;
+                let from = head[..at].trim();
+                let to = head[at + arrow.len()..].trim().trim_start_matches(['+', '-']).trim();
+                if from.is_empty() || to.is_empty() {
+                    return Err(format!("消息缺少参与者: {}", line));
+                }
+                let from = sequence.participant(from);
+                let to = sequence.participant(to);
+                sequence.steps.push(Step::Message { from, to, text: label_text(text), dashed, marker });
+            }
+        }
+    }
+    if sequence.participants.is_empty() {
+        return Err("sequenceDiagram 中没有参与者".into());
+    }
+    if sequence.participants.len() + sequence.steps.len() > MAX_ITEMS {
+        return Err(format!("sequenceDiagram 过大：参与者、消息和备注超过 {} 个", MAX_ITEMS));
+    }
+    Ok(sequence)
+}
+
+/// 参与者排成一行，生命线向下延伸，消息和备注按顺序自上而下排列
+fn layout_sequence(sequence: &Sequence, options: &MermaidOptions) -> Vec<PlaitElement> {
+    const STEP_GAP: f64 = 30.0;
+    let Point(x0, y0) = options.origin;
+    let prefix = &options.id_prefix;
+
+    let sizes: Vec<(f64, f64)> = sequence.participants.iter().map(|p| node_size(&p.label, "process")).collect();
+    let box_height = sizes.iter().map(|s| s.1).fold(0.0, f64::max);
+    let spacing = sizes.iter().map(|s| s.0).fold(0.0, f64::max) + 80.0;
+    let centers: Vec<f64> = (0..sizes.len()).map(|i| x0 + sizes[0].0 / 2.0 + i as f64 * spacing).collect();
+
+    let mut shapes = Vec::new();
+    let mut lines = Vec::new();
+    let mut y = y0 + box_height + STEP_GAP;
+    for (i, step) in sequence.steps.iter().enumerate() {
+        match step {
+            Step::Message { from, to, text, dashed, marker } => {
+                let link = Link { dotted: *dashed, ..Default::default() };
+                let (_, text_height) = text_size(text);
+                y += if text.is_empty() { 0.0 } else { text_height };
+                let end = |marker| End { bound: None, connection: [0.0, 0.0], marker };
+                let points = if from == to {
+                    // 发给自己：向右绕一圈回到生命线
+                    let x = centers[*from];
+                    vec![Point(x, y), Point(x + 50.0, y), Point(x + 50.0, y + 30.0), Point(x, y + 30.0)]
+                } else {
+                    vec![Point(centers[*from], y), Point(centers[*to], y)]
+                };
+                let height = if from == to { 30.0 } else { 0.0 };
+                lines.push(line(format!("{}-message-{}", prefix, i), "straight", points, end("none"), end(marker), text, link));
+                y += height + STEP_GAP;
+            }
+            Step::Note { first, last, placement, text } => {
+                let (w, h) = node_size(text, "process");
+                let (left, right) = match placement {
+                    NotePlacement::LeftOf => (centers[*first] - w - 20.0, centers[*first] - 20.0),
+                    NotePlacement::RightOf => (centers[*last] + 20.0, centers[*last] + w + 20.0),
+                    NotePlacement::Over => {
+                        let middle = (centers[*first] + centers[*last]) / 2.0;
+                        let w = w.max(centers[*last] - centers[*first] + 60.0);
+                        (middle - w / 2.0, middle + w / 2.0)
+                    }
+                };
+                let mut note = geometry(format!("{}-note-{}", prefix, i), "rectangle", text, left, y, right - left, h);
+                note.extra.insert("fill".into(), json!("#FFF6C7"));
+                shapes.push(note);
+                y += h + STEP_GAP;
+            }
+        }
+    }
+
+    let mut elements = Vec::new();
+    for (i, participant) in sequence.participants.iter().enumerate() {
+        let id = format!("{}-participant-{}", prefix, participant.id);
+        let (w, _) = sizes[i];
+        let shape = if participant.actor { "terminal" } else { "rectangle" };
+        elements.push(geometry(id.clone(), shape, &participant.label, centers[i] - w / 2.0, y0, w, box_height));
+        let lifeline = Link { dotted: true, ..Default::default() };
+        let mut element = line(
+            format!("{}-lifeline-{}", prefix, participant.id),
+            "straight",
+            vec![Point(centers[i], y0 + box_height), Point(centers[i], y)],
+            End { bound: Some(&id), connection: [0.5, 1.0], marker: "none" },
+            End { bound: None, connection: [0.0, 0.0], marker: "none" },
+            "",
+            lifeline,
+        );
+        element.extra.insert("strokeWidth".into(), json!(1));
+        elements.push(element);
+    }
+    elements.extend(shapes);
+    elements.extend(lines);
+    elements
+}
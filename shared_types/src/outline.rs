@@ -0,0 +1,266 @@
+//! Markdown 大纲与思维导图互转：标题和嵌套列表对应思维导图的层级，保持节点顺序和文字。
+//!
+//! 导入时标题按级别嵌套，列表按缩进嵌套在所属标题之下，普通段落作为当前标题的子节点；
+//! 只有一个顶层节点时它就是中心主题，否则用 [`OutlineOptions::title`] 作为中心主题。
+//! 导出时中心主题写成一级标题，其余节点写成两个空格缩进的嵌套列表。
+
+use crate::geometry::place_beside;
+use crate::svg::plain_text;
+use crate::types::*;
+use serde_json::json;
+
+/// 列表项的层级排在所有标题之后
+const LIST_LEVEL: usize = 10;
+
+#[derive(Debug, Clone)]
+pub struct OutlineOptions {
+    /// 中心主题在画布上的位置
+    pub origin: Point,
+    /// 节点 id 前缀，同一块白板上多次导入时应各不相同
+    pub id_prefix: String,
+    /// 大纲有多个顶层节点时使用的中心主题
+    pub title: String,
+}
+
+impl Default for OutlineOptions {
+    fn default() -> Self {
+        OutlineOptions { origin: Point(0.0, 0.0), id_prefix: "outline".into(), title: "中心主题".into() }
+    }
+}
+
+impl OutlineOptions {
+    /// 放在已有元素的右侧，避免与白板上的内容重叠
+    pub fn beside(elements: &[PlaitElement], id_prefix: impl Into<String>) -> Self {
+        OutlineOptions { origin: place_beside(elements), id_prefix: id_prefix.into(), ..Default::default() }
+    }
+}
+
+/// 解析出的大纲节点
+struct Item {
+    level: usize,
+    text: String,
+    children: Vec<Item>,
+}
+
+/// 把 Markdown 大纲转换成一个思维导图元素
+pub fn markdown_to_mind_map(markdown: &str, options: &OutlineOptions) -> Result<PlaitElement, String> {
+    let mut roots = parse_outline(markdown);
+    let root = match roots.len() {
+        0 => return Err("Markdown 中没有标题、列表或文字".into()),
+        1 => roots.remove(0),
+        _ => Item { level: 0, text: options.title.clone(), children: roots },
+    };
+
+    let mut next_id = 0;
+    let mut element = build_node(&root, true, &options.id_prefix, &mut next_id);
+    element.element_type = "mindmap".into();
+    // 与前端新建的思维导图一致：分支都在右侧
+    element.extra.insert("rightNodeCount".into(), json!(root.children.len()));
+    element.extra.insert("isRoot".into(), json!(true));
+    element.points = vec![options.origin.clone()];
+    Ok(element)
+}
+
+fn parse_outline(markdown: &str) -> Vec<Item> {
+    // 当前的祖先链，stack[0] 是虚拟根
+    let mut stack = vec![Item { level: 0, text: String::new(), children: Vec::new() }];
+    let mut in_code = false;
+    let mut last_was_item = false;
+
+    for line in markdown.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_code = !in_code;
+            continue;
+        }
+        if in_code || trimmed.is_empty() || is_rule(trimmed) {
+            last_was_item = false;
+            continue;
+        }
+
+        let indent = indent_width(line);
+        let (level, text) = if let Some((depth, text)) = heading(line) {
+            (depth, text)
+        } else if let Some(text) = list_item(trimmed) {
+            (LIST_LEVEL + 1 + indent, text)
+        } else if last_was_item && indent > 0 {
+            // 列表项的续行
+            let last = stack.last_mut().expect("续行前必有列表项");
+            last.text.push(' ');
+            last.text.push_str(&topic_text(trimmed));
+            continue;
+        } else {
+            (LIST_LEVEL + 1 + indent, trimmed)
+        };
+        last_was_item = level > LIST_LEVEL;
+
+        // 弹出同级和更深的节点，挂到父节点下
+        while stack.last().is_some_and(|top| top.level >= level) {
+            let done = stack.pop().expect("栈非空");
+            stack.last_mut().expect("虚拟根不会被弹出").children.push(done);
+        }
+        stack.push(Item { level, text: topic_text(text), children: Vec::new() });
+    }
+
+    while stack.len() > 1 {
+        let done = stack.pop().expect("栈非空");
+        stack.last_mut().expect("虚拟根不会被弹出").children.push(done);
+    }
+    stack.pop().map(|root| root.children).unwrap_or_default()
+}
+
+fn indent_width(line: &str) -> usize {
+    line.chars()
+        .take_while(|c| c.is_whitespace())
+        .map(|c| if c == '\t' { 4 } else { 1 })
+        .sum()
+}
+
+/// `## 标题`，返回级别和文字
+fn heading(line: &str) -> Option<(usize, &str)> {
+    if indent_width(line) > 3 {
+        return None;
+    }
+    let trimmed = line.trim();
+    let depth = trimmed.chars().take_while(|&c| c == '#').count();
+    let rest = &trimmed[depth..];
+    if (1..=6).contains(&depth) && (rest.is_empty() || rest.starts_with(' ')) {
+        // 结尾的 ` ##` 是可选的闭合标记，`C#` 中的 # 是文字
+        let text = rest.trim();
+        let closing = text.trim_end_matches('#');
+        let text = if closing.is_empty() || closing.ends_with(' ') { closing.trim_end() } else { text };
+        Some((depth, text))
+    } else {
+        None
+    }
+}
+
+/// `- 文字`、`* 文字`、`1. 文字`，返回去掉标记的文字
+fn list_item(trimmed: &str) -> Option<&str> {
+    let marker_len = if trimmed.starts_with(['-', '*', '+']) {
+        1
+    } else {
+        let digits = trimmed.chars().take_while(char::is_ascii_digit).count();
+        if digits == 0 || !trimmed[digits..].starts_with(['.', ')']) {
+            return None;
+        }
+        digits + 1
+    };
+    let rest = &trimmed[marker_len..];
+    (rest.is_empty() || rest.starts_with([' ', '\t'])).then(|| rest.trim())
+}
+
+/// `---`、`***`、`___` 分隔线
+fn is_rule(trimmed: &str) -> bool {
+    let compact: String = trimmed.chars().filter(|c| !c.is_whitespace()).collect();
+    compact.len() >= 3 && ['-', '*', '_'].iter().any(|&m| compact.chars().all(|c| c == m))
+}
+
+/// 还原导出时的转义：开头用于避免被当成标记的反斜杠去掉，`<br>` 换行，
+/// `\\` 和 `\<br>` 是字面文字，空白后面收尾的反斜杠只用来保住结尾的空白
+fn topic_text(text: &str) -> String {
+    let mut rest = match text.strip_prefix('\\') {
+        Some(rest) if !rest.is_empty() => rest,
+        _ => text,
+    };
+    let mut out = String::new();
+    while let Some(c) = rest.chars().next() {
+        if let Some(after) = rest.strip_prefix("\\\\") {
+            out.push('\\');
+            rest = after;
+        } else if let Some(after) = rest.strip_prefix("\\<br>") {
+            out.push_str("<br>");
+            rest = after;
+        } else if let Some(after) = rest.strip_prefix("<br>") {
+            out.push('\n');
+            rest = after;
+        } else if rest == "\\" && out.ends_with(char::is_whitespace) {
+            rest = "";
+        } else {
+            out.push(c);
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+    out
+}
+
+fn build_node(item: &Item, root: bool, prefix: &str, next_id: &mut usize) -> PlaitElement {
+    let id = format!("{}-{}", prefix, next_id);
+    *next_id += 1;
+    let children = item.children.iter().map(|child| build_node(child, false, prefix, next_id)).collect();
+    let (width, height) = topic_size(&item.text, if root { 18.0 } else { 14.0 });
+
+    let mut extra = serde_json::Map::new();
+    extra.insert("data".into(), json!({ "topic": { "children": [{ "text": item.text }] } }));
+    extra.insert("width".into(), json!(width));
+    extra.insert("height".into(), json!(if root { height + 5.0 } else { height }));
+    PlaitElement { id, element_type: String::new(), shape: String::new(), points: Vec::new(), children: Some(children), extra }
+}
+
+/// 主题文字区域的大小：全角字符按字号，其余按 0.6 倍字号估算
+fn topic_size(text: &str, font_size: f64) -> (f64, f64) {
+    let lines: Vec<&str> = text.lines().collect();
+    let width = lines
+        .iter()
+        .map(|line| line.chars().map(|c| if c.is_ascii() { font_size * 0.6 } else { font_size }).sum::<f64>())
+        .fold(0.0, f64::max);
+    (width.ceil().max(14.0), lines.len().max(1) as f64 * 20.0)
+}
+
+fn is_mind_map(element: &PlaitElement) -> bool {
+    matches!(element.element_type.as_str(), "mindmap" | "mind_map")
+}
+
+/// 把一个思维导图导出成 Markdown 大纲
+pub fn mind_map_to_markdown(root: &PlaitElement) -> Result<String, String> {
+    if !is_mind_map(root) {
+        return Err(format!("{} 不是思维导图（type 为 {:?}）", root.id, root.element_type));
+    }
+    let mut markdown = format!("# {}\n", escape(&topic(root)));
+    fn write_children(node: &PlaitElement, depth: usize, markdown: &mut String) {
+        for child in node.children.as_deref().unwrap_or_default() {
+            let line = format!("{}- {}", "  ".repeat(depth), escape(&topic(child)));
+            markdown.push_str(line.trim_end());
+            markdown.push('\n');
+            write_children(child, depth + 1, markdown);
+        }
+    }
+    write_children(root, 0, &mut markdown);
+    Ok(markdown)
+}
+
+/// 导出白板上的全部思维导图，按白板中的顺序用空行隔开
+pub fn board_to_markdown(elements: &[PlaitElement]) -> Result<String, String> {
+    let outlines = elements
+        .iter()
+        .filter(|element| is_mind_map(element))
+        .map(mind_map_to_markdown)
+        .collect::<Result<Vec<_>, _>>()?;
+    if outlines.is_empty() {
+        return Err("白板上没有思维导图".into());
+    }
+    Ok(outlines.join("\n"))
+}
+
+fn topic(node: &PlaitElement) -> String {
+    node.extra.get("data").and_then(|data| data.get("topic")).map(plain_text).unwrap_or_default()
+}
+
+/// 多行主题写成 `<br>`，文字里原有的反斜杠和 `<br>` 先转义；开头像标题、列表标记
+/// 或是空白的文字加反斜杠，导入时不会被当成结构，结尾的空白后面也补一个反斜杠
+fn escape(text: &str) -> String {
+    let mut text = text.replace('\\', "\\\\").replace("<br>", "\\<br>").replace('\n', "<br>");
+    if text.ends_with(char::is_whitespace) {
+        text.push('\\');
+    }
+    let digits = text.chars().take_while(char::is_ascii_digit).count();
+    let looks_like_marker = text.starts_with(['#', '-', '*', '+', '\\'])
+        || text.starts_with(char::is_whitespace)
+        || (digits > 0 && text[digits..].starts_with(['.', ')']))
+        || is_rule(&text);
+    if looks_like_marker {
+        format!("\\{}", text)
+    } else {
+        text
+    }
+}
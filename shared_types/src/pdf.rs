@@ -0,0 +1,705 @@
+//! 白板的 PDF 导出，用于打印大型工作坊白板。
+//!
+//! 与 PNG 导出一样先渲染成 SVG，再由 usvg 解析成路径和文字，逐个写成 PDF 绘图指令。
+//! 文字以嵌入字体（按用到的字形做子集）的真实文本写入，可以搜索和复制。
+//! 整块白板写成一个 Form XObject，每页只是以不同的偏移引用它，平铺时文件不会随页数膨胀。
+
+use crate::raster::fonts;
+use crate::svg::{render_svg, SvgOptions};
+use crate::types::PlaitElement;
+use pdf_writer::types::{CidFontType, FontFlags, LineCapStyle, LineJoinStyle, SystemInfo, UnicodeCmap};
+use pdf_writer::{Content, Filter, Finish, Name, Pdf, Ref, Str, TextStr};
+use resvg::tiny_skia::{PathSegment, Transform};
+use resvg::usvg::{self, fontdb};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+/// 画布 1px 按 96 DPI 打印为 0.75pt
+const PX_TO_PT: f32 = 0.75;
+const MM_TO_PT: f32 = 72.0 / 25.4;
+/// 分页打印最多生成的页数
+const MAX_PAGES: usize = 1000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PageSize {
+    #[default]
+    A4,
+    A3,
+    Letter,
+}
+
+impl PageSize {
+    /// 纵向时的宽高（毫米）
+    fn millimeters(self) -> (f32, f32) {
+        match self {
+            PageSize::A4 => (210.0, 297.0),
+            PageSize::A3 => (297.0, 420.0),
+            PageSize::Letter => (215.9, 279.4),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Orientation {
+    /// 单页时按白板宽高比选择，平铺时选页数较少的方向
+    #[default]
+    Auto,
+    Portrait,
+    Landscape,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PdfLayout {
+    /// 整块白板缩放到一页
+    Fit,
+    /// 按打印比例平铺到多页：画布 1px 打印为 `scale` × 0.75pt，相邻页重叠 `overlap_mm` 毫米便于拼贴
+    Tile { scale: f32, overlap_mm: f32 },
+}
+
+#[derive(Debug, Clone)]
+pub struct PdfOptions {
+    pub page: PageSize,
+    pub orientation: Orientation,
+    pub layout: PdfLayout,
+    /// 页边距（毫米），平铺时裁切标记和页码画在页边距里
+    pub margin_mm: f32,
+    pub svg: SvgOptions,
+}
+
+impl Default for PdfOptions {
+    fn default() -> Self {
+        PdfOptions {
+            page: PageSize::A4,
+            orientation: Orientation::Auto,
+            layout: PdfLayout::Fit,
+            margin_mm: 10.0,
+            svg: SvgOptions::default(),
+        }
+    }
+}
+
+/// 渲染一组白板元素，返回 PDF 文件内容
+pub fn render_pdf(elements: &[PlaitElement], options: &PdfOptions) -> Result<Vec<u8>, String> {
+    let image = render_svg(elements, &options.svg);
+    let usvg_options = usvg::Options { fontdb: fonts(), ..Default::default() };
+    let tree = usvg::Tree::from_str(&image.svg, &usvg_options).map_err(|e| format!("SVG 解析失败: {}", e))?;
+
+    let pages = plan_pages(tree.size().width(), tree.size().height(), options)?;
+
+    let mut writer = Writer::new(usvg_options.fontdb.clone());
+    let mut board = Content::new();
+    writer.group(&mut board, tree.root(), 1.0);
+    writer.finish_document(tree.size().width(), tree.size().height(), board.finish(), &pages)
+}
+
+/// 一页打印的内容：白板上 (x, y) 像素画在页面 (margin + x·scale + dx, 自顶向下 margin + y·scale + dy) 处
+#[derive(Debug, Clone, Copy)]
+struct PageTile {
+    width: f32,
+    height: f32,
+    margin: f32,
+    scale: f32,
+    dx: f32,
+    dy: f32,
+    row: usize,
+    col: usize,
+    rows: usize,
+    cols: usize,
+    overlap: f32,
+}
+
+fn plan_pages(board_width: f32, board_height: f32, options: &PdfOptions) -> Result<Vec<PageTile>, String> {
+    if !(options.margin_mm.is_finite() && options.margin_mm >= 0.0) {
+        return Err(format!("页边距无效: {}", options.margin_mm));
+    }
+    let margin = options.margin_mm * MM_TO_PT;
+    let (short, long) = options.page.millimeters();
+    let portrait = (short * MM_TO_PT, long * MM_TO_PT);
+    let landscape = (portrait.1, portrait.0);
+    let printable = |(w, h): (f32, f32)| (w - 2.0 * margin, h - 2.0 * margin);
+    if printable(portrait).0 <= 0.0 {
+        return Err("页边距超过了纸张大小".into());
+    }
+
+    match options.layout {
+        PdfLayout::Fit => {
+            let page = match options.orientation {
+                Orientation::Portrait => portrait,
+                Orientation::Landscape => landscape,
+                Orientation::Auto if board_width > board_height => landscape,
+                Orientation::Auto => portrait,
+            };
+            let (pw, ph) = printable(page);
+            let scale = (pw / board_width).min(ph / board_height);
+            Ok(vec![PageTile {
+                width: page.0,
+                height: page.1,
+                margin,
+                scale,
+                dx: (pw - board_width * scale) / 2.0,
+                dy: (ph - board_height * scale) / 2.0,
+                row: 0,
+                col: 0,
+                rows: 1,
+                cols: 1,
+                overlap: 0.0,
+            }])
+        }
+        PdfLayout::Tile { scale, overlap_mm } => {
+            if !(scale.is_finite() && scale > 0.0) {
+                return Err(format!("打印比例必须为正数: {}", scale));
+            }
+            let overlap = overlap_mm * MM_TO_PT;
+            let scale = scale * PX_TO_PT;
+            // 页数用 f64 计算，比例极大时不会在转成 usize 之前溢出
+            let grid = |page: (f32, f32)| -> Result<(f64, f64), String> {
+                let (pw, ph) = printable(page);
+                if !(overlap.is_finite() && overlap >= 0.0 && overlap < pw.min(ph) / 2.0) {
+                    return Err(format!("重叠宽度无效: {} 毫米（应小于可打印区域的一半）", overlap_mm));
+                }
+                let count = |size: f32, printable: f32| {
+                    let count = ((size as f64 - overlap as f64) / (printable as f64 - overlap as f64)).ceil();
+                    if count.is_finite() { Ok(count.max(1.0)) } else { Err("打印比例过大，页数超出范围".to_string()) }
+                };
+                Ok((count(board_height * scale, ph)?, count(board_width * scale, pw)?))
+            };
+            let page = match options.orientation {
+                Orientation::Portrait => portrait,
+                Orientation::Landscape => landscape,
+                Orientation::Auto => {
+                    let (pr, pc) = grid(portrait)?;
+                    let (lr, lc) = grid(landscape)?;
+                    if lr * lc < pr * pc { landscape } else { portrait }
+                }
+            };
+            let (rows, cols) = grid(page)?;
+            if rows * cols > MAX_PAGES as f64 {
+                return Err(format!("需要 {}×{} 页（最多 {} 页），请减小打印比例", rows, cols, MAX_PAGES));
+            }
+            let (rows, cols) = (rows as usize, cols as usize);
+            let (pw, ph) = printable(page);
+            let mut tiles = Vec::with_capacity(rows * cols);
+            for row in 0..rows {
+                for col in 0..cols {
+                    tiles.push(PageTile {
+                        width: page.0,
+                        height: page.1,
+                        margin,
+                        scale,
+                        dx: -(col as f32) * (pw - overlap),
+                        dy: -(row as f32) * (ph - overlap),
+                        row,
+                        col,
+                        rows,
+                        cols,
+                        overlap,
+                    });
+                }
+            }
+            Ok(tiles)
+        }
+    }
+}
+
+/// 嵌入的一个字体及用到的字形（字形 id → 对应的文字，用于 ToUnicode）
+struct EmbeddedFont {
+    id: fontdb::ID,
+    data: Vec<u8>,
+    index: u32,
+    glyphs: BTreeMap<u16, String>,
+}
+
+impl EmbeddedFont {
+    fn face(&self) -> Option<ttf_parser::Face<'_>> {
+        ttf_parser::Face::parse(&self.data, self.index).ok()
+    }
+}
+
+struct Writer {
+    pdf: Pdf,
+    next_ref: i32,
+    db: Arc<fontdb::Database>,
+    fonts: Vec<EmbeddedFont>,
+    loaded: HashMap<fontdb::ID, usize>,
+    /// 主字体缺字时按字符查找的后备字体
+    fallback: HashMap<char, Option<usize>>,
+    /// 透明度（千分比：填充, 描边）→ ExtGState 名称
+    alphas: BTreeMap<(u16, u16), String>,
+}
+
+impl Writer {
+    fn new(db: Arc<fontdb::Database>) -> Self {
+        Writer {
+            pdf: Pdf::new(),
+            next_ref: 1,
+            db,
+            fonts: Vec::new(),
+            loaded: HashMap::new(),
+            fallback: HashMap::new(),
+            alphas: BTreeMap::new(),
+        }
+    }
+
+    fn alloc(&mut self) -> Ref {
+        let id = Ref::new(self.next_ref);
+        self.next_ref += 1;
+        id
+    }
+
+    fn group(&mut self, content: &mut Content, group: &usvg::Group, opacity: f32) {
+        let opacity = opacity * group.opacity().get();
+        for node in group.children() {
+            match node {
+                usvg::Node::Group(group) => self.group(content, group, opacity),
+                usvg::Node::Path(path) => self.path(content, path, opacity),
+                usvg::Node::Text(text) => self.text(content, text, opacity),
+                // 图片引用的是网络地址，导出时不下载
+                usvg::Node::Image(_) => {}
+            }
+        }
+    }
+
+    fn path(&mut self, content: &mut Content, path: &usvg::Path, opacity: f32) {
+        if !path.is_visible() || (path.fill().is_none() && path.stroke().is_none()) {
+            return;
+        }
+        content.save_state();
+        content.transform(matrix(path.abs_transform()));
+
+        let fill_alpha = path.fill().map_or(1.0, |f| f.opacity().get() * opacity);
+        let stroke_alpha = path.stroke().map_or(1.0, |s| s.opacity().get() * opacity);
+        self.set_alpha(content, fill_alpha, stroke_alpha);
+        if let Some(fill) = path.fill() {
+            let (r, g, b) = rgb(fill.paint());
+            content.set_fill_rgb(r, g, b);
+        }
+        if let Some(stroke) = path.stroke() {
+            let (r, g, b) = rgb(stroke.paint());
+            content.set_stroke_rgb(r, g, b);
+            content.set_line_width(stroke.width().get());
+            content.set_line_cap(match stroke.linecap() {
+                usvg::LineCap::Butt => LineCapStyle::ButtCap,
+                usvg::LineCap::Round => LineCapStyle::RoundCap,
+                usvg::LineCap::Square => LineCapStyle::ProjectingSquareCap,
+            });
+            content.set_line_join(match stroke.linejoin() {
+                usvg::LineJoin::Round => LineJoinStyle::RoundJoin,
+                usvg::LineJoin::Bevel => LineJoinStyle::BevelJoin,
+                _ => LineJoinStyle::MiterJoin,
+            });
+            if let Some(dashes) = stroke.dasharray() {
+                content.set_dash_pattern(dashes.iter().copied(), stroke.dashoffset());
+            }
+        }
+
+        let mut last = (0.0, 0.0);
+        for segment in path.data().segments() {
+            match segment {
+                PathSegment::MoveTo(p) => {
+                    content.move_to(p.x, p.y);
+                    last = (p.x, p.y);
+                }
+                PathSegment::LineTo(p) => {
+                    content.line_to(p.x, p.y);
+                    last = (p.x, p.y);
+                }
+                PathSegment::QuadTo(c, p) => {
+                    // 二次贝塞尔升阶为三次
+                    let c1 = (last.0 + 2.0 / 3.0 * (c.x - last.0), last.1 + 2.0 / 3.0 * (c.y - last.1));
+                    let c2 = (p.x + 2.0 / 3.0 * (c.x - p.x), p.y + 2.0 / 3.0 * (c.y - p.y));
+                    content.cubic_to(c1.0, c1.1, c2.0, c2.1, p.x, p.y);
+                    last = (p.x, p.y);
+                }
+                PathSegment::CubicTo(c1, c2, p) => {
+                    content.cubic_to(c1.x, c1.y, c2.x, c2.y, p.x, p.y);
+                    last = (p.x, p.y);
+                }
+                PathSegment::Close => {
+                    content.close_path();
+                }
+            }
+        }
+
+        let even_odd = path.fill().is_some_and(|f| f.rule() == usvg::FillRule::EvenOdd);
+        match (path.fill().is_some(), path.stroke().is_some(), even_odd) {
+            (true, true, false) => content.fill_nonzero_and_stroke(),
+            (true, true, true) => content.fill_even_odd_and_stroke(),
+            (true, false, false) => content.fill_nonzero(),
+            (true, false, true) => content.fill_even_odd(),
+            _ => content.stroke(),
+        };
+        content.restore_state();
+    }
+
+    fn text(&mut self, content: &mut Content, text: &usvg::Text, opacity: f32) {
+        for chunk in text.chunks() {
+            let (Some(x), Some(y)) = (chunk.x(), chunk.y()) else { continue };
+
+            // 每个片段按字符找到字体和字形，相邻同字体的字符合成一段
+            let mut runs: Vec<(usize, f32, Vec<u16>, f32)> = Vec::new();
+            let mut fill = None;
+            for span in chunk.spans() {
+                fill = fill.or(span.fill());
+                let size = span.font_size().get();
+                let primary = self.primary_font(span.font());
+                let Some(text) = chunk.text().get(span.start()..span.end()) else { continue };
+                for ch in text.chars() {
+                    let Some((font, glyph, advance)) = self.glyph(primary, ch) else { continue };
+                    self.fonts[font].glyphs.entry(glyph).or_insert_with(|| ch.to_string());
+                    match runs.last_mut() {
+                        Some(run) if run.0 == font && run.1 == size => {
+                            run.2.push(glyph);
+                            run.3 += advance * size;
+                        }
+                        _ => runs.push((font, size, vec![glyph], advance * size)),
+                    }
+                }
+            }
+            if runs.is_empty() {
+                continue;
+            }
+
+            let width: f32 = runs.iter().map(|run| run.3).sum();
+            let mut pen = match chunk.anchor() {
+                usvg::TextAnchor::Start => x,
+                usvg::TextAnchor::Middle => x - width / 2.0,
+                usvg::TextAnchor::End => x - width,
+            };
+            content.save_state();
+            content.transform(matrix(text.abs_transform()));
+            let alpha = fill.map_or(1.0, |f| f.opacity().get()) * opacity;
+            self.set_alpha(content, alpha, alpha);
+            let (r, g, b) = fill.map_or((0.0, 0.0, 0.0), |f| rgb(f.paint()));
+            content.set_fill_rgb(r, g, b);
+            content.begin_text();
+            for (font, size, glyphs, advance) in runs {
+                let bytes: Vec<u8> = glyphs.iter().flat_map(|g| g.to_be_bytes()).collect();
+                content.set_font(Name(format!("F{}", font).as_bytes()), size);
+                // 白板坐标 y 轴向下，文字矩阵翻转回来
+                content.set_text_matrix([1.0, 0.0, 0.0, -1.0, pen, y]);
+                content.show(Str(&bytes));
+                pen += advance;
+            }
+            content.end_text();
+            content.restore_state();
+        }
+    }
+
+    fn set_alpha(&mut self, content: &mut Content, fill: f32, stroke: f32) {
+        if fill >= 1.0 && stroke >= 1.0 {
+            return;
+        }
+        let key = ((fill.clamp(0.0, 1.0) * 1000.0) as u16, (stroke.clamp(0.0, 1.0) * 1000.0) as u16);
+        let count = self.alphas.len();
+        let name = self.alphas.entry(key).or_insert_with(|| format!("G{}", count));
+        content.set_parameters(Name(name.as_bytes()));
+    }
+
+    fn load_font(&mut self, id: fontdb::ID) -> Option<usize> {
+        if let Some(&index) = self.loaded.get(&id) {
+            return Some(index);
+        }
+        let (data, index) = self.db.with_face_data(id, |data, index| (data.to_vec(), index))?;
+        ttf_parser::Face::parse(&data, index).ok()?;
+        self.fonts.push(EmbeddedFont { id, data, index, glyphs: BTreeMap::new() });
+        self.loaded.insert(id, self.fonts.len() - 1);
+        Some(self.fonts.len() - 1)
+    }
+
+    fn primary_font(&mut self, font: &usvg::Font) -> Option<usize> {
+        let families: Vec<fontdb::Family> = font
+            .families()
+            .iter()
+            .map(|family| match family {
+                usvg::FontFamily::Named(name) => fontdb::Family::Name(name),
+                usvg::FontFamily::Serif => fontdb::Family::Serif,
+                usvg::FontFamily::Cursive => fontdb::Family::Cursive,
+                usvg::FontFamily::Fantasy => fontdb::Family::Fantasy,
+                usvg::FontFamily::Monospace => fontdb::Family::Monospace,
+                usvg::FontFamily::SansSerif => fontdb::Family::SansSerif,
+            })
+            .chain([fontdb::Family::SansSerif])
+            .collect();
+        let id = self.db.query(&fontdb::Query {
+            families: &families,
+            weight: fontdb::Weight(font.weight()),
+            ..Default::default()
+        })?;
+        self.load_font(id)
+    }
+
+    /// 字符所用的字体、字形 id 和前进宽度（以字号为单位）
+    fn glyph(&mut self, primary: Option<usize>, ch: char) -> Option<(usize, u16, f32)> {
+        let lookup = |font: &EmbeddedFont| {
+            let face = font.face()?;
+            let glyph = face.glyph_index(ch)?;
+            let advance = face.glyph_hor_advance(glyph).unwrap_or(0) as f32 / face.units_per_em() as f32;
+            Some((glyph.0, advance))
+        };
+        if let Some((glyph, advance)) = primary.and_then(|font| lookup(&self.fonts[font])) {
+            return primary.map(|font| (font, glyph, advance));
+        }
+
+        if !self.fallback.contains_key(&ch) {
+            let id = self.db.faces().find_map(|face| {
+                let has = self.db.with_face_data(face.id, |data, index| {
+                    ttf_parser::Face::parse(data, index).ok().and_then(|f| f.glyph_index(ch)).is_some()
+                });
+                has.unwrap_or(false).then_some(face.id)
+            });
+            let font = id.and_then(|id| self.load_font(id));
+            self.fallback.insert(ch, font);
+        }
+        let font = self.fallback[&ch]?;
+        lookup(&self.fonts[font]).map(|(glyph, advance)| (font, glyph, advance))
+    }
+
+    fn finish_document(mut self, board_width: f32, board_height: f32, board: Vec<u8>, pages: &[PageTile]) -> Result<Vec<u8>, String> {
+        let catalog = self.alloc();
+        let page_tree = self.alloc();
+        let board_ref = self.alloc();
+        let label_font = self.alloc();
+
+        let font_refs = (0..self.fonts.len()).map(|font| self.write_font(font)).collect::<Result<Vec<_>, _>>()?;
+        let alpha_refs: Vec<(String, Ref)> = self.alphas.clone().into_values().map(|name| (name, self.alloc())).collect();
+        for ((fill, stroke), (_, id)) in self.alphas.keys().zip(&alpha_refs) {
+            self.pdf.ext_graphics(*id).non_stroking_alpha(*fill as f32 / 1000.0).stroking_alpha(*stroke as f32 / 1000.0);
+        }
+
+        let board = deflate(&board);
+        let mut form = self.pdf.form_xobject(board_ref, &board);
+        form.filter(Filter::FlateDecode);
+        form.bbox(pdf_writer::Rect::new(0.0, 0.0, board_width, board_height));
+        let mut resources = form.resources();
+        let mut fonts = resources.fonts();
+        for (font, id) in font_refs.iter().enumerate() {
+            fonts.pair(Name(format!("F{}", font).as_bytes()), *id);
+        }
+        fonts.finish();
+        let mut states = resources.ext_g_states();
+        for (name, id) in &alpha_refs {
+            states.pair(Name(name.as_bytes()), *id);
+        }
+        states.finish();
+        resources.finish();
+        form.finish();
+
+        self.pdf.type1_font(label_font).base_font(Name(b"Helvetica"));
+
+        let mut page_refs = Vec::with_capacity(pages.len());
+        for (i, tile) in pages.iter().enumerate() {
+            let page_ref = self.alloc();
+            let content_ref = self.alloc();
+            page_refs.push(page_ref);
+
+            let content = deflate(&page_content(tile, i, pages.len()));
+            self.pdf.stream(content_ref, &content).filter(Filter::FlateDecode);
+            let mut page = self.pdf.page(page_ref);
+            page.media_box(pdf_writer::Rect::new(0.0, 0.0, tile.width, tile.height));
+            page.parent(page_tree);
+            page.contents(content_ref);
+            let mut resources = page.resources();
+            resources.x_objects().pair(Name(b"Board"), board_ref);
+            resources.fonts().pair(Name(b"Label"), label_font);
+            resources.finish();
+            page.finish();
+        }
+
+        self.pdf.pages(page_tree).kids(page_refs.iter().copied()).count(page_refs.len() as i32);
+        self.pdf.catalog(catalog).pages(page_tree);
+        let info = self.alloc();
+        self.pdf.document_info(info).producer(TextStr("Drawnix"));
+        Ok(self.pdf.finish())
+    }
+
+    /// 以 Type0（Identity-H，字符码即字形 id）写入子集字体，返回字体字典的引用
+    fn write_font(&mut self, font: usize) -> Result<Ref, String> {
+        let type0 = self.alloc();
+        let cid = self.alloc();
+        let descriptor = self.alloc();
+        let cmap_ref = self.alloc();
+        let file = self.alloc();
+
+        let embedded = &self.fonts[font];
+        let face = embedded.face().ok_or("字体解析失败")?;
+        let cff = face.tables().cff.is_some() || face.tables().cff2.is_some();
+        let per_mille = 1000.0 / face.units_per_em() as f32;
+        let ps_name = self.db.face(embedded.id).map(|f| f.post_script_name.clone()).unwrap_or_default();
+        let ps_name: String = ps_name.chars().filter(|c| c.is_ascii_alphanumeric() || *c == '-').collect();
+        let base_font = format!("{}+{}", subset_tag(font), if ps_name.is_empty() { "Font" } else { &ps_name });
+        let system_info = SystemInfo { registry: Str(b"Adobe"), ordering: Str(b"Identity"), supplement: 0 };
+
+        self.pdf
+            .type0_font(type0)
+            .base_font(Name(base_font.as_bytes()))
+            .encoding_predefined(Name(b"Identity-H"))
+            .descendant_font(cid)
+            .to_unicode(cmap_ref);
+
+        let mut cid_font = self.pdf.cid_font(cid);
+        cid_font
+            .subtype(if cff { CidFontType::Type0 } else { CidFontType::Type2 })
+            .base_font(Name(base_font.as_bytes()))
+            .system_info(system_info)
+            .font_descriptor(descriptor)
+            .default_width(0.0);
+        if !cff {
+            cid_font.cid_to_gid_map_predefined(Name(b"Identity"));
+        }
+        let mut widths = cid_font.widths();
+        for &glyph in embedded.glyphs.keys() {
+            let advance = face.glyph_hor_advance(ttf_parser::GlyphId(glyph)).unwrap_or(0) as f32;
+            widths.consecutive(glyph, [advance * per_mille]);
+        }
+        widths.finish();
+        cid_font.finish();
+
+        let bbox = face.global_bounding_box();
+        let mut flags = FontFlags::SYMBOLIC;
+        if face.is_monospaced() {
+            flags |= FontFlags::FIXED_PITCH;
+        }
+        let mut descriptor_writer = self.pdf.font_descriptor(descriptor);
+        descriptor_writer
+            .name(Name(base_font.as_bytes()))
+            .flags(flags)
+            .bbox(pdf_writer::Rect::new(
+                bbox.x_min as f32 * per_mille,
+                bbox.y_min as f32 * per_mille,
+                bbox.x_max as f32 * per_mille,
+                bbox.y_max as f32 * per_mille,
+            ))
+            .italic_angle(face.italic_angle())
+            .ascent(face.ascender() as f32 * per_mille)
+            .descent(face.descender() as f32 * per_mille)
+            .cap_height(face.capital_height().unwrap_or(face.ascender()) as f32 * per_mille)
+            .stem_v(80.0);
+        if cff {
+            descriptor_writer.font_file3(file);
+        } else {
+            descriptor_writer.font_file2(file);
+        }
+        descriptor_writer.finish();
+
+        let mut cmap = UnicodeCmap::new(Name(b"Custom"), system_info);
+        for (&glyph, text) in &embedded.glyphs {
+            cmap.pair_with_multiple(glyph, text.chars());
+        }
+        self.pdf.cmap(cmap_ref, &cmap.finish());
+
+        let glyphs: Vec<u16> = std::iter::once(0).chain(embedded.glyphs.keys().copied()).collect();
+        let subset = subsetter::subset(&embedded.data, embedded.index, subsetter::Profile::pdf(&glyphs))
+            .map_err(|e| format!("字体子集化失败: {}", e))?;
+        let data = deflate(&subset);
+        let mut stream = self.pdf.stream(file, &data);
+        stream.filter(Filter::FlateDecode);
+        if cff {
+            stream.pair(Name(b"Subtype"), Name(b"OpenType"));
+        }
+        stream.finish();
+        Ok(type0)
+    }
+}
+
+/// 一页的内容：裁到可打印区域后引用整块白板；平铺时在页边距画拼接标记和页码
+fn page_content(tile: &PageTile, index: usize, total: usize) -> Vec<u8> {
+    let PageTile { width, height, margin, scale, dx, dy, .. } = *tile;
+    let (pw, ph) = (width - 2.0 * margin, height - 2.0 * margin);
+    let mut content = Content::new();
+
+    content.save_state();
+    content.rect(margin, margin, pw, ph).clip_nonzero().end_path();
+    content.transform([scale, 0.0, 0.0, -scale, margin + dx, height - margin - dy]);
+    content.x_object(Name(b"Board"));
+    content.restore_state();
+
+    if total == 1 {
+        return content.finish();
+    }
+
+    content.save_state();
+    content.set_stroke_rgb(0.6, 0.6, 0.6);
+    content.set_line_width(0.5);
+    let tick = (margin * 0.6).min(8.0 * MM_TO_PT);
+    // 可打印区域四角的裁切标记
+    for (x, y) in [(margin, margin), (width - margin, margin), (margin, height - margin), (width - margin, height - margin)] {
+        let sx = if x < width / 2.0 { -1.0 } else { 1.0 };
+        let sy = if y < height / 2.0 { -1.0 } else { 1.0 };
+        content.move_to(x + sx * 2.0, y).line_to(x + sx * (2.0 + tick), y);
+        content.move_to(x, y + sy * 2.0).line_to(x, y + sy * (2.0 + tick));
+    }
+    content.stroke();
+
+    // 与相邻页重叠区域的边界：页边距里画刻度，区域内画浅色虚线
+    let mut vertical = Vec::new();
+    let mut horizontal = Vec::new();
+    if tile.col > 0 {
+        vertical.push(margin + tile.overlap);
+    }
+    if tile.col + 1 < tile.cols {
+        vertical.push(width - margin - tile.overlap);
+    }
+    if tile.row > 0 {
+        horizontal.push(height - margin - tile.overlap);
+    }
+    if tile.row + 1 < tile.rows {
+        horizontal.push(margin + tile.overlap);
+    }
+    for &x in &vertical {
+        content.move_to(x, height - margin + 2.0).line_to(x, height - margin + 2.0 + tick);
+        content.move_to(x, margin - 2.0).line_to(x, margin - 2.0 - tick);
+    }
+    for &y in &horizontal {
+        content.move_to(margin - 2.0, y).line_to(margin - 2.0 - tick, y);
+        content.move_to(width - margin + 2.0, y).line_to(width - margin + 2.0 + tick, y);
+    }
+    content.stroke();
+    content.set_stroke_rgb(0.8, 0.8, 0.8);
+    content.set_dash_pattern([3.0, 3.0], 0.0);
+    for &x in &vertical {
+        content.move_to(x, margin).line_to(x, height - margin);
+    }
+    for &y in &horizontal {
+        content.move_to(margin, y).line_to(width - margin, y);
+    }
+    content.stroke();
+    content.restore_state();
+
+    // 页码用标准字体，只含 ASCII
+    let label = format!("Row {} / Col {}   ({} of {})", tile.row + 1, tile.col + 1, index + 1, total);
+    content.begin_text();
+    content.set_fill_gray(0.4);
+    content.set_font(Name(b"Label"), 7.0);
+    content.next_line(margin, (margin / 2.0 - 2.5).max(2.0));
+    content.show(Str(label.as_bytes()));
+    content.end_text();
+    content.finish()
+}
+
+fn matrix(t: Transform) -> [f32; 6] {
+    [t.sx, t.ky, t.kx, t.sy, t.tx, t.ty]
+}
+
+fn rgb(paint: &usvg::Paint) -> (f32, f32, f32) {
+    match paint {
+        usvg::Paint::Color(c) => (c.red as f32 / 255.0, c.green as f32 / 255.0, c.blue as f32 / 255.0),
+        // 白板渲染不产生渐变和图案
+        _ => (0.0, 0.0, 0.0),
+    }
+}
+
+/// 子集字体名前缀：6 个大写字母，同一文件内互不相同
+fn subset_tag(index: usize) -> String {
+    let mut n = index;
+    (0..6)
+        .map(|_| {
+            let c = (b'A' + (n % 26) as u8) as char;
+            n /= 26;
+            c
+        })
+        .collect()
+}
+
+fn deflate(data: &[u8]) -> Vec<u8> {
+    miniz_oxide::deflate::compress_to_vec_zlib(data, 6)
+}
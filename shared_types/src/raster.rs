@@ -0,0 +1,76 @@
+//! 白板的 PNG 导出：在 SVG 渲染结果上用 resvg 纯 CPU 光栅化，不需要 GPU 或浏览器。
+
+use crate::svg::{render_svg, SvgOptions};
+use crate::types::PlaitElement;
+use resvg::{tiny_skia, usvg};
+use std::sync::{Arc, OnceLock};
+
+/// 单边像素上限，防止超大白板或过大的缩放耗尽内存
+pub const MAX_PIXELS_PER_SIDE: u32 = 16384;
+
+#[derive(Debug, Clone)]
+pub struct PngOptions {
+    /// 画布坐标到像素的缩放倍数
+    pub scale: f32,
+    pub svg: SvgOptions,
+}
+
+impl Default for PngOptions {
+    fn default() -> Self {
+        PngOptions { scale: 1.0, svg: SvgOptions::default() }
+    }
+}
+
+/// sans-serif 优先映射到这些字体，前几个能显示中文
+const SANS_SERIF_CANDIDATES: [&str; 7] = [
+    "Noto Sans CJK SC",
+    "Source Han Sans SC",
+    "WenQuanYi Micro Hei",
+    "Microsoft YaHei",
+    "PingFang SC",
+    "DejaVu Sans",
+    "Liberation Sans",
+];
+
+/// 系统字体只加载一次，白板服务反复生成预览时不必每次扫描字体目录
+pub(crate) fn fonts() -> Arc<usvg::fontdb::Database> {
+    static FONTS: OnceLock<Arc<usvg::fontdb::Database>> = OnceLock::new();
+    FONTS
+        .get_or_init(|| {
+            let mut db = usvg::fontdb::Database::new();
+            db.load_system_fonts();
+            // fontdb 默认把 sans-serif 映射到 Arial，服务器上通常没有，文字会整段丢失
+            let installed = |name: &str| db.faces().any(|face| face.families.iter().any(|(family, _)| family == name));
+            let fallback = SANS_SERIF_CANDIDATES
+                .iter()
+                .map(|name| name.to_string())
+                .find(|name| installed(name))
+                .or_else(|| db.faces().next().and_then(|face| face.families.first()).map(|(family, _)| family.clone()));
+            if let Some(family) = fallback {
+                db.set_sans_serif_family(family);
+            }
+            Arc::new(db)
+        })
+        .clone()
+}
+
+/// 渲染一组白板元素，返回 PNG 文件内容
+pub fn render_png(elements: &[PlaitElement], options: &PngOptions) -> Result<Vec<u8>, String> {
+    if !(options.scale.is_finite() && options.scale > 0.0) {
+        return Err(format!("缩放倍数必须为正数: {}", options.scale));
+    }
+    let image = render_svg(elements, &options.svg);
+
+    let width = (image.view_box.width() as f32 * options.scale).ceil();
+    let height = (image.view_box.height() as f32 * options.scale).ceil();
+    if width > MAX_PIXELS_PER_SIDE as f32 || height > MAX_PIXELS_PER_SIDE as f32 {
+        return Err(format!("图片尺寸过大: {}x{}（单边上限 {}），请减小缩放倍数或指定导出区域", width, height, MAX_PIXELS_PER_SIDE));
+    }
+
+    let usvg_options = usvg::Options { fontdb: fonts(), ..Default::default() };
+    let tree = usvg::Tree::from_str(&image.svg, &usvg_options).map_err(|e| format!("SVG 解析失败: {}", e))?;
+    let mut pixmap = tiny_skia::Pixmap::new((width as u32).max(1), (height as u32).max(1))
+        .ok_or_else(|| format!("无法分配 {}x{} 的画布", width, height))?;
+    resvg::render(&tree, tiny_skia::Transform::from_scale(options.scale, options.scale), &mut pixmap.as_mut());
+    pixmap.encode_png().map_err(|e| format!("PNG 编码失败: {}", e))
+}
@@ -0,0 +1,251 @@
+//! 白板副本：按统一的全序应用白板变化，保证各端收到同一批变化后状态一致。
+//!
+//! DDS 不保证不同发布者的消息以相同顺序到达，而按路径的操作依赖应用顺序，
+//! 各端按到达顺序应用会逐渐分叉。副本把变化按（发布时间, source_id, seq）排序：
+//! 新变化排在末尾时直接应用，否则从它之前最近的检查点按顺序重放。
+//! set / remove 的路径在应用时若已不指向同一 id 的元素，则按 id 重新定位。
+//!
+//! 只有最近 `REORDER_WINDOW` 条变化可以重排，更早的并入基准状态，内存和重放的代价都有上限；
+//! 排在窗口之前才到达的变化无法按序放回，会被丢弃并计数。本端发布前用 [`Replica::stamp`]
+//! 把时间戳推到已收到的变化之后，时钟落后的一端也不会总是排到窗口之外。
+//! 后加入的一端用 [`Replica::from_snapshot`] 以快照为基准，快照已包含的变化再收到时丢弃。
+
+use crate::board::BoardState;
+use crate::types::*;
+
+/// 每应用这么多条变化保存一次白板，乱序到达时从最近的检查点重放
+const CHECKPOINT_INTERVAL: usize = 32;
+/// 可以重排的最近变化数
+const REORDER_WINDOW: usize = 1024;
+
+/// 变化在全序中的位置；完全相同的键视为重复消息
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct OrderKey {
+    /// 发布时间（微秒），无法解析时排在最前
+    time: i64,
+    source_id: String,
+    seq: u64,
+    /// 没有 seq 的旧消息靠操作内容区分先后；有 seq 时为空，不再保存一份变化的副本
+    body: String,
+}
+
+impl OrderKey {
+    fn of(change: &BoardChangeData) -> Self {
+        OrderKey {
+            time: publish_time(change),
+            source_id: change.source_id.clone(),
+            seq: change.seq.unwrap_or(0),
+            body: match change.seq {
+                Some(_) => String::new(),
+                None => serde_json::to_string(&change.operations).unwrap_or_default(),
+            },
+        }
+    }
+}
+
+fn publish_time(change: &BoardChangeData) -> i64 {
+    chrono::DateTime::parse_from_rfc3339(&change.timestamp)
+        .map(|t| t.timestamp_micros())
+        .unwrap_or(i64::MIN)
+}
+
+#[derive(Debug, Clone)]
+pub struct Replica {
+    /// (窗口内已应用的变化数, 当时的白板)，第一个是基准状态
+    checkpoints: Vec<(usize, BoardState)>,
+    /// 重排窗口内的变化
+    log: Vec<(OrderKey, BoardChangeData)>,
+    board: BoardState,
+    /// 最后一条并入基准状态的变化，排在它之前的变化不再接受
+    floor: Option<OrderKey>,
+    /// 已并入基准状态的变化数
+    folded: usize,
+    /// 收到的最新发布时间（微秒）
+    latest: i64,
+    /// 白板已包含的变化，应答快照请求时一起发出
+    coverage: SeqCoverage,
+    /// 基准快照已包含的变化，再收到时丢弃
+    base_coverage: SeqCoverage,
+    rebuilds: u64,
+    too_late: u64,
+}
+
+impl Replica {
+    pub fn new(base: BoardState) -> Self {
+        Replica {
+            board: base.clone(),
+            checkpoints: vec![(0, base)],
+            log: Vec::new(),
+            floor: None,
+            folded: 0,
+            latest: i64::MIN,
+            coverage: SeqCoverage::default(),
+            base_coverage: SeqCoverage::default(),
+            rebuilds: 0,
+            too_late: 0,
+        }
+    }
+
+    /// 以白板服务的快照为基准；`buffered` 是发出请求后收到的变化，快照没有包含的按全序重新应用
+    pub fn from_snapshot(snapshot: BoardSnapshot, buffered: impl IntoIterator<Item = BoardChangeData>) -> Self {
+        let mut replica = Replica::new(BoardState::from_elements(snapshot.elements));
+        for change in snapshot.changes {
+            replica.apply(change);
+        }
+        replica.coverage = snapshot.covers.clone();
+        replica.base_coverage = snapshot.covers;
+        for change in buffered {
+            replica.apply(change);
+        }
+        replica
+    }
+
+    /// 应答快照请求：重排窗口的基准状态和窗口内的变化，接收端据此得到与本端相同的副本
+    pub fn snapshot(&self, request: &SnapshotRequest, source_id: &str) -> BoardSnapshot {
+        BoardSnapshot {
+            topic: request.topic.clone(),
+            request_id: request.request_id.clone(),
+            elements: self.checkpoints.first().map(|(_, base)| base.children.clone()).unwrap_or_default(),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            source_id: source_id.to_string(),
+            changes: self.log.iter().map(|(_, change)| change.clone()).collect(),
+            covers: self.coverage.clone(),
+        }
+    }
+
+    /// 当前白板
+    pub fn board(&self) -> &BoardState {
+        &self.board
+    }
+
+    /// 已收到的变化数（不含重复）
+    pub fn len(&self) -> usize {
+        self.folded + self.log.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// 因乱序到达而从检查点重放的次数
+    pub fn rebuilds(&self) -> u64 {
+        self.rebuilds
+    }
+
+    /// 排在重排窗口之前才到达而被丢弃的变化数，包括早已并入基准状态的重复消息
+    pub fn too_late(&self) -> u64 {
+        self.too_late
+    }
+
+    /// 当前白板包含的变化
+    pub fn coverage(&self) -> &SeqCoverage {
+        &self.coverage
+    }
+
+    /// 本端即将发布的变化：时间戳不晚于已收到的最新变化时推到它之后 1 微秒
+    pub fn stamp(&self, change: &mut BoardChangeData) {
+        if self.latest == i64::MIN || publish_time(change) > self.latest {
+            return;
+        }
+        if let Some(time) = chrono::DateTime::from_timestamp_micros(self.latest + 1) {
+            change.timestamp = time.to_rfc3339_opts(chrono::SecondsFormat::Micros, true);
+        }
+    }
+
+    /// 应用一条本地或远端的变化，重复、已在基准快照中或来得太晚的消息返回 false
+    pub fn apply(&mut self, change: BoardChangeData) -> bool {
+        if self.base_coverage.covers(&change) {
+            return false;
+        }
+        let key = OrderKey::of(&change);
+        if self.floor.as_ref().is_some_and(|floor| key <= *floor) {
+            self.too_late += 1;
+            return false;
+        }
+        let index = match self.log.binary_search_by(|(k, _)| k.cmp(&key)) {
+            Ok(_) => return false,
+            Err(index) => index,
+        };
+        self.latest = self.latest.max(key.time);
+        if let Some(seq) = change.seq {
+            self.coverage.insert(&change.source_id, seq);
+        }
+
+        if index == self.log.len() {
+            apply_change(&mut self.board, &change);
+            self.log.push((key, change));
+            self.checkpoint();
+        } else {
+            self.log.insert(index, (key, change));
+            self.rebuild(index);
+        }
+        self.fold();
+        true
+    }
+
+    /// 从 `from` 之前最近的检查点重放到末尾
+    fn rebuild(&mut self, from: usize) {
+        self.rebuilds += 1;
+        self.checkpoints.retain(|(applied, _)| *applied <= from);
+        let (start, board) = self.checkpoints.last().cloned().unwrap_or_default();
+        self.board = board;
+        for i in start..self.log.len() {
+            apply_change(&mut self.board, &self.log[i].1);
+            if (i + 1).is_multiple_of(CHECKPOINT_INTERVAL) && i + 1 > from {
+                self.checkpoints.push((i + 1, self.board.clone()));
+            }
+        }
+    }
+
+    fn checkpoint(&mut self) {
+        if self.log.len().is_multiple_of(CHECKPOINT_INTERVAL) {
+            self.checkpoints.push((self.log.len(), self.board.clone()));
+        }
+    }
+
+    /// 窗口之前的变化并入基准状态：窗口外最后一个检查点成为新的基准，更早的检查点和日志丢掉
+    fn fold(&mut self) {
+        if self.log.len() < REORDER_WINDOW + CHECKPOINT_INTERVAL {
+            return;
+        }
+        let limit = self.log.len() - REORDER_WINDOW;
+        let Some(position) = self.checkpoints.iter().rposition(|(applied, _)| *applied <= limit) else {
+            return;
+        };
+        let applied = self.checkpoints[position].0;
+        if applied == 0 {
+            return;
+        }
+        self.checkpoints.drain(..position);
+        for (index, _) in &mut self.checkpoints {
+            *index -= applied;
+        }
+        self.floor = self.log.drain(..applied).next_back().map(|(key, _)| key);
+        self.folded += applied;
+    }
+}
+
+/// 无效的操作直接跳过；各端跳过的操作相同，不影响一致性
+fn apply_change(board: &mut BoardState, change: &BoardChangeData) {
+    for op in &change.operations {
+        let _ = apply_operation(board, op);
+    }
+}
+
+fn apply_operation(board: &mut BoardState, op: &Operation) -> Result<(), String> {
+    let id = &op.node().id;
+    if matches!(op, Operation::Insert(_)) || id.is_empty() {
+        return board.apply(op);
+    }
+    if board.get(op.path()).is_some_and(|node| node.id == *id) {
+        return board.apply(op);
+    }
+
+    // 并发的插入、删除让路径偏移了，按 id 找回目标元素
+    let (path, _) = board.find(id).ok_or_else(|| format!("元素 {} 已不存在", id))?;
+    let retargeted = match op {
+        Operation::Remove(_) => Operation::remove(path, op.node().clone()),
+        _ => Operation::set(path, op.node().clone()),
+    };
+    board.apply(&retargeted)
+}
@@ -0,0 +1,133 @@
+//! 线上消息和 `.drawnix` 文件的 JSON Schema（draft 2020-12），由 Rust 类型生成，
+//! 供其他语言的程序对接 DDS 主题和白板文件。
+//!
+//! Schema 描述的是可以接受的输入：带默认值的字段可以省略。[`validate_message`] 先按 Schema 校验，
+//! 通过后再用 Rust 类型解析一次，保证通过校验的消息 Rust 端一定能处理。
+
+use crate::document::DrawnixDocument;
+use crate::types::*;
+use schemars::generate::SchemaSettings;
+use schemars::{json_schema, JsonSchema, Schema, SchemaGenerator};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::borrow::Cow;
+
+/// 三种操作靠 "type" 字段区分。用 if/then 而不是 oneOf，校验错误能指到具体字段
+impl JsonSchema for Operation {
+    fn schema_name() -> Cow<'static, str> {
+        "Operation".into()
+    }
+
+    fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+        let branch = |op_type: &str, schema: Schema| json_schema!({ "if": { "properties": { "type": { "const": op_type } } }, "then": schema });
+        json_schema!({
+            "description": "白板操作，按 type 区分插入、删除和更新",
+            "type": "object",
+            "required": ["type"],
+            "properties": { "type": { "enum": ["insert_node", "remove_node", "set_node"] } },
+            "allOf": [
+                branch("insert_node", generator.subschema_for::<InsertNodeOperation>()),
+                branch("remove_node", generator.subschema_for::<RemoveNodeOperation>()),
+                branch("set_node", generator.subschema_for::<SetNodeOperation>()),
+            ]
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SchemaKind {
+    BoardChange,
+    Operation,
+    InsertNode,
+    RemoveNode,
+    SetNode,
+    Element,
+    Document,
+    SnapshotRequest,
+    BoardSnapshot,
+}
+
+impl SchemaKind {
+    pub const ALL: [SchemaKind; 9] = [
+        SchemaKind::BoardChange,
+        SchemaKind::Operation,
+        SchemaKind::InsertNode,
+        SchemaKind::RemoveNode,
+        SchemaKind::SetNode,
+        SchemaKind::Element,
+        SchemaKind::Document,
+        SchemaKind::SnapshotRequest,
+        SchemaKind::BoardSnapshot,
+    ];
+
+    /// 命令行和 Schema 文件名中使用的名字
+    pub fn name(self) -> &'static str {
+        match self {
+            SchemaKind::BoardChange => "board-change",
+            SchemaKind::Operation => "operation",
+            SchemaKind::InsertNode => "insert-node",
+            SchemaKind::RemoveNode => "remove-node",
+            SchemaKind::SetNode => "set-node",
+            SchemaKind::Element => "element",
+            SchemaKind::Document => "document",
+            SchemaKind::SnapshotRequest => "snapshot-request",
+            SchemaKind::BoardSnapshot => "board-snapshot",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        SchemaKind::ALL.into_iter().find(|kind| kind.name() == name)
+    }
+
+    pub fn schema(self) -> Value {
+        match self {
+            SchemaKind::BoardChange => root_schema::<BoardChangeData>(),
+            SchemaKind::Operation => root_schema::<Operation>(),
+            SchemaKind::InsertNode => root_schema::<InsertNodeOperation>(),
+            SchemaKind::RemoveNode => root_schema::<RemoveNodeOperation>(),
+            SchemaKind::SetNode => root_schema::<SetNodeOperation>(),
+            SchemaKind::Element => root_schema::<PlaitElement>(),
+            SchemaKind::Document => root_schema::<DrawnixDocument>(),
+            SchemaKind::SnapshotRequest => root_schema::<SnapshotRequest>(),
+            SchemaKind::BoardSnapshot => root_schema::<BoardSnapshot>(),
+        }
+    }
+
+    /// 用对应的 Rust 类型解析
+    fn parse(self, message: &Value) -> Result<(), String> {
+        fn parse<T: DeserializeOwned>(message: &Value) -> Result<(), String> {
+            T::deserialize(message).map(|_| ()).map_err(|e| e.to_string())
+        }
+        match self {
+            SchemaKind::BoardChange => parse::<BoardChangeData>(message),
+            SchemaKind::Operation => parse::<Operation>(message),
+            SchemaKind::InsertNode => parse::<InsertNodeOperation>(message),
+            SchemaKind::RemoveNode => parse::<RemoveNodeOperation>(message),
+            SchemaKind::SetNode => parse::<SetNodeOperation>(message),
+            SchemaKind::Element => parse::<PlaitElement>(message),
+            SchemaKind::Document => parse::<DrawnixDocument>(message),
+            SchemaKind::SnapshotRequest => parse::<SnapshotRequest>(message),
+            SchemaKind::BoardSnapshot => parse::<BoardSnapshot>(message),
+        }
+    }
+}
+
+fn root_schema<T: JsonSchema>() -> Value {
+    SchemaSettings::draft2020_12().into_generator().into_root_schema_for::<T>().to_value()
+}
+
+/// 按 Schema 校验一条消息，返回每处错误的 JSON Pointer 位置和原因
+pub fn validate_message(kind: SchemaKind, message: &Value) -> Result<(), Vec<String>> {
+    let validator = jsonschema::validator_for(&kind.schema()).map_err(|e| vec![format!("生成的 Schema 无效: {}", e)])?;
+    let errors: Vec<String> = validator
+        .iter_errors(message)
+        .map(|e| {
+            let path = e.instance_path().to_string();
+            format!("{}: {}", if path.is_empty() { "/" } else { &path }, e)
+        })
+        .collect();
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+    kind.parse(message).map_err(|e| vec![format!("/: 符合 Schema 但无法解析: {}", e)])
+}
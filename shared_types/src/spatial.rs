@@ -0,0 +1,339 @@
+//! 空间索引：回答“这个点上 / 这个区域里有哪些元素”。
+//!
+//! 元素的范围按类型计算，与导出时的绘制一致：几何图形和图片按 `angle` 旋转，
+//! 连线按实际路线（含折线拐点、箭头和文字），自由画笔按笔迹，思维导图按布局后的每个节点。
+//! 范围存放在 R 树中，随操作增量维护：一次操作只重新计算它所在的顶层元素。
+//! 顶层元素按位置而不是 id 区分，id 重复的元素各自有独立的条目。
+//! 导出时按区域筛选元素（`svg::render_svg` 的 `region`）使用这个索引。
+
+use crate::board::BoardState;
+use crate::geometry::Rect;
+use crate::svg::{line_labels, line_route, mind_map_nodes, stroke_width};
+use crate::types::*;
+use rstar::{RTree, RTreeObject, AABB};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// 命中测试用的轮廓
+#[derive(Debug, Clone, PartialEq)]
+enum Outline {
+    /// 矩形绕中心旋转 angle（弧度）
+    Box { rect: Rect, angle: f64 },
+    /// 折线，到折线的距离不超过 radius 算命中
+    Path { points: Vec<Point>, radius: f64 },
+}
+
+impl Outline {
+    fn bounds(&self) -> Option<Rect> {
+        match self {
+            Outline::Box { rect, angle } => Rect::from_points(&corners(rect, *angle)),
+            Outline::Path { points, radius } => Rect::from_points(points).map(|r| r.expand(*radius)),
+        }
+    }
+
+    fn hit(&self, point: &Point, tolerance: f64) -> bool {
+        match self {
+            Outline::Box { rect, angle } => {
+                // 把点反向旋转回矩形的坐标系
+                let center = center(rect);
+                rect.expand(tolerance).contains(&rotate(point, &center, -angle))
+            }
+            Outline::Path { points, radius } => match points.as_slice() {
+                [only] => distance(point, only) <= radius + tolerance,
+                _ => points.windows(2).any(|w| segment_distance(point, &w[0], &w[1]) <= radius + tolerance),
+            },
+        }
+    }
+
+    fn intersects(&self, area: &Rect) -> bool {
+        match self {
+            Outline::Box { rect, angle } => {
+                let mut polygon = corners(rect, *angle);
+                polygon.push(polygon[0].clone());
+                polygon.windows(2).any(|w| segment_intersects_rect(&w[0], &w[1], area)) || self.hit(&center(area), 0.0)
+            }
+            Outline::Path { points, radius } => {
+                let area = area.expand(*radius);
+                match points.as_slice() {
+                    [only] => area.contains(only),
+                    _ => points.windows(2).any(|w| segment_intersects_rect(&w[0], &w[1], &area)),
+                }
+            }
+        }
+    }
+}
+
+/// R 树中的一项：一个元素（思维导图中的一个节点）
+#[derive(Debug, Clone, PartialEq)]
+struct Entry {
+    id: String,
+    /// 所属顶层元素的槽位
+    slot: u64,
+    /// 在所属顶层元素内的绘制顺序
+    layer: usize,
+    bounds: Rect,
+    outlines: Vec<Outline>,
+}
+
+impl RTreeObject for Entry {
+    type Envelope = AABB<[f64; 2]>;
+
+    fn envelope(&self) -> Self::Envelope {
+        AABB::from_corners([self.bounds.min_x, self.bounds.min_y], [self.bounds.max_x, self.bounds.max_y])
+    }
+}
+
+/// 带空间索引的白板。查询结果按绘制顺序排列，最上层的在前
+#[derive(Debug, Clone)]
+pub struct SpatialIndex {
+    board: BoardState,
+    tree: RTree<Entry>,
+    /// 与 `board.children` 一一对应的槽位，插入、删除顶层元素时随之移动
+    slots: Vec<u64>,
+    next_slot: u64,
+    /// 槽位 → 该顶层元素及其子节点的条目
+    entries: HashMap<u64, Vec<Entry>>,
+    /// 槽位 → 在白板中的下标
+    order: HashMap<u64, usize>,
+    /// 元素 id → (槽位, 范围)；id 重复时有多项
+    by_id: HashMap<String, Vec<(u64, Rect)>>,
+}
+
+impl SpatialIndex {
+    pub fn new(board: BoardState) -> Self {
+        let slots: Vec<u64> = (0..board.children.len() as u64).collect();
+        let entries: HashMap<u64, Vec<Entry>> = board.children.iter().zip(&slots).map(|(e, &slot)| (slot, entries_of(e, slot))).collect();
+        let tree = RTree::bulk_load(entries.values().flatten().cloned().collect());
+        let mut index = SpatialIndex {
+            board,
+            tree,
+            next_slot: slots.len() as u64,
+            slots,
+            entries: HashMap::new(),
+            order: HashMap::new(),
+            by_id: HashMap::new(),
+        };
+        for (slot, entries) in entries {
+            index.remember(slot, entries);
+        }
+        index.reorder_from(0);
+        index
+    }
+
+    pub fn board(&self) -> &BoardState {
+        &self.board
+    }
+
+    pub fn into_board(self) -> BoardState {
+        self.board
+    }
+
+    /// 应用操作并更新索引，操作失败时白板和索引都不变
+    pub fn apply(&mut self, op: &Operation) -> Result<(), String> {
+        let path = op.path();
+        let top = *path.first().ok_or_else(|| "操作路径为空".to_string())?;
+        self.board.apply(op)?;
+
+        // 插入顶层元素时原来在这个位置的元素只是后移，范围不变
+        let slot = match op {
+            Operation::Insert(_) if path.len() == 1 => {
+                self.next_slot += 1;
+                self.slots.insert(top, self.next_slot);
+                self.reorder_from(top);
+                self.next_slot
+            }
+            Operation::Remove(_) if path.len() == 1 => {
+                let slot = self.slots.remove(top);
+                self.unindex(slot);
+                self.order.remove(&slot);
+                self.reorder_from(top);
+                return Ok(());
+            }
+            _ => {
+                let slot = self.slots[top];
+                self.unindex(slot);
+                slot
+            }
+        };
+        let entries = entries_of(&self.board.children[top], slot);
+        for entry in &entries {
+            self.tree.insert(entry.clone());
+        }
+        self.remember(slot, entries);
+        Ok(())
+    }
+
+    /// 依次应用一批操作，返回失败操作的错误
+    pub fn apply_all(&mut self, ops: &[Operation]) -> Vec<String> {
+        ops.iter().filter_map(|op| self.apply(op).err()).collect()
+    }
+
+    /// 元素在画布上的范围，思维导图的子节点也可以查询；id 重复时取绘制顺序最靠前的一个
+    pub fn bounds(&self, id: &str) -> Option<Rect> {
+        self.by_id.get(id)?.iter().min_by_key(|(slot, _)| self.order.get(slot)).map(|(_, rect)| *rect)
+    }
+
+    /// 点上的元素，tolerance 为允许的误差（画布单位），便于点中细线
+    pub fn at_point(&self, point: &Point, tolerance: f64) -> Vec<&str> {
+        let area = AABB::from_corners([point.0 - tolerance, point.1 - tolerance], [point.0 + tolerance, point.1 + tolerance]);
+        self.sorted(self.tree.locate_in_envelope_intersecting(&area).filter(|entry| entry.outlines.iter().any(|o| o.hit(point, tolerance))))
+    }
+
+    /// 与区域有重叠的元素
+    pub fn intersecting(&self, area: &Rect) -> Vec<&str> {
+        self.sorted(self.tree.locate_in_envelope_intersecting(&envelope(area)).filter(|entry| entry.outlines.iter().any(|o| o.intersects(area))))
+    }
+
+    /// 完全落在区域内的元素
+    pub fn within(&self, area: &Rect) -> Vec<&str> {
+        self.sorted(self.tree.locate_in_envelope(&envelope(area)))
+    }
+
+    /// 自身或子节点与区域有重叠的顶层元素在白板中的下标，从小到大
+    pub fn top_level_intersecting(&self, area: &Rect) -> Vec<usize> {
+        let mut indices: Vec<usize> = self
+            .tree
+            .locate_in_envelope_intersecting(&envelope(area))
+            .filter(|entry| entry.outlines.iter().any(|o| o.intersects(area)))
+            .filter_map(|entry| self.order.get(&entry.slot).copied())
+            .collect();
+        indices.sort_unstable();
+        indices.dedup();
+        indices
+    }
+
+    fn sorted<'a>(&self, entries: impl Iterator<Item = &'a Entry>) -> Vec<&'a str> {
+        let mut entries: Vec<&Entry> = entries.collect();
+        entries.sort_by_key(|entry| std::cmp::Reverse((self.order.get(&entry.slot).copied().unwrap_or(0), entry.layer)));
+        entries.into_iter().map(|entry| entry.id.as_str()).collect()
+    }
+
+    fn remember(&mut self, slot: u64, entries: Vec<Entry>) {
+        for entry in &entries {
+            self.by_id.entry(entry.id.clone()).or_default().push((slot, entry.bounds));
+        }
+        self.entries.insert(slot, entries);
+    }
+
+    fn unindex(&mut self, slot: u64) {
+        for entry in self.entries.remove(&slot).unwrap_or_default() {
+            self.tree.remove(&entry);
+            if let Some(bounds) = self.by_id.get_mut(&entry.id) {
+                bounds.retain(|(s, _)| *s != slot);
+                if bounds.is_empty() {
+                    self.by_id.remove(&entry.id);
+                }
+            }
+        }
+    }
+
+    /// 更新 start 及之后槽位的下标，前面的元素不受插入、删除影响
+    fn reorder_from(&mut self, start: usize) {
+        for (i, &slot) in self.slots.iter().enumerate().skip(start) {
+            self.order.insert(slot, i);
+        }
+    }
+}
+
+/// 元素（含子节点）在画布上的范围，没有坐标时返回 None
+pub fn element_bounds(element: &PlaitElement) -> Option<Rect> {
+    entries_of(element, 0).iter().map(|entry| entry.bounds).reduce(|a, b| a.union(&b))
+}
+
+/// 顶层元素及其子节点的条目，按绘制顺序排列
+fn entries_of(top: &PlaitElement, slot: u64) -> Vec<Entry> {
+    let mut outlines = Vec::new();
+    if matches!(top.element_type.as_str(), "mindmap" | "mind_map") {
+        outlines = mind_map_nodes(top).into_iter().map(|(id, rect)| (id, vec![Outline::Box { rect, angle: 0.0 }])).collect();
+    } else {
+        fn walk(element: &PlaitElement, out: &mut Vec<(String, Vec<Outline>)>) {
+            out.push((element.id.clone(), outlines_of(element)));
+            for child in element.children.as_deref().unwrap_or_default() {
+                walk(child, out);
+            }
+        }
+        walk(top, &mut outlines);
+    }
+
+    outlines
+        .into_iter()
+        .enumerate()
+        .filter_map(|(layer, (id, outlines))| {
+            let bounds = outlines.iter().filter_map(Outline::bounds).reduce(|a, b| a.union(&b))?;
+            Some(Entry { id, slot, layer, bounds, outlines })
+        })
+        .collect()
+}
+
+fn outlines_of(element: &PlaitElement) -> Vec<Outline> {
+    match element.element_type.as_str() {
+        "line" | "arrow-line" => {
+            let route = line_route(element);
+            if route.is_empty() {
+                return Vec::new();
+            }
+            let labels: Vec<Outline> = line_labels(element, &route).into_iter().map(|(rect, _, _)| Outline::Box { rect, angle: 0.0 }).collect();
+            // 与导出一致，留出箭头的宽度
+            let mut outlines = vec![Outline::Path { points: route, radius: stroke_width(element) * 3.0 }];
+            outlines.extend(labels);
+            outlines
+        }
+        "freehand" if !element.points.is_empty() => vec![Outline::Path { points: element.points.clone(), radius: stroke_width(element) }],
+        _ => {
+            let angle = element.extra.get("angle").and_then(Value::as_f64).unwrap_or(0.0);
+            element.bounds().map(|rect| Outline::Box { rect, angle }).into_iter().collect()
+        }
+    }
+}
+
+fn envelope(rect: &Rect) -> AABB<[f64; 2]> {
+    AABB::from_corners([rect.min_x, rect.min_y], [rect.max_x, rect.max_y])
+}
+
+fn center(rect: &Rect) -> Point {
+    Point((rect.min_x + rect.max_x) / 2.0, (rect.min_y + rect.max_y) / 2.0)
+}
+
+fn rotate(point: &Point, center: &Point, angle: f64) -> Point {
+    let (sin, cos) = angle.sin_cos();
+    let (dx, dy) = (point.0 - center.0, point.1 - center.1);
+    Point(center.0 + dx * cos - dy * sin, center.1 + dx * sin + dy * cos)
+}
+
+/// 旋转后的四个角，顺时针排列
+fn corners(rect: &Rect, angle: f64) -> Vec<Point> {
+    let center = center(rect);
+    [(rect.min_x, rect.min_y), (rect.max_x, rect.min_y), (rect.max_x, rect.max_y), (rect.min_x, rect.max_y)]
+        .into_iter()
+        .map(|(x, y)| rotate(&Point(x, y), &center, angle))
+        .collect()
+}
+
+fn distance(a: &Point, b: &Point) -> f64 {
+    (a.0 - b.0).hypot(a.1 - b.1)
+}
+
+fn segment_distance(point: &Point, a: &Point, b: &Point) -> f64 {
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let length = dx * dx + dy * dy;
+    if length == 0.0 {
+        return distance(point, a);
+    }
+    let t = (((point.0 - a.0) * dx + (point.1 - a.1) * dy) / length).clamp(0.0, 1.0);
+    distance(point, &Point(a.0 + dx * t, a.1 + dy * t))
+}
+
+fn segment_intersects_rect(a: &Point, b: &Point, rect: &Rect) -> bool {
+    if rect.contains(a) || rect.contains(b) {
+        return true;
+    }
+    let corners = corners(rect, 0.0);
+    (0..4).any(|i| segments_cross(a, b, &corners[i], &corners[(i + 1) % 4]))
+}
+
+fn segments_cross(a: &Point, b: &Point, c: &Point, d: &Point) -> bool {
+    let cross = |o: &Point, p: &Point, q: &Point| (p.0 - o.0) * (q.1 - o.1) - (p.1 - o.1) * (q.0 - o.0);
+    let (d1, d2) = (cross(c, d, a), cross(c, d, b));
+    let (d3, d4) = (cross(a, b, c), cross(a, b, d));
+    d1 * d2 <= 0.0 && d3 * d4 <= 0.0 && (d1 != 0.0 || d2 != 0.0 || d3 != 0.0 || d4 != 0.0)
+}
@@ -0,0 +1,640 @@
+//! 把白板元素渲染成独立的 SVG，用于文档和 CI 中生成图片，不需要打开桌面端。
+//!
+//! 样式尽量贴近前端的默认外观：几何图形（含流程图的 terminal、process、decision 等）、
+//! 连线（直线、曲线、折线及箭头和线上文字）、自由画笔、图片和思维导图。
+//! 思维导图只有根节点带坐标，子节点在这里按前端的逻辑图布局重新排布。
+
+use crate::board::BoardState;
+use crate::geometry::Rect;
+use crate::spatial::SpatialIndex;
+use crate::types::*;
+use serde_json::Value;
+use std::fmt::Write;
+
+const DEFAULT_STROKE: &str = "#333333";
+const DEFAULT_TEXT_COLOR: &str = "#333333";
+const FONT_FAMILY: &str = "PingFang SC, Microsoft YaHei, Noto Sans CJK SC, sans-serif";
+const FONT_SIZE: f64 = 14.0;
+const LINE_HEIGHT: f64 = 20.0;
+
+/// 思维导图布局参数与分支配色
+const MIND_H_GAP: f64 = 40.0;
+const MIND_V_GAP: f64 = 16.0;
+const BRANCH_COLORS: [&str; 6] = ["#A04AFB", "#6060FF", "#06ADBF", "#F2A90E", "#E0423B", "#2DBD6E"];
+
+#[derive(Debug, Clone)]
+pub struct SvgOptions {
+    /// 内容四周的留白（像素）
+    pub padding: f64,
+    /// 背景色，None 表示透明
+    pub background: Option<String>,
+    /// 只导出画布上的这块区域（画布坐标），None 表示整块白板
+    pub region: Option<Rect>,
+    /// 只导出这些 id 的顶层元素，None 表示全部
+    pub selection: Option<Vec<String>>,
+}
+
+impl Default for SvgOptions {
+    fn default() -> Self {
+        SvgOptions { padding: 20.0, background: Some("#ffffff".into()), region: None, selection: None }
+    }
+}
+
+/// 渲染结果：SVG 文本及其画布区域（画布坐标）
+#[derive(Debug, Clone)]
+pub struct SvgImage {
+    pub svg: String,
+    pub view_box: Rect,
+}
+
+/// 渲染一组白板元素
+pub fn render_svg(elements: &[PlaitElement], options: &SvgOptions) -> SvgImage {
+    // 指定区域时只绘制与区域（含留白）有重叠的元素，大白板导出局部时输出也小
+    let visible: Option<Vec<usize>> = options.region.map(|region| {
+        let index = SpatialIndex::new(BoardState::from_elements(elements.to_vec()));
+        index.top_level_intersecting(&region.expand(options.padding))
+    });
+    let mut canvas = Canvas::default();
+    for (i, element) in elements.iter().enumerate() {
+        let in_region = visible.as_ref().is_none_or(|visible| visible.binary_search(&i).is_ok());
+        if in_region && options.selection.as_ref().is_none_or(|ids| ids.contains(&element.id)) {
+            canvas.element(element);
+        }
+    }
+
+    // 指定区域时按区域裁剪，区域外的内容由 viewBox 截掉
+    let content = options
+        .region
+        .or(canvas.bounds)
+        .unwrap_or(Rect { min_x: 0.0, min_y: 0.0, max_x: 0.0, max_y: 0.0 });
+    let view_box = Rect {
+        min_x: content.min_x - options.padding,
+        min_y: content.min_y - options.padding,
+        max_x: content.max_x + options.padding,
+        max_y: content.max_y + options.padding,
+    };
+
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="{x} {y} {w} {h}" font-family="{font}" font-size="{size}">"#,
+        x = num(view_box.min_x),
+        y = num(view_box.min_y),
+        w = num(view_box.width()),
+        h = num(view_box.height()),
+        font = FONT_FAMILY,
+        size = num(FONT_SIZE)
+    );
+    if let Some(background) = &options.background {
+        let _ = writeln!(
+            svg,
+            r#"  <rect x="{}" y="{}" width="{}" height="{}" fill="{}"/>"#,
+            num(view_box.min_x),
+            num(view_box.min_y),
+            num(view_box.width()),
+            num(view_box.height()),
+            escape(background)
+        );
+    }
+    svg.push_str(&canvas.body);
+    svg.push_str("</svg>\n");
+    SvgImage { svg, view_box }
+}
+
+/// 逐个元素输出 SVG 片段，同时累计内容的包围盒
+#[derive(Default)]
+struct Canvas {
+    body: String,
+    bounds: Option<Rect>,
+}
+
+impl Canvas {
+    fn include(&mut self, rect: Rect) {
+        self.bounds = Some(match self.bounds {
+            Some(b) => b.union(&rect),
+            None => rect,
+        });
+    }
+
+    fn include_points(&mut self, points: &[Point], margin: f64) {
+        if let Some(r) = Rect::from_points(points) {
+            self.include(Rect { min_x: r.min_x - margin, min_y: r.min_y - margin, max_x: r.max_x + margin, max_y: r.max_y + margin });
+        }
+    }
+
+    fn element(&mut self, element: &PlaitElement) {
+        match element.element_type.as_str() {
+            "geometry" => self.geometry(element),
+            "line" | "arrow-line" => self.line(element),
+            "freehand" => self.freehand(element),
+            "image" => self.image(element),
+            "mindmap" | "mind_map" => self.mind_map(element),
+            _ => {}
+        }
+    }
+
+    fn geometry(&mut self, element: &PlaitElement) {
+        let Some(rect) = element.bounds() else { return };
+        let style = Style::of(element);
+        let (x, y, w, h) = (rect.min_x, rect.min_y, rect.width(), rect.height());
+        let (cx, cy) = (x + w / 2.0, y + h / 2.0);
+
+        let angle = number(element, "angle").unwrap_or(0.0);
+        let group = if angle != 0.0 {
+            format!(r#" transform="rotate({} {} {})""#, num(angle.to_degrees()), num(cx), num(cy))
+        } else {
+            String::new()
+        };
+        let _ = writeln!(self.body, r#"  <g data-id="{}"{}{}>"#, escape(&element.id), group, style.opacity());
+
+        let paint = style.paint();
+        let outline = match element.shape.as_str() {
+            "text" => None,
+            "ellipse" => Some(format!(r#"<ellipse cx="{}" cy="{}" rx="{}" ry="{}"{}/>"#, num(cx), num(cy), num(w / 2.0), num(h / 2.0), paint)),
+            "terminal" => Some(rounded_rect(x, y, w, h, h.min(w) / 2.0, &paint)),
+            "roundRectangle" => Some(rounded_rect(x, y, w, h, w.min(h) * 0.1, &paint)),
+            "decision" | "diamond" => Some(polygon(&[(cx, y), (x + w, cy), (cx, y + h), (x, cy)], &paint)),
+            "parallelogram" | "data" => {
+                let d = w * 0.15;
+                Some(polygon(&[(x + d, y), (x + w, y), (x + w - d, y + h), (x, y + h)], &paint))
+            }
+            "trapezoid" | "manualOperation" => {
+                let d = w * 0.15;
+                Some(polygon(&[(x + d, y), (x + w - d, y), (x + w, y + h), (x, y + h)], &paint))
+            }
+            "triangle" => Some(polygon(&[(cx, y), (x + w, y + h), (x, y + h)], &paint)),
+            "hexagon" | "preparation" => {
+                let d = w * 0.15;
+                Some(polygon(&[(x + d, y), (x + w - d, y), (x + w, cy), (x + w - d, y + h), (x + d, y + h), (x, cy)], &paint))
+            }
+            _ => Some(format!(r#"<rect x="{}" y="{}" width="{}" height="{}"{}/>"#, num(x), num(y), num(w), num(h), paint)),
+        };
+        if let Some(outline) = outline {
+            let _ = writeln!(self.body, "    {}", outline);
+        }
+
+        if let Some(text) = element.extra.get("text") {
+            let align = text.get("align").and_then(Value::as_str).unwrap_or("center");
+            self.text_block(&plain_text(text), rect, align, &text_color(text));
+        }
+        self.body.push_str("  </g>\n");
+        self.include(rect);
+    }
+
+    /// 在矩形内按对齐方式绘制多行文字，垂直居中
+    fn text_block(&mut self, text: &str, rect: Rect, align: &str, color: &str) {
+        if text.trim().is_empty() {
+            return;
+        }
+        let lines: Vec<&str> = text.lines().collect();
+        let (anchor, x) = match align {
+            "left" => ("start", rect.min_x + 4.0),
+            "right" => ("end", rect.max_x - 4.0),
+            _ => ("middle", (rect.min_x + rect.max_x) / 2.0),
+        };
+        let first = (rect.min_y + rect.max_y) / 2.0 - (lines.len() as f64 - 1.0) * LINE_HEIGHT / 2.0;
+        let _ = write!(self.body, r#"    <text x="{}" text-anchor="{}" fill="{}">"#, num(x), anchor, escape(color));
+        for (i, line) in lines.iter().enumerate() {
+            // 没有 dominant-baseline 时按字号估算基线，各种渲染器结果一致
+            let baseline = first + i as f64 * LINE_HEIGHT + FONT_SIZE * 0.35;
+            let _ = write!(self.body, r#"<tspan x="{}" y="{}">{}</tspan>"#, num(x), num(baseline), escape(line));
+        }
+        self.body.push_str("</text>\n");
+    }
+
+    fn line(&mut self, element: &PlaitElement) {
+        let route = line_route(element);
+        if route.len() < 2 {
+            return;
+        }
+        let style = Style::of(element);
+        let d = match element.shape.as_str() {
+            "curve" => curve_path(&route),
+            _ => polyline_path(&route),
+        };
+        let _ = writeln!(self.body, r#"  <g data-id="{}"{}>"#, escape(&element.id), style.opacity());
+        let _ = writeln!(
+            self.body,
+            r#"    <path d="{}" fill="none" stroke="{}" stroke-width="{}" stroke-linejoin="round"{}/>"#,
+            d,
+            escape(&style.stroke),
+            num(style.stroke_width),
+            style.dash()
+        );
+
+        for (end, tip, from) in [("source", &route[0], &route[1]), ("target", &route[route.len() - 1], &route[route.len() - 2])] {
+            let marker = element.extra.get(end).and_then(|e| e.get("marker")).and_then(Value::as_str).unwrap_or("none");
+            if marker != "none" {
+                let _ = writeln!(self.body, "    {}", arrow_head(tip, from, style.stroke_width, &style.stroke));
+            }
+        }
+
+        for (rect, content, text) in line_labels(element, &route) {
+            let _ = writeln!(
+                self.body,
+                r##"    <rect x="{}" y="{}" width="{}" height="{}" fill="#ffffff"/>"##,
+                num(rect.min_x),
+                num(rect.min_y),
+                num(rect.width()),
+                num(rect.height())
+            );
+            self.text_block(&content, rect, "center", &text_color(text));
+            self.include(rect);
+        }
+        self.body.push_str("  </g>\n");
+        self.include_points(&route, style.stroke_width * 3.0);
+    }
+
+    fn freehand(&mut self, element: &PlaitElement) {
+        if element.points.is_empty() {
+            return;
+        }
+        let style = Style::of(element);
+        let _ = writeln!(
+            self.body,
+            r#"  <path data-id="{}" d="{}" fill="none" stroke="{}" stroke-width="{}" stroke-linecap="round" stroke-linejoin="round"{}/>"#,
+            escape(&element.id),
+            polyline_path(&element.points),
+            escape(&style.stroke),
+            num(style.stroke_width),
+            style.opacity()
+        );
+        self.include_points(&element.points, style.stroke_width);
+    }
+
+    fn image(&mut self, element: &PlaitElement) {
+        let Some(rect) = element.bounds() else { return };
+        let Some(url) = element.extra.get("url").and_then(Value::as_str) else { return };
+        let _ = writeln!(
+            self.body,
+            r#"  <image data-id="{}" href="{}" x="{}" y="{}" width="{}" height="{}" preserveAspectRatio="none"/>"#,
+            escape(&element.id),
+            escape(url),
+            num(rect.min_x),
+            num(rect.min_y),
+            num(rect.width()),
+            num(rect.height())
+        );
+        self.include(rect);
+    }
+
+    fn mind_map(&mut self, root: &PlaitElement) {
+        let Some(origin) = root.points.first() else { return };
+        let layout = layout_mind_map(root, Point(origin.0, origin.1));
+
+        let _ = writeln!(self.body, r#"  <g data-id="{}">"#, escape(&root.id));
+        for edge in &layout.edges {
+            let mid = (edge.from.0 + edge.to.0) / 2.0;
+            let _ = writeln!(
+                self.body,
+                r#"    <path d="M {} {} C {} {}, {} {}, {} {}" fill="none" stroke="{}" stroke-width="{}"/>"#,
+                num(edge.from.0),
+                num(edge.from.1),
+                num(mid),
+                num(edge.from.1),
+                num(mid),
+                num(edge.to.1),
+                num(edge.to.0),
+                num(edge.to.1),
+                edge.color,
+                if edge.depth == 0 { 3 } else { 2 }
+            );
+        }
+        for node in &layout.nodes {
+            let r = node.rect;
+            if node.depth == 0 {
+                let _ = writeln!(self.body, "    {}", rounded_rect(r.min_x, r.min_y, r.width(), r.height(), 6.0, r##" fill="#F5F5F5" stroke="#333333" stroke-width="2""##));
+            } else {
+                let _ = writeln!(
+                    self.body,
+                    "    {}",
+                    rounded_rect(r.min_x, r.min_y, r.width(), r.height(), 4.0, &format!(r##" fill="#ffffff" stroke="{}" stroke-width="2""##, node.color))
+                );
+            }
+            self.text_block(&node.text, r, "center", DEFAULT_TEXT_COLOR);
+            self.include(r);
+        }
+        self.body.push_str("  </g>\n");
+    }
+}
+
+/// 连线上的文字：所占区域、纯文本和原始富文本，空文字跳过
+pub(crate) fn line_labels<'a>(element: &'a PlaitElement, route: &[Point]) -> Vec<(Rect, String, &'a Value)> {
+    let Some(Value::Array(texts)) = element.extra.get("texts") else { return Vec::new() };
+    texts
+        .iter()
+        .filter_map(|label| {
+            let text = label.get("text")?;
+            let content = plain_text(text);
+            if content.trim().is_empty() {
+                return None;
+            }
+            let position = label.get("position").and_then(Value::as_f64).unwrap_or(0.5);
+            let at = point_along(route, position);
+            let width = label.get("width").and_then(Value::as_f64).unwrap_or(content.chars().count() as f64 * FONT_SIZE);
+            let height = label.get("height").and_then(Value::as_f64).unwrap_or(LINE_HEIGHT);
+            let rect = Rect { min_x: at.0 - width / 2.0 - 2.0, min_y: at.1 - height / 2.0, max_x: at.0 + width / 2.0 + 2.0, max_y: at.1 + height / 2.0 };
+            Some((rect, content, text))
+        })
+        .collect()
+}
+
+/// 描边、填充等公共样式
+struct Style {
+    stroke: String,
+    fill: String,
+    stroke_width: f64,
+    stroke_style: String,
+    opacity: f64,
+}
+
+impl Style {
+    fn of(element: &PlaitElement) -> Self {
+        let text = |key: &str| element.extra.get(key).and_then(Value::as_str).map(String::from);
+        Style {
+            stroke: text("strokeColor").unwrap_or_else(|| DEFAULT_STROKE.into()),
+            fill: text("fill").unwrap_or_else(|| "none".into()),
+            stroke_width: stroke_width(element),
+            stroke_style: text("strokeStyle").unwrap_or_default(),
+            opacity: number(element, "opacity").unwrap_or(1.0),
+        }
+    }
+
+    fn paint(&self) -> String {
+        format!(r#" fill="{}" stroke="{}" stroke-width="{}"{}"#, escape(&self.fill), escape(&self.stroke), num(self.stroke_width), self.dash())
+    }
+
+    fn dash(&self) -> String {
+        match self.stroke_style.as_str() {
+            "dashed" => format!(r#" stroke-dasharray="{} {}""#, num(self.stroke_width * 4.0), num(self.stroke_width * 2.0)),
+            "dotted" => format!(r#" stroke-dasharray="{} {}""#, num(self.stroke_width), num(self.stroke_width * 2.0)),
+            _ => String::new(),
+        }
+    }
+
+    fn opacity(&self) -> String {
+        if self.opacity < 1.0 {
+            format!(r#" opacity="{}""#, num(self.opacity))
+        } else {
+            String::new()
+        }
+    }
+}
+
+pub(crate) fn stroke_width(element: &PlaitElement) -> f64 {
+    number(element, "strokeWidth").unwrap_or(2.0)
+}
+
+fn number(element: &PlaitElement, key: &str) -> Option<f64> {
+    element.extra.get(key).and_then(Value::as_f64)
+}
+
+/// 富文本（Slate 结构）中的纯文本，段落之间换行
+pub fn plain_text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Object(map) => {
+            if let Some(Value::String(text)) = map.get("text") {
+                return text.clone();
+            }
+            match map.get("children") {
+                Some(Value::Array(children)) => {
+                    let block = children.iter().any(|c| c.get("children").is_some());
+                    children.iter().map(plain_text).collect::<Vec<_>>().join(if block { "\n" } else { "" })
+                }
+                _ => map.get("text").map(plain_text).unwrap_or_default(),
+            }
+        }
+        _ => String::new(),
+    }
+}
+
+/// 文字颜色取第一个带 color 的文本片段
+fn text_color(value: &Value) -> String {
+    fn find(value: &Value) -> Option<String> {
+        if let Some(color) = value.get("color").and_then(Value::as_str) {
+            return Some(color.to_string());
+        }
+        value.get("children")?.as_array()?.iter().find_map(find)
+    }
+    find(value).unwrap_or_else(|| DEFAULT_TEXT_COLOR.into())
+}
+
+/// 连线实际经过的点：折线按两端的连接方向补出拐点
+pub(crate) fn line_route(element: &PlaitElement) -> Vec<Point> {
+    let points = &element.points;
+    if element.shape != "elbow" || points.len() != 2 {
+        return points.clone();
+    }
+    let (start, end) = (&points[0], &points[1]);
+    let horizontal = |end: &str| {
+        element
+            .extra
+            .get(end)
+            .and_then(|e| e.get("connection"))
+            .and_then(Value::as_array)
+            .and_then(|c| c.first())
+            .and_then(Value::as_f64)
+            .map(|x| x == 0.0 || x == 1.0)
+    };
+    let source_horizontal = horizontal("source").unwrap_or((end.0 - start.0).abs() >= (end.1 - start.1).abs());
+    let target_horizontal = horizontal("target").unwrap_or(source_horizontal);
+
+    let mut route = vec![start.clone()];
+    match (source_horizontal, target_horizontal) {
+        (true, true) => {
+            let mid = (start.0 + end.0) / 2.0;
+            route.push(Point(mid, start.1));
+            route.push(Point(mid, end.1));
+        }
+        (false, false) => {
+            let mid = (start.1 + end.1) / 2.0;
+            route.push(Point(start.0, mid));
+            route.push(Point(end.0, mid));
+        }
+        (true, false) => route.push(Point(end.0, start.1)),
+        (false, true) => route.push(Point(start.0, end.1)),
+    }
+    route.push(end.clone());
+    route.dedup_by(|a, b| (a.0 - b.0).abs() < 0.01 && (a.1 - b.1).abs() < 0.01);
+    route
+}
+
+fn polyline_path(points: &[Point]) -> String {
+    let mut d = String::new();
+    for (i, p) in points.iter().enumerate() {
+        let _ = write!(d, "{}{} {}", if i == 0 { "M " } else { " L " }, num(p.0), num(p.1));
+    }
+    d
+}
+
+/// 经过所有点的平滑曲线（Catmull-Rom 转三次贝塞尔）
+fn curve_path(points: &[Point]) -> String {
+    if points.len() < 3 {
+        return polyline_path(points);
+    }
+    let mut d = format!("M {} {}", num(points[0].0), num(points[0].1));
+    for i in 0..points.len() - 1 {
+        let p0 = &points[i.saturating_sub(1)];
+        let p1 = &points[i];
+        let p2 = &points[i + 1];
+        let p3 = &points[(i + 2).min(points.len() - 1)];
+        let c1 = (p1.0 + (p2.0 - p0.0) / 6.0, p1.1 + (p2.1 - p0.1) / 6.0);
+        let c2 = (p2.0 - (p3.0 - p1.0) / 6.0, p2.1 - (p3.1 - p1.1) / 6.0);
+        let _ = write!(d, " C {} {}, {} {}, {} {}", num(c1.0), num(c1.1), num(c2.0), num(c2.1), num(p2.0), num(p2.1));
+    }
+    d
+}
+
+/// 折线上按长度比例取点
+fn point_along(points: &[Point], ratio: f64) -> Point {
+    let lengths: Vec<f64> = points.windows(2).map(|w| ((w[1].0 - w[0].0).powi(2) + (w[1].1 - w[0].1).powi(2)).sqrt()).collect();
+    let mut remaining = lengths.iter().sum::<f64>() * ratio.clamp(0.0, 1.0);
+    for (w, length) in points.windows(2).zip(&lengths) {
+        if remaining <= *length && *length > 0.0 {
+            let t = remaining / length;
+            return Point(w[0].0 + (w[1].0 - w[0].0) * t, w[0].1 + (w[1].1 - w[0].1) * t);
+        }
+        remaining -= length;
+    }
+    points[points.len() - 1].clone()
+}
+
+/// 箭头画成实心三角形而不是 marker，各种渲染器（含 PNG、PDF 导出）都能正确显示
+fn arrow_head(tip: &Point, from: &Point, stroke_width: f64, color: &str) -> String {
+    let (dx, dy) = (tip.0 - from.0, tip.1 - from.1);
+    let length = (dx * dx + dy * dy).sqrt().max(0.001);
+    let (ux, uy) = (dx / length, dy / length);
+    let size = 6.0 + stroke_width * 2.0;
+    let base = (tip.0 - ux * size, tip.1 - uy * size);
+    let half = size * 0.5;
+    polygon(
+        &[(tip.0, tip.1), (base.0 - uy * half, base.1 + ux * half), (base.0 + uy * half, base.1 - ux * half)],
+        &format!(r#" fill="{}" stroke="none""#, escape(color)),
+    )
+}
+
+fn polygon(points: &[(f64, f64)], paint: &str) -> String {
+    let points: Vec<String> = points.iter().map(|(x, y)| format!("{},{}", num(*x), num(*y))).collect();
+    format!(r#"<polygon points="{}"{}/>"#, points.join(" "), paint)
+}
+
+fn rounded_rect(x: f64, y: f64, w: f64, h: f64, radius: f64, paint: &str) -> String {
+    format!(
+        r#"<rect x="{}" y="{}" width="{}" height="{}" rx="{}"{}/>"#,
+        num(x),
+        num(y),
+        num(w),
+        num(h),
+        num(radius),
+        paint
+    )
+}
+
+struct MindNode {
+    id: String,
+    rect: Rect,
+    text: String,
+    depth: usize,
+    color: &'static str,
+}
+
+struct MindEdge {
+    from: Point,
+    to: Point,
+    depth: usize,
+    color: &'static str,
+}
+
+struct MindLayout {
+    nodes: Vec<MindNode>,
+    edges: Vec<MindEdge>,
+}
+
+/// 逻辑图布局：根节点位于 `origin`，前 rightNodeCount 个分支向右展开，其余向左
+fn layout_mind_map(root: &PlaitElement, origin: Point) -> MindLayout {
+    let mut layout = MindLayout { nodes: Vec::new(), edges: Vec::new() };
+    let (w, h) = node_size(root);
+    let rect = Rect { min_x: origin.0, min_y: origin.1, max_x: origin.0 + w, max_y: origin.1 + h };
+    layout.nodes.push(MindNode { id: root.id.clone(), rect, text: node_text(root), depth: 0, color: DEFAULT_STROKE });
+
+    let children = root.children.as_deref().unwrap_or_default();
+    let right_count = root.extra.get("rightNodeCount").and_then(Value::as_u64).map_or(children.len(), |n| n as usize);
+    let (right, left) = children.split_at(right_count.min(children.len()));
+    let center_y = (rect.min_y + rect.max_y) / 2.0;
+    for (side, branch, offset) in [(1.0, right, 0), (-1.0, left, right.len())] {
+        place_children(&mut layout, branch, &rect, center_y, side, 1, Some(offset));
+    }
+    layout
+}
+
+/// 把一组兄弟节点排成一列，整体相对父节点垂直居中
+fn place_children(layout: &mut MindLayout, children: &[PlaitElement], parent: &Rect, center_y: f64, side: f64, depth: usize, branch_offset: Option<usize>) {
+    let heights: Vec<f64> = children.iter().map(subtree_height).collect();
+    let total = heights.iter().sum::<f64>() + MIND_V_GAP * (children.len().saturating_sub(1)) as f64;
+    let mut y = center_y - total / 2.0;
+    let parent_color = layout.nodes.last().map(|n| n.color).unwrap_or(DEFAULT_STROKE);
+
+    for (i, (child, height)) in children.iter().zip(&heights).enumerate() {
+        let (w, h) = node_size(child);
+        let child_center = y + height / 2.0;
+        let x = if side > 0.0 { parent.max_x + MIND_H_GAP } else { parent.min_x - MIND_H_GAP - w };
+        let rect = Rect { min_x: x, min_y: child_center - h / 2.0, max_x: x + w, max_y: child_center + h / 2.0 };
+        let color = match branch_offset {
+            Some(offset) => BRANCH_COLORS[(offset + i) % BRANCH_COLORS.len()],
+            None => parent_color,
+        };
+        let from = if side > 0.0 { Point(parent.max_x, center_y) } else { Point(parent.min_x, center_y) };
+        let to = if side > 0.0 { Point(rect.min_x, child_center) } else { Point(rect.max_x, child_center) };
+        layout.edges.push(MindEdge { from, to, depth: depth - 1, color });
+        layout.nodes.push(MindNode { id: child.id.clone(), rect, text: node_text(child), depth, color });
+
+        if let Some(grandchildren) = child.children.as_deref() {
+            place_children(layout, grandchildren, &rect, child_center, side, depth + 1, None);
+        }
+        y += height + MIND_V_GAP;
+    }
+}
+
+/// 思维导图各节点（含中心主题）在画布上的区域，与导出时的布局一致
+pub(crate) fn mind_map_nodes(root: &PlaitElement) -> Vec<(String, Rect)> {
+    let Some(origin) = root.points.first() else { return Vec::new() };
+    layout_mind_map(root, origin.clone()).nodes.into_iter().map(|node| (node.id, node.rect)).collect()
+}
+
+fn subtree_height(node: &PlaitElement) -> f64 {
+    let (_, h) = node_size(node);
+    let children = node.children.as_deref().unwrap_or_default();
+    if children.is_empty() {
+        return h;
+    }
+    let nested = children.iter().map(subtree_height).sum::<f64>() + MIND_V_GAP * (children.len() - 1) as f64;
+    h.max(nested)
+}
+
+/// 节点尺寸：前端保存的 width/height 只是文字区域，四周加上内边距
+fn node_size(node: &PlaitElement) -> (f64, f64) {
+    let text = node_text(node);
+    let width = number(node, "width").unwrap_or(text.chars().count().max(1) as f64 * FONT_SIZE);
+    let height = number(node, "height").unwrap_or(LINE_HEIGHT);
+    (width + 24.0, height + 12.0)
+}
+
+fn node_text(node: &PlaitElement) -> String {
+    node.extra.get("data").and_then(|d| d.get("topic")).map(plain_text).unwrap_or_default()
+}
+
+/// 坐标保留两位小数并去掉多余的 0
+fn num(value: f64) -> String {
+    let text = format!("{:.2}", value);
+    let text = text.trim_end_matches('0').trim_end_matches('.');
+    if text == "-0" { "0".into() } else { text.into() }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
@@ -0,0 +1,254 @@
+use serde::{Serialize, Deserialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Point(pub f64, pub f64);
+
+/// 白板元素，思维导图的子节点没有 type 和 points
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct PlaitElement {
+    pub id: String,
+    #[serde(rename = "type", default, skip_serializing_if = "String::is_empty")]
+    pub element_type: String, // ⚠️ 序列化时会变成 "type"；思维导图子节点没有 type
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub shape: String,        // "rectangle" | "ellipse" | ...
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub points: Vec<Point>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub children: Option<Vec<PlaitElement>>,
+    /// 其余属性（text、angle、strokeWidth、source/target 等）原样保留，保证存档和转发不丢字段
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl PlaitElement {
+    /// 除 id 和 children 之外的全部属性，键名与 JSON 中一致
+    pub fn properties(&self) -> BTreeMap<String, Value> {
+        let mut props: BTreeMap<String, Value> = self.extra.clone().into_iter().collect();
+        if !self.element_type.is_empty() {
+            props.insert("type".into(), Value::String(self.element_type.clone()));
+        }
+        if !self.shape.is_empty() {
+            props.insert("shape".into(), Value::String(self.shape.clone()));
+        }
+        if !self.points.is_empty() {
+            props.insert("points".into(), serde_json::to_value(&self.points).unwrap_or(Value::Null));
+        }
+        props
+    }
+
+    /// `properties` 的逆操作
+    pub fn from_properties(
+        id: &str,
+        properties: BTreeMap<String, Value>,
+        children: Option<Vec<PlaitElement>>,
+    ) -> Result<Self, String> {
+        let mut object: Map<String, Value> = properties.into_iter().collect();
+        object.insert("id".into(), Value::String(id.to_string()));
+        let mut element: PlaitElement = serde_json::from_value(Value::Object(object))
+            .map_err(|e| format!("元素 {} 的属性无效: {}", id, e))?;
+        element.children = children;
+        Ok(element)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct InsertNodeOperation {
+    #[serde(rename = "type")]   // 👈 关键
+    #[cfg_attr(feature = "schema", schemars(extend("const" = "insert_node")))]
+    pub op_type: String,        // "insert_node"
+    #[cfg_attr(feature = "schema", schemars(length(min = 1)))]
+    pub path: Vec<usize>,
+    pub node: PlaitElement,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct RemoveNodeOperation {
+    #[serde(rename = "type")]
+    #[cfg_attr(feature = "schema", schemars(extend("const" = "remove_node")))]
+    pub op_type: String,        // "remove_node"
+    #[cfg_attr(feature = "schema", schemars(length(min = 1)))]
+    pub path: Vec<usize>,
+    pub node: PlaitElement,
+}
+
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct SetNodeOperation {
+    #[serde(rename = "type")]
+    #[cfg_attr(feature = "schema", schemars(extend("const" = "set_node")))]
+    pub op_type: String,   // "set_node"
+    #[cfg_attr(feature = "schema", schemars(length(min = 1)))]
+    pub path: Vec<usize>,  // 要更新的节点路径
+    pub node: PlaitElement // 新属性
+}
+
+
+
+/// 序列化时不加外层标签，与前端的格式一致；反序列化按 "type" 字段分派，
+/// 三种操作字段相同，不能靠 `untagged` 逐个尝试
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(untagged, try_from = "RawOperation")]
+pub enum Operation {
+    Insert(InsertNodeOperation),
+    Remove(RemoveNodeOperation),
+    Set(SetNodeOperation),
+}
+
+/// 线上操作的原始结构，先解析出 "type" 再决定变体
+#[derive(Deserialize)]
+struct RawOperation {
+    #[serde(rename = "type")]
+    op_type: String,
+    path: Vec<usize>,
+    node: PlaitElement,
+}
+
+impl TryFrom<RawOperation> for Operation {
+    type Error = String;
+
+    fn try_from(raw: RawOperation) -> Result<Self, String> {
+        if raw.path.is_empty() {
+            return Err(format!("{} 操作的路径为空", raw.op_type));
+        }
+        match raw.op_type.as_str() {
+            "insert_node" => Ok(Operation::insert(raw.path, raw.node)),
+            "remove_node" => Ok(Operation::remove(raw.path, raw.node)),
+            "set_node" => Ok(Operation::set(raw.path, raw.node)),
+            other => Err(format!("未知的操作类型: {:?}（应为 insert_node、remove_node 或 set_node）", other)),
+        }
+    }
+}
+
+impl Operation {
+    pub fn insert(path: Vec<usize>, node: PlaitElement) -> Self {
+        Operation::Insert(InsertNodeOperation { op_type: "insert_node".into(), path, node })
+    }
+
+    pub fn remove(path: Vec<usize>, node: PlaitElement) -> Self {
+        Operation::Remove(RemoveNodeOperation { op_type: "remove_node".into(), path, node })
+    }
+
+    pub fn set(path: Vec<usize>, node: PlaitElement) -> Self {
+        Operation::Set(SetNodeOperation { op_type: "set_node".into(), path, node })
+    }
+
+    /// 线上的 "type" 字段："insert_node" | "remove_node" | "set_node"
+    pub fn op_type(&self) -> &str {
+        match self {
+            Operation::Insert(op) => &op.op_type,
+            Operation::Remove(op) => &op.op_type,
+            Operation::Set(op) => &op.op_type,
+        }
+    }
+
+    pub fn path(&self) -> &[usize] {
+        match self {
+            Operation::Insert(op) => &op.path,
+            Operation::Remove(op) => &op.path,
+            Operation::Set(op) => &op.path,
+        }
+    }
+
+    pub fn node(&self) -> &PlaitElement {
+        match self {
+            Operation::Insert(op) => &op.node,
+            Operation::Remove(op) => &op.node,
+            Operation::Set(op) => &op.node,
+        }
+    }
+
+    pub fn node_mut(&mut self) -> &mut PlaitElement {
+        match self {
+            Operation::Insert(op) => &mut op.node,
+            Operation::Remove(op) => &mut op.node,
+            Operation::Set(op) => &mut op.node,
+        }
+    }
+}
+
+
+/// 一批白板操作，DDS 白板主题和 WebSocket 桥上传输的消息
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct BoardChangeData {
+    pub operations: Vec<Operation>,
+    pub timestamp: String,
+    pub source_id: String,
+    /// 同一 source_id 发布的消息序号，从 1 开始，订阅者据此发现丢失的消息。
+    /// 由 `DDSManager` 发布时填写；前端和旧版本发布者的消息没有这个字段
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seq: Option<u64>,
+}
+
+/// 后加入的客户端向白板服务请求某个主题（房间）的完整状态
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct SnapshotRequest {
+    pub topic: String,
+    pub request_id: String,
+    pub source_id: String,
+    pub timestamp: String,
+}
+
+/// 对 SnapshotRequest 的应答：房间的白板为 `elements` 依次按全序应用 `changes` 的结果。
+/// 最近的变化单独发送，接收端才能把与快照赛跑的变化排到正确的位置
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct BoardSnapshot {
+    pub topic: String,
+    pub request_id: String,
+    pub elements: Vec<PlaitElement>,
+    pub timestamp: String,
+    pub source_id: String,
+    /// 白板服务重排窗口内的变化；旧版本白板服务的快照没有这个字段，`elements` 即当前白板
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub changes: Vec<BoardChangeData>,
+    /// 快照已包含的变化。收到快照的一端丢弃这些变化，重新应用发出请求后收到的其他变化
+    #[serde(default)]
+    pub covers: SeqCoverage,
+}
+
+/// 每个 source_id 已包含的 seq，按从小到大的闭区间保存；按顺序收到时只有一段 `[1, n]`
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(transparent)]
+pub struct SeqCoverage(BTreeMap<String, Vec<(u64, u64)>>);
+
+impl SeqCoverage {
+    pub fn contains(&self, source_id: &str, seq: u64) -> bool {
+        self.0.get(source_id).is_some_and(|ranges| {
+            let i = ranges.partition_point(|&(_, end)| end < seq);
+            ranges.get(i).is_some_and(|&(start, _)| start <= seq)
+        })
+    }
+
+    /// 是否已包含这条变化；没有 seq 的旧消息无法判断，视为未包含
+    pub fn covers(&self, change: &BoardChangeData) -> bool {
+        change.seq.is_some_and(|seq| self.contains(&change.source_id, seq))
+    }
+
+    pub fn insert(&mut self, source_id: &str, seq: u64) {
+        let ranges = self.0.entry(source_id.to_string()).or_default();
+        // 第一段结尾与 seq 相接或在它之后的区间
+        let i = ranges.partition_point(|&(_, end)| end.saturating_add(1) < seq);
+        match ranges.get_mut(i) {
+            Some(range) if range.0 <= seq.saturating_add(1) => {
+                range.0 = range.0.min(seq);
+                range.1 = range.1.max(seq);
+                let end = range.1;
+                if ranges.get(i + 1).is_some_and(|next| next.0 <= end.saturating_add(1)) {
+                    ranges[i].1 = ranges[i + 1].1;
+                    ranges.remove(i + 1);
+                }
+            }
+            _ => ranges.insert(i, (seq, seq)),
+        }
+    }
+}
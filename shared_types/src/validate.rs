@@ -0,0 +1,361 @@
+//! 白板校验：找出会让前端崩溃或显示异常的数据，并修复其中可以安全修复的部分。
+//!
+//! 诊断带有元素路径（与 Operation 的 path 含义相同）和 id。可以修复的问题：
+//! 空 id 和重复 id 换成新 id，连到已删除元素的连线解除绑定，空文字元素和连线上的空文字删除，
+//! 缺少 source/target 的连线补上默认端点，无效的缩放比例重置为 1。
+//! 坐标非有限数、点数不够等问题无法推断原意，只报告不修复。
+
+use crate::board::BoardState;
+use crate::document::{DrawnixDocument, DRAWNIX_VERSION};
+use crate::svg::plain_text;
+use crate::types::*;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// 校验规则
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "kebab-case")]
+pub enum Rule {
+    EmptyId,
+    DuplicateId,
+    NonFiniteCoordinate,
+    MissingPoints,
+    MissingType,
+    MissingLineEnd,
+    DanglingBinding,
+    EmptyText,
+    InvalidViewport,
+    NewerVersion,
+}
+
+impl Rule {
+    pub fn severity(self) -> Severity {
+        match self {
+            Rule::EmptyText | Rule::MissingType | Rule::NewerVersion => Severity::Warning,
+            _ => Severity::Error,
+        }
+    }
+
+    /// `--fix` 能否修复
+    pub fn fixable(self) -> bool {
+        matches!(
+            self,
+            Rule::EmptyId | Rule::DuplicateId | Rule::MissingLineEnd | Rule::DanglingBinding | Rule::EmptyText | Rule::InvalidViewport
+        )
+    }
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub rule: Rule,
+    pub severity: Severity,
+    /// 元素路径，文档级的问题为空
+    pub path: Vec<usize>,
+    pub id: String,
+    pub message: String,
+}
+
+impl Diagnostic {
+    fn new(rule: Rule, path: &[usize], id: &str, message: String) -> Self {
+        Diagnostic { rule, severity: rule.severity(), path: path.to_vec(), id: id.to_string(), message }
+    }
+}
+
+/// 按行输出诊断，末尾附统计
+pub fn diagnostics_to_text(diagnostics: &[Diagnostic]) -> String {
+    if diagnostics.is_empty() {
+        return "✅ 没有发现问题\n".into();
+    }
+    let mut out = String::new();
+    for d in diagnostics {
+        let icon = if d.severity == Severity::Error { "❌" } else { "⚠️ " };
+        let location = if d.path.is_empty() { "文档".to_string() } else { format!("{:?} {}", d.path, d.id) };
+        out += &format!("{} {}: {} [{}]\n", icon, location, d.message, rule_name(d.rule));
+    }
+    let errors = diagnostics.iter().filter(|d| d.severity == Severity::Error).count();
+    out += &format!("📊 {} 个错误，{} 个警告\n", errors, diagnostics.len() - errors);
+    out
+}
+
+fn rule_name(rule: Rule) -> String {
+    serde_json::to_value(rule).ok().and_then(|v| v.as_str().map(String::from)).unwrap_or_default()
+}
+
+/// 校验一组元素（含子节点）
+pub fn validate_elements(elements: &[PlaitElement]) -> Vec<Diagnostic> {
+    let mut ids = HashSet::new();
+    walk(elements, &mut Vec::new(), &mut |_, element| {
+        ids.insert(element.id.clone());
+    });
+
+    let mut diagnostics = Vec::new();
+    let mut seen = HashSet::new();
+    walk(elements, &mut Vec::new(), &mut |path, element| {
+        check_element(path, element, &ids, &mut seen, &mut diagnostics);
+    });
+    diagnostics
+}
+
+/// 校验 `.drawnix` 文档，包括视口和版本
+pub fn validate_document(document: &DrawnixDocument) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    if document.version > DRAWNIX_VERSION {
+        diagnostics.push(Diagnostic::new(
+            Rule::NewerVersion,
+            &[],
+            "",
+            format!("文件版本 {} 高于支持的版本 {}，部分内容可能无法识别", document.version, DRAWNIX_VERSION),
+        ));
+    }
+    let viewport = &document.viewport;
+    let origin_ok = viewport.origination.as_ref().is_none_or(|p| p.0.is_finite() && p.1.is_finite());
+    if !(viewport.zoom.is_finite() && viewport.zoom > 0.0 && origin_ok) {
+        diagnostics.push(Diagnostic::new(Rule::InvalidViewport, &[], "", format!("视口无效（缩放 {}）", viewport.zoom)));
+    }
+    diagnostics.extend(validate_elements(&document.elements));
+    diagnostics
+}
+
+/// 先序遍历，path 为当前元素的路径
+fn walk(elements: &[PlaitElement], path: &mut Vec<usize>, visit: &mut dyn FnMut(&[usize], &PlaitElement)) {
+    for (i, element) in elements.iter().enumerate() {
+        path.push(i);
+        visit(path, element);
+        if let Some(children) = &element.children {
+            walk(children, path, visit);
+        }
+        path.pop();
+    }
+}
+
+fn check_element(path: &[usize], element: &PlaitElement, ids: &HashSet<String>, seen: &mut HashSet<String>, out: &mut Vec<Diagnostic>) {
+    let id = element.id.as_str();
+    let mut report = |rule: Rule, message: String| out.push(Diagnostic::new(rule, path, id, message));
+
+    if id.trim().is_empty() {
+        report(Rule::EmptyId, "元素没有 id".into());
+    } else if !seen.insert(id.to_string()) {
+        report(Rule::DuplicateId, format!("id {} 与之前的元素重复", id));
+    }
+
+    if let Some(i) = element.points.iter().position(|p| !p.0.is_finite() || !p.1.is_finite()) {
+        report(Rule::NonFiniteCoordinate, format!("第 {} 个点的坐标不是有限数", i + 1));
+    }
+
+    // 思维导图的子节点没有 type，也没有坐标
+    if path.len() == 1 && element.element_type.is_empty() {
+        report(Rule::MissingType, "顶层元素没有 type，前端无法显示".into());
+    }
+    let required = match element.element_type.as_str() {
+        "geometry" | "image" | "line" | "arrow-line" => 2,
+        "freehand" | "mindmap" | "mind_map" => 1,
+        _ => 0,
+    };
+    if element.points.len() < required {
+        report(Rule::MissingPoints, format!("{} 元素至少需要 {} 个点，实际 {} 个", element.element_type, required, element.points.len()));
+    }
+
+    match element.element_type.as_str() {
+        "line" | "arrow-line" => {
+            for end in ["source", "target"] {
+                match element.extra.get(end) {
+                    Some(Value::Object(handle)) => {
+                        if let Some(bound) = handle.get("boundId").and_then(Value::as_str) {
+                            if !ids.contains(bound) {
+                                report(Rule::DanglingBinding, format!("{} 端绑定的元素 {} 不存在", end, bound));
+                            }
+                        }
+                    }
+                    _ => report(Rule::MissingLineEnd, format!("连线缺少 {}", end)),
+                }
+            }
+            let empty = line_texts(element).iter().filter(|label| label_is_empty(label)).count();
+            if empty > 0 {
+                report(Rule::EmptyText, format!("连线上有 {} 处空文字", empty));
+            }
+        }
+        "geometry" if element.shape == "text" && element.extra.get("text").is_none_or(|t| plain_text(t).trim().is_empty()) => {
+            report(Rule::EmptyText, "文字元素没有内容".into());
+        }
+        _ => {}
+    }
+}
+
+fn line_texts(element: &PlaitElement) -> &[Value] {
+    element.extra.get("texts").and_then(Value::as_array).map(Vec::as_slice).unwrap_or_default()
+}
+
+fn label_is_empty(label: &Value) -> bool {
+    label.get("text").is_none_or(|t| plain_text(t).trim().is_empty())
+}
+
+/// 修复结果：修复了哪些问题，还剩哪些问题
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct FixReport {
+    pub fixed: Vec<Diagnostic>,
+    pub remaining: Vec<Diagnostic>,
+}
+
+/// 修复一组元素中可以安全修复的问题
+pub fn fix_elements(elements: &mut Vec<PlaitElement>) -> FixReport {
+    let before = validate_elements(elements);
+    repair(elements);
+    FixReport { fixed: before.into_iter().filter(|d| d.rule.fixable()).collect(), remaining: validate_elements(elements) }
+}
+
+/// 修复 `.drawnix` 文档，包括视口
+pub fn fix_document(document: &mut DrawnixDocument) -> FixReport {
+    let before = validate_document(document);
+    let viewport = &mut document.viewport;
+    if !(viewport.zoom.is_finite() && viewport.zoom > 0.0) {
+        viewport.zoom = 1.0;
+    }
+    if viewport.origination.as_ref().is_some_and(|p| !p.0.is_finite() || !p.1.is_finite()) {
+        viewport.origination = None;
+    }
+    repair(&mut document.elements);
+    FixReport { fixed: before.into_iter().filter(|d| d.rule.fixable()).collect(), remaining: validate_document(document) }
+}
+
+fn repair(elements: &mut Vec<PlaitElement>) {
+    // 先删空文字：删掉的元素可能被连线绑定，之后一并解除
+    fn remove_empty(elements: &mut Vec<PlaitElement>) {
+        elements.retain(|e| !(e.element_type == "geometry" && e.shape == "text" && e.extra.get("text").is_none_or(|t| plain_text(t).trim().is_empty())));
+        for element in elements.iter_mut() {
+            if let Some(Value::Array(texts)) = element.extra.get_mut("texts") {
+                texts.retain(|label| !label_is_empty(label));
+            }
+            if let Some(children) = element.children.as_mut() {
+                remove_empty(children);
+            }
+        }
+    }
+    remove_empty(elements);
+
+    // 重复的 id 保留第一个，之后的加上序号；空 id 按路径生成
+    let mut taken = HashSet::new();
+    walk(elements, &mut Vec::new(), &mut |_, e| {
+        taken.insert(e.id.clone());
+    });
+    let mut seen = HashSet::new();
+    fn renumber(elements: &mut [PlaitElement], path: &mut Vec<usize>, seen: &mut HashSet<String>, taken: &mut HashSet<String>) {
+        for (i, element) in elements.iter_mut().enumerate() {
+            path.push(i);
+            let empty = element.id.trim().is_empty();
+            if empty || !seen.insert(element.id.clone()) {
+                let base = if empty { format!("element-{}", path.iter().map(usize::to_string).collect::<Vec<_>>().join("-")) } else { element.id.clone() };
+                let id = (if empty { 0 } else { 1 }..)
+                    .map(|n| if n == 0 { base.clone() } else { format!("{}-{}", base, n) })
+                    .find(|id| !taken.contains(id))
+                    .expect("总能找到未使用的 id");
+                taken.insert(id.clone());
+                seen.insert(id.clone());
+                element.id = id;
+            }
+            if let Some(children) = element.children.as_mut() {
+                renumber(children, path, seen, taken);
+            }
+            path.pop();
+        }
+    }
+    renumber(elements, &mut Vec::new(), &mut seen, &mut taken);
+
+    // 补上缺少的端点，解除指向不存在元素的绑定（保留端点位置）
+    fn repair_lines(elements: &mut [PlaitElement], ids: &HashSet<String>) {
+        for element in elements {
+            if matches!(element.element_type.as_str(), "line" | "arrow-line") {
+                for (end, marker) in [("source", "none"), ("target", "arrow")] {
+                    let handle = element.extra.entry(end).or_insert(Value::Null);
+                    if !handle.is_object() {
+                        *handle = json!({ "marker": marker });
+                    }
+                    let dangling = handle.get("boundId").and_then(Value::as_str).is_some_and(|id| !ids.contains(id));
+                    if let (true, Some(handle)) = (dangling, handle.as_object_mut()) {
+                        handle.remove("boundId");
+                        handle.remove("connection");
+                    }
+                }
+            }
+            if let Some(children) = element.children.as_mut() {
+                repair_lines(children, ids);
+            }
+        }
+    }
+    repair_lines(elements, &seen);
+}
+
+/// 广播前检查：应用这批操作后不应出现新的错误。白板原有的问题不影响检查。
+///
+/// 只校验操作涉及的元素（插入、替换、删除的节点及其子节点）和绑定到它们的连线，
+/// 同样的问题按出现次数比较，原本就有一个空 id 元素时再插入一个也会被拒绝
+pub fn check_operations(board: &BoardState, operations: &[Operation]) -> Result<(), String> {
+    let mut after = board.clone();
+    let mut touched = HashSet::new();
+    for op in operations {
+        // 被替换、删除的节点原来的 id 也算涉及：绑定到它们的连线可能因此失效
+        if !matches!(op, Operation::Insert(_)) {
+            if let Some(old) = after.get(op.path()) {
+                collect_ids(old, &mut touched);
+            }
+        }
+        collect_ids(op.node(), &mut touched);
+        after.apply(op).map_err(|e| format!("操作无法应用: {}", e))?;
+    }
+
+    let key = |d: &Diagnostic| (d.rule, d.id.clone(), d.message.clone());
+    let mut existing: HashMap<_, usize> = HashMap::new();
+    for d in touched_errors(&board.children, &touched) {
+        *existing.entry(key(&d)).or_default() += 1;
+    }
+    let introduced: Vec<String> = touched_errors(&after.children, &touched)
+        .into_iter()
+        .filter(|d| match existing.get_mut(&key(d)) {
+            Some(count) if *count > 0 => {
+                *count -= 1;
+                false
+            }
+            _ => true,
+        })
+        .map(|d| format!("{} {}", d.id, d.message))
+        .collect();
+    if introduced.is_empty() {
+        Ok(())
+    } else {
+        Err(format!("操作会使白板无效: {}", introduced.join("；")))
+    }
+}
+
+fn collect_ids(element: &PlaitElement, ids: &mut HashSet<String>) {
+    walk(std::slice::from_ref(element), &mut Vec::new(), &mut |_, e| {
+        ids.insert(e.id.clone());
+    });
+}
+
+/// 涉及的 id 对应的所有元素（重复 id 的每一份都算）和绑定到它们的连线上的错误
+fn touched_errors(elements: &[PlaitElement], touched: &HashSet<String>) -> Vec<Diagnostic> {
+    let mut ids = HashSet::new();
+    walk(elements, &mut Vec::new(), &mut |_, element| {
+        ids.insert(element.id.clone());
+    });
+
+    let mut diagnostics = Vec::new();
+    let mut seen = HashSet::new();
+    walk(elements, &mut Vec::new(), &mut |path, element| {
+        let bound = ["source", "target"]
+            .iter()
+            .any(|end| element.extra.get(*end).and_then(|h| h.get("boundId")).and_then(Value::as_str).is_some_and(|id| touched.contains(id)));
+        if touched.contains(&element.id) || bound {
+            check_element(path, element, &ids, &mut seen, &mut diagnostics);
+        }
+    });
+    diagnostics.retain(|d| d.severity == Severity::Error);
+    diagnostics
+}
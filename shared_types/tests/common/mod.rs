@@ -0,0 +1,36 @@
+//! 各测试文件共用的元素构造函数和仓库里的示例白板。
+
+#![allow(dead_code)]
+
+use shared_types::document::DrawnixDocument;
+use shared_types::PlaitElement;
+use std::path::{Path, PathBuf};
+
+pub fn element(json: &str) -> PlaitElement {
+    serde_json::from_str(json).unwrap()
+}
+
+pub fn rect(id: &str, x: f64, y: f64, w: f64, h: f64) -> PlaitElement {
+    element(&format!(r#"{{"id":"{}","type":"geometry","shape":"rectangle","points":[[{},{}],[{},{}]]}}"#, id, x, y, x + w, y + h))
+}
+
+/// 原点处 100x100 的矩形
+pub fn square(id: &str) -> PlaitElement {
+    rect(id, 0.0, 0.0, 100.0, 100.0)
+}
+
+/// 100x50、带文字的矩形
+pub fn labeled_rect(id: &str, x: f64, y: f64, text: &str) -> PlaitElement {
+    let mut element = rect(id, x, y, 100.0, 50.0);
+    element.extra.insert("text".into(), serde_json::json!({ "children": [{ "text": text }] }));
+    element
+}
+
+/// src-tauri 下的示例白板，包含思维导图、连线和各种图形
+pub fn sample_path() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("../src-tauri/1757408364422.drawnix")
+}
+
+pub fn sample() -> DrawnixDocument {
+    DrawnixDocument::load(sample_path()).unwrap()
+}
@@ -0,0 +1,456 @@
+//! 多端收敛测试：在进程内模拟若干个协作端，随机产生并发编辑，
+//! 经过一个会延迟、乱序、重复投递的模拟网络互相广播，最后检查各端白板完全一致。
+//! 消息在网络中以线上 JSON 传输，与 DDS 上的格式相同。
+//!
+//! 不依赖 DDS 和网络，`cargo test` 即可运行。失败时会打印随机种子和各端的差异，
+//! 用同一个种子可以稳定复现。
+
+mod common;
+
+use common::rect;
+use shared_types::board::BoardState;
+use shared_types::diff::diff_elements;
+use shared_types::replica::Replica;
+use shared_types::*;
+
+/// 模拟网络的参数（时间单位为毫秒）
+#[derive(Clone, Copy)]
+struct NetworkConfig {
+    peers: usize,
+    ticks: u64,
+    /// 每个时刻每个端发起编辑的概率
+    edit_chance: f64,
+    max_delay: u64,
+    /// 消息被重复投递的概率
+    duplicate_chance: f64,
+    /// 各端时钟的最大偏差
+    max_clock_skew: i64,
+}
+
+impl Default for NetworkConfig {
+    fn default() -> Self {
+        NetworkConfig {
+            peers: 4,
+            ticks: 300,
+            edit_chance: 0.15,
+            max_delay: 80,
+            duplicate_chance: 0.05,
+            max_clock_skew: 50,
+        }
+    }
+}
+
+struct Peer {
+    source_id: String,
+    replica: Replica,
+    seq: u64,
+    clock_skew: i64,
+    next_element: u64,
+}
+
+struct InFlight {
+    deliver_at: u64,
+    to: usize,
+    json: String,
+}
+
+struct Network {
+    config: NetworkConfig,
+    rng: fastrand::Rng,
+    peers: Vec<Peer>,
+    in_flight: Vec<InFlight>,
+    epoch: chrono::DateTime<chrono::Utc>,
+    now: u64,
+    /// 按发布顺序记录的全部变化
+    published: Vec<BoardChangeData>,
+}
+
+impl Network {
+    fn new(seed: u64, config: NetworkConfig, base: BoardState) -> Self {
+        let mut rng = fastrand::Rng::with_seed(seed);
+        let peers = (0..config.peers)
+            .map(|i| Peer {
+                source_id: format!("peer-{}", i),
+                replica: Replica::new(base.clone()),
+                seq: 0,
+                clock_skew: rng.i64(-config.max_clock_skew..=config.max_clock_skew),
+                next_element: 0,
+            })
+            .collect();
+        Network {
+            config,
+            rng,
+            peers,
+            in_flight: Vec::new(),
+            epoch: chrono::DateTime::parse_from_rfc3339("2024-01-01T00:00:00Z").unwrap().into(),
+            now: 0,
+            published: Vec::new(),
+        }
+    }
+
+    /// 某个端在本地做一次编辑并广播
+    fn edit(&mut self, index: usize) {
+        let peer = &mut self.peers[index];
+        peer.next_element += 1;
+        let id = format!("{}-{}", peer.source_id, peer.next_element);
+        let Some(op) = random_operation(peer.replica.board(), &id, &mut self.rng) else {
+            return;
+        };
+        self.publish(index, vec![op]);
+    }
+
+    fn publish(&mut self, index: usize, operations: Vec<Operation>) {
+        let timestamp = self.clock(index);
+        let peer = &mut self.peers[index];
+        peer.seq += 1;
+        let mut change = BoardChangeData {
+            operations,
+            timestamp,
+            source_id: peer.source_id.clone(),
+            seq: Some(peer.seq),
+        };
+        peer.replica.stamp(&mut change);
+        peer.replica.apply(change.clone());
+        let json = serde_json::to_string(&change).expect("白板变化应能编码");
+        self.published.push(change);
+
+        for to in (0..self.peers.len()).filter(|&to| to != index) {
+            let copies = if self.rng.f64() < self.config.duplicate_chance { 2 } else { 1 };
+            for _ in 0..copies {
+                self.in_flight.push(InFlight {
+                    deliver_at: self.now + self.rng.u64(0..=self.config.max_delay),
+                    to,
+                    json: json.clone(),
+                });
+            }
+        }
+    }
+
+    /// 本端看到的当前时间（带时钟偏差）
+    fn clock(&self, index: usize) -> String {
+        let skewed = self.now as i64 + self.peers[index].clock_skew;
+        (self.epoch + chrono::Duration::milliseconds(skewed)).to_rfc3339()
+    }
+
+    /// 投递所有到期的消息，同一时刻到期的消息顺序随机
+    fn deliver_due(&mut self) {
+        let (mut due, pending): (Vec<_>, Vec<_>) = self.in_flight.drain(..).partition(|m| m.deliver_at <= self.now);
+        self.in_flight = pending;
+        self.rng.shuffle(&mut due);
+        for message in due {
+            let change: BoardChangeData = serde_json::from_str(&message.json).expect("线上消息应能解码");
+            self.peers[message.to].replica.apply(change);
+        }
+    }
+
+    fn run(&mut self) {
+        for _ in 0..self.config.ticks {
+            for index in 0..self.peers.len() {
+                if self.rng.f64() < self.config.edit_chance {
+                    self.edit(index);
+                }
+            }
+            self.deliver_due();
+            self.now += 1;
+        }
+        self.settle();
+    }
+
+    /// 停止编辑，等所有消息送达
+    fn settle(&mut self) {
+        self.now += self.config.max_delay;
+        self.deliver_due();
+        assert!(self.in_flight.is_empty());
+    }
+
+    fn assert_converged(&self, seed: u64) {
+        let reference = self.peers[0].replica.board();
+        for peer in &self.peers[1..] {
+            let board = peer.replica.board();
+            if board != reference {
+                panic!(
+                    "种子 {}：{} 与 {} 不一致（各自收到 {} / {} 条变化）\n{}",
+                    seed,
+                    self.peers[0].source_id,
+                    peer.source_id,
+                    self.peers[0].replica.len(),
+                    peer.replica.len(),
+                    diff_elements(&reference.children, &board.children).to_text()
+                );
+            }
+        }
+    }
+}
+
+/// 白板上所有节点及其路径（含子节点）
+fn all_nodes(board: &BoardState) -> Vec<(Vec<usize>, PlaitElement)> {
+    fn walk(nodes: &[PlaitElement], prefix: &mut Vec<usize>, out: &mut Vec<(Vec<usize>, PlaitElement)>) {
+        for (i, node) in nodes.iter().enumerate() {
+            prefix.push(i);
+            out.push((prefix.clone(), node.clone()));
+            if let Some(children) = &node.children {
+                walk(children, prefix, out);
+            }
+            prefix.pop();
+        }
+    }
+    let mut out = Vec::new();
+    walk(&board.children, &mut Vec::new(), &mut out);
+    out
+}
+
+/// 在本端当前白板上随机生成一次插入、移动、改文字或删除
+fn random_operation(board: &BoardState, new_id: &str, rng: &mut fastrand::Rng) -> Option<Operation> {
+    let nodes = all_nodes(board);
+    let roll = if nodes.is_empty() { 0 } else { rng.u32(0..10) };
+    match roll {
+        // 插入到顶层任意位置，或作为某个顶层元素的子节点
+        0..=3 => {
+            let node = rect(new_id, rng.f64() * 1000.0, rng.f64() * 800.0, 100.0, 60.0);
+            let path = match board.children.len() {
+                0 => vec![0],
+                len if rng.bool() => vec![rng.usize(0..=len)],
+                len => {
+                    let parent = rng.usize(0..len);
+                    let siblings = board.children[parent].children.as_ref().map_or(0, Vec::len);
+                    vec![parent, rng.usize(0..=siblings)]
+                }
+            };
+            Some(Operation::insert(path, node))
+        }
+        4..=6 => {
+            let (path, mut node) = nodes[rng.usize(0..nodes.len())].clone();
+            let (dx, dy) = (rng.f64() * 20.0 - 10.0, rng.f64() * 20.0 - 10.0);
+            for point in node.points.iter_mut() {
+                point.0 += dx;
+                point.1 += dy;
+            }
+            Some(Operation::set(path, node))
+        }
+        7..=8 => {
+            let (path, mut node) = nodes[rng.usize(0..nodes.len())].clone();
+            node.extra.insert("text".into(), serde_json::json!({ "children": [{ "text": new_id }] }));
+            Some(Operation::set(path, node))
+        }
+        _ => {
+            let (path, node) = nodes[rng.usize(0..nodes.len())].clone();
+            Some(Operation::remove(path, node))
+        }
+    }
+}
+
+fn check_seeds(seeds: std::ops::Range<u64>, config: NetworkConfig) {
+    for seed in seeds {
+        let mut network = Network::new(seed, config, BoardState::new());
+        network.run();
+        network.assert_converged(seed);
+    }
+}
+
+#[test]
+fn peers_converge_under_random_delivery() {
+    check_seeds(0..40, NetworkConfig::default());
+}
+
+#[test]
+fn peers_converge_with_heavy_contention() {
+    // 两个端频繁编辑同一批元素，延迟远大于编辑间隔
+    let config = NetworkConfig {
+        peers: 2,
+        ticks: 200,
+        edit_chance: 0.5,
+        max_delay: 200,
+        duplicate_chance: 0.2,
+        max_clock_skew: 0,
+    };
+    check_seeds(100..120, config);
+}
+
+#[test]
+fn peers_converge_from_shared_snapshot() {
+    // 从同一份快照开始（后加入的客户端从白板服务拿到的状态）
+    let base = BoardState::from_elements((0..10).map(|i| rect(&format!("seed-{}", i), i as f64 * 120.0, 0.0, 100.0, 60.0)).collect());
+    for seed in 200..212 {
+        let mut network = Network::new(seed, NetworkConfig { peers: 6, ..Default::default() }, base.clone());
+        network.run();
+        network.assert_converged(seed);
+    }
+}
+
+#[test]
+fn peers_converge_with_large_clock_skew() {
+    // 两端的时钟慢了一小时，变化数远超重排窗口：慢的一端靠 stamp 排到已收到的变化之后，
+    // 否则它们的变化会排到窗口之外被丢弃，各端随之分叉
+    let seed = 300;
+    let mut network = Network::new(seed, NetworkConfig { ticks: 4000, ..Default::default() }, BoardState::new());
+    for (peer, skew) in network.peers.iter_mut().zip([-3_600_000, -3_599_500, 0, 300]) {
+        peer.clock_skew = skew;
+    }
+    network.run();
+    network.assert_converged(seed);
+    assert!(network.published.len() > 2000);
+    for peer in &network.peers {
+        assert_eq!(peer.replica.too_late(), 0, "{}", peer.source_id);
+        assert_eq!(peer.replica.len(), network.published.len());
+    }
+}
+
+#[test]
+fn changes_behind_the_reorder_window_are_dropped() {
+    let change = |seconds: i64, seq: u64| BoardChangeData {
+        operations: vec![Operation::insert(vec![0], rect(&format!("e{}", seq), 0.0, 0.0, 10.0, 10.0))],
+        timestamp: (chrono::DateTime::UNIX_EPOCH + chrono::Duration::seconds(seconds)).to_rfc3339(),
+        source_id: "peer-0".into(),
+        seq: Some(seq),
+    };
+    let mut replica = Replica::new(BoardState::new());
+    for seq in 1..=3000 {
+        assert!(replica.apply(change(1000 + seq as i64, seq)));
+    }
+
+    // 窗口内的乱序照常重排，只从最近的检查点重放
+    assert!(replica.apply(change(3900, 5000)));
+    assert_eq!(replica.rebuilds(), 1);
+    // 时钟慢了很多的消息和早已并入基准的重复消息都放不回去了
+    assert!(!replica.apply(change(10, 5001)));
+    assert!(!replica.apply(change(1001, 1)));
+    assert_eq!(replica.too_late(), 2);
+    assert_eq!((replica.len(), replica.board().children.len()), (3001, 3001));
+
+    // stamp 把落后的时间戳推到最新的变化之后
+    let mut late = change(10, 5002);
+    replica.stamp(&mut late);
+    let stamped = chrono::DateTime::parse_from_rfc3339(&late.timestamp).unwrap();
+    assert_eq!(stamped.timestamp_micros(), 4000 * 1_000_000 + 1);
+    assert!(replica.apply(late));
+    assert_eq!(replica.board().children[0].id, "e5002");
+}
+
+#[test]
+fn sequential_edits_match_plain_board() {
+    // 没有并发时，副本与按顺序直接应用的结果相同
+    let config = NetworkConfig { max_delay: 0, duplicate_chance: 0.0, max_clock_skew: 0, ..Default::default() };
+    let mut network = Network::new(7, config, BoardState::new());
+    for _ in 0..config.ticks {
+        let index = network.rng.usize(0..config.peers);
+        network.edit(index);
+        network.deliver_due();
+        network.now += 1;
+    }
+    network.settle();
+    network.assert_converged(7);
+
+    let mut plain = BoardState::new();
+    for change in &network.published {
+        assert!(plain.apply_all(&change.operations).is_empty());
+    }
+    for peer in &network.peers {
+        assert_eq!(peer.replica.rebuilds(), 0, "{} 不应该因乱序而重放", peer.source_id);
+        assert_eq!(peer.replica.board(), &plain);
+    }
+}
+
+#[test]
+fn concurrent_insert_does_not_redirect_set_to_another_element() {
+    let base = BoardState::from_elements(vec![rect("a", 0.0, 0.0, 100.0, 60.0), rect("b", 200.0, 0.0, 100.0, 60.0)]);
+    let mut network = Network::new(0, NetworkConfig { peers: 2, max_clock_skew: 0, ..Default::default() }, base);
+
+    // peer-0 在最前面插入，peer-1 同时移动 a（它看到的路径还是 [0]）
+    network.publish(0, vec![Operation::insert(vec![0], rect("c", 0.0, 300.0, 100.0, 60.0))]);
+    network.publish(1, vec![Operation::set(vec![0], rect("a", 50.0, 50.0, 100.0, 60.0))]);
+    network.settle();
+    network.assert_converged(0);
+
+    let board = network.peers[0].replica.board();
+    let ids: Vec<&str> = board.children.iter().map(|e| e.id.as_str()).collect();
+    assert_eq!(ids, ["c", "a", "b"]);
+    assert_eq!(board.children[1].points[0], Point(50.0, 50.0));
+    assert_eq!(board.children[0].points[0], Point(0.0, 300.0));
+}
+
+#[test]
+fn set_after_concurrent_remove_is_dropped_everywhere() {
+    let base = BoardState::from_elements(vec![rect("a", 0.0, 0.0, 100.0, 60.0), rect("b", 200.0, 0.0, 100.0, 60.0)]);
+    let mut network = Network::new(0, NetworkConfig { peers: 3, max_clock_skew: 0, ..Default::default() }, base);
+
+    network.publish(0, vec![Operation::remove(vec![0], rect("a", 0.0, 0.0, 100.0, 60.0))]);
+    network.now += 1;
+    network.publish(1, vec![Operation::set(vec![0], rect("a", 10.0, 10.0, 100.0, 60.0))]);
+    network.settle();
+    network.assert_converged(0);
+
+    let ids: Vec<&str> = network.peers[2].replica.board().children.iter().map(|e| e.id.as_str()).collect();
+    assert_eq!(ids, ["b"]);
+}
+
+#[test]
+fn seq_coverage_keeps_disjoint_ranges() {
+    let mut coverage = SeqCoverage::default();
+    for seq in [1, 2, 5, 3, 7, 6] {
+        coverage.insert("a", seq);
+    }
+    assert_eq!(serde_json::to_value(&coverage).unwrap(), serde_json::json!({ "a": [[1, 3], [5, 7]] }));
+    assert!(coverage.contains("a", 6) && !coverage.contains("a", 4) && !coverage.contains("a", 8));
+    assert!(!coverage.contains("b", 1));
+    coverage.insert("a", 4);
+    coverage.insert("a", 9);
+    assert_eq!(serde_json::to_value(&coverage).unwrap(), serde_json::json!({ "a": [[1, 7], [9, 9]] }));
+
+    let legacy = BoardChangeData { operations: Vec::new(), timestamp: String::new(), source_id: "a".into(), seq: None };
+    assert!(!coverage.covers(&legacy));
+}
+
+#[test]
+fn late_joiner_keeps_changes_that_race_the_snapshot() {
+    let change = |source: &str, seq: u64| BoardChangeData {
+        operations: vec![Operation::insert(vec![0], rect(&format!("{}{}", source, seq), 0.0, 0.0, 10.0, 10.0))],
+        timestamp: format!("2024-01-01T00:00:0{}Z", seq),
+        source_id: source.into(),
+        seq: Some(seq),
+    };
+    let mut server = Replica::new(BoardState::new());
+    server.apply(change("a", 1));
+    server.apply(change("a", 2));
+
+    // 后加入的一端发出请求后先收到了 a2、a3，白板服务此时只有 a1、a2
+    let buffered = vec![change("a", 2), change("a", 3)];
+    let request = SnapshotRequest { topic: "room".into(), request_id: "r".into(), source_id: "joiner".into(), timestamp: String::new() };
+    let snapshot = server.snapshot(&request, "board-server");
+    assert_eq!(snapshot.changes.len(), 2);
+    let mut joiner = Replica::from_snapshot(snapshot, buffered);
+    assert_eq!(joiner.board().children.len(), 3);
+
+    // 快照之后才到的 a1 已包含在快照里，丢弃；a3 到达白板服务；
+    // 与 a1 同时发布的 b1 排在 a2 之前，两边都要重排
+    assert!(!joiner.apply(change("a", 1)));
+    server.apply(change("a", 3));
+    for replica in [&mut server, &mut joiner] {
+        assert!(replica.apply(change("b", 1)));
+    }
+    assert_eq!(joiner.board(), server.board());
+    let ids: Vec<&str> = joiner.board().children.iter().map(|e| e.id.as_str()).collect();
+    assert_eq!(ids, ["a3", "a2", "b1", "a1"]);
+
+    // 旧版本白板服务的快照没有 changes 和 covers，elements 即当前白板
+    let legacy: BoardSnapshot = serde_json::from_value(serde_json::json!({
+        "topic": "room", "request_id": "r", "elements": server.board().children, "timestamp": "", "source_id": "old",
+    }))
+    .unwrap();
+    assert_eq!(Replica::from_snapshot(legacy, Vec::new()).board(), server.board());
+}
+
+#[test]
+fn duplicate_messages_are_applied_once() {
+    let mut replica = Replica::new(BoardState::new());
+    let change = BoardChangeData {
+        operations: vec![Operation::insert(vec![0], rect("a", 0.0, 0.0, 100.0, 60.0))],
+        timestamp: "2024-01-01T00:00:00Z".into(),
+        source_id: "peer-0".into(),
+        seq: Some(1),
+    };
+    assert!(replica.apply(change.clone()));
+    assert!(!replica.apply(change));
+    assert_eq!(replica.board().children.len(), 1);
+    assert_eq!(replica.len(), 1);
+}
+
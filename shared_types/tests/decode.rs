@@ -0,0 +1,126 @@
+//! 线上白板变化的解码：操作按 `type` 得到正确的变体，无效消息返回错误而不是 panic。
+//! 这里的用例来自 fuzz/ 下的模糊测试发现的问题。
+
+use shared_types::board::BoardState;
+use shared_types::history::BoardHistory;
+use shared_types::*;
+
+/// 与 DDS 和 WebSocket 桥一样直接用 serde 解码，操作的校验在 `Operation` 的反序列化里
+fn decode(json: &str) -> Result<BoardChangeData, String> {
+    serde_json::from_str(json).map_err(|e| e.to_string())
+}
+
+fn change_json(operations: &str) -> String {
+    format!(r#"{{"operations":[{}],"timestamp":"2024-01-01T00:00:00Z","source_id":"peer-1"}}"#, operations)
+}
+
+#[test]
+fn operations_decode_as_declared_kind() {
+    let json = change_json(
+        r#"{"type":"insert_node","path":[0],"node":{"id":"a"}},
+           {"type":"set_node","path":[0],"node":{"id":"a","shape":"ellipse"}},
+           {"type":"remove_node","path":[0],"node":{"id":"a"}}"#,
+    );
+    let change = decode(&json).unwrap();
+    assert!(matches!(change.operations[0], Operation::Insert(_)));
+    assert!(matches!(change.operations[1], Operation::Set(_)));
+    assert!(matches!(change.operations[2], Operation::Remove(_)));
+
+    let mut board = BoardState::new();
+    assert!(board.apply_all(&change.operations).is_empty());
+    assert!(board.children.is_empty());
+}
+
+#[test]
+fn unknown_operation_type_is_rejected() {
+    let json = change_json(r#"{"type":"move_node","path":[0],"node":{"id":"a"}}"#);
+    let err = decode(&json).unwrap_err();
+    assert!(err.contains("move_node"), "{}", err);
+}
+
+#[test]
+fn empty_path_is_rejected() {
+    let json = change_json(r#"{"type":"set_node","path":[],"node":{"id":"a"}}"#);
+    assert!(decode(&json).is_err());
+}
+
+#[test]
+fn malformed_messages_return_errors() {
+    for json in [
+        "",
+        "null",
+        "[]",
+        r#"{"operations":{}}"#,
+        r#"{"operations":[{"type":"insert_node","path":[-1],"node":{"id":"a"}}],"timestamp":"","source_id":""}"#,
+        r#"{"operations":[{"type":"insert_node","path":[0],"node":{}}],"timestamp":"","source_id":""}"#,
+        &format!("{}{}", "[".repeat(10_000), "]".repeat(10_000)),
+    ] {
+        assert!(decode(json).is_err(), "{}", json);
+    }
+}
+
+#[test]
+fn coordinates_survive_reencoding() {
+    let json = change_json(r#"{"type":"insert_node","path":[0],"node":{"id":"a","points":[[10016582032024286527599,0.1]]}}"#);
+    let change = decode(&json).unwrap();
+    let again = decode(&serde_json::to_string(&change).unwrap()).unwrap();
+    assert_eq!(again, change);
+}
+
+#[test]
+fn history_restores_operation_kinds() {
+    let dir = std::env::temp_dir().join(format!("drawnix-decode-{}", std::process::id()));
+    let path = dir.join("history.jsonl");
+    let _ = std::fs::remove_file(&path);
+
+    let rect = |x: f64| PlaitElement {
+        id: "a".into(),
+        element_type: "geometry".into(),
+        shape: "rectangle".into(),
+        points: vec![Point(x, 0.0), Point(x + 10.0, 10.0)],
+        children: None,
+        extra: Default::default(),
+    };
+    {
+        let mut history = BoardHistory::open(&path).unwrap();
+        for op in [Operation::insert(vec![0], rect(0.0)), Operation::set(vec![0], rect(50.0))] {
+            let change = BoardChangeData {
+                operations: vec![op],
+                timestamp: "2024-01-01T00:00:00Z".into(),
+                source_id: "peer-1".into(),
+                seq: None,
+            };
+            history.record_change(&change).unwrap();
+        }
+    }
+
+    // 重新打开后 set_node 仍是更新而不是再插入一个
+    let board = BoardHistory::open(&path).unwrap().current();
+    assert_eq!(board.children, vec![rect(50.0)]);
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn wire_format_is_unchanged() {
+    let op = Operation::set(vec![1, 0], PlaitElement {
+        id: "a".into(),
+        element_type: String::new(),
+        shape: String::new(),
+        points: vec![Point(1.0, 2.0)],
+        children: None,
+        extra: Default::default(),
+    });
+    assert_eq!(
+        serde_json::to_string(&op).unwrap(),
+        r#"{"type":"set_node","path":[1,0],"node":{"id":"a","points":[[1.0,2.0]]}}"#
+    );
+    let decoded: Operation = serde_json::from_str(&serde_json::to_string(&op).unwrap()).unwrap();
+    assert_eq!(decoded, op);
+}
+
+#[test]
+fn missing_operation_type_is_rejected() {
+    let json = change_json(r#"{"path":[0],"node":{"id":"a"}}"#);
+    let err = decode(&json).unwrap_err();
+    assert!(err.contains("type"), "{}", err);
+}
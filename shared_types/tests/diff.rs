@@ -0,0 +1,145 @@
+//! 结构化差异：报告按 id 对齐，生成的操作应用到旧白板上得到新白板。
+
+mod common;
+
+use common::{element, rect};
+use shared_types::board::BoardState;
+use shared_types::diff::{diff_elements, diff_operations, PropertyKind};
+use shared_types::*;
+
+fn mind_map(id: &str, children: Vec<PlaitElement>) -> PlaitElement {
+    let mut node = element(&format!(r#"{{"id":"{}","type":"mindmap","points":[[0,0]],"data":{{"topic":{{"children":[{{"text":"{}"}}]}}}}}}"#, id, id));
+    node.children = Some(children);
+    node
+}
+
+fn node(id: &str, topic: &str) -> PlaitElement {
+    element(&format!(r#"{{"id":"{}","data":{{"topic":{{"children":[{{"text":"{}"}}]}}}}}}"#, id, topic))
+}
+
+/// 按顺序把操作应用到 `before` 上
+fn replay(before: &[PlaitElement], ops: &[Operation]) -> Vec<PlaitElement> {
+    let mut board = BoardState::from_elements(before.to_vec());
+    assert_eq!(board.apply_all(ops), Vec::<String>::new());
+    board.children
+}
+
+fn assert_round_trip(before: &[PlaitElement], after: &[PlaitElement]) -> Vec<Operation> {
+    let ops = diff_operations(before, after);
+    assert_eq!(replay(before, &ops), after);
+    ops
+}
+
+#[test]
+fn reports_added_removed_moved_and_changed() {
+    let before = vec![rect("a", 0.0, 0.0, 10.0, 10.0), rect("b", 0.0, 0.0, 10.0, 10.0), rect("c", 0.0, 0.0, 10.0, 10.0), mind_map("m", vec![node("x", "一"), node("y", "二")])];
+    let mut moved_c = rect("c", 5.0, 0.0, 10.0, 10.0);
+    moved_c.extra.insert("strokeColor".into(), "#f00".into());
+    let after = vec![rect("new", 0.0, 0.0, 10.0, 10.0), moved_c, rect("a", 0.0, 0.0, 10.0, 10.0), mind_map("m", vec![node("y", "二"), node("x", "改")])];
+
+    let diff = diff_elements(&before, &after);
+    let ids = |refs: &[shared_types::diff::ElementRef]| refs.iter().map(|r| (r.id.clone(), r.path.clone())).collect::<Vec<_>>();
+    assert_eq!(ids(&diff.added), [("new".to_string(), vec![0])]);
+    assert_eq!(ids(&diff.removed), [("b".to_string(), vec![1])]);
+    // 前面插入 new、删除 b 不算移动；c 和 a、x 和 y 交换了次序，两边都报告
+    let moved: Vec<&str> = diff.moved.iter().map(|m| m.id.as_str()).collect();
+    assert_eq!(moved, ["c", "a", "y", "x"]);
+
+    let changed: Vec<(&str, Vec<(&str, PropertyKind)>)> =
+        diff.changed.iter().map(|c| (c.id.as_str(), c.changes.iter().map(|p| (p.key.as_str(), p.kind)).collect())).collect();
+    assert_eq!(changed, [("c", vec![("points", PropertyKind::Points), ("strokeColor", PropertyKind::Style)]), ("x", vec![("data", PropertyKind::Text)])]);
+    assert_eq!(diff.changed[0].path, [1]);
+
+    let text = diff.to_text();
+    assert!(text.contains("➕ 新增 new (geometry/rectangle) @ [0]"), "{}", text);
+    assert!(text.contains("📊 新增 1，删除 1，移动 4，修改 2"), "{}", text);
+    assert!(diff_elements(&after, &after).is_empty());
+}
+
+#[test]
+fn operations_round_trip_nested_changes() {
+    let before = vec![rect("a", 0.0, 0.0, 10.0, 10.0), mind_map("m", vec![node("x", "一"), node("y", "二")]), rect("b", 0.0, 0.0, 10.0, 10.0)];
+
+    // 只改子节点时不替换整个导图
+    let after = vec![before[0].clone(), mind_map("m", vec![node("y", "二"), node("z", "三"), node("x", "一")]), before[2].clone()];
+    let ops = assert_round_trip(&before, &after);
+    assert!(ops.iter().all(|op| op.path().len() == 2), "{:?}", ops);
+
+    // 叶子节点新增子节点、节点跨层移动、导图本身修改
+    let mut leaf = rect("a", 0.0, 0.0, 10.0, 10.0);
+    leaf.children = Some(vec![node("x", "一")]);
+    let after = vec![mind_map("m", vec![node("y", "改")]), rect("b", 1.0, 1.0, 10.0, 10.0), leaf];
+    assert_round_trip(&before, &after);
+
+    assert!(diff_operations(&before, &before).is_empty());
+    assert_round_trip(&before, &[]);
+    assert_round_trip(&[], &before);
+}
+
+#[test]
+fn operations_handle_duplicate_ids() {
+    let before = vec![rect("a", 0.0, 0.0, 10.0, 10.0), rect("a", 50.0, 0.0, 10.0, 10.0), rect("b", 0.0, 0.0, 10.0, 10.0)];
+    assert_round_trip(&before, &[rect("b", 0.0, 0.0, 10.0, 10.0), rect("a", 50.0, 0.0, 10.0, 10.0)]);
+    assert_round_trip(&before, &[rect("a", 1.0, 0.0, 10.0, 10.0), rect("a", 2.0, 0.0, 10.0, 10.0), rect("a", 3.0, 0.0, 10.0, 10.0)]);
+    assert_round_trip(&[rect("b", 0.0, 0.0, 10.0, 10.0)], &before);
+}
+
+#[test]
+fn concurrent_versions_transform_into_each_other() {
+    // 两个对端从同一白板出发各自修改，差异操作能把任一方的白板变成另一方的
+    let base = vec![rect("a", 0.0, 0.0, 10.0, 10.0), mind_map("m", vec![node("x", "一")]), rect("b", 0.0, 0.0, 10.0, 10.0)];
+    let ours = vec![rect("a", 9.0, 9.0, 10.0, 10.0), mind_map("m", vec![node("x", "一"), node("o", "我方")]), rect("b", 0.0, 0.0, 10.0, 10.0)];
+    let theirs = vec![rect("b", 0.0, 0.0, 10.0, 10.0), rect("t", 0.0, 0.0, 10.0, 10.0), mind_map("m", vec![node("x", "对方")])];
+
+    assert_round_trip(&ours, &theirs);
+    assert_round_trip(&theirs, &ours);
+    // 先应用一方的操作，再应用从它到另一方的操作，结果与直接得到另一方相同
+    let to_ours = diff_operations(&base, &ours);
+    let mut ops = to_ours.clone();
+    ops.extend(diff_operations(&ours, &theirs));
+    assert_eq!(replay(&base, &ops), theirs);
+
+    let diff = diff_elements(&ours, &theirs);
+    let removed: Vec<&str> = diff.removed.iter().map(|r| r.id.as_str()).collect();
+    assert_eq!(removed, ["a", "o"]);
+}
+
+/// 随机的元素树：顶层若干图形和思维导图，导图下有子节点，id 不重复
+fn random_tree(rng: &mut fastrand::Rng, pool: &mut Vec<String>, next: &mut usize) -> Vec<PlaitElement> {
+    let mut take = |rng: &mut fastrand::Rng| {
+        if !pool.is_empty() && rng.bool() {
+            pool.swap_remove(rng.usize(0..pool.len()))
+        } else {
+            *next += 1;
+            format!("n{}", next)
+        }
+    };
+    (0..rng.usize(0..6))
+        .map(|_| {
+            let id = take(rng);
+            if rng.u8(0..3) == 0 {
+                let children = (0..rng.usize(0..4)).map(|_| node(&take(rng), &rng.u8(0..3).to_string())).collect();
+                mind_map(&id, children)
+            } else {
+                rect(&id, rng.u8(0..3) as f64, 0.0, 10.0, 10.0)
+            }
+        })
+        .collect()
+}
+
+fn ids_of(elements: &[PlaitElement]) -> Vec<String> {
+    elements.iter().flat_map(|e| std::iter::once(e.id.clone()).chain(e.children.iter().flatten().map(|c| c.id.clone()))).collect()
+}
+
+#[test]
+fn random_operations_round_trip() {
+    let mut rng = fastrand::Rng::with_seed(27);
+    for _ in 0..500 {
+        let mut next = 0;
+        let before = random_tree(&mut rng, &mut Vec::new(), &mut next);
+        // 新白板复用旧白板的一部分 id，模拟移动、修改和跨层移动
+        let mut pool = ids_of(&before);
+        let after = random_tree(&mut rng, &mut pool, &mut next);
+        assert_round_trip(&before, &after);
+    }
+}
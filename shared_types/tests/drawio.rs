@@ -0,0 +1,139 @@
+//! draw.io 导入，需要开启 `drawio` 特性：`cargo test --features drawio`
+
+#![cfg(feature = "drawio")]
+
+use shared_types::drawio::{import_drawio, DrawioOptions};
+use shared_types::*;
+
+/// draw.io 默认保存的压缩页面：一个圆角矩形经折线连到菱形
+const COMPRESSED: &str = r#"<mxfile host="app.diagrams.net"><diagram id="p1" name="流程">rZTLcoMwDEW/RnuC83CWQElWXeULHFAwUxNljMjj72vA04RC+pjJghlJ11egg20QSXXdWnXS75SjAZGCSCwR91F1TdAYCIMyB/EGYRi4B8LNE3XWqcFJWTzyXwyqN5yVabCvQLqAOAEZtEG0Ahn3S2q+Gb/EUnPMse0wAxFfdMm4O6msVS9uElfTXBkvH0pjEjJkO6/IFcpD5uo1W/rAB2WZSdwfnOI/CS3j9elYXcnPtEWqkO3NLfGGeT91cBumlzJn7f0eTKCxLLRvuvQ1Vfd58dX4jtAFnuI00f0E0XWLcx1BKiHegFyB2Iyhaqr2Tf070Bfg+c5HjPnICTzyBXhwAs8cYtnuMx+4nbeE6A2ixQgS5gXufEqWNRV0VCa9V8egWsvPmNwbqLEZDk4EK1sgP/zSMUyLRnF5Hnb/BxuX3g96pw3ugU8=</diagram></mxfile>"#;
+
+const PLAIN: &str = r##"<mxfile>
+  <diagram name="草稿"><mxGraphModel><root><mxCell id="0"/><mxCell id="1" parent="0"/></root></mxGraphModel></diagram>
+  <diagram name="架构">
+    <mxGraphModel>
+      <root>
+        <mxCell id="0"/>
+        <mxCell id="1" parent="0"/>
+        <mxCell id="g" style="group" vertex="1" parent="1"><mxGeometry x="100" y="100" width="300" height="200" as="geometry"/></mxCell>
+        <UserObject label="数据库" link="https://example.com" id="db">
+          <mxCell style="shape=cylinder3;fillColor=none;dashed=1;rotation=90;opacity=50;" vertex="1" parent="g"><mxGeometry x="20" y="30" width="60" height="80" as="geometry"/></mxCell>
+        </UserObject>
+        <mxCell id="t" value="&lt;b&gt;说明&lt;/b&gt;&lt;br&gt;第二行 &amp;amp; 更多" style="text;html=1;align=left;fontColor=#ff0000;" vertex="1" parent="1"><mxGeometry x="0" y="0" width="80" height="40" as="geometry"/></mxCell>
+        <mxCell id="s" value="" style="shape=mxgraph.aws4.lambda;" vertex="1" parent="1"><mxGeometry x="400" y="0" width="40" height="40" as="geometry"/></mxCell>
+        <mxCell id="i" value="" style="shape=image;image=data:image/png,abc;" vertex="1" parent="1"><mxGeometry x="500" y="0" width="40" height="40" as="geometry"/></mxCell>
+        <mxCell id="e1" value="读" style="curved=1;endArrow=block;endFill=0;startArrow=dash;strokeWidth=2;exitX=0.5;exitY=0;" edge="1" parent="1" source="db" target="s">
+          <mxGeometry x="-0.5" relative="1" as="geometry"><Array as="points"><mxPoint x="300" y="20"/></Array></mxGeometry>
+        </mxCell>
+        <mxCell id="l1" value="写" style="edgeLabel;html=1;" vertex="1" connectable="0" parent="e1"><mxGeometry x="0.5" relative="1" as="geometry"/></mxCell>
+        <mxCell id="e2" style="endArrow=none;" edge="1" parent="1" target="t"><mxGeometry relative="1" as="geometry"><mxPoint x="0" y="300" as="sourcePoint"/></mxGeometry></mxCell>
+        <mxCell id="e3" edge="1" parent="1" source="t"><mxGeometry relative="1" as="geometry"/></mxCell>
+      </root>
+    </mxGraphModel>
+  </diagram>
+</mxfile>"##;
+
+fn find<'a>(elements: &'a [PlaitElement], id: &str) -> &'a PlaitElement {
+    elements.iter().find(|e| e.id == id).unwrap_or_else(|| panic!("没有 {}", id))
+}
+
+#[test]
+fn imports_compressed_pages() {
+    let conversion = import_drawio(COMPRESSED, &DrawioOptions::default()).unwrap();
+    assert!(conversion.unsupported.is_empty());
+    let elements = conversion.output;
+    assert_eq!(elements.len(), 3);
+
+    let start = find(&elements, "drawio-a");
+    assert_eq!(start.shape, "roundRectangle");
+    assert_eq!(start.points, vec![Point(40.0, 40.0), Point(160.0, 100.0)]);
+    assert_eq!(start.extra["text"]["children"][0]["text"], "开始");
+    assert_eq!(start.extra["fill"], "#dae8fc");
+    assert_eq!(start.extra["strokeColor"], "#6c8ebf");
+    assert_eq!(find(&elements, "drawio-b").shape, "diamond");
+    assert_eq!(find(&elements, "drawio-b").extra["fill"], "#ffffff");
+
+    // 折线两端绑定图形，从朝向对方的一侧连出
+    let line = find(&elements, "drawio-e");
+    assert_eq!(line.element_type, "line");
+    assert_eq!(line.shape, "elbow");
+    assert_eq!(line.points, vec![Point(160.0, 70.0), Point(240.0, 70.0)]);
+    assert_eq!(line.extra["source"]["boundId"], "drawio-a");
+    assert_eq!(line.extra["source"]["connection"], serde_json::json!([1.0, 0.5]));
+    assert_eq!(line.extra["target"]["connection"], serde_json::json!([0.0, 0.5]));
+    assert_eq!(line.extra["target"]["marker"], "arrow");
+    assert_eq!(line.extra["texts"][0]["text"]["children"][0]["text"], "下一步");
+    assert_eq!(line.extra["texts"][0]["position"], 0.5);
+}
+
+#[test]
+fn imports_styles_groups_and_labels() {
+    let options = DrawioOptions { page: Some("架构".into()), ..Default::default() };
+    let conversion = import_drawio(PLAIN, &options).unwrap();
+    let elements = &conversion.output;
+    let ids: Vec<&str> = elements.iter().map(|e| e.id.as_str()).collect();
+    assert_eq!(ids, ["drawio-db", "drawio-t", "drawio-s", "drawio-e1", "drawio-e2"]);
+
+    // 分组内的坐标相对于分组
+    let db = find(elements, "drawio-db");
+    assert_eq!(db.shape, "database");
+    assert_eq!(db.points, vec![Point(120.0, 130.0), Point(180.0, 210.0)]);
+    assert!(db.extra.get("fill").is_none());
+    assert_eq!(db.extra["strokeStyle"], "dashed");
+    assert_eq!(db.extra["opacity"], 0.5);
+    assert!((db.extra["angle"].as_f64().unwrap() - std::f64::consts::FRAC_PI_2).abs() < 1e-9);
+    assert_eq!(db.extra["text"]["children"][0]["text"], "数据库");
+
+    let text = find(elements, "drawio-t");
+    assert_eq!(text.shape, "text");
+    assert!(text.extra.get("fill").is_none());
+    assert_eq!(text.extra["text"]["children"][0]["text"], "说明\n第二行 & 更多");
+    assert_eq!(text.extra["text"]["children"][0]["color"], "#ff0000");
+    assert_eq!(text.extra["text"]["align"], "left");
+
+    let curve = find(elements, "drawio-e1");
+    assert_eq!(curve.shape, "curve");
+    assert_eq!(curve.points, vec![Point(150.0, 130.0), Point(300.0, 20.0), Point(400.0, 20.0)]);
+    assert_eq!(curve.extra["source"]["marker"], "single-line");
+    assert_eq!(curve.extra["target"]["marker"], "hollow-triangle");
+    assert_eq!(curve.extra["target"]["boundId"], "drawio-s");
+    assert_eq!(curve.extra["strokeWidth"], 2.0);
+    let texts = curve.extra["texts"].as_array().unwrap();
+    assert_eq!(texts.len(), 2);
+    assert_eq!(texts[0]["position"], 0.25);
+    assert_eq!(texts[1]["text"]["children"][0]["text"], "写");
+    assert_eq!(texts[1]["position"], 0.75);
+
+    let free = find(elements, "drawio-e2");
+    assert_eq!(free.points, vec![Point(0.0, 300.0), Point(40.0, 40.0)]);
+    assert!(free.extra["source"].get("boundId").is_none());
+    assert_eq!(free.extra["target"]["marker"], "none");
+
+    let reported: Vec<(&str, &str)> = conversion.unsupported.iter().map(|u| (u.id.as_str(), u.kind.as_str())).collect();
+    assert_eq!(reported, [("s", "mxgraph.aws4.lambda"), ("i", "image"), ("e3", "edge")]);
+}
+
+#[test]
+fn selects_pages_and_moves_to_origin() {
+    let by_index = DrawioOptions { page: Some("2".into()), origin: Some(Point(1000.0, 500.0)), id_prefix: "x".into() };
+    let elements = import_drawio(PLAIN, &by_index).unwrap().output;
+    let bounds = geometry::bounds_of(&elements).unwrap();
+    assert_eq!((bounds.min_x, bounds.min_y), (1000.0, 500.0));
+    assert!(elements.iter().all(|e| e.id.starts_with("x-")));
+
+    assert!(import_drawio(PLAIN, &DrawioOptions::default()).unwrap().output.is_empty());
+    let missing = import_drawio(PLAIN, &DrawioOptions { page: Some("3".into()), ..Default::default() }).unwrap_err();
+    assert!(missing.contains("草稿、架构"), "{}", missing);
+    assert!(import_drawio("<svg/>", &DrawioOptions::default()).is_err());
+}
+
+#[test]
+fn oversized_compressed_pages_are_rejected() {
+    // 几十 KB 的压缩数据展开成超过上限的页面：在上限处停止解压并报错
+    use base64::Engine;
+    let bomb = miniz_oxide::deflate::compress_to_vec(&vec![b'a'; 65 * 1024 * 1024], 1);
+    let data = base64::engine::general_purpose::STANDARD.encode(bomb);
+    let xml = format!(r#"<mxfile><diagram name="大">{}</diagram></mxfile>"#, data);
+    let err = import_drawio(&xml, &DrawioOptions::default()).map(|_| ()).unwrap_err();
+    assert!(err.contains("超过"), "{}", err);
+}
@@ -0,0 +1,199 @@
+use serde_json::{json, Value};
+use shared_types::document::DrawnixDocument;
+use shared_types::excalidraw::{from_excalidraw, to_excalidraw};
+use shared_types::*;
+
+fn element(value: Value) -> PlaitElement {
+    serde_json::from_value(value).unwrap()
+}
+
+/// Excalidraw 元素的公共字段，`fields` 覆盖或补充
+fn excalidraw(id: &str, kind: &str, fields: Value) -> Value {
+    let mut value = json!({
+        "id": id, "type": kind, "x": 0, "y": 0, "width": 100, "height": 50, "angle": 0,
+        "strokeColor": "#1e1e1e", "backgroundColor": "transparent", "fillStyle": "hachure",
+        "strokeWidth": 1, "strokeStyle": "solid", "roughness": 1, "opacity": 100,
+        "groupIds": [], "frameId": null, "roundness": null, "seed": 1, "version": 1,
+        "versionNonce": 1, "isDeleted": false, "boundElements": null, "updated": 1, "link": null, "locked": false
+    });
+    for (key, field) in fields.as_object().unwrap() {
+        value[key] = field.clone();
+    }
+    value
+}
+
+fn scene(elements: Vec<Value>) -> String {
+    json!({ "type": "excalidraw", "version": 2, "source": "https://excalidraw.com", "elements": elements, "appState": {}, "files": {} })
+        .to_string()
+}
+
+fn find<'a>(document: &'a DrawnixDocument, id: &str) -> &'a PlaitElement {
+    document.elements.iter().find(|e| e.id == id).unwrap_or_else(|| panic!("缺少 {}", id))
+}
+
+#[test]
+fn imports_common_excalidraw_elements() {
+    let json = scene(vec![
+        excalidraw("box", "rectangle", json!({ "roundness": { "type": 3 }, "backgroundColor": "#ffc9c9", "boundElements": [{ "id": "box-text", "type": "text" }, { "id": "arrow", "type": "arrow" }] })),
+        excalidraw("box-text", "text", json!({ "x": 10, "y": 15, "width": 80, "height": 20, "text": "你好", "textAlign": "center", "containerId": "box", "strokeColor": "#e03131" })),
+        excalidraw("round", "ellipse", json!({ "x": 200, "strokeStyle": "dashed", "opacity": 50 })),
+        excalidraw("gem", "diamond", json!({ "x": 400, "angle": 0.5 })),
+        excalidraw("note", "text", json!({ "y": 100, "height": 25, "text": "独立文字", "textAlign": "left" })),
+        excalidraw("arrow", "arrow", json!({
+            "x": 100, "y": 25, "width": 100, "height": 0, "points": [[0, 0], [100, 0]],
+            "startBinding": { "elementId": "box", "focus": 0, "gap": 1 },
+            "endBinding": { "elementId": "round", "focus": 0, "gap": 1, "fixedPoint": [0, 0.5] },
+            "startArrowhead": null, "endArrowhead": "triangle",
+            "boundElements": [{ "id": "arrow-label", "type": "text" }]
+        })),
+        excalidraw("arrow-label", "text", json!({ "width": 32, "height": 20, "text": "是", "containerId": "arrow" })),
+        excalidraw("pen", "freedraw", json!({ "x": 10, "y": 200, "points": [[0, 0], [5, 5], [10, 0]], "strokeColor": "#1971c2" })),
+        excalidraw("pic", "image", json!({ "fileId": "f1" })),
+        excalidraw("frame", "frame", json!({ "name": "画框" })),
+        excalidraw("gone", "rectangle", json!({ "isDeleted": true })),
+    ]);
+    let conversion = from_excalidraw(&json).unwrap();
+    let document = &conversion.output;
+    let ids: Vec<&str> = document.elements.iter().map(|e| e.id.as_str()).collect();
+    assert_eq!(ids, ["box", "round", "gem", "note", "arrow", "pen"]);
+
+    let unsupported: Vec<(&str, &str)> = conversion.unsupported.iter().map(|u| (u.id.as_str(), u.kind.as_str())).collect();
+    assert_eq!(unsupported, [("pic", "image"), ("frame", "frame")]);
+    assert!(conversion.to_text().contains("2 处内容未能转换"));
+
+    let rect = find(document, "box");
+    assert_eq!((rect.element_type.as_str(), rect.shape.as_str()), ("geometry", "roundRectangle"));
+    assert_eq!(rect.points, vec![Point(0.0, 0.0), Point(100.0, 50.0)]);
+    assert_eq!(rect.extra["fill"], "#ffc9c9");
+    assert!(rect.extra.get("strokeColor").is_none(), "Excalidraw 默认描边色对应 Drawnix 默认值");
+    assert_eq!(rect.extra["text"], json!({ "children": [{ "text": "你好", "color": "#e03131" }], "align": "center" }));
+
+    let ellipse = find(document, "round");
+    assert_eq!(ellipse.shape, "ellipse");
+    assert_eq!((ellipse.extra["strokeStyle"].clone(), ellipse.extra["opacity"].clone()), (json!("dashed"), json!(0.5)));
+    assert_eq!(find(document, "gem").extra["angle"], 0.5);
+
+    let note = find(document, "note");
+    assert_eq!(note.shape, "text");
+    assert_eq!(note.extra["text"]["children"][0]["text"], "独立文字");
+
+    let arrow = find(document, "arrow");
+    assert_eq!(arrow.element_type, "line");
+    assert_eq!(arrow.shape, "straight");
+    assert_eq!(arrow.points, vec![Point(100.0, 25.0), Point(200.0, 25.0)]);
+    // 没有 fixedPoint 时按端点在图形上的位置换算连接点
+    assert_eq!(arrow.extra["source"], json!({ "marker": "none", "connection": [1, 0.5], "boundId": "box" }));
+    assert_eq!(arrow.extra["target"], json!({ "marker": "solid-triangle", "connection": [0, 0.5], "boundId": "round" }));
+    assert_eq!(arrow.extra["texts"][0]["text"]["children"][0]["text"], "是");
+
+    let pen = find(document, "pen");
+    assert_eq!((pen.element_type.as_str(), pen.extra["strokeColor"].as_str()), ("freehand", Some("#1971c2")));
+    assert_eq!(pen.points, vec![Point(10.0, 200.0), Point(15.0, 205.0), Point(20.0, 200.0)]);
+}
+
+#[test]
+fn drawnix_round_trips_through_excalidraw() {
+    let elements = vec![
+        element(json!({
+            "id": "a", "type": "geometry", "shape": "rectangle", "points": [[0, 0], [120, 60]], "angle": 0, "opacity": 1,
+            "strokeWidth": 2, "strokeColor": "#e03131", "fill": "#ffec99", "textHeight": 20,
+            "text": { "children": [{ "text": "开始" }], "align": "center" }
+        })),
+        element(json!({ "id": "b", "type": "geometry", "shape": "roundRectangle", "points": [[200, 0], [320, 60]], "angle": 0.25, "opacity": 0.5, "strokeWidth": 4, "strokeStyle": "dotted" })),
+        element(json!({ "id": "c", "type": "geometry", "shape": "ellipse", "points": [[0, 100], [80, 140]], "angle": 0, "opacity": 1, "strokeWidth": 2 })),
+        element(json!({ "id": "d", "type": "geometry", "shape": "diamond", "points": [[200, 100], [340, 170]], "angle": 0, "opacity": 1, "strokeWidth": 2, "strokeStyle": "dashed" })),
+        element(json!({
+            "id": "t", "type": "geometry", "shape": "text", "points": [[0, 300], [96.5, 320]], "angle": 0, "opacity": 1, "textHeight": 20,
+            "text": { "children": [{ "text": "标题", "color": "#1971c2" }], "align": "left" }
+        })),
+        element(json!({
+            "id": "l1", "type": "line", "shape": "straight", "points": [[120, 30], [200, 30]], "opacity": 1, "strokeWidth": 2,
+            "source": { "marker": "none", "connection": [1, 0.5], "boundId": "a" },
+            "target": { "marker": "arrow", "connection": [0, 0.5], "boundId": "b" },
+            "texts": [{ "text": { "children": [{ "text": "是" }] }, "position": 0.5, "width": 14, "height": 20 }]
+        })),
+        element(json!({
+            "id": "l2", "type": "line", "shape": "elbow", "points": [[40, 60], [200, 135]], "opacity": 1, "strokeWidth": 2,
+            "source": { "marker": "none", "connection": [0.5, 1], "boundId": "a" },
+            "target": { "marker": "hollow-triangle", "connection": [0, 0.5], "boundId": "d" },
+            "texts": []
+        })),
+        element(json!({
+            "id": "l3", "type": "line", "shape": "curve", "points": [[0, 400], [50, 380.5], [100, 400]], "opacity": 1, "strokeWidth": 2,
+            "source": { "marker": "single-line" }, "target": { "marker": "none" }, "texts": []
+        })),
+        element(json!({ "id": "l4", "type": "line", "shape": "straight", "points": [[0, 450], [100, 450]], "opacity": 1, "strokeWidth": 1, "source": { "marker": "none" }, "target": { "marker": "none" }, "texts": [] })),
+        element(json!({ "id": "p", "type": "freehand", "shape": "feltTipPen", "points": [[3.25, 500], [10.5, 512.75], [20, 501]], "opacity": 1, "strokeWidth": 2 })),
+    ];
+    let document = DrawnixDocument::new(elements.clone());
+
+    let exported = to_excalidraw(&document);
+    assert!(exported.unsupported.is_empty(), "{}", exported.to_text());
+    let scene = &exported.output;
+    assert_eq!(scene["type"], "excalidraw");
+    let kinds: Vec<&str> = scene["elements"].as_array().unwrap().iter().map(|e| e["type"].as_str().unwrap()).collect();
+    assert_eq!(kinds, ["rectangle", "text", "rectangle", "ellipse", "diamond", "text", "arrow", "text", "arrow", "arrow", "line", "freedraw"]);
+    // 图形反向记录绑定到它的连线和文字
+    let bound = &scene["elements"][0]["boundElements"];
+    assert_eq!(bound.as_array().unwrap().len(), 3, "{}", bound);
+
+    let imported = from_excalidraw(&scene.to_string()).unwrap();
+    assert!(imported.unsupported.is_empty(), "{}", imported.to_text());
+    for (before, after) in elements.iter().zip(&imported.output.elements) {
+        assert_eq!(before, after);
+    }
+    assert_eq!(imported.output.elements.len(), elements.len());
+}
+
+/// 整数和浮点数统一成浮点数再比较
+fn as_float(value: &Value) -> Value {
+    match value {
+        Value::Number(n) => json!(n.as_f64().unwrap()),
+        Value::Array(items) => Value::Array(items.iter().map(as_float).collect()),
+        other => other.clone(),
+    }
+}
+
+#[test]
+fn excalidraw_round_trips_through_drawnix() {
+    let json = scene(vec![
+        excalidraw("box", "rectangle", json!({ "strokeColor": "#2f9e44", "backgroundColor": "#b2f2bb", "strokeWidth": 2, "opacity": 80, "boundElements": [{ "id": "box-text", "type": "text" }] })),
+        excalidraw("box-text", "text", json!({ "x": 5, "y": 15, "width": 90, "height": 20, "text": "第一行\n第二行", "textAlign": "center", "containerId": "box", "strokeWidth": 2, "opacity": 80 })),
+        excalidraw("line", "line", json!({ "x": 0, "y": 100, "width": 60, "height": 20, "points": [[0, 0], [30, 20], [60, 0]], "strokeStyle": "dashed", "startArrowhead": null, "endArrowhead": null })),
+    ]);
+    let drawnix = from_excalidraw(&json).unwrap().output;
+    let back = to_excalidraw(&drawnix).output;
+    let original: Value = serde_json::from_str(&json).unwrap();
+
+    let keys = ["type", "x", "y", "width", "height", "angle", "strokeColor", "backgroundColor", "strokeWidth", "strokeStyle", "opacity", "points", "text", "textAlign", "containerId"];
+    let pick = |element: &Value| keys.iter().map(|k| element.get(*k).map(as_float)).collect::<Vec<_>>();
+    let original = original["elements"].as_array().unwrap();
+    let back = back["elements"].as_array().unwrap();
+    assert_eq!(back.len(), original.len());
+    for (before, after) in original.iter().zip(back) {
+        assert_eq!(pick(before), pick(after), "{}", before["id"]);
+    }
+}
+
+#[test]
+fn reports_drawnix_elements_excalidraw_cannot_hold() {
+    let document = DrawnixDocument::new(vec![
+        element(json!({ "id": "m", "type": "mindmap", "data": { "topic": { "children": [{ "text": "主题" }] } }, "children": [], "points": [[0, 0]] })),
+        element(json!({ "id": "flow", "type": "geometry", "shape": "process", "points": [[0, 0], [10, 10]] })),
+        element(json!({ "id": "img", "type": "image", "url": "data:image/png;base64,", "points": [[0, 0], [10, 10]] })),
+        element(json!({
+            "id": "two", "type": "line", "shape": "straight", "points": [[0, 0], [100, 0]], "source": { "marker": "none" }, "target": { "marker": "arrow" },
+            "texts": [{ "text": { "children": [{ "text": "一" }] }, "position": 0.3 }, { "text": { "children": [{ "text": "二" }] }, "position": 0.7 }]
+        })),
+    ]);
+    let conversion = to_excalidraw(&document);
+    let ids: Vec<&str> = conversion.unsupported.iter().map(|u| u.id.as_str()).collect();
+    assert_eq!(ids, ["m", "flow", "img", "two"]);
+    assert_eq!(conversion.unsupported[1].kind, "geometry/process");
+    // 连线本身和第一段文字仍然导出
+    let kinds: Vec<&str> = conversion.output["elements"].as_array().unwrap().iter().map(|e| e["type"].as_str().unwrap()).collect();
+    assert_eq!(kinds, ["arrow", "text"]);
+
+    assert!(from_excalidraw(r#"{"type":"drawnix","elements":[]}"#).is_err());
+    assert!(from_excalidraw("not json").is_err());
+}
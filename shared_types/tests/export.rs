@@ -0,0 +1,68 @@
+//! 白板导出：仓库里的示例文件能完整渲染，特殊字符被转义。
+
+mod common;
+
+use common::{element, labeled_rect, sample};
+use shared_types::geometry::Rect;
+use shared_types::svg::{render_svg, SvgOptions};
+
+#[test]
+fn sample_board_renders_every_kind() {
+    let image = render_svg(&sample().elements, &SvgOptions::default());
+    assert!(image.svg.starts_with("<svg "));
+    assert!(image.svg.trim_end().ends_with("</svg>"));
+
+    // 流程图形状、连线文字和思维导图节点都在
+    for text in ["开始", "过程", "判断", "结束", "是", "否", "思维导图", "观点一", "观点四"] {
+        assert!(image.svg.contains(&format!(">{}</tspan>", text)), "缺少 {}", text);
+    }
+    assert!(image.svg.contains(r#" rx="30""#), "terminal 应为圆角矩形");
+    assert!(image.svg.contains("<polygon"), "decision 应为菱形");
+}
+
+#[test]
+fn view_box_covers_content_with_padding() {
+    let rect = element(r#"{"id":"a","type":"geometry","shape":"rectangle","points":[[10,20],[110,70]]}"#);
+    let image = render_svg(&[rect], &SvgOptions { padding: 5.0, background: None, ..Default::default() });
+    assert_eq!((image.view_box.min_x, image.view_box.min_y), (5.0, 15.0));
+    assert_eq!((image.view_box.width(), image.view_box.height()), (110.0, 60.0));
+    assert!(!image.svg.contains(r##"fill="#ffffff""##));
+}
+
+#[test]
+fn text_is_escaped() {
+    let rect = element(
+        r#"{"id":"a\"b","type":"geometry","shape":"rectangle","points":[[0,0],[100,50]],
+            "text":{"children":[{"text":"<a & b>"}]}}"#,
+    );
+    let svg = render_svg(&[rect], &SvgOptions::default()).svg;
+    assert!(svg.contains("&lt;a &amp; b&gt;"));
+    assert!(svg.contains(r#"data-id="a&quot;b""#));
+}
+
+#[test]
+fn arrow_lines_get_heads_only_where_marked() {
+    let line = element(
+        r#"{"id":"l","type":"arrow-line","shape":"straight","points":[[0,0],[100,0]],
+            "source":{"marker":"none"},"target":{"marker":"arrow"}}"#,
+    );
+    let svg = render_svg(&[line], &SvgOptions::default()).svg;
+    assert_eq!(svg.matches("<polygon").count(), 1);
+}
+
+#[test]
+fn empty_board_is_valid_svg() {
+    let image = render_svg(&[], &SvgOptions::default());
+    assert!(image.svg.contains(r#"viewBox="-20 -20 40 40""#));
+}
+
+#[test]
+fn region_export_skips_elements_outside() {
+    let elements = [labeled_rect("in", 0.0, 0.0, "里面"), labeled_rect("edge", 70.0, 0.0, "边上"), labeled_rect("out", 500.0, 500.0, "外面")];
+    let region = Some(Rect { min_x: 0.0, min_y: 0.0, max_x: 60.0, max_y: 30.0 });
+    let image = render_svg(&elements, &SvgOptions { padding: 20.0, region, ..Default::default() });
+    // 落在留白里的元素照常绘制，区域外的不输出
+    assert!(image.svg.contains(">里面</tspan>"));
+    assert!(image.svg.contains(">边上</tspan>"));
+    assert!(!image.svg.contains("外面"));
+}
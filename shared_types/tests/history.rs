@@ -0,0 +1,181 @@
+//! 白板历史：回溯目标的解析、按序号重放、以快照为新的基准，以及与远端操作交错时的撤销 / 重做（还原到某个时刻）。
+
+mod common;
+
+use common::rect;
+use shared_types::diff::diff_operations;
+use shared_types::board::BoardState;
+use shared_types::history::{BoardHistory, HistoryRecord, HistoryTarget};
+use shared_types::replica::Replica;
+use shared_types::*;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicI64, Ordering};
+
+/// 每个用例使用独立的历史文件
+fn history_path(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("drawnix-history-{}-{}", std::process::id(), name)).join("history.jsonl");
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+/// 发布时间依次递增，与真实的消息一样按发布先后排序
+fn change(source_id: &str, operations: Vec<Operation>) -> BoardChangeData {
+    static CLOCK: AtomicI64 = AtomicI64::new(0);
+    let published = chrono::DateTime::UNIX_EPOCH + chrono::Duration::seconds(CLOCK.fetch_add(1, Ordering::Relaxed));
+    BoardChangeData { operations, timestamp: published.to_rfc3339(), source_id: source_id.into(), seq: None }
+}
+
+fn ids(history: &BoardHistory, seq: u64) -> Vec<String> {
+    history.materialize(seq).children.into_iter().map(|e| e.id).collect()
+}
+
+/// 像桌面端 `restore_board_at` 一样，把白板还原到目标时刻并作为一条新的本地变化写入历史
+fn restore(history: &mut BoardHistory, target: &HistoryTarget) -> u64 {
+    let seq = history.resolve(target).unwrap();
+    let operations = diff_operations(&history.current().children, &history.materialize(seq).children);
+    history.record_change(&change("local", operations)).unwrap()
+}
+
+#[test]
+fn resolves_seq_time_and_checkpoint_targets() {
+    let path = history_path("resolve");
+    let records = [
+        HistoryRecord::Change { seq: 1, recorded_at: "2024-01-01T10:00:00+00:00".into(), data: change("local", vec![Operation::insert(vec![0], rect("a", 0.0, 0.0, 10.0, 10.0))]) },
+        HistoryRecord::Checkpoint { seq: 2, recorded_at: "2024-01-01T10:05:00+00:00".into(), name: "初稿".into(), source_id: "local".into() },
+        HistoryRecord::Change { seq: 3, recorded_at: "2024-01-01T10:10:00+00:00".into(), data: change("remote", vec![Operation::insert(vec![1], rect("b", 0.0, 0.0, 10.0, 10.0))]) },
+    ];
+    let lines: Vec<String> = records.iter().map(|r| serde_json::to_string(r).unwrap()).collect();
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(&path, lines.join("\n") + "\n无法解析的一行\n").unwrap();
+
+    let history = BoardHistory::open(&path).unwrap();
+    assert_eq!(history.entries().len(), 3);
+    assert_eq!(history.resolve(&HistoryTarget::Seq(3)), Ok(3));
+    assert!(history.resolve(&HistoryTarget::Seq(9)).is_err());
+    assert_eq!(history.resolve(&HistoryTarget::Checkpoint("初稿".into())), Ok(2));
+    assert!(history.resolve(&HistoryTarget::Checkpoint("终稿".into())).is_err());
+
+    // 时间点取在它之前（含）最后记录的一条，时区不同也按绝对时间比较
+    assert_eq!(history.resolve(&HistoryTarget::Time("2024-01-01T10:07:00Z".into())), Ok(2));
+    assert_eq!(history.resolve(&HistoryTarget::Time("2024-01-01T18:10:00+08:00".into())), Ok(3));
+    assert!(history.resolve(&HistoryTarget::Time("2024-01-01T09:00:00Z".into())).is_err());
+    assert!(history.resolve(&HistoryTarget::Time("昨天".into())).is_err());
+
+    // 检查点本身不改变白板
+    assert_eq!(ids(&history, 2), ["a"]);
+    assert_eq!(ids(&history, 3), ["a", "b"]);
+    let _ = std::fs::remove_dir_all(path.parent().unwrap());
+}
+
+#[test]
+fn materialize_replays_interleaved_remote_changes() {
+    let path = history_path("materialize");
+    let mut history = BoardHistory::open(&path).unwrap();
+    history.record_change(&change("local", vec![Operation::insert(vec![0], rect("a", 0.0, 0.0, 10.0, 10.0))])).unwrap();
+    history.record_change(&change("remote", vec![Operation::insert(vec![0], rect("r", 50.0, 0.0, 10.0, 10.0))])).unwrap();
+    // 远端变化与本地基于旧状态的操作交错：路径失效的操作被跳过，其余照常生效
+    history
+        .record_change(&change("local", vec![Operation::set(vec![1], rect("a", 5.0, 5.0, 10.0, 10.0)), Operation::remove(vec![7], rect("x", 0.0, 0.0, 1.0, 1.0))]))
+        .unwrap();
+    history.create_checkpoint("评审前", "local").unwrap();
+    history.record_change(&change("remote", vec![Operation::remove(vec![0], rect("r", 50.0, 0.0, 10.0, 10.0))])).unwrap();
+
+    assert_eq!(ids(&history, 0), Vec::<String>::new());
+    assert_eq!(ids(&history, 1), ["a"]);
+    assert_eq!(ids(&history, 2), ["r", "a"]);
+    assert_eq!(history.materialize(3).children[1].points[0], Point(5.0, 5.0));
+    assert_eq!(ids(&history, 5), ["a"]);
+    assert_eq!(history.current(), history.materialize(5));
+    assert!(history.create_checkpoint("评审前", "local").is_err());
+    assert!(history.create_checkpoint("  ", "local").is_err());
+
+    // 重新打开得到同样的历史
+    let reopened = BoardHistory::open(&path).unwrap();
+    assert_eq!(reopened.entries().len(), 5);
+    assert_eq!(reopened.current(), history.current());
+    let _ = std::fs::remove_dir_all(path.parent().unwrap());
+}
+
+#[test]
+fn restore_undoes_and_redoes_around_remote_edits() {
+    let path = history_path("restore");
+    let mut history = BoardHistory::open(&path).unwrap();
+    history.record_change(&change("local", vec![Operation::insert(vec![0], rect("a", 0.0, 0.0, 10.0, 10.0))])).unwrap();
+    history.create_checkpoint("只有 a", "local").unwrap();
+    history.record_change(&change("remote", vec![Operation::insert(vec![1], rect("b", 20.0, 0.0, 10.0, 10.0))])).unwrap();
+    let before_undo = history.record_change(&change("local", vec![Operation::set(vec![0], rect("a", 0.0, 40.0, 10.0, 10.0))])).unwrap();
+    let edited = history.current();
+
+    // 撤销：回到检查点，远端加入的 b 和本地的移动都被撤掉
+    let undo = restore(&mut history, &HistoryTarget::Checkpoint("只有 a".into()));
+    assert_eq!(history.current(), history.materialize(1));
+
+    // 撤销之后远端又有新的变化
+    history.record_change(&change("remote", vec![Operation::insert(vec![1], rect("c", 80.0, 0.0, 10.0, 10.0))])).unwrap();
+    assert_eq!(ids(&history, u64::MAX), ["a", "c"]);
+
+    // 重做：回到撤销前的状态，撤销之后的远端变化随之消失；撤销本身仍可回溯
+    restore(&mut history, &HistoryTarget::Seq(before_undo));
+    assert_eq!(history.current(), edited);
+    assert_eq!(ids(&history, undo), ["a"]);
+    let _ = std::fs::remove_dir_all(path.parent().unwrap());
+}
+
+#[test]
+fn replay_follows_publish_order_not_arrival_order() {
+    let insert_a = change("p1", vec![Operation::insert(vec![0], rect("a", 0.0, 0.0, 10.0, 10.0))]);
+    let insert_b = change("p2", vec![Operation::insert(vec![0], rect("b", 0.0, 0.0, 10.0, 10.0))]);
+    let move_a = change("p1", vec![Operation::set(vec![1], rect("a", 9.0, 9.0, 10.0, 10.0))]);
+    let orders = [vec![&insert_a, &insert_b, &move_a], vec![&move_a, &insert_b, &insert_a, &insert_b]];
+
+    // 两端收到的先后不同（其中一端还收到了重复消息），重放结果相同
+    let boards: Vec<_> = orders
+        .iter()
+        .enumerate()
+        .map(|(i, order)| {
+            let path = history_path(&format!("order-{}", i));
+            let mut history = BoardHistory::open(&path).unwrap();
+            for change in order {
+                history.record_change(change).unwrap();
+            }
+            let reopened = BoardHistory::open(&path).unwrap().current();
+            assert_eq!(reopened, history.current());
+            let _ = std::fs::remove_dir_all(path.parent().unwrap());
+            history.current()
+        })
+        .collect();
+    assert_eq!(boards[0], boards[1]);
+    assert_eq!(boards[0].children.iter().map(|e| e.id.as_str()).collect::<Vec<_>>(), ["b", "a"]);
+    assert_eq!(boards[0].children[1].points[0], Point(9.0, 9.0));
+}
+
+#[test]
+fn snapshot_resets_the_base_and_keeps_later_changes() {
+    let path = history_path("snapshot");
+    let mut history = BoardHistory::open(&path).unwrap();
+    // 上次运行留下的白板，白板服务上已经没有了
+    history.record_change(&change("local", vec![Operation::insert(vec![0], rect("old", 0.0, 0.0, 10.0, 10.0))])).unwrap();
+
+    let insert_x = BoardChangeData { seq: Some(1), ..change("remote", vec![Operation::insert(vec![0], rect("x", 0.0, 0.0, 10.0, 10.0))]) };
+    let mut server = Replica::new(BoardState::new());
+    server.apply(insert_x.clone());
+
+    // 发出请求之后、快照到达之前的本地编辑在快照之上重新应用
+    let since = history.latest_seq();
+    history.record_change(&change("local", vec![Operation::insert(vec![0], rect("l", 0.0, 0.0, 10.0, 10.0))])).unwrap();
+    let request = SnapshotRequest { topic: "room".into(), request_id: "r".into(), source_id: "local".into(), timestamp: String::new() };
+    let reset = history.reset_to_snapshot(server.snapshot(&request, "board-server"), since).unwrap();
+    assert_eq!(ids(&history, u64::MAX), ["l", "x"]);
+
+    // 快照已包含的变化再次到达时不重复应用
+    history.record_change(&insert_x).unwrap();
+    assert_eq!(ids(&history, u64::MAX), ["l", "x"]);
+    assert_eq!(ids(&history, since), ["old"]);
+    assert_eq!(ids(&history, reset), ["l", "x"]);
+    assert_eq!(history.entries()[2].kind, "snapshot");
+
+    let reopened = BoardHistory::open(&path).unwrap();
+    assert_eq!(reopened.current(), history.current());
+    assert_eq!(reopened.materialize(since), history.materialize(since));
+    let _ = std::fs::remove_dir_all(path.parent().unwrap());
+}
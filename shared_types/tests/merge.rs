@@ -0,0 +1,164 @@
+//! 三方合并：并发修改逐属性合并，冲突按 prefer 选定一边并逐条报告。
+
+mod common;
+
+use common::{element, rect};
+use shared_types::merge::{merge_elements, ConflictKind, MergeOutcome, Side};
+use shared_types::*;
+
+fn topic(id: &str, children: Option<Vec<PlaitElement>>) -> PlaitElement {
+    let mut node = element(&format!(r#"{{"id":"{}","data":{{"topic":{{"children":[{{"text":"{}"}}]}}}}}}"#, id, id));
+    node.children = children;
+    node
+}
+
+fn styled(mut element: PlaitElement, key: &str, value: &str) -> PlaitElement {
+    element.extra.insert(key.into(), value.into());
+    element
+}
+
+fn merge(base: &[PlaitElement], ours: &[PlaitElement], theirs: &[PlaitElement], prefer: Side) -> MergeOutcome {
+    merge_elements(base, ours, theirs, prefer).unwrap()
+}
+
+fn ids(elements: &[PlaitElement]) -> Vec<&str> {
+    elements.iter().map(|e| e.id.as_str()).collect()
+}
+
+fn conflicts(outcome: &MergeOutcome) -> Vec<(Option<&str>, ConflictKind, Option<&str>)> {
+    outcome.conflicts.iter().map(|c| (c.id.as_deref(), c.kind, c.property.as_deref())).collect()
+}
+
+#[test]
+fn concurrent_edits_merge_property_by_property() {
+    let base = vec![rect("a", 0.0, 0.0, 10.0, 10.0), rect("b", 0.0, 0.0, 10.0, 10.0)];
+    let ours = vec![styled(rect("a", 0.0, 0.0, 10.0, 10.0), "strokeColor", "#f00"), rect("b", 0.0, 0.0, 10.0, 10.0), rect("o", 0.0, 0.0, 10.0, 10.0)];
+    let theirs = vec![rect("t", 0.0, 0.0, 10.0, 10.0), rect("a", 5.0, 5.0, 10.0, 10.0), styled(rect("b", 0.0, 0.0, 10.0, 10.0), "fill", "#0f0")];
+
+    let outcome = merge(&base, &ours, &theirs, Side::Ours);
+    assert!(!outcome.has_conflicts(), "{}", outcome.to_text());
+    assert_eq!(ids(&outcome.elements), ["t", "a", "b", "o"]);
+    let a = &outcome.elements[1];
+    assert_eq!(a.extra["strokeColor"], "#f00");
+    assert_eq!(a.points[0], Point(5.0, 5.0));
+    assert_eq!(outcome.elements[2].extra["fill"], "#0f0");
+
+    // 两边做了同样的修改不算冲突
+    let same = merge(&base, &theirs, &theirs, Side::Ours);
+    assert!(!same.has_conflicts());
+    assert_eq!(same.elements, theirs);
+}
+
+#[test]
+fn same_property_conflicts_follow_prefer() {
+    let base = vec![rect("a", 0.0, 0.0, 10.0, 10.0)];
+    let ours = vec![styled(rect("a", 1.0, 0.0, 10.0, 10.0), "fill", "#111")];
+    let theirs = vec![styled(rect("a", 2.0, 0.0, 10.0, 10.0), "fill", "#222")];
+
+    let outcome = merge(&base, &ours, &theirs, Side::Ours);
+    assert_eq!(conflicts(&outcome), [(Some("a"), ConflictKind::Property, Some("fill")), (Some("a"), ConflictKind::Property, Some("points"))]);
+    assert_eq!(outcome.elements, ours);
+    assert_eq!(outcome.conflicts[0].base, None);
+    assert!(outcome.to_text().contains("📊 共 2 处冲突"));
+
+    let outcome = merge(&base, &ours, &theirs, Side::Theirs);
+    assert_eq!(outcome.elements, theirs);
+    assert!(outcome.conflicts.iter().all(|c| c.resolved == Side::Theirs));
+
+    // 双方新增同一 id、内容不同
+    let outcome = merge(&[], &[rect("n", 0.0, 0.0, 10.0, 10.0)], &[rect("n", 9.0, 0.0, 10.0, 10.0)], Side::Theirs);
+    assert_eq!(conflicts(&outcome), [(Some("n"), ConflictKind::AddAdd, None)]);
+    assert_eq!(outcome.elements[0].points[0], Point(9.0, 0.0));
+}
+
+#[test]
+fn delete_versus_edit_keeps_the_edit() {
+    let base = vec![rect("a", 0.0, 0.0, 10.0, 10.0), rect("b", 0.0, 0.0, 10.0, 10.0)];
+    // 我方删除 a、b，对方修改了 a：a 保留修改后的版本，未修改的 b 删除
+    let theirs = vec![rect("a", 3.0, 3.0, 10.0, 10.0), rect("b", 0.0, 0.0, 10.0, 10.0)];
+    let outcome = merge(&base, &[], &theirs, Side::Ours);
+    assert_eq!(conflicts(&outcome), [(Some("a"), ConflictKind::DeleteModify, None)]);
+    assert_eq!(outcome.elements, [rect("a", 3.0, 3.0, 10.0, 10.0)]);
+    assert_eq!(outcome.conflicts[0].ours, None);
+
+    // 反过来也一样，与 prefer 无关
+    let outcome = merge(&base, &theirs[..1], &base[1..], Side::Theirs);
+    assert_eq!(ids(&outcome.elements), ["a"]);
+    assert_eq!(outcome.elements[0].points[0], Point(3.0, 3.0));
+}
+
+#[test]
+fn deleted_parents_are_restored_for_new_children() {
+    let base = vec![topic("root", Some(vec![topic("branch", Some(vec![topic("leaf", None)]))]))];
+    // 我方删掉整个导图，对方在最深处新增节点：父节点逐层恢复，对方未改动的 leaf 仍删除
+    let theirs = vec![topic("root", Some(vec![topic("branch", Some(vec![topic("leaf", None), topic("new", None)]))]))];
+    let outcome = merge(&base, &[], &theirs, Side::Ours);
+    assert_eq!(outcome.elements, [topic("root", Some(vec![topic("branch", Some(vec![topic("new", None)]))]))]);
+    let restored: Vec<Option<&str>> = outcome.conflicts.iter().map(|c| c.id.as_deref()).collect();
+    assert_eq!(restored, [Some("branch"), Some("root")]);
+    assert!(outcome.conflicts.iter().all(|c| c.kind == ConflictKind::DeleteModify));
+}
+
+#[test]
+fn duplicate_ids_keep_their_children() {
+    // 第二个 m 的子节点并入第一个 m，而不是随重复的节点一起丢掉
+    let board = vec![topic("m", Some(vec![topic("x", None)])), topic("m", Some(vec![topic("y", None)]))];
+    let outcome = merge(&board, &board, &board, Side::Ours);
+    assert!(!outcome.has_conflicts());
+    assert_eq!(outcome.elements, [topic("m", Some(vec![topic("x", None), topic("y", None)]))]);
+
+    let mut edited = board.clone();
+    edited[1].children.as_mut().unwrap().push(topic("z", None));
+    let outcome = merge(&board, &board, &edited, Side::Ours);
+    let children = outcome.elements[0].children.as_ref().unwrap();
+    assert_eq!(ids(children), ["x", "y", "z"]);
+}
+
+#[test]
+fn empty_children_follow_the_side_that_changed_them() {
+    // 一边删掉了空的 children，另一边没动：删除生效
+    let base = vec![topic("m", Some(vec![]))];
+    let outcome = merge(&base, &[topic("m", None)], &base, Side::Theirs);
+    assert!(!outcome.has_conflicts());
+    assert_eq!(outcome.elements, [topic("m", None)]);
+    let outcome = merge(&base, &base, &[topic("m", None)], Side::Ours);
+    assert_eq!(outcome.elements, [topic("m", None)]);
+
+    // 一边加上空的 children，另一边没动：保留
+    let outcome = merge(&[topic("m", None)], &[topic("m", None)], &base, Side::Ours);
+    assert_eq!(outcome.elements, base);
+}
+
+#[test]
+fn wide_boards_merge_quickly() {
+    let base: Vec<PlaitElement> = (0..20000).map(|i| rect(&format!("r{}", i), i as f64, 0.0, 10.0, 10.0)).collect();
+    let mut theirs = base.clone();
+    theirs.push(topic("m", Some((0..2000).map(|i| topic(&format!("t{}", i), None)).collect())));
+    let outcome = merge(&base, &base, &theirs, Side::Ours);
+    assert!(!outcome.has_conflicts());
+    assert_eq!(outcome.elements, theirs);
+}
+
+#[test]
+fn moves_and_reorders() {
+    let base = vec![rect("a", 0.0, 0.0, 10.0, 10.0), rect("b", 0.0, 0.0, 10.0, 10.0), rect("c", 0.0, 0.0, 10.0, 10.0), topic("m", Some(vec![]))];
+    let ours = vec![base[2].clone(), base[0].clone(), base[1].clone(), base[3].clone()];
+    let theirs = vec![base[1].clone(), base[0].clone(), base[2].clone(), base[3].clone()];
+
+    let outcome = merge(&base, &ours, &base, Side::Theirs);
+    assert!(!outcome.has_conflicts());
+    assert_eq!(ids(&outcome.elements), ["c", "a", "b", "m"]);
+
+    let outcome = merge(&base, &ours, &theirs, Side::Theirs);
+    assert_eq!(conflicts(&outcome), [(None, ConflictKind::Order, None)]);
+    assert_eq!(ids(&outcome.elements), ["b", "a", "c", "m"]);
+
+    // 两边把 a 挪到不同的父节点
+    let into_m = vec![base[1].clone(), base[2].clone(), topic("m", Some(vec![base[0].clone()]))];
+    let mut into_c = vec![base[1].clone(), base[2].clone(), base[3].clone()];
+    into_c[1].children = Some(vec![base[0].clone()]);
+    let outcome = merge(&base, &into_m, &into_c, Side::Ours);
+    assert_eq!(conflicts(&outcome), [(Some("a"), ConflictKind::Parent, None)]);
+    assert_eq!(ids(&outcome.elements), ["b", "c", "m"]);
+    assert_eq!(outcome.elements[2], into_m[2]);
+}
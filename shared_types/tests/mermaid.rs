@@ -0,0 +1,177 @@
+use shared_types::board::{insert_operations, BoardState};
+use shared_types::geometry::Rect;
+use shared_types::mermaid::{import_mermaid, MermaidOptions};
+use shared_types::*;
+
+fn import(source: &str) -> Vec<PlaitElement> {
+    import_mermaid(source, &MermaidOptions::default()).unwrap()
+}
+
+fn find<'a>(elements: &'a [PlaitElement], id: &str) -> &'a PlaitElement {
+    elements.iter().find(|e| e.id == id).unwrap_or_else(|| panic!("缺少元素 {}", id))
+}
+
+fn text(element: &PlaitElement) -> &str {
+    element.extra["text"]["children"][0]["text"].as_str().unwrap()
+}
+
+fn rect(element: &PlaitElement) -> Rect {
+    Rect::from_points(&element.points).unwrap()
+}
+
+#[test]
+fn flowchart_shapes_labels_and_edges() {
+    let elements = import(
+        "flowchart TD
+            %% 注释会被忽略
+            A([开始]) --> B{是否登录?}
+            B -->|是| C[进入首页]
+            B -- 否 --> D[/登录页/]
+            D -.-> A; C ==> E((完成))
+            style A fill:#f9f",
+    );
+    let shapes: Vec<_> = elements.iter().filter(|e| e.element_type == "geometry").collect();
+    let lines: Vec<_> = elements.iter().filter(|e| e.element_type == "line").collect();
+    assert_eq!(shapes.len(), 5);
+    assert_eq!(lines.len(), 5);
+
+    let a = find(&elements, "mermaid-node-A");
+    assert_eq!((a.shape.as_str(), text(a)), ("terminal", "开始"));
+    assert_eq!(find(&elements, "mermaid-node-B").shape, "decision");
+    assert_eq!(find(&elements, "mermaid-node-D").shape, "data");
+    assert_eq!(find(&elements, "mermaid-node-E").shape, "ellipse");
+
+    let yes = lines.iter().find(|l| l.extra["target"]["boundId"] == "mermaid-node-C").unwrap();
+    assert_eq!(yes.extra["source"]["boundId"], "mermaid-node-B");
+    assert_eq!(yes.extra["target"]["marker"], "arrow");
+    assert_eq!(yes.extra["texts"][0]["text"]["children"][0]["text"], "是");
+    let no = lines.iter().find(|l| l.extra["target"]["boundId"] == "mermaid-node-D").unwrap();
+    assert_eq!(no.extra["texts"][0]["text"]["children"][0]["text"], "否");
+    let dotted = lines.iter().find(|l| l.extra["target"]["boundId"] == "mermaid-node-A").unwrap();
+    assert_eq!(dotted.extra["strokeStyle"], "dashed");
+    let thick = lines.iter().find(|l| l.extra["target"]["boundId"] == "mermaid-node-E").unwrap();
+    assert_eq!(thick.extra["strokeWidth"], 4);
+}
+
+#[test]
+fn flowchart_layout_follows_direction() {
+    let down = import("graph TD\nA --> B --> C");
+    let (a, b, c) = (rect(find(&down, "mermaid-node-A")), rect(find(&down, "mermaid-node-B")), rect(find(&down, "mermaid-node-C")));
+    assert!(a.max_y < b.min_y && b.max_y < c.min_y);
+    assert_eq!((a.min_x, a.min_y), (0.0, 0.0));
+
+    let right = import("graph LR\nA --> B & C");
+    let (a, b, c) = (rect(find(&right, "mermaid-node-A")), rect(find(&right, "mermaid-node-B")), rect(find(&right, "mermaid-node-C")));
+    assert!(a.max_x < b.min_x && b.min_x == c.min_x);
+    assert!(b.max_y < c.min_y || c.max_y < b.min_y, "同层节点不应重叠");
+
+    let up = import("graph BT\nA --> B");
+    assert!(rect(find(&up, "mermaid-node-B")).max_y < rect(find(&up, "mermaid-node-A")).min_y);
+
+    // 环不影响分层，且节点不会重叠
+    let cycle = import("graph TD\nA --> B --> C --> A");
+    let rects: Vec<Rect> = cycle.iter().filter(|e| e.element_type == "geometry").map(rect).collect();
+    for (i, x) in rects.iter().enumerate() {
+        for y in &rects[i + 1..] {
+            assert!(x.max_y < y.min_y || y.max_y < x.min_y || x.max_x < y.min_x || y.max_x < x.min_x);
+        }
+    }
+}
+
+#[test]
+fn sequence_diagram_participants_messages_and_notes() {
+    let elements = import(
+        "sequenceDiagram
+            participant C as 客户端
+            actor U
+            C->>+S: 请求
+            S-->>-C: 响应
+            U-)C: 异步
+            S->>S: 自检
+            Note over C,S: 备注",
+    );
+    let c = find(&elements, "mermaid-participant-C");
+    assert_eq!(text(c), "客户端");
+    let s = find(&elements, "mermaid-participant-S");
+    assert!(rect(c).max_x < rect(s).min_x, "参与者按出现顺序从左到右排列");
+    assert_eq!(find(&elements, "mermaid-participant-U").shape, "terminal");
+    assert_eq!(find(&elements, "mermaid-lifeline-C").extra["strokeStyle"], "dashed");
+
+    let messages: Vec<_> = elements.iter().filter(|e| e.id.contains("-message-")).collect();
+    assert_eq!(messages.len(), 4);
+    assert_eq!(messages[0].extra["target"]["marker"], "arrow");
+    assert_eq!(messages[1].extra["strokeStyle"], "dashed");
+    assert_eq!(messages[2].extra["target"]["marker"], "open-triangle");
+    assert_eq!(messages[3].points.len(), 4, "自调用消息绕回生命线");
+    // 消息自上而下排列
+    assert!(messages.windows(2).all(|w| w[0].points[0].1 < w[1].points[0].1));
+
+    let note = elements.iter().find(|e| e.id.contains("-note-")).unwrap();
+    assert_eq!(text(note), "备注");
+    assert!(rect(note).min_x < rect(c).max_x && rect(note).max_x > rect(s).min_x, "备注横跨两个参与者");
+}
+
+#[test]
+fn imported_elements_apply_as_insert_operations() {
+    let options = MermaidOptions { origin: Point(500.0, 100.0), id_prefix: "m1".into() };
+    let elements = import_mermaid("graph TD\nA --> B", &options).unwrap();
+    assert_eq!(rect(&elements[0]).min_x, 500.0);
+
+    let mut board = BoardState::from_elements(vec![serde_json::from_str(r#"{"id":"old"}"#).unwrap()]);
+    let ops = insert_operations(1, elements.clone());
+    assert!(board.apply_all(&ops).is_empty());
+    assert_eq!(board.children.len(), 4);
+    assert_eq!(board.children[0].id, "old");
+    assert_eq!(board.children[1..], elements[..]);
+    assert_eq!(board.find("m1-node-A").unwrap().0, vec![1]);
+}
+
+#[test]
+fn unsupported_input_is_rejected() {
+    for source in ["", "pie\n\"a\": 1", "graph XY\nA-->B", "graph TD\nA[未闭合", "sequenceDiagram\nA ~~ B"] {
+        assert!(import_mermaid(source, &MermaidOptions::default()).is_err(), "{:?}", source);
+    }
+}
+
+#[test]
+fn element_ids_are_unique() {
+    // 节点名与连线、消息的序号形式相同时 id 也不能重复，否则连线会绑定到错误的元素上
+    for source in [
+        "graph TD\ne0 --> e1 --> edge\nnode --> e0",
+        "sequenceDiagram\nm0->>n1: 消息\nNote over m0: 备注\nlifeline-m0->>m0: 再次",
+    ] {
+        let elements = import(source);
+        let mut ids: Vec<&str> = elements.iter().map(|e| e.id.as_str()).collect();
+        ids.sort();
+        let count = ids.len();
+        ids.dedup();
+        assert_eq!(ids.len(), count, "{:?}", ids);
+    }
+    let elements = import("graph TD\ne0 --> e1");
+    let edge = find(&elements, "mermaid-edge-0");
+    assert_eq!(edge.extra["source"]["boundId"], "mermaid-node-e0");
+    assert_eq!(find(&elements, "mermaid-node-e0").element_type, "geometry");
+}
+
+#[test]
+fn long_chains_and_oversized_input() {
+    // 很长的链和环用显式栈遍历，不会耗尽调用栈
+    let chain: Vec<String> = (0..2400).map(|i| format!("n{}", i)).collect();
+    let source = format!("graph TD\n{} --> n0", chain.join(" --> "));
+    let elements = import(&source);
+    assert_eq!(elements.iter().filter(|e| e.element_type == "geometry").count(), 2400);
+    assert!(rect(find(&elements, "mermaid-node-n0")).max_y < rect(find(&elements, "mermaid-node-n2399")).min_y);
+
+    // 超过上限的输入直接拒绝，包括 & 展开出的大量连线
+    let wide: Vec<String> = (0..80).map(|i| format!("n{}", i)).collect();
+    let group = wide.join(" & ");
+    let err = import_mermaid(&format!("graph TD\n{} --> {}", group, group), &MermaidOptions::default()).unwrap_err();
+    assert!(err.contains("过大"), "{}", err);
+    // 两边各 2000 个节点，展开前就按预算拒绝，不会先生成四百万条连线
+    let huge: Vec<String> = (0..2000).map(|i| format!("m{}", i)).collect();
+    let group = huge.join(" & ");
+    let err = import_mermaid(&format!("graph TD\n{} --> {}", group, group), &MermaidOptions::default()).unwrap_err();
+    assert!(err.contains("过大"), "{}", err);
+    let steps: String = (0..6000).map(|_| "A->>B: x\n").collect();
+    assert!(import_mermaid(&format!("sequenceDiagram\n{}", steps), &MermaidOptions::default()).is_err());
+}
@@ -0,0 +1,141 @@
+mod common;
+
+use shared_types::outline::{board_to_markdown, markdown_to_mind_map, mind_map_to_markdown, OutlineOptions};
+use shared_types::*;
+
+fn topic(node: &PlaitElement) -> &str {
+    node.extra["data"]["topic"]["children"][0]["text"].as_str().unwrap()
+}
+
+fn children(node: &PlaitElement) -> &[PlaitElement] {
+    node.children.as_deref().unwrap_or_default()
+}
+
+/// 按先序遍历列出 (深度, 主题)
+fn flatten(node: &PlaitElement) -> Vec<(usize, String)> {
+    fn walk(node: &PlaitElement, depth: usize, out: &mut Vec<(usize, String)>) {
+        out.push((depth, topic(node).to_string()));
+        for child in children(node) {
+            walk(child, depth + 1, out);
+        }
+    }
+    let mut out = Vec::new();
+    walk(node, 0, &mut out);
+    out
+}
+
+fn import(markdown: &str) -> PlaitElement {
+    markdown_to_mind_map(markdown, &OutlineOptions::default()).unwrap()
+}
+
+#[test]
+fn headings_and_nested_lists_become_branches() {
+    let root = import(
+        "# 周会纪要
+
+## 进展
+- 导出 PDF
+  - 平铺打印
+  - 裁切标记
+- 导入 Mermaid
+
+## 待办
+1. 写文档
+2) 发版本
+    * 更新 changelog
+
+```
+- 代码块里的内容不导入
+```
+",
+    );
+    assert_eq!(root.element_type, "mindmap");
+    assert_eq!(root.extra["isRoot"], true);
+    assert_eq!(root.extra["rightNodeCount"], 2);
+    assert_eq!(root.points, vec![Point(0.0, 0.0)]);
+
+    let expected = [
+        (0, "周会纪要"),
+        (1, "进展"),
+        (2, "导出 PDF"),
+        (3, "平铺打印"),
+        (3, "裁切标记"),
+        (2, "导入 Mermaid"),
+        (1, "待办"),
+        (2, "写文档"),
+        (2, "发版本"),
+        (3, "更新 changelog"),
+    ];
+    let actual = flatten(&root);
+    assert_eq!(actual, expected.iter().map(|(d, t)| (*d, t.to_string())).collect::<Vec<_>>());
+
+    // 子节点没有 type，与前端的思维导图结构一致
+    assert!(children(&root)[0].element_type.is_empty());
+    let mut ids: Vec<String> = Vec::new();
+    fn collect(node: &PlaitElement, ids: &mut Vec<String>) {
+        ids.push(node.id.clone());
+        node.children.iter().flatten().for_each(|c| collect(c, ids));
+    }
+    collect(&root, &mut ids);
+    let count = ids.len();
+    ids.sort();
+    ids.dedup();
+    assert_eq!(ids.len(), count, "节点 id 不重复");
+}
+
+#[test]
+fn several_top_level_nodes_get_a_central_topic() {
+    let options = OutlineOptions { title: "项目".into(), origin: Point(300.0, 40.0), ..Default::default() };
+    let root = markdown_to_mind_map("- 甲\n- 乙\n  继续写\n- 丙", &options).unwrap();
+    assert_eq!(topic(&root), "项目");
+    assert_eq!(root.points, vec![Point(300.0, 40.0)]);
+    let topics: Vec<&str> = children(&root).iter().map(topic).collect();
+    assert_eq!(topics, ["甲", "乙 继续写", "丙"]);
+
+    assert!(markdown_to_mind_map("\n\n---\n", &options).is_err());
+}
+
+#[test]
+fn mind_map_round_trips_through_markdown() {
+    let tricky = import(
+        "# Root #1
+- C#
+- \\- 不是列表
+  - \\# 不是标题
+  - 1. 不是编号
+- 多行<br>主题
+-
+  - 空主题下的节点",
+    );
+    let markdown = mind_map_to_markdown(&tricky).unwrap();
+    let again = import(&markdown);
+    assert_eq!(flatten(&again), flatten(&tricky));
+    assert_eq!(topic(&tricky), "Root #1");
+    assert_eq!(topic(&children(&tricky)[1]), "- 不是列表");
+    assert_eq!(topic(&children(&tricky)[2]), "多行\n主题");
+    assert_eq!(topic(&children(&tricky)[3]), "");
+}
+
+#[test]
+fn literal_markup_and_spaces_survive_export() {
+    let mut root = import("# 根\n- a\n- b\n- c\n- d\n- e\n- f");
+    let texts = ["  缩进", "结尾空格  ", "写了 <br> 标签", "路径 C:\\temp\\", "反斜杠\\<br>和换行\\\n", "   "];
+    for (child, text) in root.children.as_mut().unwrap().iter_mut().zip(texts) {
+        child.extra["data"]["topic"]["children"][0]["text"] = text.into();
+    }
+    let markdown = mind_map_to_markdown(&root).unwrap();
+    assert!(markdown.contains("- 写了 \\<br> 标签\n"), "{}", markdown);
+    let again = import(&markdown);
+    assert_eq!(flatten(&again), flatten(&root));
+}
+
+#[test]
+fn exports_mind_maps_from_the_sample_board() {
+    let document = common::sample();
+    let markdown = board_to_markdown(&document.elements).unwrap();
+    assert!(markdown.starts_with("# 思维导图\n- 观点一\n  -\n    -\n- 观点二\n"), "{}", markdown);
+
+    let shape = document.elements.iter().find(|e| e.element_type == "geometry").unwrap();
+    assert!(mind_map_to_markdown(shape).is_err());
+    assert!(board_to_markdown(std::slice::from_ref(shape)).is_err());
+}
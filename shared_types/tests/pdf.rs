@@ -0,0 +1,76 @@
+//! PDF 导出，需要开启 `pdf` 特性：`cargo test --features pdf`
+
+#![cfg(feature = "pdf")]
+
+mod common;
+
+use common::labeled_rect;
+use shared_types::pdf::{render_pdf, Orientation, PdfLayout, PdfOptions};
+
+fn page_count(pdf: &[u8]) -> usize {
+    let text = String::from_utf8_lossy(pdf);
+    let start = text.find("/Count ").expect("缺少页面树") + "/Count ".len();
+    text[start..].split(|c: char| !c.is_ascii_digit()).next().unwrap().parse().unwrap()
+}
+
+#[test]
+fn fit_puts_board_on_one_page_with_searchable_text() {
+    let pdf = render_pdf(&[labeled_rect("a", 0.0, 0.0, "Hi"), labeled_rect("b", 3000.0, 2000.0, "Hi")], &PdfOptions::default()).unwrap();
+    assert!(pdf.starts_with(b"%PDF-"));
+    assert_eq!(page_count(&pdf), 1);
+
+    // 文字通过 ToUnicode 映射回 Unicode，而不是转成轮廓
+    let text = String::from_utf8_lossy(&pdf);
+    assert!(text.contains("/ToUnicode"));
+    assert!(text.contains("<0048>") && text.contains("<0069>"), "ToUnicode 中应有 H 和 i");
+}
+
+#[test]
+fn tiling_splits_large_boards_across_pages() {
+    let elements = [labeled_rect("a", 0.0, 0.0, "a"), labeled_rect("b", 2000.0, 1300.0, "b")];
+    let options = |scale, orientation| PdfOptions {
+        orientation,
+        layout: PdfLayout::Tile { scale, overlap_mm: 10.0 },
+        ..Default::default()
+    };
+
+    // 含留白 2140×1390px，打印为 1605×1043pt；A4 横向可打印约 785×539pt，扣除重叠后每页前进约 757×510pt
+    let pdf = render_pdf(&elements, &options(1.0, Orientation::Landscape)).unwrap();
+    assert_eq!(page_count(&pdf), 6);
+    // 各页共用同一个白板 XObject
+    assert_eq!(String::from_utf8_lossy(&pdf).matches("/Subtype /Form").count(), 1);
+
+    // 自动方向选页数较少的一种
+    let auto = render_pdf(&elements, &options(1.0, Orientation::Auto)).unwrap();
+    assert!(page_count(&auto) <= 6);
+
+    let small = render_pdf(&elements, &options(0.1, Orientation::Auto)).unwrap();
+    assert_eq!(page_count(&small), 1);
+}
+
+#[test]
+fn invalid_options_are_rejected() {
+    let elements = [labeled_rect("a", 0.0, 0.0, "a")];
+    for options in [
+        PdfOptions { margin_mm: 200.0, ..Default::default() },
+        PdfOptions { margin_mm: f32::NAN, ..Default::default() },
+        PdfOptions { layout: PdfLayout::Tile { scale: 0.0, overlap_mm: 10.0 }, ..Default::default() },
+        PdfOptions { layout: PdfLayout::Tile { scale: 1.0, overlap_mm: 500.0 }, ..Default::default() },
+        PdfOptions { layout: PdfLayout::Tile { scale: 1000.0, overlap_mm: 10.0 }, ..Default::default() },
+    ] {
+        assert!(render_pdf(&elements, &options).is_err(), "{:?}", options.layout);
+    }
+}
+
+#[test]
+fn huge_tile_scales_are_rejected_without_overflow() {
+    // 页数超出 usize 或无穷大时返回错误，而不是溢出 panic 或尝试生成海量页面
+    let elements = [labeled_rect("a", 0.0, 0.0, "a"), labeled_rect("b", 5000.0, 5000.0, "b")];
+    for scale in [1e6, 1e30, f32::MAX] {
+        for orientation in [Orientation::Auto, Orientation::Portrait, Orientation::Landscape] {
+            let options = PdfOptions { layout: PdfLayout::Tile { scale, overlap_mm: 10.0 }, orientation, ..Default::default() };
+            let err = render_pdf(&elements, &options).unwrap_err();
+            assert!(err.contains("打印比例"), "{}: {}", scale, err);
+        }
+    }
+}
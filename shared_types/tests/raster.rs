@@ -0,0 +1,48 @@
+//! PNG 导出，需要开启 `raster` 特性：`cargo test --features raster`
+
+#![cfg(feature = "raster")]
+
+mod common;
+
+use common::labeled_rect;
+use shared_types::geometry::Rect;
+use shared_types::raster::{render_png, PngOptions};
+use shared_types::svg::SvgOptions;
+
+/// PNG 头部 IHDR 中的宽高
+fn size(png: &[u8]) -> (u32, u32) {
+    assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+    let width = u32::from_be_bytes(png[16..20].try_into().unwrap());
+    let height = u32::from_be_bytes(png[20..24].try_into().unwrap());
+    (width, height)
+}
+
+#[test]
+fn scale_and_padding_set_pixel_size() {
+    let options = PngOptions { scale: 2.0, svg: SvgOptions { padding: 10.0, ..Default::default() } };
+    let png = render_png(&[labeled_rect("a", 0.0, 0.0, "a")], &options).unwrap();
+    assert_eq!(size(&png), (240, 140));
+}
+
+#[test]
+fn region_and_selection_crop() {
+    let elements = [labeled_rect("a", 0.0, 0.0, "a"), labeled_rect("b", 500.0, 500.0, "b")];
+
+    let region = SvgOptions {
+        padding: 0.0,
+        region: Some(Rect { min_x: 0.0, min_y: 0.0, max_x: 60.0, max_y: 30.0 }),
+        ..Default::default()
+    };
+    assert_eq!(size(&render_png(&elements, &PngOptions { scale: 1.0, svg: region }).unwrap()), (60, 30));
+
+    let selection = SvgOptions { padding: 0.0, selection: Some(vec!["b".into()]), ..Default::default() };
+    assert_eq!(size(&render_png(&elements, &PngOptions { scale: 1.0, svg: selection }).unwrap()), (100, 50));
+}
+
+#[test]
+fn invalid_sizes_are_rejected() {
+    let elements = [labeled_rect("a", 0.0, 0.0, "a")];
+    for scale in [0.0, -1.0, f32::NAN, 1000.0] {
+        assert!(render_png(&elements, &PngOptions { scale, ..Default::default() }).is_err(), "{}", scale);
+    }
+}
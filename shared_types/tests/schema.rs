@@ -0,0 +1,60 @@
+//! JSON Schema，需要开启 `schema` 特性：`cargo test --features schema`
+
+#![cfg(feature = "schema")]
+
+mod common;
+
+use common::square;
+use shared_types::schema::{validate_message, SchemaKind};
+use shared_types::*;
+use std::path::Path;
+
+#[test]
+fn checked_in_schemas_are_up_to_date() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("schemas");
+    for kind in SchemaKind::ALL {
+        let path = dir.join(format!("{}.schema.json", kind.name()));
+        let text = std::fs::read_to_string(&path).unwrap_or_default();
+        let checked_in: serde_json::Value = serde_json::from_str(&text).unwrap_or_default();
+        assert_eq!(checked_in, kind.schema(), "{} 已过期，用 `drawnix-cli schema -o ../shared_types/schemas` 重新生成", path.display());
+    }
+}
+
+#[test]
+fn accepts_what_rust_produces() {
+    let change = BoardChangeData {
+        operations: vec![Operation::insert(vec![0], square("a")), Operation::set(vec![0], square("a")), Operation::remove(vec![0], square("a"))],
+        timestamp: "2024-01-01T00:00:00Z".into(),
+        source_id: "peer-0".into(),
+        seq: Some(1),
+    };
+    assert_eq!(validate_message(SchemaKind::BoardChange, &serde_json::to_value(&change).unwrap()), Ok(()));
+
+    // 带默认值的字段可以省略，未知字段原样保留
+    let minimal = serde_json::json!({ "id": "m", "data": { "topic": {} }, "customField": [1, 2] });
+    assert_eq!(validate_message(SchemaKind::Element, &minimal), Ok(()));
+
+    let document: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(common::sample_path()).unwrap()).unwrap();
+    assert_eq!(validate_message(SchemaKind::Document, &document), Ok(()));
+}
+
+#[test]
+fn rejects_invalid_messages_with_locations() {
+    let message = serde_json::json!({
+        "timestamp": "2024-01-01T00:00:00Z",
+        "source_id": "peer-0",
+        "operations": [
+            { "type": "move_node", "path": [0] },
+            { "type": "remove_node", "path": [], "node": square("a") },
+            { "type": "insert_node", "path": [0], "node": { "id": "b", "points": [[0, "x"]] } },
+        ],
+    });
+    let errors = validate_message(SchemaKind::BoardChange, &message).unwrap_err();
+    let locations: Vec<&str> = errors.iter().map(|e| e.split(": ").next().unwrap()).collect();
+    assert_eq!(locations, ["/operations/0/type", "/operations/1/path", "/operations/2/node/points/0/1"], "{:?}", errors);
+
+    let errors = validate_message(SchemaKind::SnapshotRequest, &serde_json::json!({ "topic": "room" })).unwrap_err();
+    assert_eq!(errors.len(), 3, "{:?}", errors);
+    assert_eq!(SchemaKind::from_name("board-snapshot"), Some(SchemaKind::BoardSnapshot));
+    assert_eq!(SchemaKind::from_name("board"), None);
+}
@@ -0,0 +1,145 @@
+mod common;
+
+use common::{element, rect};
+use shared_types::board::BoardState;
+use shared_types::geometry::Rect;
+use shared_types::spatial::{element_bounds, SpatialIndex};
+use shared_types::*;
+
+fn area(min_x: f64, min_y: f64, max_x: f64, max_y: f64) -> Rect {
+    Rect { min_x, min_y, max_x, max_y }
+}
+
+fn close(a: Rect, b: Rect) -> bool {
+    [(a.min_x, b.min_x), (a.min_y, b.min_y), (a.max_x, b.max_x), (a.max_y, b.max_y)].iter().all(|(x, y)| (x - y).abs() < 1e-6)
+}
+
+#[test]
+fn bounds_follow_element_type() {
+    // 100x20 的矩形旋转 90° 后变成 20x100
+    let rotated = element(r#"{"id":"r","type":"geometry","shape":"rectangle","points":[[0,0],[100,20]],"angle":1.5707963267948966}"#);
+    assert!(close(element_bounds(&rotated).unwrap(), area(40.0, -40.0, 60.0, 60.0)));
+
+    // 折线补出拐点，范围包含箭头宽度和文字
+    let elbow = element(
+        r#"{"id":"l","type":"line","shape":"elbow","strokeWidth":2,"points":[[0,0],[100,50]],
+            "source":{"connection":[0.5,1]},"target":{"connection":[0,0.5]},
+            "texts":[{"text":{"children":[{"text":"标签"}]},"position":0.5,"width":40,"height":20}]}"#,
+    );
+    assert!(close(element_bounds(&elbow).unwrap(), area(-6.0, -6.0, 106.0, 60.0)));
+
+    let pen = element(r#"{"id":"p","type":"freehand","shape":"feltTipPen","strokeWidth":4,"points":[[0,0],[10,30],[20,5]]}"#);
+    assert!(close(element_bounds(&pen).unwrap(), area(-4.0, -4.0, 24.0, 34.0)));
+
+    assert!(element_bounds(&element(r#"{"id":"empty","type":"geometry","points":[]}"#)).is_none());
+}
+
+#[test]
+fn hit_testing_points_and_areas() {
+    let line = element(r#"{"id":"line","type":"line","shape":"straight","strokeWidth":2,"points":[[0,200],[200,0]]}"#);
+    let tilted = element(r#"{"id":"tilted","type":"geometry","shape":"rectangle","points":[[300,0],[400,100]],"angle":0.7853981633974483}"#);
+    let index = SpatialIndex::new(BoardState::from_elements(vec![rect("below", 0.0, 0.0, 150.0, 150.0), rect("above", 50.0, 50.0, 150.0, 150.0), line, tilted]));
+
+    // 重叠处最上层的在前
+    assert_eq!(index.at_point(&Point(100.0, 100.0), 0.0), ["line", "above", "below"]);
+    assert_eq!(index.at_point(&Point(20.0, 20.0), 0.0), ["below"]);
+    // 线附近需要容差才能点中
+    assert_eq!(index.at_point(&Point(160.0, 52.0), 0.0), ["above"]);
+    assert_eq!(index.at_point(&Point(160.0, 52.0), 5.0), ["line", "above"]);
+    // 旋转 45° 的正方形：包围盒的角上没有内容，边的中点附近有
+    assert!(index.at_point(&Point(305.0, 5.0), 0.0).is_empty());
+    assert_eq!(index.at_point(&Point(350.0, -15.0), 0.0), ["tilted"]);
+
+    assert_eq!(index.intersecting(&area(160.0, 160.0, 170.0, 170.0)), ["above"]);
+    assert_eq!(index.intersecting(&area(290.0, -10.0, 310.0, 10.0)), Vec::<&str>::new());
+    assert_eq!(index.intersecting(&area(-10.0, 185.0, 5.0, 195.0)), ["line"]);
+    assert_eq!(index.within(&area(-10.0, -10.0, 260.0, 260.0)), ["line", "above", "below"]);
+    assert!(close(index.bounds("below").unwrap(), area(0.0, 0.0, 150.0, 150.0)));
+}
+
+fn rect_at(id: &str, x: i32, y: i32) -> PlaitElement {
+    rect(id, x as f64, y as f64, 40.0, 30.0)
+}
+
+#[test]
+fn index_follows_operations() {
+    let document = common::sample();
+    let mut index = SpatialIndex::new(BoardState::from_elements(document.elements));
+
+    // 思维导图按节点命中，子节点的 id 也能查到
+    let root = index.board().children.iter().find(|e| e.element_type == "mindmap").unwrap();
+    let child = root.children.as_ref().unwrap()[0].id.clone();
+    let child_bounds = index.bounds(&child).unwrap();
+    let center = Point((child_bounds.min_x + child_bounds.max_x) / 2.0, (child_bounds.min_y + child_bounds.max_y) / 2.0);
+    assert!(index.at_point(&center, 0.0).contains(&child.as_str()));
+
+    // 修改思维导图的子节点会重新布局整个导图
+    let (path, _) = index.board().find(&child).unwrap();
+    let mut widened = index.board().get(&path).unwrap().clone();
+    widened.extra.insert("width".into(), serde_json::json!(400));
+    index.apply(&Operation::set(path, widened)).unwrap();
+    assert!(index.bounds(&child).unwrap().width() > child_bounds.width());
+    assert!(index.apply(&Operation::remove(vec![999], rect_at("x", 0, 0))).is_err());
+
+    let mut rng = fastrand::Rng::with_seed(7);
+    let mut next = 0;
+    for round in 0..300 {
+        let len = index.board().children.len();
+        let op = match rng.u8(0..3) {
+            0 => {
+                next += 1;
+                Operation::insert(vec![rng.usize(0..=len)], rect_at(&format!("n{}", next), rng.i32(-500..500), rng.i32(-500..500)))
+            }
+            1 if len > 0 => {
+                let i = rng.usize(0..len);
+                Operation::remove(vec![i], index.board().children[i].clone())
+            }
+            _ if len > 0 => {
+                let i = rng.usize(0..len);
+                let mut moved = index.board().children[i].clone();
+                for p in &mut moved.points {
+                    *p = Point(p.0 + 15.0, p.1 - 10.0);
+                }
+                Operation::set(vec![i], moved)
+            }
+            _ => continue,
+        };
+        index.apply(&op).unwrap();
+
+        // 增量维护的结果与重新建立的索引一致
+        let rebuilt = SpatialIndex::new(index.board().clone());
+        let query = area(rng.i32(-600..400) as f64, rng.i32(-600..400) as f64, 0.0, 0.0);
+        let query = Rect { max_x: query.min_x + 300.0, max_y: query.min_y + 300.0, ..query };
+        assert_eq!(index.intersecting(&query), rebuilt.intersecting(&query), "第 {} 次操作后", round);
+        assert_eq!(index.within(&query), rebuilt.within(&query), "第 {} 次操作后", round);
+        assert_eq!(index.top_level_intersecting(&query), rebuilt.top_level_intersecting(&query), "第 {} 次操作后", round);
+        let point = Point(rng.i32(-500..500) as f64, rng.i32(-500..500) as f64);
+        assert_eq!(index.at_point(&point, 5.0), rebuilt.at_point(&point, 5.0), "第 {} 次操作后", round);
+    }
+}
+
+#[test]
+fn duplicate_ids_are_indexed_separately() {
+    let mut index = SpatialIndex::new(BoardState::from_elements(vec![rect_at("a", 0, 0), rect_at("a", 200, 0), rect_at("b", 400, 0)]));
+    assert_eq!(index.at_point(&Point(10.0, 10.0), 0.0), ["a"]);
+    assert_eq!(index.at_point(&Point(210.0, 10.0), 0.0), ["a"]);
+    assert!(close(index.bounds("a").unwrap(), area(0.0, 0.0, 40.0, 30.0)));
+
+    // 删除第一个 a：它的条目不能残留在树里，另一个 a 不受影响
+    index.apply(&Operation::remove(vec![0], rect_at("a", 0, 0))).unwrap();
+    assert!(index.at_point(&Point(10.0, 10.0), 0.0).is_empty());
+    assert_eq!(index.at_point(&Point(210.0, 10.0), 0.0), ["a"]);
+    assert!(close(index.bounds("a").unwrap(), area(200.0, 0.0, 240.0, 30.0)));
+
+    // 再插入一个同 id 的元素并移动它
+    index.apply(&Operation::insert(vec![2], rect_at("a", 600, 0))).unwrap();
+    index.apply(&Operation::set(vec![2], rect_at("a", 800, 0))).unwrap();
+    assert!(index.at_point(&Point(610.0, 10.0), 0.0).is_empty());
+    assert_eq!(index.at_point(&Point(810.0, 10.0), 0.0), ["a"]);
+    assert_eq!(index.top_level_intersecting(&area(0.0, 0.0, 1000.0, 100.0)), [0, 1, 2]);
+    index.apply(&Operation::remove(vec![0], rect_at("a", 200, 0))).unwrap();
+    assert!(close(index.bounds("a").unwrap(), area(800.0, 0.0, 840.0, 30.0)));
+    index.apply(&Operation::remove(vec![1], rect_at("a", 800, 0))).unwrap();
+    assert_eq!(index.bounds("a"), None);
+    assert_eq!(index.within(&area(-1000.0, -1000.0, 2000.0, 1000.0)), ["b"]);
+}
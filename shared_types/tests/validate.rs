@@ -0,0 +1,114 @@
+mod common;
+
+use common::{element, square};
+use shared_types::board::BoardState;
+use shared_types::document::DrawnixDocument;
+use shared_types::validate::{check_operations, fix_document, fix_elements, validate_elements, Rule, Severity};
+use shared_types::*;
+
+fn arrow(id: &str, from: &str, to: &str) -> PlaitElement {
+    element(&format!(
+        r#"{{"id":"{}","type":"line","shape":"straight","points":[[0,0],[100,0]],
+            "source":{{"marker":"none","boundId":"{}","connection":[1,0.5]}},"target":{{"marker":"arrow","boundId":"{}"}}}}"#,
+        id, from, to
+    ))
+}
+
+fn corrupt_board() -> Vec<PlaitElement> {
+    let mut nan = square("nan");
+    nan.points[1] = Point(f64::NAN, 10.0);
+    vec![
+        square("a"),
+        square("a"),
+        arrow("l", "a", "deleted"),
+        element(r#"{"id":"t","type":"geometry","shape":"text","points":[[0,0],[10,10]],"text":{"children":[{"text":"  "}]}}"#),
+        element(r#"{"id":"m","type":"mindmap","points":[[0,0]],"data":{"topic":{"children":[{"text":"根"}]}},"children":[{"id":"","data":{}}]}"#),
+        element(r#"{"id":"bare","type":"line","points":[[0,0],[5,5]],"texts":[{"text":{"children":[{"text":""}]},"position":0.5}]}"#),
+        nan,
+        element(r#"{"id":"dot","type":"geometry","shape":"rectangle","points":[[0,0]]}"#),
+    ]
+}
+
+#[test]
+fn reports_problems_with_paths() {
+    let diagnostics = validate_elements(&corrupt_board());
+    let found: Vec<(Rule, Vec<usize>, &str)> = diagnostics.iter().map(|d| (d.rule, d.path.clone(), d.id.as_str())).collect();
+    assert_eq!(
+        found,
+        [
+            (Rule::DuplicateId, vec![1], "a"),
+            (Rule::DanglingBinding, vec![2], "l"),
+            (Rule::EmptyText, vec![3], "t"),
+            (Rule::EmptyId, vec![4, 0], ""),
+            (Rule::MissingLineEnd, vec![5], "bare"),
+            (Rule::MissingLineEnd, vec![5], "bare"),
+            (Rule::EmptyText, vec![5], "bare"),
+            (Rule::NonFiniteCoordinate, vec![6], "nan"),
+            (Rule::MissingPoints, vec![7], "dot"),
+        ]
+    );
+    assert_eq!(diagnostics[2].severity, Severity::Warning);
+    assert!(diagnostics[1].message.contains("deleted"));
+
+    let json = serde_json::to_value(&diagnostics[0]).unwrap();
+    assert_eq!(json["rule"], "duplicate-id");
+    assert_eq!(json["severity"], "error");
+}
+
+#[test]
+fn fix_repairs_what_it_safely_can() {
+    let mut elements = corrupt_board();
+    let report = fix_elements(&mut elements);
+    assert_eq!(report.fixed.len(), 7);
+    let remaining: Vec<Rule> = report.remaining.iter().map(|d| d.rule).collect();
+    assert_eq!(remaining, [Rule::NonFiniteCoordinate, Rule::MissingPoints]);
+
+    let ids: Vec<&str> = elements.iter().map(|e| e.id.as_str()).collect();
+    assert_eq!(ids, ["a", "a-1", "l", "m", "bare", "nan", "dot"]);
+    assert_eq!(elements[3].children.as_ref().unwrap()[0].id, "element-3-0");
+
+    // 解除绑定时保留端点的其他属性，有效的绑定不变
+    let line = &elements[2];
+    assert_eq!(line.extra["source"]["boundId"], "a");
+    assert_eq!(line.extra["target"], serde_json::json!({ "marker": "arrow" }));
+    let bare = &elements[4];
+    assert_eq!(bare.extra["source"]["marker"], "none");
+    assert_eq!(bare.extra["target"]["marker"], "arrow");
+    assert_eq!(bare.extra["texts"], serde_json::json!([]));
+
+    // 修复过的白板再修复没有变化（NaN 不等于自身，按调试输出比较）
+    let again = format!("{:?}", elements);
+    assert!(fix_elements(&mut elements).fixed.is_empty());
+    assert_eq!(format!("{:?}", elements), again);
+
+    let mut document = DrawnixDocument::new(vec![square("a")]);
+    document.viewport.zoom = 0.0;
+    let report = fix_document(&mut document);
+    assert_eq!(report.fixed[0].rule, Rule::InvalidViewport);
+    assert!(report.remaining.is_empty());
+    assert_eq!(document.viewport.zoom, 1.0);
+}
+
+#[test]
+fn refuses_operations_that_break_the_board() {
+    let board = BoardState::from_elements(vec![square("a"), square("b"), arrow("l", "a", "b")]);
+
+    assert!(check_operations(&board, &[Operation::set(vec![0], square("a"))]).is_ok());
+    // 删除连线两端绑定的图形前应先处理连线
+    let error = check_operations(&board, &[Operation::remove(vec![1], square("b"))]).unwrap_err();
+    assert!(error.contains("b 不存在"), "{}", error);
+    assert!(check_operations(&board, &[Operation::remove(vec![2], board.children[2].clone()), Operation::remove(vec![1], square("b"))]).is_ok());
+    assert!(check_operations(&board, &[Operation::insert(vec![3], square("a"))]).is_err());
+    assert!(check_operations(&board, &[Operation::set(vec![7], square("x"))]).is_err());
+
+    // 白板原有的问题不阻止无关的操作
+    let broken = BoardState::from_elements(vec![arrow("l", "gone", "gone"), square("c")]);
+    assert!(check_operations(&broken, &[Operation::set(vec![1], square("c"))]).is_ok());
+
+    // 原有的问题再多出一份同样算新错误
+    let unnamed = BoardState::from_elements(vec![square(""), square("c")]);
+    assert!(check_operations(&unnamed, &[Operation::set(vec![1], square("c"))]).is_ok());
+    let error = check_operations(&unnamed, &[Operation::insert(vec![2], square(""))]).unwrap_err();
+    assert!(error.contains("元素没有 id"), "{}", error);
+    assert!(check_operations(&unnamed, &[Operation::insert(vec![2], square("c"))]).is_err());
+}
@@ -1,19 +1,232 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
-mod shared_types;
-mod dds_manager;
-
 use shared_types::*;
-use dds_manager::DDSManager;
+use shared_types::dds_manager::{self, DDSManager, PendingSnapshot};
+use shared_types::history::{BoardHistory, HistoryEntry, HistoryTarget};
+use shared_types::document::DrawnixDocument;
+use shared_types::merge::{self, MergeOutcome, Side};
+use shared_types::{board, diff, mermaid, outline, svg, validate};
 use std::{
     sync::{Arc, Mutex},
     thread,
     time::Duration,
 };
-use tauri::{AppHandle, Emitter};
+use tauri::{AppHandle, Emitter, Manager, State};
 use chrono;
 use uuid;
 
+type SharedDDS = Option<Arc<Mutex<DDSManager>>>;
+type SharedHistory = Arc<Mutex<BoardHistory>>;
+
+/// 由 Tauri 托管、供命令使用的全局状态
+struct AppState {
+    source_id: String,
+    dds_manager: SharedDDS,
+    history: SharedHistory,
+}
+
+/// 本地产生的变化：在同一把历史锁内由当前白板算出操作、校验、编号并写入历史，再发送到前端并通过 DDS 广播；
+/// 会使白板无效的变化不广播，没有操作时什么也不做
+fn broadcast_change(
+    handle: &AppHandle,
+    dds_manager: &SharedDDS,
+    history: &SharedHistory,
+    source_id: &str,
+    operations: impl FnOnce(&BoardHistory) -> Result<Vec<Operation>, String>,
+) -> Result<BoardChangeData, String> {
+    let mut history_lock = history.lock().map_err(|e| e.to_string())?;
+    let mut change = BoardChangeData {
+        operations: operations(&history_lock)?,
+        timestamp: chrono::Utc::now().to_rfc3339(),
+        source_id: source_id.to_string(),
+        seq: None,
+    };
+    if change.operations.is_empty() {
+        return Ok(change);
+    }
+    if let Err(e) = validate::check_operations(history_lock.board(), &change.operations) {
+        eprintln!("🚫 拒绝广播: {}", e);
+        return Err(e);
+    }
+
+    // 先编号再写入历史，本地副本与对端收到的是同一条消息；发布前一直持有历史锁，编号顺序即发布顺序
+    history_lock.stamp(&mut change);
+    if let Some(ref manager) = dds_manager {
+        if let Ok(manager_lock) = manager.lock() {
+            manager_lock.assign_seq(&mut change);
+        }
+    }
+    record_and_emit(handle, &mut history_lock, &change);
+
+    // 通过DDS广播（如果可用）
+    if let Some(ref manager) = dds_manager {
+        if let Ok(manager_lock) = manager.lock() {
+            if let Err(e) = manager_lock.publish_board_change(&change) {
+                eprintln!("DDS发布失败: {}", e);
+            }
+        }
+    }
+    Ok(change)
+}
+
+/// 写入历史并通知前端。历史按全序应用变化，乱序到达的变化可能排在已应用的变化之前，
+/// 因此发给前端的是从旧白板到新白板的差异，而不是原始操作
+fn record_and_emit(handle: &AppHandle, history_lock: &mut BoardHistory, change: &BoardChangeData) {
+    let before = history_lock.current().children;
+    if let Err(e) = history_lock.record_change(change) {
+        eprintln!("历史记录写入失败: {}", e);
+        return;
+    }
+    emit_update(handle, &before, history_lock, change);
+}
+
+/// 白板服务返回的快照：作为新的基准写入历史，发出请求（`since`）之后记录的变化在其上重新应用；
+/// 与原白板的差异作为一批操作发给前端（不再广播）
+fn apply_snapshot(handle: &AppHandle, history: &SharedHistory, snapshot: BoardSnapshot, since: u64) {
+    let Ok(mut history_lock) = history.lock() else { return };
+    let before = history_lock.current().children;
+    let update = BoardChangeData {
+        operations: Vec::new(),
+        timestamp: snapshot.timestamp.clone(),
+        source_id: snapshot.source_id.clone(),
+        seq: None,
+    };
+    println!("🗂️ 收到白板快照: {} 个元素，附带 {} 条变化", snapshot.elements.len(), snapshot.changes.len());
+    if let Err(e) = history_lock.reset_to_snapshot(snapshot, since) {
+        eprintln!("历史记录写入失败: {}", e);
+        return;
+    }
+    emit_update(handle, &before, &history_lock, &update);
+}
+
+/// 把 `before` 到当前白板的差异以 `change` 的来源发给前端，没有差异时不发送
+fn emit_update(handle: &AppHandle, before: &[PlaitElement], history_lock: &BoardHistory, change: &BoardChangeData) {
+    let operations = diff::diff_operations(before, &history_lock.board().children);
+    if operations.is_empty() {
+        return;
+    }
+    let update = BoardChangeData { operations, ..change.clone() };
+    if let Err(e) = handle.emit("board-change", &update) {
+        eprintln!("前端发送失败: {}", e);
+    }
+}
+
+#[tauri::command]
+fn list_board_history(state: State<AppState>) -> Result<Vec<HistoryEntry>, String> {
+    let history = state.history.lock().map_err(|e| e.to_string())?;
+    Ok(history.entries())
+}
+
+#[tauri::command]
+fn create_board_checkpoint(state: State<AppState>, name: String) -> Result<HistoryEntry, String> {
+    let mut history = state.history.lock().map_err(|e| e.to_string())?;
+    history.create_checkpoint(&name, &state.source_id)
+}
+
+/// 只读预览：返回历史某一时刻的白板元素
+#[tauri::command]
+fn preview_board_at(state: State<AppState>, target: HistoryTarget) -> Result<Vec<PlaitElement>, String> {
+    let history = state.history.lock().map_err(|e| e.to_string())?;
+    let seq = history.resolve(&target)?;
+    Ok(history.materialize(seq).children)
+}
+
+/// 还原：把当前白板变回历史某一时刻，作为一批新操作广播给前端和其他节点
+#[tauri::command]
+fn restore_board_at(app: AppHandle, state: State<AppState>, target: HistoryTarget) -> Result<BoardChangeData, String> {
+    let change = broadcast_change(&app, &state.dds_manager, &state.history, &state.source_id, |history| {
+        let seq = history.resolve(&target)?;
+        Ok(diff::diff_operations(&history.current().children, &history.materialize(seq).children))
+    })?;
+    println!("⏪ 已还原白板，共 {} 个操作", change.operations.len());
+    Ok(change)
+}
+
+/// 把离线编辑的副本合并进当前白板：当前白板作为 ours，合并结果作为一批新操作广播
+#[tauri::command]
+fn merge_board_files(
+    app: AppHandle,
+    state: State<AppState>,
+    base_path: String,
+    theirs_path: String,
+    prefer: Option<Side>,
+) -> Result<MergeOutcome, String> {
+    let base = DrawnixDocument::load(&base_path)?;
+    let theirs = DrawnixDocument::load(&theirs_path)?;
+
+    let mut merged = None;
+    broadcast_change(&app, &state.dds_manager, &state.history, &state.source_id, |history| {
+        let ours = history.current().children;
+        let outcome = merge::merge_elements(&base.elements, &ours, &theirs.elements, prefer.unwrap_or_default())?;
+        let operations = diff::diff_operations(&ours, &outcome.elements);
+        merged = Some(outcome);
+        Ok(operations)
+    })?;
+    let outcome = merged.ok_or_else(|| "合并没有完成".to_string())?;
+    println!("🔀 已合并 {}，冲突 {} 处", theirs_path, outcome.conflicts.len());
+    Ok(outcome)
+}
+
+/// 把当前白板导出为 SVG；指定 path 时同时写入文件
+#[tauri::command]
+fn export_board_svg(state: State<AppState>, path: Option<String>) -> Result<String, String> {
+    let elements = state.history.lock().map_err(|e| e.to_string())?.current().children;
+    let image = svg::render_svg(&elements, &svg::SvgOptions::default());
+    if let Some(path) = &path {
+        std::fs::write(path, &image.svg).map_err(|e| format!("写入 {} 失败: {}", path, e))?;
+        println!("🖼️ 已导出 SVG: {}", path);
+    }
+    Ok(image.svg)
+}
+
+/// 导入 Mermaid 流程图或时序图，放在当前内容右侧，作为一批插入操作广播
+#[tauri::command]
+fn import_mermaid(app: AppHandle, state: State<AppState>, source: String) -> Result<BoardChangeData, String> {
+    let change = broadcast_change(&app, &state.dds_manager, &state.history, &state.source_id, |history| {
+        let current = history.current().children;
+        let id_prefix = format!("mermaid-{:x}", chrono::Utc::now().timestamp_millis());
+        let elements = mermaid::import_mermaid(&source, &mermaid::MermaidOptions::beside(&current, id_prefix))?;
+        Ok(board::insert_operations(current.len(), elements))
+    })?;
+    println!("🧜 已导入 Mermaid 图，共 {} 个元素", change.operations.len());
+    Ok(change)
+}
+
+/// 把 Markdown 大纲导入成思维导图，放在当前内容右侧并广播
+#[tauri::command]
+fn import_markdown_outline(
+    app: AppHandle,
+    state: State<AppState>,
+    markdown: String,
+    title: Option<String>,
+) -> Result<BoardChangeData, String> {
+    let change = broadcast_change(&app, &state.dds_manager, &state.history, &state.source_id, |history| {
+        let current = history.current().children;
+        let id_prefix = format!("outline-{:x}", chrono::Utc::now().timestamp_millis());
+        let mut options = outline::OutlineOptions::beside(&current, id_prefix);
+        if let Some(title) = title {
+            options.title = title;
+        }
+        let mind_map = outline::markdown_to_mind_map(&markdown, &options)?;
+        Ok(board::insert_operations(current.len(), vec![mind_map]))
+    })?;
+    println!("🧠 已导入 Markdown 大纲");
+    Ok(change)
+}
+
+/// 把思维导图导出成 Markdown 大纲；不指定 id 时导出当前白板上的全部思维导图
+#[tauri::command]
+fn export_markdown_outline(state: State<AppState>, id: Option<String>) -> Result<String, String> {
+    let elements = state.history.lock().map_err(|e| e.to_string())?.current().children;
+    match id {
+        Some(id) => {
+            let element = elements.iter().find(|e| e.id == id).ok_or_else(|| format!("顶层元素中没有 {}", id))?;
+            outline::mind_map_to_markdown(element)
+        }
+        None => outline::board_to_markdown(&elements),
+    }
+}
+
 fn main() {
     tauri::Builder::default()
         .setup(|app| {
@@ -30,10 +243,21 @@ fn main() {
 
             let source_id = uuid::Uuid::new_v4().to_string();
 
+            // 打开白板历史文件
+            let history_path = app.path().app_data_dir()?.join("board_history.jsonl");
+            let history: SharedHistory = Arc::new(Mutex::new(BoardHistory::open(&history_path)?));
+
+            app.manage(AppState {
+                source_id: source_id.clone(),
+                dds_manager: dds_manager.clone(),
+                history: history.clone(),
+            });
+
             // 为发布线程克隆
             let dds_manager_publish = dds_manager.clone();
             let source_id_publish = source_id.clone();
             let handle_publish = handle.clone();
+            let history_publish = history.clone();
 
             thread::spawn(move || {
                 let mut x = 0.0;
@@ -49,48 +273,32 @@ fn main() {
                         shape: "rectangle".into(),
                         points: vec![Point(x, 0.0), Point(x + 100.0, 100.0)],
                         children: None,
+                        extra: Default::default(),
                     };
 
-                    let change = if first {
+                    let operation = if first {
                         first = false;
                         // 第一次：插入节点
-                        BoardChangeData {
-                            operations: vec![Operation::Insert(InsertNodeOperation {
-                                op_type: "insert_node".into(),
-                                path: vec![0], // 插到根节点 children[0]
-                                node: rect,
-                            })],
-                            timestamp: chrono::Utc::now().to_rfc3339(),
-                            source_id: source_id_publish.clone(),
-                        }
+                        Operation::Insert(InsertNodeOperation {
+                            op_type: "insert_node".into(),
+                            path: vec![0], // 插到根节点 children[0]
+                            node: rect,
+                        })
                     } else {
                         // 后续：只更新节点（移动矩形）
-                        BoardChangeData {
-                            operations: vec![Operation::Set(SetNodeOperation {
-                                op_type: "set_node".into(),
-                                path: vec![0], // 更新第一个节点
-                                node: rect,
-                            })],
-                            timestamp: chrono::Utc::now().to_rfc3339(),
-                            source_id: source_id_publish.clone(),
-                        }
+                        Operation::Set(SetNodeOperation {
+                            op_type: "set_node".into(),
+                            path: vec![0], // 更新第一个节点
+                            node: rect,
+                        })
                     };
 
-                    // 发送到前端
-                    if let Err(e) = handle_publish.emit("board-change", &change) {
-                        eprintln!("前端发送失败: {}", e);
+                    let sent = broadcast_change(&handle_publish, &dds_manager_publish, &history_publish, &source_id_publish, |_| {
+                        Ok(vec![operation])
+                    });
+                    if sent.is_ok() {
+                        println!("✅ 已发送操作，x = {}", x);
                     }
-
-                    // 通过DDS广播（如果可用）
-                    if let Some(ref manager) = dds_manager_publish {
-                        if let Ok(manager_lock) = manager.lock() {
-                            if let Err(e) = manager_lock.publish_board_change(&change) {
-                                eprintln!("DDS发布失败: {}", e);
-                            }
-                        }
-                    }
-
-                    println!("✅ 已发送操作，x = {}", x);
                 }
             });
 
@@ -98,37 +306,82 @@ fn main() {
             if let Some(dds_manager_subscribe) = dds_manager {
                 let handle_subscribe = handle.clone();
                 let source_id_subscribe = source_id.clone();
+                let history_subscribe = history.clone();
+
+                // 向白板服务请求当前状态，晚加入的节点也能看到完整白板；请求可能早于发现过程，按间隔重发。
+                // 请求之后记录的变化会在快照之上重新应用
+                let snapshot_since = history.lock().map(|history_lock| history_lock.latest_seq()).unwrap_or(0);
+                let mut pending_snapshot = dds_manager_subscribe.lock().ok().map(|manager_lock| {
+                    let request = SnapshotRequest {
+                        topic: manager_lock.board_topic().to_string(),
+                        request_id: uuid::Uuid::new_v4().to_string(),
+                        source_id: source_id.clone(),
+                        timestamp: chrono::Utc::now().to_rfc3339(),
+                    };
+                    PendingSnapshot::new(request, dds_manager::SNAPSHOT_TIMEOUT)
+                });
 
                 thread::spawn(move || loop {
-                    if let Ok(mut manager_lock) = dds_manager_subscribe.lock() {
-                        match manager_lock.try_receive_board_change() {
-                            Ok(Some(board_data)) => {
-                                // 避免回环：不处理自己发送的消息
-                                if board_data.source_id != source_id_subscribe {
-                                    println!("📨 收到远程白板变化: {:?}", board_data.operations.len());
-                                    // 转发到前端
-                                    if let Err(e) = handle_subscribe.emit("board-change", &board_data) {
-                                        eprintln!("转发到前端失败: {}", e);
-                                    }
+                    // 持有 DDS 锁时只收消息，写历史前先释放：broadcast_change 先锁历史再锁 DDS，交叉持有会互相等待
+                    let Ok(mut manager_lock) = dds_manager_subscribe.lock() else {
+                        thread::sleep(Duration::from_millis(100));
+                        continue;
+                    };
+                    if let Some(pending) = pending_snapshot.as_mut() {
+                        if !pending.poll(&manager_lock) {
+                            println!("⌛ 没有收到白板服务的快照，从本地白板继续");
+                            pending_snapshot = None;
+                        }
+                    }
+                    let snapshot = manager_lock
+                        .try_receive_snapshot()
+                        .ok()
+                        .flatten()
+                        .filter(|snapshot| pending_snapshot.as_ref().is_some_and(|pending| pending.answered_by(snapshot)));
+                    let received = manager_lock.try_receive_board_change();
+                    drop(manager_lock);
+
+                    if let Some(snapshot) = snapshot {
+                        pending_snapshot = None;
+                        apply_snapshot(&handle_subscribe, &history_subscribe, snapshot, snapshot_since);
+                    }
+
+                    match received {
+                        Ok(Some(board_data)) => {
+                            // 避免回环：不处理自己发送的消息
+                            if board_data.source_id != source_id_subscribe {
+                                println!("📨 收到远程白板变化: {:?}", board_data.operations.len());
+                                // 写入历史并把白板的变化转发到前端
+                                if let Ok(mut history_lock) = history_subscribe.lock() {
+                                    record_and_emit(&handle_subscribe, &mut history_lock, &board_data);
                                 }
                             }
-                            Ok(None) => {
-                                // 没有消息，短暂休眠
-                                thread::sleep(Duration::from_millis(100));
-                            }
-                            Err(e) => {
-                                eprintln!("DDS接收失败: {}", e);
-                                thread::sleep(Duration::from_millis(1000));
-                            }
                         }
-                    } else {
-                        thread::sleep(Duration::from_millis(100));
+                        Ok(None) => {
+                            // 没有消息，短暂休眠
+                            thread::sleep(Duration::from_millis(100));
+                        }
+                        Err(e) => {
+                            eprintln!("DDS接收失败: {}", e);
+                            thread::sleep(Duration::from_millis(1000));
+                        }
                     }
                 });
             }
 
             Ok(())
         })
+        .invoke_handler(tauri::generate_handler![
+            list_board_history,
+            create_board_checkpoint,
+            preview_board_at,
+            restore_board_at,
+            merge_board_files,
+            export_board_svg,
+            import_mermaid,
+            import_markdown_outline,
+            export_markdown_outline,
+        ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
@@ -46,6 +46,9 @@ echo "2. 在另一个终端启动发布者:"
 echo -e "   ${YELLOW}cd test_publisher && ./target/release/test_publisher${NC}"
 echo ""
 echo "3. 观察订阅者终端的实时消息接收"
+echo ""
+echo "4. 压力测试：20 个虚拟用户各 10 条/秒，每条 2KB，持续 60 秒，订阅者用 --stats-interval 观察吞吐和延迟:"
+echo -e "   ${YELLOW}cd test_publisher && ./target/release/test_publisher --users 20 --rate 10 --message-size 2048 --duration 60${NC}"
 
 echo ""
 echo -e "${BLUE}📋 项目结构:${NC}"
@@ -54,8 +57,9 @@ echo "├── dds_subscriber/          # DDS 订阅者程序"
 echo "│   ├── src/main.rs         # 订阅者源码"
 echo "│   ├── Cargo.toml          # 订阅者依赖"
 echo "│   └── ZRDDS_QOS_PROFILES.xml"
-echo "├── test_publisher/          # DDS 发布者程序"
-echo "│   ├── src/main.rs         # 发布者源码"
+echo "├── test_publisher/          # DDS 发布者 / 负载生成器"
+echo "│   ├── src/main.rs         # 负载生成器（命令行与调度）"
+echo "│   ├── src/user.rs         # 虚拟用户的操作流"
 echo "│   ├── Cargo.toml          # 发布者依赖"
 echo "│   └── ZRDDS_QOS_PROFILES.xml"
 echo "└── test_dds.sh             # 本测试脚本"
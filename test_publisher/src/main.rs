@@ -1,72 +1,249 @@
+mod user;
+
+use clap::Parser;
+use shared_types::board::BoardState;
+use shared_types::dds_manager::{BOARD_TOPIC, DEFAULT_DOMAIN_ID};
+use shared_types::BoardChangeData;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use user::{Action, Mix, VirtualUser};
 use zrdds_safe::prelude::*;
-use serde_json::json;
 
-fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
-    println!("🚀 启动简单发布者测试...");
-    
-    let domain = Domain::builder()
-        .domain_id(150)
-        .build()?;
-    
-    let board_publisher = domain.create_publisher("DrawnixBoardChanges")?;
-    let element_publisher = domain.create_publisher("DrawnixElementChanges")?;
-    
-    println!("📡 发布者创建成功，开始发送测试消息...");
-    
-    // 发送白板变化测试消息
-    for i in 1..=3 {
-        let test_data = json!({
-            "children_count": i,
-            "operation_count": 1,
-            "operations": [{
-                "type": "test",
-                "message": format!("测试消息 #{}", i),
-                "timestamp": chrono::Utc::now().to_rfc3339()
-            }],
-            "changes": [{
-                "operation_type": "create",
-                "element_id": format!("test-element-{}", i),
-                "element_type": "test",
-                "element_data": {
-                    "x": i * 10,
-                    "y": i * 10,
-                    "test": true
-                },
-                "timestamp": chrono::Utc::now().to_rfc3339()
-            }],
-            "timestamp": chrono::Utc::now().to_rfc3339()
-        });
-        
-        board_publisher.publish(serde_json::to_string(&test_data)?.as_bytes())?;
-        println!("📨 发送白板变化消息 #{}", i);
-        
-        std::thread::sleep(std::time::Duration::from_secs(1));
+/// 负载生成器：模拟多个用户同时编辑白板
+#[derive(Parser)]
+#[command(name = "test_publisher")]
+#[command(about = "Drawnix DDS 负载生成器")]
+#[command(version = "0.1.0")]
+struct Cli {
+    /// DDS 域 ID
+    #[arg(short, long, default_value_t = DEFAULT_DOMAIN_ID)]
+    domain_id: u32,
+
+    /// 白板主题
+    #[arg(short, long, default_value = BOARD_TOPIC)]
+    topic: String,
+
+    /// 虚拟用户数
+    #[arg(short, long, default_value_t = 3)]
+    users: usize,
+
+    /// 每个用户每秒发送的消息数
+    #[arg(short, long, default_value_t = 2.0)]
+    rate: f64,
+
+    /// 持续时间（秒）
+    #[arg(short = 'D', long, default_value_t = 10)]
+    duration: u64,
+
+    /// 每条消息的目标字节数，不足时用 padding 字段补齐（0 表示不补齐）
+    #[arg(short = 's', long, default_value_t = 0)]
+    message_size: usize,
+
+    /// 动作权重，如 insert=2,drag=5,text=2,delete=1
+    #[arg(short, long, default_value = "insert=2,drag=5,text=2,delete=1")]
+    mix: String,
+
+    /// 每个用户最多同时拥有的元素数
+    #[arg(long, default_value_t = 30)]
+    max_elements: usize,
+
+    /// 随机种子，相同种子生成相同的操作序列
+    #[arg(long)]
+    seed: Option<u64>,
+
+    /// 进度汇总间隔（秒），0 表示不输出
+    #[arg(long, default_value_t = 5)]
+    report_interval: u64,
+}
+
+#[derive(Default)]
+struct Report {
+    sent: u64,
+    errors: u64,
+    bytes: u64,
+    actions: [u64; 4],
+    last_error: Option<String>,
+}
+
+fn main() {
+    let cli = Cli::parse();
+    if let Err(e) = run(cli) {
+        eprintln!("❌ {}", e);
+        std::process::exit(1);
+    }
+}
+
+fn run(cli: Cli) -> Result<(), String> {
+    let mix = Mix::parse(&cli.mix)?;
+    if cli.users == 0 || cli.rate <= 0.0 || !cli.rate.is_finite() {
+        return Err("用户数和速率必须大于 0".to_string());
     }
-    
-    // 发送元素变化测试消息
-    for i in 1..=2 {
-        let test_data = json!({
-            "added": [{
-                "id": format!("new-element-{}", i),
-                "type": "rectangle",
-                "x": i * 50,
-                "y": i * 50
-            }],
-            "removed": [],
-            "modified": [],
-            "timestamp": chrono::Utc::now().to_rfc3339()
-        });
-        
-        element_publisher.publish(serde_json::to_string(&test_data)?.as_bytes())?;
-        println!("📨 发送元素变化消息 #{}", i);
-        
-        std::thread::sleep(std::time::Duration::from_secs(1));
+
+    println!("🚀 启动负载生成器...");
+    let domain = Domain::builder()
+        .domain_id(cli.domain_id)
+        .build()
+        .map_err(|e| format!("创建 DDS 域失败: {}", e))?;
+    let publisher = domain
+        .create_publisher(&cli.topic)
+        .map_err(|e| format!("创建发布者失败: {}", e))?;
+
+    let running = Arc::new(AtomicBool::new(true));
+    let r = running.clone();
+    ctrlc::set_handler(move || r.store(false, Ordering::SeqCst)).map_err(|e| format!("设置 Ctrl+C 处理失败: {}", e))?;
+
+    let seed = cli.seed.unwrap_or_else(|| fastrand::u64(..));
+    let mut rng = fastrand::Rng::with_seed(seed);
+    let run_id = format!("{:04x}", seed & 0xffff);
+    let mut users: Vec<VirtualUser> = (0..cli.users).map(|i| VirtualUser::new(format!("load-{}-{}", run_id, i + 1))).collect();
+    let mut board = BoardState::new();
+
+    let target_rate = cli.rate * cli.users as f64;
+    println!(
+        "📡 域 {}，主题 {}：{} 个虚拟用户 × {} 条/秒，持续 {} 秒（种子 {}）",
+        cli.domain_id, cli.topic, cli.users, cli.rate, cli.duration, seed
+    );
+    println!("⏹️  按 Ctrl+C 提前结束");
+
+    // 各用户错开起步，之后按平均间隔 ±50% 随机抖动
+    let interval = Duration::from_secs_f64(1.0 / cli.rate);
+    let start = Instant::now();
+    let deadline = start + Duration::from_secs(cli.duration);
+    let mut due: Vec<Instant> = (0..cli.users).map(|i| start + interval.mul_f64(i as f64 / cli.users as f64)).collect();
+
+    let mut report = Report::default();
+    let mut window = Report::default();
+    let mut window_started = start;
+
+    while running.load(Ordering::SeqCst) {
+        let (index, &next) = due.iter().enumerate().min_by_key(|(_, t)| **t).expect("至少有一个用户");
+        if next >= deadline {
+            break;
+        }
+        let now = Instant::now();
+        if next > now {
+            // 分段睡眠，及时响应 Ctrl+C
+            std::thread::sleep((next - now).min(Duration::from_millis(100)));
+            continue;
+        }
+        due[index] = next + interval.mul_f64(0.5 + rng.f64());
+
+        if let Some((action, mut change)) = users[index].next_change(&mut board, &mut rng, &mix, cli.max_elements) {
+            let outcome = encode(&mut change, cli.message_size)
+                .and_then(|json| publisher.publish(json.as_bytes()).map(|_| json.len()).map_err(|e| format!("发送失败: {}", e)));
+            for r in [&mut report, &mut window] {
+                match &outcome {
+                    Ok(size) => {
+                        r.sent += 1;
+                        r.bytes += *size as u64;
+                        r.actions[action as usize] += 1;
+                    }
+                    Err(e) => {
+                        r.errors += 1;
+                        r.last_error = Some(e.clone());
+                    }
+                }
+            }
+        }
+
+        let elapsed = window_started.elapsed();
+        if cli.report_interval > 0 && elapsed.as_secs() >= cli.report_interval {
+            print_progress(start.elapsed(), elapsed, &window, target_rate);
+            window = Report::default();
+            window_started = Instant::now();
+        }
     }
-    
-    println!("✅ 测试消息发送完成");
-    
-    // 等待一下确保消息都发送出去
-    std::thread::sleep(std::time::Duration::from_secs(2));
-    
+
+    print_report(&cli, start.elapsed(), &report, target_rate, board.children.len());
     Ok(())
 }
+
+/// 序列化消息，必要时在第一个节点的 padding 字段补齐到目标大小
+fn encode(change: &mut BoardChangeData, message_size: usize) -> Result<String, String> {
+    let json = serde_json::to_string(change).map_err(|e| format!("JSON序列化失败: {}", e))?;
+    // `,"padding":""` 本身占 13 字节
+    let overhead = 13;
+    if json.len() + overhead > message_size {
+        return Ok(json);
+    }
+    let Some(op) = change.operations.first_mut() else {
+        return Ok(json);
+    };
+    op.node_mut().extra.insert("padding".into(), "x".repeat(message_size - json.len() - overhead).into());
+    serde_json::to_string(change).map_err(|e| format!("JSON序列化失败: {}", e))
+}
+
+fn print_progress(total: Duration, elapsed: Duration, window: &Report, target_rate: f64) {
+    let secs = elapsed.as_secs_f64().max(0.001);
+    let mut line = format!(
+        "📈 [{:.0}s] 已发送 {} 条（{:.1} 条/秒，目标 {:.1}），{:.1} KB/秒，错误 {}",
+        total.as_secs_f64(),
+        window.sent,
+        window.sent as f64 / secs,
+        target_rate,
+        window.bytes as f64 / 1024.0 / secs,
+        window.errors
+    );
+    if let Some(e) = &window.last_error {
+        line.push_str(&format!("（{}）", e));
+    }
+    println!("{}", line);
+}
+
+fn print_report(cli: &Cli, elapsed: Duration, report: &Report, target_rate: f64, elements: usize) {
+    let secs = elapsed.as_secs_f64().max(0.001);
+    let achieved = report.sent as f64 / secs;
+    println!();
+    println!("📊 负载测试结果:");
+    println!("   👥 虚拟用户: {}，目标 {:.1} 条/秒（每用户 {} 条/秒）", cli.users, target_rate, cli.rate);
+    println!("   ⏱️  实际时长: {:.1} 秒", secs);
+    println!("   📨 已发送: {} 条，实际 {:.1} 条/秒（目标的 {:.1}%）", report.sent, achieved, achieved * 100.0 / target_rate);
+    println!("   ❌ 发送错误: {}", report.errors);
+    if let Some(e) = &report.last_error {
+        println!("      最后一次错误: {}", e);
+    }
+    if let Some(average) = report.bytes.checked_div(report.sent) {
+        println!(
+            "   📦 数据量: {:.1} KB，平均 {} 字节/条，{:.1} KB/秒",
+            report.bytes as f64 / 1024.0,
+            average,
+            report.bytes as f64 / 1024.0 / secs
+        );
+    }
+    let actions: Vec<String> = Action::ALL.iter().map(|a| format!("{} {}", a.label(), report.actions[*a as usize])).collect();
+    println!("   🔧 操作分布: {}", actions.join("，"));
+    println!("   🧩 结束时白板上共有 {} 个元素", elements);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shared_types::{Operation, PlaitElement};
+
+    fn change(operations: Vec<Operation>) -> BoardChangeData {
+        BoardChangeData { operations, timestamp: "2024-01-01T00:00:00Z".into(), source_id: "load-test".into(), seq: Some(1) }
+    }
+
+    fn node(id: &str) -> PlaitElement {
+        serde_json::from_value(serde_json::json!({ "id": id, "type": "geometry", "shape": "rectangle", "points": [[0, 0], [10, 10]] })).unwrap()
+    }
+
+    #[test]
+    fn padding_reaches_the_message_size_exactly() {
+        let unpadded = encode(&mut change(vec![Operation::insert(vec![0], node("a"))]), 0).unwrap();
+        for size in [unpadded.len() + 13, unpadded.len() + 14, 512, 4096, 65536] {
+            let json = encode(&mut change(vec![Operation::insert(vec![0], node("a"))]), size).unwrap();
+            assert_eq!(json.len(), size, "{}", json);
+            let decoded: BoardChangeData = serde_json::from_str(&json).unwrap();
+            assert!(decoded.operations[0].node().extra.contains_key("padding"));
+        }
+
+        // 补不进 padding 字段时原样发送
+        for size in [unpadded.len(), unpadded.len() + 12] {
+            assert_eq!(encode(&mut change(vec![Operation::insert(vec![0], node("a"))]), size).unwrap(), unpadded);
+        }
+        let empty = encode(&mut change(Vec::new()), 4096).unwrap();
+        assert!(empty.len() < 4096);
+    }
+}
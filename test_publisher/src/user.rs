@@ -0,0 +1,277 @@
+//! 虚拟用户：模拟真实的编辑行为，生成插入、拖动、改文字、删除的操作流
+
+use shared_types::board::BoardState;
+use shared_types::{BoardChangeData, Operation, PlaitElement, Point};
+use serde_json::json;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Action {
+    Insert,
+    Drag,
+    Text,
+    Delete,
+}
+
+impl Action {
+    pub const ALL: [Action; 4] = [Action::Insert, Action::Drag, Action::Text, Action::Delete];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::Insert => "insert",
+            Action::Drag => "drag",
+            Action::Text => "text",
+            Action::Delete => "delete",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Action::Insert => "插入",
+            Action::Drag => "拖动",
+            Action::Text => "改文字",
+            Action::Delete => "删除",
+        }
+    }
+}
+
+/// 各类动作的权重
+#[derive(Clone, Debug)]
+pub struct Mix {
+    weights: [u32; 4],
+}
+
+impl Mix {
+    /// 解析 `insert=2,drag=5,text=2,delete=1`，未列出的动作权重为 0
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let mut weights = [0; 4];
+        for part in spec.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            let (name, weight) = part.split_once('=').ok_or_else(|| format!("无效的动作权重: {}", part))?;
+            let index = Action::ALL
+                .iter()
+                .position(|a| a.name() == name.trim())
+                .ok_or_else(|| format!("未知的动作: {}（可用 insert、drag、text、delete）", name))?;
+            weights[index] = weight.trim().parse().map_err(|_| format!("无效的权重: {}", part))?;
+        }
+        if weights.iter().all(|&w| w == 0) {
+            return Err("至少需要一个动作的权重大于 0".to_string());
+        }
+        Ok(Mix { weights })
+    }
+
+    fn pick(&self, rng: &mut fastrand::Rng, allowed: impl Fn(Action) -> bool) -> Option<Action> {
+        let candidates: Vec<(Action, u32)> = Action::ALL
+            .iter()
+            .zip(self.weights)
+            .filter(|(a, w)| *w > 0 && allowed(**a))
+            .map(|(a, w)| (*a, w))
+            .collect();
+        let total: u32 = candidates.iter().map(|(_, w)| w).sum();
+        if total == 0 {
+            return None;
+        }
+        let mut roll = rng.u32(0..total);
+        for (action, weight) in candidates {
+            if roll < weight {
+                return Some(action);
+            }
+            roll -= weight;
+        }
+        None
+    }
+}
+
+/// 正在进行的拖动：连续若干次 set_node，每次移动一小段
+struct Drag {
+    id: String,
+    remaining: u32,
+    dx: f64,
+    dy: f64,
+}
+
+pub struct VirtualUser {
+    pub source_id: String,
+    seq: u64,
+    next_id: u64,
+    /// 该用户创建、仍在白板上的元素
+    owned: Vec<String>,
+    drag: Option<Drag>,
+}
+
+impl VirtualUser {
+    pub fn new(source_id: String) -> Self {
+        VirtualUser { source_id, seq: 0, next_id: 0, owned: Vec::new(), drag: None }
+    }
+
+    /// 生成下一条白板变化并应用到共享的白板上，保证各用户的路径一致
+    pub fn next_change(
+        &mut self,
+        board: &mut BoardState,
+        rng: &mut fastrand::Rng,
+        mix: &Mix,
+        max_elements: usize,
+    ) -> Option<(Action, BoardChangeData)> {
+        let (action, op) = match self.continue_drag(board) {
+            Some(op) => (Action::Drag, op),
+            None => {
+                let has_elements = !self.owned.is_empty();
+                let action = mix.pick(rng, |a| match a {
+                    Action::Insert => self.owned.len() < max_elements,
+                    _ => has_elements,
+                })?;
+                (action, self.start(action, board, rng)?)
+            }
+        };
+
+        if let Err(e) = board.apply(&op) {
+            eprintln!("⚠️ {} 生成了无效操作: {}", self.source_id, e);
+        }
+        self.seq += 1;
+        Some((
+            action,
+            BoardChangeData {
+                operations: vec![op],
+                timestamp: chrono::Utc::now().to_rfc3339(),
+                source_id: self.source_id.clone(),
+                seq: Some(self.seq),
+            },
+        ))
+    }
+
+    fn start(&mut self, action: Action, board: &BoardState, rng: &mut fastrand::Rng) -> Option<Operation> {
+        match action {
+            Action::Insert => {
+                self.next_id += 1;
+                let id = format!("{}-{}", self.source_id, self.next_id);
+                let node = random_element(&id, &self.source_id, rng);
+                self.owned.push(id);
+                Some(Operation::insert(vec![board.children.len()], node))
+            }
+            Action::Drag => {
+                let id = self.owned[rng.usize(0..self.owned.len())].clone();
+                self.drag = Some(Drag {
+                    id,
+                    remaining: rng.u32(5..20),
+                    dx: rng.f64() * 40.0 - 20.0,
+                    dy: rng.f64() * 40.0 - 20.0,
+                });
+                self.continue_drag(board)
+            }
+            Action::Text => {
+                let id = &self.owned[rng.usize(0..self.owned.len())];
+                let (path, node) = board.find(id)?;
+                let mut node = node.clone();
+                node.extra.insert("text".into(), text_value(&format!("{} 的笔记 #{}", self.source_id, rng.u32(1..1000))));
+                Some(Operation::set(path, node))
+            }
+            Action::Delete => {
+                let index = rng.usize(0..self.owned.len());
+                let id = self.owned.remove(index);
+                let (path, node) = board.find(&id)?;
+                Some(Operation::remove(path, node.clone()))
+            }
+        }
+    }
+
+    fn continue_drag(&mut self, board: &BoardState) -> Option<Operation> {
+        let drag = self.drag.as_mut()?;
+        let found = board.find(&drag.id);
+        let Some((path, node)) = found else {
+            self.drag = None;
+            return None;
+        };
+        let mut node = node.clone();
+        for point in node.points.iter_mut() {
+            point.0 += drag.dx;
+            point.1 += drag.dy;
+        }
+        drag.remaining -= 1;
+        if drag.remaining == 0 {
+            self.drag = None;
+        }
+        Some(Operation::set(path, node))
+    }
+}
+
+/// 与前端一致的文本结构
+fn text_value(text: &str) -> serde_json::Value {
+    json!({ "children": [{ "text": text }] })
+}
+
+/// 随机的矩形、椭圆、文本框或箭头
+fn random_element(id: &str, owner: &str, rng: &mut fastrand::Rng) -> PlaitElement {
+    let x = rng.f64() * 2000.0;
+    let y = rng.f64() * 1200.0;
+    let width = 60.0 + rng.f64() * 180.0;
+    let height = 40.0 + rng.f64() * 120.0;
+    let mut element = PlaitElement {
+        id: id.to_string(),
+        element_type: "geometry".into(),
+        shape: "rectangle".into(),
+        points: vec![Point(x, y), Point(x + width, y + height)],
+        children: None,
+        extra: Default::default(),
+    };
+    match rng.u32(0..4) {
+        0 => {}
+        1 => element.shape = "ellipse".into(),
+        2 => {
+            element.shape = "text".into();
+            element.extra.insert("text".into(), text_value(&format!("{} 的文字", owner)));
+        }
+        _ => {
+            element.element_type = "arrow-line".into();
+            element.shape = "straight".into();
+        }
+    }
+    element.extra.insert("strokeWidth".into(), json!(2));
+    element
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mix_parsing_and_errors() {
+        let mix = Mix::parse(" insert=2, drag = 5 ,delete=1,").unwrap();
+        assert_eq!(mix.weights, [2, 5, 0, 1]);
+        assert!(Mix::parse("insert").unwrap_err().contains("无效的动作权重"));
+        assert!(Mix::parse("jump=1").unwrap_err().contains("未知的动作"));
+        assert!(Mix::parse("drag=-1").unwrap_err().contains("无效的权重"));
+        assert!(Mix::parse("drag=x").unwrap_err().contains("无效的权重"));
+        assert!(Mix::parse("insert=0,drag=0").unwrap_err().contains("至少需要一个动作"));
+        assert!(Mix::parse("").is_err());
+
+        // 权重为 0 的动作不会被选中
+        let mut rng = fastrand::Rng::with_seed(1);
+        let only_text = Mix::parse("text=3").unwrap();
+        assert!((0..100).all(|_| only_text.pick(&mut rng, |_| true) == Some(Action::Text)));
+        assert_eq!(only_text.pick(&mut rng, |a| a != Action::Text), None);
+    }
+
+    #[test]
+    fn generated_changes_apply_cleanly() {
+        let mix = Mix::parse("insert=2,drag=5,text=2,delete=1").unwrap();
+        let mut rng = fastrand::Rng::with_seed(42);
+        let mut users: Vec<VirtualUser> = (1..=3).map(|i| VirtualUser::new(format!("u{}", i))).collect();
+        let mut board = BoardState::new();
+        // 订阅者按收到的顺序重放，每条操作都必须能应用
+        let mut replayed = BoardState::new();
+        let mut seen = [0; 4];
+        for round in 0..2000 {
+            let user = &mut users[round % 3];
+            let Some((action, change)) = user.next_change(&mut board, &mut rng, &mix, 5) else { continue };
+            seen[action as usize] += 1;
+            assert_eq!(change.seq, Some(user.seq));
+            for op in &change.operations {
+                replayed.apply(op).unwrap_or_else(|e| panic!("第 {} 条变化无法应用: {}", round, e));
+            }
+        }
+        assert_eq!(replayed, board);
+        assert!(seen.iter().all(|&n| n > 0), "{:?}", seen);
+        for user in &users {
+            assert!(user.owned.len() <= 5);
+            assert!(user.owned.iter().all(|id| board.find(id).is_some()));
+        }
+    }
+}
@@ -0,0 +1,480 @@
+use clap::Parser;
+use futures_util::{SinkExt, StreamExt};
+use shared_types::board::{self, BoardState};
+use shared_types::dds_manager::{self, DDSManager, PendingSnapshot};
+use shared_types::replica::Replica;
+use shared_types::{validate, BoardChangeData, BoardSnapshot, SnapshotRequest};
+use std::collections::{HashSet, VecDeque};
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::Duration;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::broadcast;
+use tokio_tungstenite::tungstenite::Message;
+
+/// Drawnix WebSocket 桥 - 让浏览器用户加入 DDS 白板会话
+#[derive(Parser)]
+#[command(name = "drawnix-ws-bridge")]
+#[command(about = "在 WebSocket 与 DDS 之间转发 BoardChangeData")]
+struct Cli {
+    /// WebSocket 监听地址
+    #[arg(short, long, default_value = "0.0.0.0:9001")]
+    listen: SocketAddr,
+
+    /// DDS 域 ID
+    #[arg(short, long, default_value_t = dds_manager::DEFAULT_DOMAIN_ID)]
+    domain_id: u32,
+
+    /// 白板主题
+    #[arg(short, long, default_value = dds_manager::BOARD_TOPIC)]
+    topic: String,
+}
+
+/// 最多记住多少条本桥发布过的消息；回环通常很快就到，更早的不再等待
+const MAX_PUBLISHED: usize = 4096;
+
+/// 要发给浏览器的一条消息，`origin` 为发送者的 source_id；`to` 不为空时只发给这个连接
+struct Envelope {
+    origin: String,
+    to: Option<String>,
+    json: String,
+}
+
+/// 浏览器连接发给 DDS 线程的消息
+enum ToDds {
+    /// 新连接，DDS 线程把当前白板发给它
+    Join(String),
+    Change(BoardChangeData),
+}
+
+/// 本桥发布过、还没有从 DDS 上收到回环的 (source_id, seq)
+#[derive(Default)]
+struct Published {
+    order: VecDeque<(String, u64)>,
+    pending: HashSet<(String, u64)>,
+}
+
+impl Published {
+    fn insert(&mut self, change: &BoardChangeData) {
+        let Some(seq) = change.seq else { return };
+        let key = (change.source_id.clone(), seq);
+        if self.pending.insert(key.clone()) {
+            self.order.push_back(key);
+        }
+        while self.order.len() > MAX_PUBLISHED {
+            if let Some(oldest) = self.order.pop_front() {
+                self.pending.remove(&oldest);
+            }
+        }
+    }
+
+    /// 是本桥发布的消息时返回 true，同一条消息只认一次
+    fn is_loopback(&mut self, change: &BoardChangeData) -> bool {
+        change.seq.is_some_and(|seq| self.pending.remove(&(change.source_id.clone(), seq)))
+    }
+}
+
+/// DDS 线程的状态：白板副本、等待快照期间收到的变化，以及本桥发布过的消息
+struct Bridge {
+    bridge_id: String,
+    replica: Replica,
+    /// 等待快照期间收到的变化，快照到达后把其中快照没有包含的重新应用；为 None 表示不再等待
+    buffered: Option<Vec<BoardChangeData>>,
+    published: Published,
+    inbound: broadcast::Sender<Arc<Envelope>>,
+}
+
+impl Bridge {
+    fn new(bridge_id: String, inbound: broadcast::Sender<Arc<Envelope>>) -> Self {
+        Bridge {
+            bridge_id,
+            replica: Replica::new(BoardState::new()),
+            buffered: Some(Vec::new()),
+            published: Published::default(),
+            inbound,
+        }
+    }
+
+    fn waiting(&self) -> bool {
+        self.buffered.is_some()
+    }
+
+    fn snapshot_arrived(&mut self, snapshot: BoardSnapshot) {
+        let buffered = self.buffered.take().unwrap_or_default();
+        self.replica = Replica::from_snapshot(snapshot, buffered);
+    }
+
+    /// 没有白板服务时收不到快照，从已收到的变化继续
+    fn snapshot_timed_out(&mut self) {
+        self.buffered = None;
+    }
+
+    /// 新连接的第一条消息：当前白板作为一批插入操作；在此之前广播的变化都已包含在内
+    fn join(&self, source_id: &str) {
+        let change = BoardChangeData {
+            operations: board::insert_operations(0, self.replica.board().children.clone()),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            source_id: self.bridge_id.clone(),
+            seq: None,
+        };
+        self.send(&change, Some(source_id));
+    }
+
+    /// 浏览器发来的变化：会让白板出现新错误的变化既不转发也不发布，其余调整好时间戳等待编号
+    fn prepare_local(&self, change: &mut BoardChangeData) -> Result<(), String> {
+        validate::check_operations(self.replica.board(), &change.operations)?;
+        self.replica.stamp(change);
+        Ok(())
+    }
+
+    /// 已编号的本地变化：应用到副本，直接转发给同一个桥上的其他浏览器；DDS 上的回环之后会被丢弃
+    fn commit_local(&mut self, change: &BoardChangeData) {
+        self.published.insert(change);
+        self.replica.apply(change.clone());
+        self.send(change, None);
+    }
+
+    /// DDS 上收到的变化；回环、重复的和快照已包含的变化不再转发
+    fn receive_remote(&mut self, change: BoardChangeData) {
+        if self.published.is_loopback(&change) {
+            return;
+        }
+        if let Some(buffered) = self.buffered.as_mut() {
+            buffered.push(change.clone());
+        }
+        if self.replica.apply(change.clone()) {
+            self.send(&change, None);
+        }
+    }
+
+    /// 把一条变化发给浏览器，发送者自己不会收到
+    fn send(&self, change: &BoardChangeData, to: Option<&str>) {
+        match serde_json::to_string(change) {
+            Ok(json) => {
+                let envelope = Envelope { origin: change.source_id.clone(), to: to.map(str::to_string), json };
+                // 没有浏览器连接时发送会失败，忽略即可
+                let _ = self.inbound.send(Arc::new(envelope));
+            }
+            Err(e) => eprintln!("❌ JSON序列化失败: {}", e),
+        }
+    }
+}
+
+/// DDS 线程：维护白板副本，校验并发布浏览器发来的变化，把其他节点的变化广播给所有浏览器
+fn run_dds(
+    mut manager: DDSManager,
+    outbound: mpsc::Receiver<ToDds>,
+    inbound: broadcast::Sender<Arc<Envelope>>,
+    running: Arc<AtomicBool>,
+) {
+    let bridge_id = format!("ws-bridge-{}", uuid::Uuid::new_v4());
+    let request = SnapshotRequest {
+        topic: manager.board_topic().to_string(),
+        request_id: bridge_id.clone(),
+        source_id: bridge_id.clone(),
+        timestamp: chrono::Utc::now().to_rfc3339(),
+    };
+    let mut pending_snapshot = Some(PendingSnapshot::new(request, dds_manager::SNAPSHOT_TIMEOUT));
+    let mut bridge = Bridge::new(bridge_id, inbound);
+
+    while running.load(Ordering::SeqCst) {
+        let mut busy = false;
+
+        if pending_snapshot.as_mut().is_some_and(|pending| !pending.poll(&manager)) {
+            pending_snapshot = None;
+            bridge.snapshot_timed_out();
+        }
+        if let Ok(Some(snapshot)) = manager.try_receive_snapshot() {
+            if pending_snapshot.as_ref().is_some_and(|pending| pending.answered_by(&snapshot)) {
+                pending_snapshot = None;
+                bridge.snapshot_arrived(snapshot);
+                println!("🗂️ 已从白板服务同步 {} 个元素", bridge.replica.board().children.len());
+            }
+        }
+
+        // 快照到达（或请求超时）之前副本还不完整，新连接和浏览器的编辑留在队列里
+        while !bridge.waiting() {
+            let Ok(message) = outbound.try_recv() else { break };
+            busy = true;
+            match message {
+                ToDds::Join(source_id) => bridge.join(&source_id),
+                ToDds::Change(mut change) => {
+                    if let Err(e) = bridge.prepare_local(&mut change) {
+                        eprintln!("🚫 拒绝来自 {} 的变化: {}", change.source_id, e);
+                        continue;
+                    }
+                    manager.assign_seq(&mut change);
+                    bridge.commit_local(&change);
+                    if let Err(e) = manager.publish_board_change(&change) {
+                        eprintln!("❌ DDS发布失败: {}", e);
+                    }
+                }
+            }
+        }
+
+        match manager.try_receive_board_change() {
+            Ok(Some(change)) => {
+                busy = true;
+                bridge.receive_remote(change);
+            }
+            Ok(None) => {}
+            Err(e) => eprintln!("❌ DDS接收失败: {}", e),
+        }
+
+        if !busy {
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+}
+
+async fn handle_client(
+    stream: TcpStream,
+    addr: SocketAddr,
+    to_dds: mpsc::Sender<ToDds>,
+    inbound: broadcast::Sender<Arc<Envelope>>,
+) {
+    let ws = match tokio_tungstenite::accept_async(stream).await {
+        Ok(ws) => ws,
+        Err(e) => {
+            eprintln!("❌ WebSocket 握手失败 {}: {}", addr, e);
+            return;
+        }
+    };
+    let (mut sink, mut stream) = ws.split();
+    let mut from_dds = inbound.subscribe();
+
+    let source_id = format!("ws-{}", uuid::Uuid::new_v4());
+    // 先订阅再请求白板：白板之前广播的变化已包含在白板里，收到白板之前的消息都跳过
+    if to_dds.send(ToDds::Join(source_id.clone())).is_err() {
+        eprintln!("❌ DDS 线程已退出");
+        return;
+    }
+    let mut joined = false;
+    println!("🔗 浏览器已连接: {} ({})", addr, source_id);
+
+    loop {
+        tokio::select! {
+            message = stream.next() => {
+                let text = match message {
+                    Some(Ok(Message::Text(text))) => text,
+                    Some(Ok(Message::Close(_))) | None => break,
+                    Some(Ok(_)) => continue,
+                    Some(Err(e)) => {
+                        eprintln!("❌ 读取 {} 失败: {}", addr, e);
+                        break;
+                    }
+                };
+
+                let mut change = match serde_json::from_str::<BoardChangeData>(&text) {
+                    Ok(change) => change,
+                    Err(e) => {
+                        eprintln!("⚠️ 丢弃来自 {} 的无效消息: {}", addr, e);
+                        continue;
+                    }
+                };
+                // 一律使用桥分配的 id：浏览器不能冒充其他节点，让对方自己的消息被当作回环丢弃
+                change.source_id = source_id.clone();
+                change.seq = None;
+                if change.timestamp.is_empty() {
+                    change.timestamp = chrono::Utc::now().to_rfc3339();
+                }
+
+                // 由 DDS 线程校验后转发给其他浏览器并发布
+                if to_dds.send(ToDds::Change(change)).is_err() {
+                    eprintln!("❌ DDS 线程已退出");
+                    break;
+                }
+            }
+            envelope = from_dds.recv() => {
+                match envelope {
+                    Ok(envelope) => {
+                        match &envelope.to {
+                            Some(to) if *to == source_id && !joined => joined = true,
+                            Some(_) => continue,
+                            // 不把消息回送给它的发送者
+                            None if !joined || envelope.origin == source_id => continue,
+                            None => {}
+                        }
+                        if let Err(e) = sink.send(Message::Text(envelope.json.clone())).await {
+                            eprintln!("❌ 发送到 {} 失败: {}", addr, e);
+                            break;
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        eprintln!("⚠️ {} 处理过慢，丢弃了 {} 条消息", addr, skipped);
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        }
+    }
+
+    println!("👋 浏览器已断开: {} ({})", addr, source_id);
+}
+
+#[tokio::main]
+async fn main() {
+    let cli = Cli::parse();
+
+    let manager = match DDSManager::connect(cli.domain_id, &cli.topic) {
+        Ok(manager) => manager,
+        Err(e) => {
+            eprintln!("❌ 初始化失败: {}", e);
+            std::process::exit(1);
+        }
+    };
+    let listener = match TcpListener::bind(cli.listen).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            eprintln!("❌ 监听 {} 失败: {}", cli.listen, e);
+            std::process::exit(1);
+        }
+    };
+
+    let running = Arc::new(AtomicBool::new(true));
+    let (to_dds, outbound) = mpsc::channel();
+    let (inbound, _) = broadcast::channel(1024);
+
+    let dds_thread = {
+        let inbound = inbound.clone();
+        let running = running.clone();
+        thread::spawn(move || run_dds(manager, outbound, inbound, running))
+    };
+
+    println!("🌉 WebSocket 桥已启动: ws://{}", cli.listen);
+    println!("   📡 主题: {}，域 ID: {}", cli.topic, cli.domain_id);
+
+    loop {
+        tokio::select! {
+            accepted = listener.accept() => {
+                match accepted {
+                    Ok((stream, addr)) => {
+                        tokio::spawn(handle_client(stream, addr, to_dds.clone(), inbound.clone()));
+                    }
+                    Err(e) => eprintln!("❌ 接受连接失败: {}", e),
+                }
+            }
+            _ = tokio::signal::ctrl_c() => {
+                println!("\n🛑 接收到中断信号，正在退出...");
+                break;
+            }
+        }
+    }
+
+    running.store(false, Ordering::SeqCst);
+    let _ = dds_thread.join();
+    println!("🎉 WebSocket 桥已停止");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn change(source_id: &str, seq: Option<u64>, operations: serde_json::Value) -> BoardChangeData {
+        serde_json::from_value(serde_json::json!({ "operations": operations, "timestamp": "2024-01-01T10:00:00Z", "source_id": source_id, "seq": seq })).unwrap()
+    }
+
+    fn insert(id: &str) -> serde_json::Value {
+        serde_json::json!([{ "type": "insert_node", "path": [0], "node": { "id": id, "type": "geometry", "shape": "rectangle", "points": [[0, 0], [10, 10]] } }])
+    }
+
+    fn bridge() -> (Bridge, broadcast::Receiver<Arc<Envelope>>) {
+        let (inbound, received) = broadcast::channel(16);
+        (Bridge::new("ws-bridge-test".into(), inbound), received)
+    }
+
+    fn envelopes(received: &mut broadcast::Receiver<Arc<Envelope>>) -> Vec<(String, Option<String>, BoardChangeData)> {
+        std::iter::from_fn(|| received.try_recv().ok())
+            .map(|envelope| (envelope.origin.clone(), envelope.to.clone(), serde_json::from_str(&envelope.json).unwrap()))
+            .collect()
+    }
+
+    fn ids(bridge: &Bridge) -> Vec<&str> {
+        bridge.replica.board().children.iter().map(|e| e.id.as_str()).collect()
+    }
+
+    #[test]
+    fn published_changes_are_dropped_as_loopback() {
+        let (mut bridge, mut received) = bridge();
+        bridge.snapshot_timed_out();
+
+        let mut local = change("ws-a", None, insert("a"));
+        bridge.prepare_local(&mut local).unwrap();
+        local.seq = Some(1);
+        bridge.commit_local(&local);
+        bridge.receive_remote(local.clone());
+        // 同一个 source_id 的其他消息（例如上次运行留下的）不是回环
+        bridge.receive_remote(change("ws-a", Some(7), insert("old")));
+        bridge.receive_remote(change("peer", Some(1), insert("b")));
+
+        let forwarded: Vec<_> = envelopes(&mut received).into_iter().map(|(origin, _, change)| (origin, change.seq)).collect();
+        assert_eq!(forwarded, [("ws-a".into(), Some(1)), ("ws-a".into(), Some(7)), ("peer".into(), Some(1))]);
+        assert_eq!(ids(&bridge).len(), 3);
+        assert!(!bridge.published.is_loopback(&local));
+    }
+
+    #[test]
+    fn published_keys_are_bounded() {
+        let mut published = Published::default();
+        for seq in 1..=MAX_PUBLISHED as u64 + 10 {
+            published.insert(&change("ws-a", Some(seq), insert("a")));
+        }
+        assert_eq!(published.pending.len(), MAX_PUBLISHED);
+        assert!(!published.is_loopback(&change("ws-a", Some(1), insert("a"))));
+        assert!(published.is_loopback(&change("ws-a", Some(MAX_PUBLISHED as u64 + 10), insert("a"))));
+        assert!(!published.is_loopback(&change("ws-a", None, insert("a"))));
+    }
+
+    #[test]
+    fn joining_client_gets_the_board_first() {
+        let (mut bridge, mut received) = bridge();
+        bridge.snapshot_timed_out();
+        bridge.receive_remote(change("peer", Some(1), insert("a")));
+        bridge.receive_remote(change("peer", Some(2), insert("b")));
+        envelopes(&mut received);
+
+        bridge.join("ws-new");
+        let sent = envelopes(&mut received);
+        assert_eq!(sent.len(), 1);
+        let (origin, to, board) = &sent[0];
+        assert_eq!((origin.as_str(), to.as_deref()), ("ws-bridge-test", Some("ws-new")));
+        let mut replayed = BoardState::new();
+        assert!(replayed.apply_all(&board.operations).is_empty());
+        assert_eq!(&replayed, bridge.replica.board());
+    }
+
+    #[test]
+    fn invalid_local_changes_are_rejected() {
+        let (mut bridge, _received) = bridge();
+        bridge.snapshot_timed_out();
+        let mut update = change("ws-a", None, serde_json::json!([
+            { "type": "set_node", "path": [0], "node": { "id": "x", "type": "geometry", "shape": "rectangle", "points": [[0, 0], [10, 10]] } }
+        ]));
+        assert!(bridge.prepare_local(&mut update).is_err());
+
+        bridge.receive_remote(change("peer", Some(1), insert("a")));
+        assert!(bridge.prepare_local(&mut change("ws-a", None, insert("a"))).is_err());
+    }
+
+    #[test]
+    fn changes_racing_the_snapshot_are_kept() {
+        let (mut bridge, _received) = bridge();
+        assert!(bridge.waiting());
+        // 请求发出后先收到了 p1 的第 2、3 条，快照只包含前两条
+        bridge.receive_remote(change("p1", Some(2), insert("b")));
+        bridge.receive_remote(change("p1", Some(3), insert("c")));
+
+        let snapshot: BoardSnapshot = serde_json::from_value(serde_json::json!({
+            "topic": "t", "request_id": "ws-bridge-test", "timestamp": "", "source_id": "board-server",
+            "elements": [],
+            "changes": [change("p1", Some(1), insert("a")), change("p1", Some(2), insert("b"))],
+            "covers": { "p1": [[1, 2]] },
+        }))
+        .unwrap();
+        bridge.snapshot_arrived(snapshot);
+        assert!(!bridge.waiting());
+        assert_eq!(ids(&bridge), ["c", "b", "a"]);
+    }
+}